@@ -2,7 +2,10 @@
 // Distributed under the MIT License
 // (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
 
-use bemodel::Model;
+use bemodel::{
+    energy::{ray_dir_to_sun, FshobstOptions},
+    Model,
+};
 use criterion::{criterion_group, criterion_main, Criterion};
 
 fn fshobst_update_benchmark(c: &mut Criterion) {
@@ -12,12 +15,68 @@ fn fshobst_update_benchmark(c: &mut Criterion) {
     c.bench_function("Actualiza F_sh;obst", |b| b.iter(|| model.compute_fshobst()));
 }
 
+fn fshobst_options_benchmark(c: &mut Criterion) {
+    let strdata = include_str!("../tests/data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+
+    let fast = FshobstOptions {
+        samples_per_window: Some(3),
+        time_step: 2,
+        ..Default::default()
+    };
+    c.bench_function("Actualiza F_sh;obst (rápido)", |b| {
+        b.iter(|| model.compute_fshobst_with_options(&fast))
+    });
+    let precise = FshobstOptions {
+        samples_per_window: Some(20),
+        ..Default::default()
+    };
+    c.bench_function("Actualiza F_sh;obst (preciso)", |b| {
+        b.iter(|| model.compute_fshobst_with_options(&precise))
+    });
+    let sequential = FshobstOptions {
+        parallel: false,
+        ..Default::default()
+    };
+    c.bench_function("Actualiza F_sh;obst (secuencial)", |b| {
+        b.iter(|| model.compute_fshobst_with_options(&sequential))
+    });
+}
+
+fn sunlit_fraction_hourly_benchmark(c: &mut Criterion) {
+    let strdata = include_str!("../tests/data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+    let met = climate::met::parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+    let windows: Vec<_> = model.windows.iter().take(5).collect();
+
+    c.bench_function("Fracción soleada anual (BVH reutilizada)", |b| {
+        b.iter(|| {
+            let bvh = model.build_bvh();
+            for window in &windows {
+                model.sunlit_fraction_hourly(window, &met, &bvh);
+            }
+        })
+    });
+    c.bench_function("Fracción soleada anual (BVH en cada hora)", |b| {
+        b.iter(|| {
+            let occluders = model.collect_occluders();
+            for window in &windows {
+                let ray_origins = model.ray_origins_for_window(window);
+                for d in met.data.iter().filter(|d| d.zenith < 90.0) {
+                    let ray_dir = ray_dir_to_sun(d.azimuth, 90.0 - d.zenith);
+                    model.sunlit_fraction(window, &ray_origins, &ray_dir, &occluders);
+                }
+            }
+        })
+    });
+}
+
 // Configuración del benchmarking
 criterion_group! {
     name = benches;
     // https://docs.rs/criterion/0.3.4/criterion/struct.Criterion.html
     config = Criterion::default().sample_size(10);
-    targets = fshobst_update_benchmark
+    targets = fshobst_update_benchmark, fshobst_options_benchmark, sunlit_fraction_hourly_benchmark
 }
 
 // Genera función main que ejecuta benchmarks en el grupo indicado
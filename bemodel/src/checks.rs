@@ -6,7 +6,13 @@
 
 use std::collections::HashSet;
 
-use super::{Model, Uuid, Warning, WarningLevel};
+use super::{BoundaryType, Model, SpaceType, Uuid, WallCons, Warning, WarningLevel};
+
+/// Superficie de solape entre huecos a partir de la cual se considera significativo [m²]
+pub(crate) const WINDOW_OVERLAP_MIN_AREA: f32 = 0.01;
+
+/// Nombre de la construcción de opacos que se asigna por defecto al importar archivos BDL
+pub(crate) const EMPTY_WALLCONS_NAME: &str = "Ninguno";
 
 /// Comprueba consistencia del modelo y devuelve lista de avisos / errores detectados
 ///
@@ -16,6 +22,13 @@ use super::{Model, Uuid, Warning, WarningLevel};
 ///     - Muros sin referencias de espacios válidas
 ///     - Muros sin referencias de construcciones válidas
 ///     - Muros con next_to con referencia no válida
+///     - Muros con la construcción por defecto "Ninguno" o con una construcción sin capas
+///     - Espacios sin muros o sin superficie
+/// 2. Incoherencias en la definición de la envolvente térmica:
+///     - Espacios dentro de la ET sin muros que la delimiten (exteriores, con el terreno o interiores con espacios fuera de la ET)
+///     - Espacios acondicionados fuera de la ET con muros exteriores o en contacto con el terreno
+/// 3. Incoherencias geométricas:
+///     - Huecos de un mismo muro que se solapan
 /// TODO: Comprobaciones pendientes
 ///     - Muros con bounds INTERIOR y next_to sin Uuid
 ///     - Muros sin definición geométrica completa
@@ -25,7 +38,6 @@ use super::{Model, Uuid, Warning, WarningLevel};
 ///     - la superficie de elemento (wall.a) coincide con la de su polígono
 ///     - espacio no habitable sin n_v definido
 ///     - las ventanas en particiones interiores se ignoran en los cálculos de U_i
-///     - superficies de hueco < superficie de opaco en el que se inserta
 pub fn check(model: &Model) -> Vec<Warning> {
     use WarningLevel::WARNING;
 
@@ -95,7 +107,25 @@ pub fn check(model: &Model) -> Vec<Warning> {
                 ),
             });
         };
+        if let Some(double_window) = w.double_window {
+            if !winconsids.contains(&double_window.cons) {
+                warnings.push(Warning {
+                    level: WARNING,
+                    id: Some(w.id),
+                    msg: format!(
+                        "Hueco {} ({}) con referencia incorrecta de construcción de la hoja interior {}",
+                        w.id, w.name, double_window.cons
+                    ),
+                });
+            }
+        };
     });
+    // Muros con construcciones vacías
+    warnings.extend(check_walls_empty_cons(model));
+
+    // Espacios sin muros o sin superficie
+    warnings.extend(check_spaces_empty(model));
+
     // Puentes térmicos con longitudes negativas
     model.thermal_bridges.iter().for_each(|tb| {
         if tb.l.is_sign_negative() {
@@ -110,5 +140,249 @@ pub fn check(model: &Model) -> Vec<Warning> {
         };
     });
 
+    // Coherencia entre la pertenencia a la ET de los espacios y las condiciones de contorno de sus muros
+    warnings.extend(check_spaces_tenv(model));
+
+    // Huecos solapados
+    warnings.extend(check_windows_overlap(model));
+
+    warnings
+}
+
+impl Model {
+    /// Comprueba el modelo y devuelve la lista completa de avisos, ordenada por nivel de afectación
+    ///
+    /// Incluye las comprobaciones de consistencia de `check` y, además:
+    ///     - Huecos que no caben en el opaco al que pertenecen
+    ///     - Opacos con superficie de huecos mayor que la del propio opaco
+    ///     - Puentes térmicos con longitud o transmitancia lineal nulas
+    ///
+    /// Los avisos se ordenan de mayor a menor afectación (DANGER, WARNING, INFO, SUCCESS),
+    /// manteniendo el orden de detección dentro de cada nivel.
+    pub fn validate(&self) -> Vec<Warning> {
+        let mut warnings = check(self);
+        warnings.extend(check_windows_fit(self));
+        warnings.extend(check_thermal_bridges(self));
+        warnings.sort_by_key(|w| level_rank(w.level));
+        warnings
+    }
+}
+
+/// Orden de los niveles de aviso, de mayor a menor afectación
+fn level_rank(level: WarningLevel) -> u8 {
+    match level {
+        WarningLevel::DANGER => 0,
+        WarningLevel::WARNING => 1,
+        WarningLevel::INFO => 2,
+        WarningLevel::SUCCESS => 3,
+    }
+}
+
+/// Comprueba que los huecos caben en sus opacos
+///
+/// Se comprueba la posición de los huecos con definición geométrica completa y, para todos los opacos,
+/// que la superficie de sus huecos no supere la del opaco
+fn check_windows_fit(model: &Model) -> Vec<Warning> {
+    use WarningLevel::{DANGER, WARNING};
+
+    let mut warnings = Vec::new();
+
+    for win in &model.windows {
+        let wall = match model.get_wall(win.wall) {
+            Some(wall) => wall,
+            None => continue,
+        };
+        if wall.geometry.contains_window(&win.geometry) == Some(false) {
+            warnings.push(Warning {
+                level: WARNING,
+                id: Some(win.id),
+                msg: format!(
+                    "Hueco {} ({}) fuera de los límites del opaco {} ({})",
+                    win.id, win.name, wall.id, wall.name
+                ),
+            });
+        }
+    }
+
+    for wall in &model.walls {
+        let windows_area: f32 = wall.windows(&model.windows).map(|w| w.area()).sum();
+        let wall_area = wall.area();
+        // Tolerancia de 0,01 m² para errores de redondeo
+        if windows_area > wall_area + 0.01 {
+            warnings.push(Warning {
+                level: DANGER,
+                id: Some(wall.id),
+                msg: format!(
+                    "Opaco {} ({}) con superficie de huecos ({:.2} m²) mayor que la del opaco ({:.2} m²)",
+                    wall.id, wall.name, windows_area, wall_area
+                ),
+            });
+        }
+    }
+
+    warnings
+}
+
+/// ¿Es una construcción de opaco vacía, por ser la construcción por defecto "Ninguno" o no tener capas?
+pub(crate) fn is_empty_wallcons(cons: &WallCons) -> bool {
+    cons.name == EMPTY_WALLCONS_NAME || cons.layers.is_empty()
+}
+
+/// Comprueba que los muros no tengan asignada una construcción vacía
+///
+/// Estos muros tendrían una transmitancia sin sentido físico en los indicadores
+fn check_walls_empty_cons(model: &Model) -> Vec<Warning> {
+    use WarningLevel::DANGER;
+
+    model
+        .walls
+        .iter()
+        .filter_map(|w| {
+            let cons = model.cons.get_wallcons(w.cons)?;
+            is_empty_wallcons(cons).then(|| Warning {
+                level: DANGER,
+                id: Some(w.id),
+                msg: format!(
+                    "Muro {} ({}) con construcción vacía {} ({}), sin capas definidas",
+                    w.id, w.name, cons.id, cons.name
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Comprueba que los espacios tengan algún muro y superficie no nula
+///
+/// Estos espacios, habituales en modelos importados, distorsionan los indicadores que dependen de la superficie
+fn check_spaces_empty(model: &Model) -> Vec<Warning> {
+    use WarningLevel::DANGER;
+
+    model
+        .spaces
+        .iter()
+        .filter_map(|s| {
+            let msg = if model.walls_of_space(s.id).next().is_none() {
+                format!("Espacio {} ({}) sin muros", s.id, s.name)
+            } else if s.area(&model.walls) <= 0.0 {
+                format!("Espacio {} ({}) con superficie nula", s.id, s.name)
+            } else {
+                return None;
+            };
+            Some(Warning {
+                level: DANGER,
+                id: Some(s.id),
+                msg,
+            })
+        })
+        .collect()
+}
+
+/// Comprueba que los puentes térmicos tengan longitud y transmitancia lineal no nulas
+fn check_thermal_bridges(model: &Model) -> Vec<Warning> {
+    use WarningLevel::INFO;
+
+    model
+        .thermal_bridges
+        .iter()
+        .filter(|tb| tb.l == 0.0 || tb.psi == 0.0)
+        .map(|tb| Warning {
+            level: INFO,
+            id: Some(tb.id),
+            msg: format!(
+                "Puente térmico {} ({}) sin efecto, con longitud ({}) o transmitancia lineal ({}) nulas",
+                tb.id, tb.name, tb.l, tb.psi
+            ),
+        })
+        .collect()
+}
+
+/// Comprueba que los huecos de un mismo muro no se solapen
+///
+/// Se avisa de los solapes con una superficie significativa, que se contarían dos veces en la superficie de huecos
+fn check_windows_overlap(model: &Model) -> Vec<Warning> {
+    use WarningLevel::WARNING;
+
+    let mut warnings = Vec::new();
+
+    for (i, win) in model.windows.iter().enumerate() {
+        for other in model.windows[i + 1..].iter().filter(|w| w.wall == win.wall) {
+            let overlap = win.geometry.overlap_area(&other.geometry);
+            if overlap > WINDOW_OVERLAP_MIN_AREA {
+                warnings.push(Warning {
+                    level: WARNING,
+                    id: Some(win.id),
+                    msg: format!(
+                        "Hueco {} ({}) solapado con el hueco {} ({}) en {:.2} m²",
+                        win.id, win.name, other.id, other.name, overlap
+                    ),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Comprueba la coherencia entre Space.inside_tenv y las condiciones de contorno de los muros del espacio
+///
+/// Se consideran los muros del espacio y los muros de otros espacios que lo tienen como adyacente
+fn check_spaces_tenv(model: &Model) -> Vec<Warning> {
+    use BoundaryType::{EXTERIOR, GROUND, INTERIOR};
+    use WarningLevel::WARNING;
+
+    let mut warnings = Vec::new();
+
+    for space in &model.spaces {
+        let own_walls = model.walls.iter().filter(|w| w.space == space.id);
+
+        if space.inside_tenv {
+            // Muros que separan el espacio del exterior o de espacios fuera de la ET
+            let has_tenv_walls = own_walls
+                .map(|w| (w.bounds, w.next_to))
+                .chain(
+                    model
+                        .walls
+                        .iter()
+                        .filter(|w| w.next_to == Some(space.id))
+                        .map(|w| (w.bounds, Some(w.space))),
+                )
+                .any(|(bounds, next_to)| match bounds {
+                    EXTERIOR | GROUND => true,
+                    INTERIOR => next_to
+                        .and_then(|id| model.get_space(id))
+                        .map(|next| !next.inside_tenv)
+                        .unwrap_or(false),
+                    _ => false,
+                });
+            if !has_tenv_walls {
+                warnings.push(Warning {
+                    level: WARNING,
+                    id: Some(space.id),
+                    msg: format!(
+                        "Espacio {} ({}) dentro de la envolvente térmica sin muros exteriores, en contacto con el terreno o con espacios fuera de la envolvente",
+                        space.id, space.name
+                    ),
+                });
+            }
+        } else if space.kind == SpaceType::CONDITIONED {
+            let exterior_walls = own_walls
+                .filter(|w| matches!(w.bounds, EXTERIOR | GROUND))
+                .map(|w| w.name.as_str())
+                .collect::<Vec<_>>();
+            if !exterior_walls.is_empty() {
+                warnings.push(Warning {
+                    level: WARNING,
+                    id: Some(space.id),
+                    msg: format!(
+                        "Espacio acondicionado {} ({}) fuera de la envolvente térmica con muros exteriores o en contacto con el terreno: {}",
+                        space.id,
+                        space.name,
+                        exterior_walls.join(", ")
+                    ),
+                });
+            }
+        }
+    }
+
     warnings
 }
@@ -117,6 +117,13 @@ impl Display for ClimateZone {
     }
 }
 
+impl ClimateZone {
+    /// ¿Es una zona climática de Canarias?
+    pub fn is_canarian(&self) -> bool {
+        self.to_string().ends_with('c')
+    }
+}
+
 /// Convierte str a ClimateZone
 impl TryFrom<&str> for ClimateZone {
     type Error = Box<dyn Error + 'static>;
@@ -0,0 +1,176 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Temperaturas mensuales del agua fría de red por zona climática y por provincia
+//!
+//! Valores tabulados para las capitales de provincia (UNE 94002) y valores aproximados según
+//! la severidad climática de invierno, a partir de los de capitales de provincia representativas de cada una.
+
+use super::ClimateZone;
+
+/// Temperaturas del agua fría de red, por meses, para las zonas climáticas canarias de costa (α) [ºC]
+const T_COLDWATER_ALFA: [f32; 12] = [
+    15.0, 15.0, 16.0, 16.0, 17.0, 18.0, 19.0, 19.0, 19.0, 18.0, 17.0, 16.0,
+];
+/// Temperaturas del agua fría de red, por meses, para las zonas climáticas de invierno A [ºC]
+const T_COLDWATER_A: [f32; 12] = [
+    12.0, 12.0, 13.0, 14.0, 16.0, 18.0, 20.0, 20.0, 19.0, 16.0, 14.0, 12.0,
+];
+/// Temperaturas del agua fría de red, por meses, para las zonas climáticas de invierno B [ºC]
+const T_COLDWATER_B: [f32; 12] = [
+    11.0, 11.0, 13.0, 14.0, 16.0, 19.0, 21.0, 21.0, 19.0, 16.0, 13.0, 11.0,
+];
+/// Temperaturas del agua fría de red, por meses, para las zonas climáticas de invierno C [ºC]
+const T_COLDWATER_C: [f32; 12] = [
+    8.0, 9.0, 11.0, 13.0, 14.0, 17.0, 19.0, 19.0, 17.0, 14.0, 11.0, 8.0,
+];
+/// Temperaturas del agua fría de red, por meses, para las zonas climáticas de invierno D [ºC]
+const T_COLDWATER_D: [f32; 12] = [
+    6.0, 7.0, 9.0, 11.0, 14.0, 17.0, 20.0, 19.0, 16.0, 12.0, 9.0, 7.0,
+];
+/// Temperaturas del agua fría de red, por meses, para las zonas climáticas de invierno E [ºC]
+const T_COLDWATER_E: [f32; 12] = [
+    4.0, 5.0, 6.0, 7.0, 9.0, 12.0, 14.0, 14.0, 12.0, 9.0, 6.0, 4.0,
+];
+
+/// Temperaturas del agua fría de red, por meses, para las capitales de provincia [ºC] (UNE 94002)
+#[rustfmt::skip]
+const T_COLDWATER_PROVINCES: [(&str, [f32; 12]); 52] = [
+    ("A Coruña", [10.0, 10.0, 11.0, 12.0, 13.0, 14.0, 16.0, 16.0, 15.0, 14.0, 12.0, 11.0]),
+    ("Albacete", [7.0, 8.0, 9.0, 11.0, 14.0, 17.0, 19.0, 19.0, 17.0, 13.0, 9.0, 7.0]),
+    ("Alicante", [11.0, 12.0, 13.0, 14.0, 16.0, 18.0, 20.0, 20.0, 19.0, 16.0, 13.0, 12.0]),
+    ("Almería", [12.0, 12.0, 13.0, 14.0, 16.0, 18.0, 20.0, 21.0, 19.0, 17.0, 14.0, 12.0]),
+    ("Álava", [7.0, 7.0, 8.0, 10.0, 12.0, 14.0, 16.0, 16.0, 14.0, 12.0, 8.0, 7.0]),
+    ("Asturias", [9.0, 9.0, 10.0, 10.0, 12.0, 14.0, 15.0, 16.0, 15.0, 13.0, 10.0, 9.0]),
+    ("Ávila", [6.0, 6.0, 7.0, 9.0, 11.0, 14.0, 17.0, 16.0, 14.0, 11.0, 8.0, 6.0]),
+    ("Badajoz", [9.0, 10.0, 11.0, 13.0, 15.0, 18.0, 20.0, 20.0, 18.0, 15.0, 12.0, 9.0]),
+    ("Barcelona", [9.0, 10.0, 11.0, 12.0, 14.0, 17.0, 19.0, 19.0, 17.0, 15.0, 12.0, 10.0]),
+    ("Bizkaia", [9.0, 10.0, 10.0, 11.0, 13.0, 15.0, 17.0, 17.0, 16.0, 14.0, 11.0, 10.0]),
+    ("Burgos", [5.0, 6.0, 7.0, 9.0, 11.0, 13.0, 16.0, 16.0, 14.0, 11.0, 7.0, 6.0]),
+    ("Cáceres", [9.0, 10.0, 11.0, 12.0, 14.0, 18.0, 21.0, 20.0, 19.0, 15.0, 11.0, 9.0]),
+    ("Cádiz", [12.0, 12.0, 13.0, 14.0, 16.0, 18.0, 19.0, 20.0, 19.0, 17.0, 14.0, 12.0]),
+    ("Cantabria", [10.0, 10.0, 11.0, 11.0, 13.0, 15.0, 16.0, 16.0, 16.0, 14.0, 12.0, 10.0]),
+    ("Castellón", [10.0, 11.0, 12.0, 13.0, 15.0, 18.0, 19.0, 20.0, 18.0, 16.0, 12.0, 11.0]),
+    ("Ceuta", [11.0, 11.0, 12.0, 13.0, 14.0, 16.0, 18.0, 18.0, 17.0, 15.0, 13.0, 12.0]),
+    ("Ciudad Real", [7.0, 8.0, 10.0, 11.0, 14.0, 17.0, 20.0, 20.0, 17.0, 13.0, 10.0, 7.0]),
+    ("Córdoba", [10.0, 11.0, 12.0, 14.0, 16.0, 19.0, 21.0, 21.0, 19.0, 16.0, 12.0, 10.0]),
+    ("Cuenca", [6.0, 7.0, 8.0, 10.0, 13.0, 16.0, 18.0, 18.0, 16.0, 12.0, 9.0, 7.0]),
+    ("Gipuzkoa", [9.0, 9.0, 10.0, 11.0, 12.0, 14.0, 16.0, 16.0, 15.0, 14.0, 11.0, 9.0]),
+    ("Girona", [8.0, 9.0, 10.0, 11.0, 14.0, 16.0, 19.0, 18.0, 17.0, 14.0, 10.0, 9.0]),
+    ("Granada", [8.0, 9.0, 10.0, 12.0, 14.0, 17.0, 20.0, 19.0, 17.0, 14.0, 11.0, 8.0]),
+    ("Guadalajara", [7.0, 8.0, 9.0, 11.0, 14.0, 17.0, 19.0, 19.0, 16.0, 13.0, 9.0, 7.0]),
+    ("Huelva", [12.0, 12.0, 13.0, 14.0, 16.0, 18.0, 20.0, 20.0, 19.0, 17.0, 14.0, 12.0]),
+    ("Huesca", [7.0, 8.0, 10.0, 11.0, 14.0, 16.0, 19.0, 18.0, 17.0, 13.0, 9.0, 7.0]),
+    ("Illes Balears", [11.0, 11.0, 12.0, 13.0, 15.0, 18.0, 20.0, 20.0, 19.0, 17.0, 14.0, 12.0]),
+    ("Jaén", [9.0, 10.0, 11.0, 13.0, 16.0, 19.0, 21.0, 21.0, 19.0, 15.0, 12.0, 9.0]),
+    ("La Rioja", [7.0, 8.0, 10.0, 11.0, 13.0, 16.0, 18.0, 18.0, 16.0, 13.0, 10.0, 8.0]),
+    ("Las Palmas", [15.0, 15.0, 16.0, 16.0, 17.0, 18.0, 19.0, 19.0, 19.0, 18.0, 17.0, 16.0]),
+    ("León", [6.0, 6.0, 8.0, 9.0, 12.0, 14.0, 16.0, 16.0, 15.0, 11.0, 8.0, 6.0]),
+    ("Lleida", [7.0, 9.0, 10.0, 12.0, 15.0, 17.0, 20.0, 19.0, 17.0, 14.0, 10.0, 7.0]),
+    ("Lugo", [7.0, 8.0, 9.0, 10.0, 11.0, 13.0, 15.0, 15.0, 14.0, 12.0, 9.0, 8.0]),
+    ("Madrid", [8.0, 8.0, 10.0, 12.0, 14.0, 17.0, 20.0, 19.0, 17.0, 13.0, 10.0, 8.0]),
+    ("Málaga", [12.0, 12.0, 13.0, 14.0, 16.0, 18.0, 20.0, 20.0, 19.0, 16.0, 14.0, 12.0]),
+    ("Melilla", [13.0, 13.0, 14.0, 15.0, 16.0, 18.0, 20.0, 20.0, 19.0, 17.0, 15.0, 14.0]),
+    ("Murcia", [11.0, 11.0, 12.0, 13.0, 15.0, 17.0, 19.0, 20.0, 18.0, 16.0, 13.0, 11.0]),
+    ("Navarra", [7.0, 8.0, 9.0, 10.0, 12.0, 15.0, 17.0, 17.0, 16.0, 13.0, 9.0, 7.0]),
+    ("Ourense", [8.0, 10.0, 11.0, 12.0, 14.0, 16.0, 18.0, 18.0, 17.0, 13.0, 11.0, 9.0]),
+    ("Palencia", [6.0, 7.0, 8.0, 10.0, 12.0, 15.0, 17.0, 17.0, 15.0, 12.0, 9.0, 6.0]),
+    ("Pontevedra", [10.0, 11.0, 11.0, 13.0, 14.0, 16.0, 17.0, 17.0, 16.0, 14.0, 12.0, 10.0]),
+    ("Salamanca", [6.0, 7.0, 8.0, 10.0, 12.0, 15.0, 17.0, 17.0, 15.0, 12.0, 8.0, 6.0]),
+    ("Santa Cruz de Tenerife", [15.0, 15.0, 16.0, 16.0, 17.0, 18.0, 20.0, 20.0, 20.0, 18.0, 17.0, 16.0]),
+    ("Segovia", [6.0, 7.0, 8.0, 10.0, 12.0, 15.0, 18.0, 18.0, 15.0, 12.0, 8.0, 6.0]),
+    ("Sevilla", [11.0, 11.0, 13.0, 14.0, 16.0, 19.0, 21.0, 21.0, 20.0, 16.0, 13.0, 11.0]),
+    ("Soria", [5.0, 6.0, 7.0, 9.0, 11.0, 14.0, 17.0, 16.0, 14.0, 11.0, 7.0, 6.0]),
+    ("Tarragona", [10.0, 11.0, 12.0, 14.0, 16.0, 18.0, 20.0, 20.0, 19.0, 16.0, 12.0, 11.0]),
+    ("Teruel", [6.0, 7.0, 8.0, 10.0, 12.0, 15.0, 18.0, 17.0, 15.0, 12.0, 8.0, 6.0]),
+    ("Toledo", [8.0, 9.0, 11.0, 12.0, 15.0, 18.0, 21.0, 20.0, 18.0, 14.0, 11.0, 8.0]),
+    ("Valencia", [10.0, 11.0, 12.0, 13.0, 15.0, 17.0, 19.0, 20.0, 18.0, 16.0, 13.0, 11.0]),
+    ("Valladolid", [6.0, 8.0, 9.0, 10.0, 12.0, 15.0, 18.0, 18.0, 16.0, 12.0, 9.0, 7.0]),
+    ("Zamora", [6.0, 8.0, 9.0, 10.0, 13.0, 16.0, 18.0, 18.0, 16.0, 12.0, 9.0, 7.0]),
+    ("Zaragoza", [8.0, 9.0, 10.0, 12.0, 15.0, 17.0, 20.0, 19.0, 17.0, 14.0, 10.0, 8.0]),
+];
+
+/// Otras denominaciones de las provincias (nombres en otras lenguas oficiales y capitales de provincia)
+const PROVINCE_ALIASES: [(&str, &str); 32] = [
+    ("la coruña", "A Coruña"),
+    ("coruña", "A Coruña"),
+    ("alacant", "Alicante"),
+    ("araba", "Álava"),
+    ("araba/álava", "Álava"),
+    ("vitoria", "Álava"),
+    ("vitoria-gasteiz", "Álava"),
+    ("oviedo", "Asturias"),
+    ("vizcaya", "Bizkaia"),
+    ("bilbao", "Bizkaia"),
+    ("santander", "Cantabria"),
+    ("castelló", "Castellón"),
+    ("castellón de la plana", "Castellón"),
+    ("castelló de la plana", "Castellón"),
+    ("guipúzcoa", "Gipuzkoa"),
+    ("san sebastián", "Gipuzkoa"),
+    ("donostia", "Gipuzkoa"),
+    ("donostia-san sebastián", "Gipuzkoa"),
+    ("gerona", "Girona"),
+    ("baleares", "Illes Balears"),
+    ("islas baleares", "Illes Balears"),
+    ("palma", "Illes Balears"),
+    ("palma de mallorca", "Illes Balears"),
+    ("rioja", "La Rioja"),
+    ("logroño", "La Rioja"),
+    ("las palmas de gran canaria", "Las Palmas"),
+    ("lérida", "Lleida"),
+    ("pamplona", "Navarra"),
+    ("iruña", "Navarra"),
+    ("orense", "Ourense"),
+    ("tenerife", "Santa Cruz de Tenerife"),
+    ("valència", "Valencia"),
+];
+
+/// Temperaturas mensuales del agua fría de red para la zona climática [ºC]
+pub fn cold_water_temperatures(climate: &ClimateZone) -> [f32; 12] {
+    let zone = climate.to_string();
+    if zone.starts_with("Alfa") {
+        return T_COLDWATER_ALFA;
+    }
+    match zone.chars().next() {
+        Some('A') => T_COLDWATER_A,
+        Some('B') => T_COLDWATER_B,
+        Some('C') => T_COLDWATER_C,
+        Some('D') => T_COLDWATER_D,
+        _ => T_COLDWATER_E,
+    }
+}
+
+/// Temperaturas mensuales del agua fría de red para la capital de la provincia [ºC] (UNE 94002)
+///
+/// La provincia se identifica por su nombre, sin distinguir mayúsculas, minúsculas ni tildes,
+/// admitiendo las denominaciones en las lenguas cooficiales y el nombre de la capital (p.e. "Gipuzkoa" o "Donostia").
+/// Devuelve None si no se reconoce la provincia.
+pub fn cold_water_temperature(province: &str) -> Option<[f32; 12]> {
+    let key = simplify(province);
+    let name = PROVINCE_ALIASES
+        .iter()
+        .find(|(alias, _)| simplify(alias) == key)
+        .map_or(key, |(_, name)| simplify(name));
+    T_COLDWATER_PROVINCES
+        .iter()
+        .find(|(province, _)| simplify(province) == name)
+        .map(|(_, temps)| *temps)
+}
+
+/// Nombre en minúsculas, sin tildes ni espacios sobrantes, para la comparación de nombres
+fn simplify(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' => 'a',
+            'é' | 'è' => 'e',
+            'í' | 'ï' => 'i',
+            'ó' | 'ò' => 'o',
+            'ú' | 'ü' => 'u',
+            c => c,
+        })
+        .collect()
+}
@@ -5,21 +5,27 @@
 //! Datos generales de zonas climáticas (latitud, longitud de referencia, nombre, etc)
 //! Datos de radiación mensuales para superficies
 //! Datos de radiación horaria por zona climática para el 21 de julio
+//! Temperaturas mensuales del agua fría de red por zona climática y por provincia
+//! Generación de datos horarios aproximados a partir de los datos mensuales
 //! Criterios de orientación UNE-EN ISO 52016-1, (S=0, E=+90, W=-90)
 #![allow(clippy::approx_constant)]
 
 use std::collections::HashMap;
 
-use crate::Orientation;
+use crate::{utils::normalize, Orientation};
 
 mod climatezone;
+mod coldwater;
 mod hourlyraddata;
 mod monthlyraddata;
+mod synthetic;
 mod zonesmeta;
 
 pub use climatezone::ClimateZone;
+pub use coldwater::{cold_water_temperature, cold_water_temperatures};
 pub use hourlyraddata::{RadData, JULYRADDATA};
-pub use monthlyraddata::MONTHLYRADDATA;
+pub use monthlyraddata::{SurfaceMonthlyRadiation, MONTHLYRADDATA};
+pub use synthetic::{monthly_temperatures, synthesize_hourly};
 pub use zonesmeta::CLIMATEMETADATA;
 
 /// Diccionario con el valor de la radiación total por orientación para el mes de julio
@@ -32,3 +38,88 @@ pub fn total_radiation_in_july_by_orientation(climate: &ClimateZone) -> HashMap<
         .map(|e| (e.orientation, e.dir[6] + e.dif[6]))
         .collect()
 }
+
+/// Diccionario con los valores mensuales de radiación total por orientación [kWh/m²·mes]
+pub fn total_radiation_by_orientation_and_month(
+    climate: &ClimateZone,
+) -> HashMap<Orientation, Vec<f32>> {
+    MONTHLYRADDATA
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|e| &e.zone == climate)
+        .map(|e| {
+            let total = e
+                .dir
+                .iter()
+                .zip(&e.dif)
+                .map(|(dir, dif)| dir + dif)
+                .collect();
+            (e.orientation, total)
+        })
+        .collect()
+}
+
+/// Valores mensuales de radiación total sobre una superficie con cualquier inclinación y orientación [kWh/m²·mes]
+///
+/// Se interpola linealmente según el azimut entre las dos orientaciones verticales tipo más próximas
+/// y, según la inclinación (tilt), entre la superficie horizontal (0º) y la vertical (90º).
+/// Para las superficies orientadas hacia abajo (tilt > 90º) se reduce linealmente el valor vertical
+/// hasta anularse en las superficies horizontales hacia abajo (180º).
+/// Devuelve None si no hay datos para la zona climática.
+pub fn monthly_radiation_for_surface(
+    climate: &ClimateZone,
+    tilt: f32,
+    azimuth: f32,
+) -> Option<[f32; 12]> {
+    let total = |e: &SurfaceMonthlyRadiation| {
+        let mut total = [0.0; 12];
+        for (i, value) in total.iter_mut().enumerate() {
+            *value = e.dir[i] + e.dif[i];
+        }
+        total
+    };
+    let raddata = MONTHLYRADDATA.lock().unwrap();
+    let horizontal = raddata
+        .iter()
+        .find(|e| &e.zone == climate && e.orientation == Orientation::HZ)
+        .map(total)?;
+    // Superficies verticales por azimut, en el criterio del modelo y en [0, 360)
+    let mut vertical: Vec<_> = raddata
+        .iter()
+        .filter(|e| &e.zone == climate && e.orientation != Orientation::HZ)
+        .map(|e| (normalize(-e.gamma, 0.0, 360.0), total(e)))
+        .collect();
+    if vertical.is_empty() {
+        return None;
+    }
+    vertical.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let azimuth = normalize(azimuth, 0.0, 360.0);
+    let n = vertical.len();
+    let i1 = vertical
+        .iter()
+        .position(|(az, _)| *az > azimuth)
+        .unwrap_or(0);
+    let (az0, v0) = vertical[(i1 + n - 1) % n];
+    let (az1, v1) = vertical[i1];
+    let span = match normalize(az1 - az0, 0.0, 360.0) {
+        span if span > 0.0 => span,
+        _ => 360.0,
+    };
+    let f_az = normalize(azimuth - az0, 0.0, 360.0) / span;
+
+    let tilt = tilt.clamp(0.0, 180.0);
+    let (f_hz, f_vert) = if tilt <= 90.0 {
+        (1.0 - tilt / 90.0, tilt / 90.0)
+    } else {
+        (0.0, (180.0 - tilt) / 90.0)
+    };
+
+    let mut radiation = [0.0; 12];
+    for (i, value) in radiation.iter_mut().enumerate() {
+        let vert = v0[i] + f_az * (v1[i] - v0[i]);
+        *value = f_hz * horizontal[i] + f_vert * vert;
+    }
+    Some(radiation)
+}
@@ -22,7 +22,7 @@ pub struct SurfaceMonthlyRadiation {
     pub orientation: Orientation,
     /// Inclinación (Horiz=0, vertical=90)
     pub beta: f32,
-    /// Orientación (N=0, E=-90, W=+90, S=180)
+    /// Orientación (S=0, E=-90, W=+90, N=180)
     /// Nota: el criterio difiere del de UNE-EN ISO 52016-1
     /// donde se mide desde el sur, positivo al este, negativo al oeste (S=0, E=+90, W=-90)
     pub gamma: f32,
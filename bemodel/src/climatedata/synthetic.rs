@@ -0,0 +1,167 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Generación de series climáticas horarias aproximadas a partir de los datos mensuales tabulados
+//!
+//! Permite disponer de datos horarios (radiación y temperatura) para zonas sin archivo .met:
+//! - la radiación directa y difusa sobre superficie horizontal se reparte en cada día según la altura solar,
+//!   conservando los totales mensuales de MONTHLYRADDATA
+//! - la temperatura exterior sigue un perfil anual y diario sinusoidal con valores medios típicos
+//!   de la severidad climática de invierno y de verano de la zona
+//!
+//! No se sintetizan datos de humedad o viento, que quedan con valor nulo.
+
+use anyhow::{format_err, Error};
+use climate::{
+    solar::{declination_from_nday, hourangle_from_data},
+    sun_position, HourlyData, Location, MetData, Meta as MetMeta, MONTH_DAYS,
+};
+
+use super::{ClimateZone, CLIMATEMETADATA, MONTHLYRADDATA};
+use crate::Orientation;
+
+/// Genera datos climáticos horarios (8760 valores) aproximados para una zona climática
+///
+/// Los totales mensuales de radiación directa y difusa sobre superficie horizontal
+/// coinciden con los datos mensuales tabulados (MONTHLYRADDATA).
+pub fn synthesize_hourly(zone: &ClimateZone) -> Result<MetData, Error> {
+    let metinfo = CLIMATEMETADATA
+        .lock()
+        .unwrap()
+        .get(zone)
+        .cloned()
+        .ok_or_else(|| format_err!("Metadatos no encontrados para la zona climática {}", zone))?;
+    let (dir_month, dif_month) = MONTHLYRADDATA
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|e| &e.zone == zone && e.orientation == Orientation::HZ)
+        .map(|e| (e.dir.clone(), e.dif.clone()))
+        .ok_or_else(|| {
+            format_err!(
+                "Datos de radiación no encontrados para la zona climática {}",
+                zone
+            )
+        })?;
+
+    let location = Location {
+        latitude: metinfo.latitude,
+        longitude: metinfo.longitude,
+        tz: (metinfo.reflong / 15.0).round() as i32,
+    };
+    let (_, _, t_amp_day) = typical_temperatures(zone);
+    let t_months = monthly_temperatures(zone);
+
+    let mut data = Vec::with_capacity(8760);
+    let mut nday = 0;
+    for (m, &ndays) in MONTH_DAYS.iter().enumerate() {
+        let month = m as u32 + 1;
+        let t_month = t_months[m];
+        // Radiación diaria, Wh/m²
+        let dir_day = 1000.0 * dir_month[m] / ndays as f32;
+        let dif_day = 1000.0 * dif_month[m] / ndays as f32;
+
+        for day in 1..=ndays {
+            nday += 1;
+            let declination = declination_from_nday(nday);
+            // Posición solar en el punto medio de cada hora
+            let sunpos = (1..=24)
+                .map(|hour| {
+                    let hourangle = hourangle_from_data(hour as f32 - 0.5, nday, location);
+                    sun_position(declination, hourangle, location)
+                })
+                .collect::<Vec<_>>();
+            // Reparto de la radiación diaria proporcional al seno de la altura solar
+            let weights = sunpos
+                .iter()
+                .map(|p| p.altitude.to_radians().sin().max(0.0))
+                .collect::<Vec<_>>();
+            let sum_weights: f32 = weights.iter().sum();
+
+            for (h, (pos, weight)) in sunpos.iter().zip(weights).enumerate() {
+                let hour = h as f32 + 1.0;
+                let fraction = if sum_weights > 0.0 {
+                    weight / sum_weights
+                } else {
+                    0.0
+                };
+                // Máxima a las 15h y mínima a las 3h
+                let db_temp =
+                    t_month + t_amp_day * (2.0 * std::f32::consts::PI * (hour - 15.0) / 24.0).cos();
+                data.push(HourlyData {
+                    month,
+                    day,
+                    hour,
+                    db_temp,
+                    sky_temp: sky_temp_swinbank(db_temp),
+                    rdirhor: dir_day * fraction,
+                    rdifhor: dif_day * fraction,
+                    azimuth: pos.azimuth,
+                    zenith: 90.0 - pos.altitude,
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    Ok(MetData {
+        meta: MetMeta {
+            metname: metinfo.metname,
+            zc: zone.to_string(),
+            latitude: metinfo.latitude,
+            longitude: metinfo.longitude,
+            altitude: metinfo.altitude,
+            reflong: metinfo.reflong,
+        },
+        data,
+    })
+}
+
+/// Temperaturas medias mensuales aproximadas de una zona climática (ºC)
+///
+/// Siguen un perfil sinusoidal con mínimo en enero y máximo en julio, a partir de las temperaturas típicas de la zona
+pub fn monthly_temperatures(zone: &ClimateZone) -> [f32; 12] {
+    let (t_jan, t_jul, _) = typical_temperatures(zone);
+    let mut temps = [0.0; 12];
+    for (m, t) in temps.iter_mut().enumerate() {
+        *t = 0.5 * (t_jan + t_jul)
+            - 0.5 * (t_jul - t_jan) * (2.0 * std::f32::consts::PI * m as f32 / 12.0).cos();
+    }
+    temps
+}
+
+/// Temperaturas típicas de una zona climática (ºC)
+///
+/// Devuelve la temperatura media de enero, la temperatura media de julio y la semiamplitud de la oscilación diaria,
+/// aproximadas a partir de la severidad climática de invierno (letra) y de verano (número) de la zona.
+fn typical_temperatures(zone: &ClimateZone) -> (f32, f32, f32) {
+    let name = zone.to_string();
+    let is_canarias = name.ends_with('c');
+    let name = name.trim_end_matches('c');
+    let (winter, summer) = name.split_at(name.len() - 1);
+
+    let t_jan = match winter {
+        "Alfa" => 17.0,
+        "A" => 12.0,
+        "B" => 10.0,
+        "C" => 8.0,
+        "D" => 5.5,
+        _ => 3.0,
+    };
+    let t_jul = match summer {
+        "1" => 20.0,
+        "2" => 22.5,
+        "3" => 25.0,
+        _ => 27.0,
+    };
+    let t_amp_day = if is_canarias { 3.0 } else { 5.0 };
+
+    (t_jan, t_jul, t_amp_day)
+}
+
+/// Temperatura efectiva de cielo (ºC) según la expresión de Swinbank
+#[inline]
+fn sky_temp_swinbank(db_temp: f32) -> f32 {
+    0.0552 * (db_temp + 273.15).powf(1.5) - 273.15
+}
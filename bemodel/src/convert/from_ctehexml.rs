@@ -11,36 +11,23 @@ use std::{
 };
 
 use anyhow::{anyhow, bail, format_err, Error};
+use log::warn;
 use nalgebra::{point, Point3, Rotation2, Rotation3, Translation3, Vector3};
 
-use crate::utils::{fround2, normalize, uuid_from_obj};
+use crate::utils::{azimuth_bdl_to_model, fround2, normalize, uuid_from_obj, uuid_from_str};
 use hulc::{
     bdl::{self, BdlBlockType, Data},
     ctehexml,
 };
 
 pub use crate::{
-    BoundaryType, ConsDb, Frame, Glass, Layer, MatProps, Material, Meta, Model, Orientation,
-    PropsOverrides, Schedule, ScheduleDay, ScheduleWeek, SchedulesDb, Shade, Space, SpaceLoads,
-    SpaceType, ThermalBridge, ThermalBridgeKind, Thermostat, Tilt, Uuid, Wall, WallCons, WallGeom,
-    WinCons, WinGeom, Window,
+    BoundaryType, CavityVentilation, ConsDb, Floor, Frame, Glass, Layer, MatProps, Material, Meta,
+    Model, OpaqueDoor, Orientation, PropsOverrides, Schedule, ScheduleDay, ScheduleWeek,
+    SchedulesDb, Shade, Space, SpaceLoads, SpaceType, ThermalBridge, ThermalBridgeKind,
+    ThermalZone, Thermostat, Tilt, Uuid, VentilatedCavity, Wall, WallCons, WallGeom, WinCons,
+    WinGeom, Window,
 };
 
-// Utilidades varias de conversión
-
-/// Normaliza azimuth [-180, 180]
-#[inline]
-pub fn normalize_azimuth(azimuth: f32) -> f32 {
-    normalize(azimuth, -180.0, 180.0)
-}
-
-/// Convierte el azimuth desde el criterio del BDL al criterio de la 52016-1 y normaliza
-/// BDL: Ángulo entre el eje Y del espacio y la proyección horizontal de la normal exterior del opaco (N=0, E=+90, W=-90)
-/// UNE-EN ISO 52016-1: S=0, E=+90, W=-90
-pub fn orientation_bdl_to_52016(azimuth: f32) -> f32 {
-    normalize_azimuth(180.0 - azimuth)
-}
-
 // Conversiones de BDL a tipos CTE -------------------
 
 impl From<bdl::BoundaryType> for BoundaryType {
@@ -61,9 +48,11 @@ impl TryFrom<&ctehexml::CtehexmlData> for Model {
         let id_maps = IdMaps::new(bdl);
 
         let cons = cons_from_bdl(bdl, &id_maps)?;
+        let floors = floors_from_bdl(bdl, &id_maps)?;
         let spaces = spaces_from_bdl(bdl, &id_maps)?;
+        let zones = zones_from_ctehexml(&d.zonas, &id_maps);
         let walls = walls_from_bdl(bdl, &id_maps)?;
-        let (windows, shades) = windows_and_shades_from_bdl(bdl, &walls, &id_maps);
+        let (windows, shades) = windows_and_shades_from_bdl(bdl, &walls, &id_maps)?;
         let thermal_bridges = thermal_bridges_from_bdl(bdl);
 
         // Completa metadatos desde ctehexml y el bdl
@@ -95,12 +84,17 @@ impl TryFrom<&ctehexml::CtehexmlData> for Model {
                 .as_str()
                 .try_into()
                 .map_err(|e| anyhow!("ERROR: {}", e))?,
+            latitude: dg.latitud,
+            longitude: dg.longitud,
+            province: Some(dg.provincia.trim().to_string()).filter(|p| !p.is_empty()),
             global_ventilation_l_s: if is_dwelling {
                 Some(dg.valor_impulsion_aire)
             } else {
                 None
             },
             n50_test_ach: dg.valor_n50_medido,
+            extra_leakage: Vec::new(),
+            dhw: None,
             d_perim_insulation,
             rn_perim_insulation,
         };
@@ -111,15 +105,18 @@ impl TryFrom<&ctehexml::CtehexmlData> for Model {
 
         let model = Model {
             meta,
+            floors,
             walls,
             windows,
             thermal_bridges,
             shades,
             spaces,
+            zones,
             cons,
             schedules,
             loads,
             thermostats,
+            radiant_surfaces: Vec::new(),
             overrides: PropsOverrides::default(),
             extra: None,
         };
@@ -127,6 +124,22 @@ impl TryFrom<&ctehexml::CtehexmlData> for Model {
     }
 }
 
+/// Construye lista de plantas a partir de datos BDL (Data)
+fn floors_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<Vec<Floor>, Error> {
+    bdl.floors
+        .iter()
+        .map(|f| {
+            Ok(Floor {
+                id: id_maps.floor_id(&f.name)?,
+                name: f.name.clone(),
+                z: f.z,
+                height: fround2(f.height),
+                multiplier: f.multiplier,
+            })
+        })
+        .collect::<Result<Vec<Floor>, Error>>()
+}
+
 /// Construye diccionario de espacios a partir de datos BDL (Data)
 fn spaces_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<Vec<Space>, Error> {
     bdl.spaces
@@ -143,6 +156,7 @@ fn spaces_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<Vec<Space>, Error> {
                 id: id_maps.space_id(&s.name)?,
                 name: s.name.clone(),
                 z: s.z,
+                floor: Some(id_maps.floor_id(&s.floor)?),
                 height: fround2(s.height),
                 inside_tenv: s.insidete,
                 multiplier: s.multiplier * s.floor_multiplier,
@@ -215,18 +229,16 @@ fn wall_geometry(wall: &hulc::bdl::Wall, bdl: &Data) -> WallGeom {
         (Some("TOP"), None) => {
             // Giramos el polígono según la desviación respecto al norte del opaco y el espacio
             // El giro global del edificio respecto al norte ya está incluido
-            let azimuth = orientation_bdl_to_52016(
-                space.angle_with_building_north + wall.angle_with_space_north,
-            );
+            let azimuth =
+                azimuth_bdl_to_model(space.angle_with_building_north + wall.angle_with_space_north);
             space_polygon.rotate(azimuth.to_radians()).as_vec()
         }
         // 4. Elementos BOTTOM definidos por la geometría de su espacio
         (Some("BOTTOM"), None) => {
             // Giramos el polígono según la desviación respecto al norte del opaco y el espacio
             // El giro global del edificio respecto al norte ya está incluido
-            let azimuth = orientation_bdl_to_52016(
-                space.angle_with_building_north + wall.angle_with_space_north,
-            );
+            let azimuth =
+                azimuth_bdl_to_model(space.angle_with_building_north + wall.angle_with_space_north);
             // Hacemos un mirror (y -> -y para cada punto) sobre el eje X para que el giro del tilt 180 lo deje igual
             space_polygon
                 .rotate(azimuth.to_radians())
@@ -254,7 +266,7 @@ fn wall_geometry(wall: &hulc::bdl::Wall, bdl: &Data) -> WallGeom {
     };
 
     WallGeom {
-        azimuth: fround2(orientation_bdl_to_52016(
+        azimuth: fround2(azimuth_bdl_to_model(
             global_deviation + space.angle_with_building_north + wall.angle_with_space_north,
         )),
         tilt: fround2(wall.tilt),
@@ -280,26 +292,75 @@ fn walls_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<Vec<Wall>, Error> {
                 },
                 bounds: wall.bounds.into(),
                 geometry: wall_geometry(wall, bdl),
+                ventilated: ventilated_cavity_from_bdl(wall, bdl),
+                perim_insulation: None,
             })
         })
         .collect::<Result<Vec<Wall>, _>>()
 }
 
+/// Cámara de aire ventilada de un muro exterior, identificada a partir de las capas de su construcción
+/// HULC nombra las capas como "Cámara de aire ligeramente ventilada ..." o "Cámara de aire muy ventilada ..."
+fn ventilated_cavity_from_bdl(wall: &hulc::bdl::Wall, bdl: &Data) -> Option<VentilatedCavity> {
+    if wall.bounds != bdl::BoundaryType::EXTERIOR {
+        return None;
+    }
+    let cons = bdl.db.wallcons.get(&wall.cons)?;
+    cons.material
+        .iter()
+        .zip(cons.thickness.iter())
+        .find_map(|(name, thickness)| {
+            let ventilation = if !name.starts_with("Cámara de aire ") {
+                return None;
+            } else if name.contains("ligeramente ventilada") {
+                CavityVentilation::SLIGHTLY
+            } else if name.contains("muy ventilada") {
+                CavityVentilation::WELL
+            } else {
+                return None;
+            };
+            Some(VentilatedCavity {
+                thickness: *thickness,
+                ventilation,
+            })
+        })
+}
+
 /// Desviación global del edificio respecto al norte
 /// Sigue la misma referencia al Norte que el azimuth, pero un criterio de signos distinto: N=0, E = -90, O=90.
+///
+/// Se toma el AZIMUTH de BUILD-PARAMETERS y, si no está definido, el de GENERAL-DATA.
+/// Esta desviación se aplica a la posición y azimut de todos los opacos, y por tanto a sus huecos, y sombras.
 fn global_deviation_from_north(bdl: &Data) -> f32 {
-    bdl.meta
-        .get(&BdlBlockType::BuildParameters)
-        .map(|params| params.attrs.get_f32_or_default("AZIMUTH"))
+    [BdlBlockType::BuildParameters, BdlBlockType::GeneralData]
+        .iter()
+        .filter_map(|block| bdl.meta.get(block))
+        .find_map(|params| params.attrs.get_f32("AZIMUTH").ok())
         .unwrap_or_default()
 }
 
+/// Puerta opaca de una construcción de hueco BDL
+///
+/// Son puertas opacas las construcciones marcadas como puerta (ISDOOR) con un porcentaje de marco del 100%,
+/// y toman la transmitancia (con su incremento) y la absortividad del marco.
+fn opaque_door_from_bdl(bdl: &Data, consname: &str) -> Option<OpaqueDoor> {
+    let cons = bdl.db.wincons.get(consname)?;
+    if !cons.isdoor || cons.framefrac < 1.0 {
+        return None;
+    }
+    let frame = bdl.db.frames.get(&cons.frame)?;
+    Some(OpaqueDoor {
+        u_value: fround2((1.0 + cons.deltau / 100.0) * frame.conductivity),
+        absorptance: frame.absorptivity,
+    })
+}
+
 /// Construye huecos de la envolvente a partir de datos BDL
 fn windows_and_shades_from_bdl(
     bdl: &Data,
     walls: &[Wall],
     id_maps: &IdMaps,
-) -> (Vec<Window>, Vec<Shade>) {
+) -> Result<(Vec<Window>, Vec<Shade>), Error> {
     //TODO: falta por trasladar la definición de lamas (louvres)
     let mut windows = vec![];
     let mut shades = vec![];
@@ -320,6 +381,12 @@ fn windows_and_shades_from_bdl(
                 height: win.height,
                 setback: win.setback,
             },
+            louvers: None,
+            night_insulation: None,
+            night_insulation_schedule: None,
+            double_window: None,
+            door_c_100: None,
+            opaque_door: opaque_door_from_bdl(bdl, &win.cons),
         };
 
         // Sombras de contorno de huecos
@@ -355,6 +422,7 @@ fn windows_and_shades_from_bdl(
                     id: uuid_from_obj(&format!("{:?}-{:?}-{:?}", win.name, overhang, geometry)),
                     name: format!("{}_overhang", win.name),
                     geometry,
+                    schedule: None,
                 });
             };
 
@@ -377,6 +445,7 @@ fn windows_and_shades_from_bdl(
                     id: uuid_from_obj(&format!("{:?}-{:?}-{:?}", win.name, lfin, geometry)),
                     name: format!("{}_left_fin", win.name),
                     geometry,
+                    schedule: None,
                 });
             }
 
@@ -400,16 +469,17 @@ fn windows_and_shades_from_bdl(
                     id: uuid_from_obj(&format!("{:?}-{:?}-{:?}", win.name, rfin, geometry)),
                     name: format!("{}_right_fin", win.name),
                     geometry,
+                    schedule: None,
                 });
             }
         }
     }
 
     // Añade sombras independientes
-    let othershades = shades_from_bdl(bdl);
+    let othershades = shades_from_bdl(bdl, id_maps)?;
     shades.extend_from_slice(&othershades);
 
-    (windows, shades)
+    Ok((windows, shades))
 }
 
 /// Construye puentes térmicos de la envolvente a partir de datos BDL
@@ -438,6 +508,7 @@ fn thermal_bridges_from_bdl(bdl: &Data) -> Vec<ThermalBridge> {
                 kind,
                 l: fround2(tb.length.unwrap_or(0.0)),
                 psi: tb.psi,
+                space: None,
             }
         })
         .collect()
@@ -450,9 +521,11 @@ fn thermal_bridges_from_bdl(bdl: &Data) -> Vec<ThermalBridge> {
 /// Las BUILDING-SHADE, además, se pueden definir:
 /// - por geometría, con X, Y, Z, WIDTH, HEIGHT
 /// - por vértices
+///
+/// Las sombras pueden tener un horario de transmisividad (SHADE-SCHEDULE), p.e. para vegetación de hoja caduca
 /// Ver BDL Topics p.158
 /// Convertimos todos los casos a geometría como la de los muros: position + tilt + azimuth + Pol2D
-fn shades_from_bdl(bdl: &Data) -> Vec<Shade> {
+fn shades_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<Vec<Shade>, Error> {
     bdl.shadings
         .iter()
         .filter_map(|sh| {
@@ -471,7 +544,7 @@ fn shades_from_bdl(bdl: &Data) -> Vec<Shade> {
                         * point![geom.x, geom.y, geom.z],
                 );
                 // El azimuth acumula la orientación de la sombra y la desviación del norte (tienen el mismo criterio de giro)
-                let azimuth = fround2(orientation_bdl_to_52016(geom.azimuth + global_deviation));
+                let azimuth = fround2(azimuth_bdl_to_model(geom.azimuth + global_deviation));
                 let polygon = vec![
                     point![0.0, 0.0],
                     point![geom.width, 0.0],
@@ -534,7 +607,15 @@ fn shades_from_bdl(bdl: &Data) -> Vec<Shade> {
                 panic!("Definición inesperada de elemento de sombra");
             };
 
-            Some(Shade {
+            let schedule = match sh.schedule.as_ref() {
+                Some(sch) => match id_maps.schedule_year_id(sch) {
+                    Ok(id) => Some(id),
+                    Err(e) => return Some(Err(e)),
+                },
+                None => None,
+            };
+
+            Some(Ok(Shade {
                 id,
                 name,
                 geometry: WallGeom {
@@ -543,7 +624,8 @@ fn shades_from_bdl(bdl: &Data) -> Vec<Shade> {
                     position,
                     polygon,
                 },
-            })
+                schedule,
+            }))
         })
         .collect()
 }
@@ -566,6 +648,7 @@ fn cons_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<ConsDb, Error> {
                 MatProps::Resistance {
                     resistance: material.resistance.unwrap_or_default(),
                     vapour_diff: None,
+                    thickness: material.resistance_thickness,
                 }
             },
         });
@@ -578,6 +661,7 @@ fn cons_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<ConsDb, Error> {
             name: name.clone(),
             u_value: glass.conductivity,
             g_gln: glass.g_gln,
+            g_angular: None,
         });
     }
     let mut frames = Vec::new();
@@ -615,13 +699,18 @@ fn cons_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<ConsDb, Error> {
                     .iter()
                     .copied()
                     .zip(cons.thickness.iter().copied())
-                    .map(|(material, e)| Layer { material, e })
+                    .map(|(material, e)| Layer {
+                        material,
+                        e,
+                        e_max: None,
+                    })
                     .collect();
                 let wc = WallCons {
                     id: id_maps.wallcons_id(wcons)?,
                     name: cons.name.clone(),
                     layers,
                     absorptance: cons.absorptance,
+                    emissivity: None,
                 };
                 wallcons.push(wc);
             }
@@ -681,9 +770,17 @@ fn cons_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<ConsDb, Error> {
                     glass,
                     frame,
                     f_f: cons.framefrac,
+                    frame_width: bdl
+                        .db
+                        .frames
+                        .get(&cons.frame)
+                        .map(|f| f.width)
+                        .filter(|w| *w > 0.0),
                     delta_u: cons.deltau,
+                    shutter_box: None,
                     g_glshwi: cons.gglshwi,
                     c_100: cons.infcoeff,
+                    c_100_parts: None,
                 }
             }
             _ => {
@@ -758,10 +855,16 @@ fn schedules_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<SchedulesDb, Error
                     }
                     n => bail!("Longitud {} incorrecta de horario semanal: {}", n, sch.name),
                 };
+                let holiday = sch
+                    .holiday
+                    .as_ref()
+                    .map(|name| id_maps.schedule_day_id(name))
+                    .transpose()?;
                 week.push(ScheduleWeek {
                     id,
                     name: sch.name.clone(),
                     values,
+                    holiday,
                 });
             }
             bdl::Schedule::Year(sch) => {
@@ -857,11 +960,52 @@ fn loads_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<Vec<SpaceLoads>, Error
                 id_maps.schedule_year_id(space_cond.attrs.get_str("LIGHTING-SCHEDULE")?)?,
             ),
             area_per_person,
+            air_quality: None,
         });
     }
     Ok(space_loads)
 }
 
+/// Zonas térmicas a partir de las zonas de los sistemas GT
+///
+/// Los espacios de las zonas GT servidas por un mismo sistema secundario se agrupan en una única
+/// zona térmica, con el nombre del sistema. Las zonas GT sin sistema asignado forman zonas independientes.
+/// Las zonas GT de espacios no identificados (p.e. datos de sistemas desactualizados) se descartan con un aviso.
+fn zones_from_ctehexml(zonas: &[ctehexml::GtZoneSystem], id_maps: &IdMaps) -> Vec<ThermalZone> {
+    let mut zones: Vec<ThermalZone> = Vec::new();
+    let mut system_zones: BTreeMap<&str, usize> = BTreeMap::new();
+
+    for zona in zonas {
+        let space = match id_maps.space_id(&zona.space) {
+            Ok(space) => space,
+            Err(e) => {
+                warn!("Se descarta la zona {}: {}", zona.name, e);
+                continue;
+            }
+        };
+        match zona.system.as_deref() {
+            Some(system) if system_zones.contains_key(system) => {
+                zones[system_zones[system]].spaces.push(space);
+            }
+            Some(system) => {
+                system_zones.insert(system, zones.len());
+                zones.push(ThermalZone {
+                    id: uuid_from_str(system),
+                    name: system.to_string(),
+                    spaces: vec![space],
+                });
+            }
+            None => zones.push(ThermalZone {
+                id: uuid_from_obj(zona),
+                name: zona.name.clone(),
+                spaces: vec![space],
+            }),
+        }
+    }
+
+    zones
+}
+
 /// Condiciones operacionales de espacios a partir de datos BDL
 fn thermostats_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<Vec<Thermostat>, Error> {
     let mut space_conds = Vec::new();
@@ -890,6 +1034,7 @@ fn thermostats_from_bdl(bdl: &Data, id_maps: &IdMaps) -> Result<Vec<Thermostat>,
 
 /// Mapping de nombres a ids
 struct IdMaps<'a> {
+    floors: BTreeMap<&'a str, Uuid>,
     spaces: BTreeMap<&'a str, Uuid>,
     walls: BTreeMap<&'a str, Uuid>,
     wallcons: BTreeMap<&'a str, Uuid>,
@@ -911,6 +1056,14 @@ impl<'a> IdMaps<'a> {
             .ok_or_else(|| format_err!("Muro {} no identificado", name.as_ref()))
     }
 
+    /// Localiza id de planta desde nombre
+    fn floor_id<T: AsRef<str>>(&self, name: T) -> Result<Uuid, anyhow::Error> {
+        self.floors
+            .get(name.as_ref())
+            .copied()
+            .ok_or_else(|| format_err!("Planta {} no identificada", name.as_ref()))
+    }
+
     /// Localiza id de espacio desde nombre
     fn space_id<T: AsRef<str>>(&self, name: T) -> Result<Uuid, anyhow::Error> {
         self.spaces
@@ -985,6 +1138,11 @@ impl<'a> IdMaps<'a> {
 
     fn new(bdl: &'a Data) -> Self {
         IdMaps {
+            floors: bdl
+                .floors
+                .iter()
+                .map(|f| (f.name.as_str(), uuid_from_obj(&f)))
+                .collect::<BTreeMap<&str, Uuid>>(),
             spaces: bdl
                 .spaces
                 .iter()
@@ -0,0 +1,245 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Conversión desde el formato JSON del editor web de EnvolventeCTE
+//!
+//! El editor web usa la estructura JSON antigua (cte::Model), con elementos y construcciones
+//! indexados por nombre. Los identificadores del modelo se obtienen a partir del tipo de elemento
+//! y su nombre, de modo que son estables entre importaciones.
+//!
+//! Los opacos y huecos sin definición geométrica se generan con una geometría rectangular
+//! de la superficie indicada, sin posición, que es suficiente para el cálculo de indicadores.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Error};
+
+use super::legacy::{LegacyModel, LegacyWallCons, LegacyWinCons};
+use crate::energy::F_W;
+use crate::utils::{azimuth_bdl_to_model, uuid_from_str};
+use crate::{
+    point, ConsDb, Frame, Glass, Layer, MatProps, Material, Model, Polygon, Space, ThermalBridge,
+    Uuid, Wall, WallCons, WallGeom, WinCons, WinGeom, WinPropsOverrides, Window,
+};
+
+/// Genera un modelo a partir del JSON del editor web de EnvolventeCTE
+pub fn from_envolventecte_json(data: &str) -> Result<Model, Error> {
+    let legacy: LegacyModel = serde_json::from_str(data)?;
+    Model::try_from(legacy)
+}
+
+impl TryFrom<LegacyModel> for Model {
+    type Error = Error;
+
+    fn try_from(web: LegacyModel) -> Result<Self, Self::Error> {
+        let space_ids = ids_by_name("space", web.spaces.keys());
+        let wall_ids = ids_by_name("wall", web.walls.keys());
+        let wallcons_ids = ids_by_name("wallcons", web.wallcons.keys());
+        let wincons_ids = ids_by_name("wincons", web.windowcons.keys());
+
+        let spaces = web
+            .spaces
+            .iter()
+            .map(|(name, s)| Space {
+                id: space_ids[name],
+                name: name.clone(),
+                multiplier: s.multiplier,
+                kind: s.kind,
+                inside_tenv: s.inside_tenv,
+                height: s.height,
+                z: s.z,
+                floor: None,
+                loads: None,
+                thermostat: None,
+                n_v: s.n_v,
+                illuminance: None,
+            })
+            .collect();
+
+        // Superficie de huecos de cada opaco, para obtener su superficie bruta
+        let mut win_area_by_wall: BTreeMap<&str, f32> = BTreeMap::new();
+        for win in web.windows.values() {
+            *win_area_by_wall.entry(win.wall.as_str()).or_default() += win.a;
+        }
+
+        let mut walls = Vec::with_capacity(web.walls.len());
+        for (name, w) in &web.walls {
+            let next_to = match &w.nextto {
+                Some(nextto) => Some(find_id(&space_ids, nextto, "Espacio adyacente", name)?),
+                None => None,
+            };
+            let area_gross = w.a + win_area_by_wall.get(name.as_str()).unwrap_or(&0.0);
+            walls.push(Wall {
+                id: wall_ids[name],
+                name: name.clone(),
+                bounds: w.bounds,
+                cons: find_id(&wallcons_ids, &w.cons, "Construcción", name)?,
+                space: find_id(&space_ids, &w.space, "Espacio", name)?,
+                next_to,
+                geometry: WallGeom {
+                    tilt: w.tilt,
+                    azimuth: azimuth_bdl_to_model(w.azimuth),
+                    position: w.position,
+                    polygon: w
+                        .polygon
+                        .clone()
+                        .unwrap_or_else(|| square_polygon(area_gross)),
+                },
+                ventilated: None,
+                perim_insulation: None,
+            });
+        }
+
+        let mut model = Model {
+            meta: web.meta,
+            spaces,
+            walls,
+            ..Default::default()
+        };
+
+        for (name, w) in &web.windows {
+            let id = uuid_from_str(&format!("window-{}", name));
+            let side = w.a.sqrt();
+            let (width, height) = match (w.width, w.height) {
+                (Some(width), Some(height)) => (width, height),
+                _ => (side, side),
+            };
+            model.windows.push(Window {
+                id,
+                name: name.clone(),
+                cons: find_id(&wincons_ids, &w.cons, "Construcción", name)?,
+                wall: find_id(&wall_ids, &w.wall, "Opaco", name)?,
+                geometry: WinGeom {
+                    position: w.position,
+                    height,
+                    width,
+                    setback: w.setback.unwrap_or_default(),
+                },
+                ..Default::default()
+            });
+            model.overrides.windows.insert(
+                id,
+                WinPropsOverrides {
+                    u_value: None,
+                    f_shobst: Some(w.fshobst),
+                },
+            );
+        }
+
+        model.thermal_bridges = web
+            .thermal_bridges
+            .iter()
+            .map(|(name, tb)| ThermalBridge {
+                id: uuid_from_str(&format!("thermal_bridge-{}", name)),
+                name: name.clone(),
+                kind: tb.kind,
+                l: tb.l,
+                psi: tb.psi,
+                space: None,
+            })
+            .collect();
+
+        model.cons = cons_from_web(&web.wallcons, &web.windowcons, &wallcons_ids, &wincons_ids);
+
+        Ok(model)
+    }
+}
+
+/// Construcciones del modelo a partir de las construcciones del editor web
+///
+/// Cada construcción de opaco se convierte en una composición de una sola capa, de un material
+/// definido por resistencia, y cada construcción de hueco en un vidrio y un marco con la U del hueco
+fn cons_from_web(
+    wallcons: &BTreeMap<String, LegacyWallCons>,
+    wincons: &BTreeMap<String, LegacyWinCons>,
+    wallcons_ids: &BTreeMap<String, Uuid>,
+    wincons_ids: &BTreeMap<String, Uuid>,
+) -> ConsDb {
+    let mut cons = ConsDb::default();
+    for (name, wc) in wallcons {
+        let material = Material {
+            id: uuid_from_str(&format!("material-{}", name)),
+            name: name.clone(),
+            properties: MatProps::Resistance {
+                resistance: wc.r_intrinsic,
+                vapour_diff: None,
+                thickness: Some(wc.thickness),
+            },
+        };
+        cons.wallcons.push(WallCons {
+            id: wallcons_ids[name],
+            name: name.clone(),
+            layers: vec![Layer {
+                material: material.id,
+                e: wc.thickness,
+                e_max: None,
+            }],
+            absorptance: wc.absorptance,
+            emissivity: None,
+        });
+        cons.materials.push(material);
+    }
+    for (name, wc) in wincons {
+        let glass = Glass {
+            id: uuid_from_str(&format!("glass-{}", name)),
+            name: name.clone(),
+            u_value: wc.U,
+            // Relación inversa a la usada en el modelo, g_gl;wi = F_w · g_gl;n
+            g_gln: wc.gglwi / F_W,
+            g_angular: None,
+        };
+        let frame = Frame {
+            id: uuid_from_str(&format!("frame-{}", name)),
+            name: name.clone(),
+            u_value: wc.U,
+            ..Default::default()
+        };
+        cons.wincons.push(WinCons {
+            id: wincons_ids[name],
+            name: name.clone(),
+            glass: glass.id,
+            frame: frame.id,
+            f_f: wc.Ff,
+            delta_u: 0.0,
+            g_glshwi: Some(wc.gglshwi),
+            c_100: wc.C_100,
+            ..Default::default()
+        });
+        cons.glasses.push(glass);
+        cons.frames.push(frame);
+    }
+    cons
+}
+
+/// Diccionario de nombre a id de los elementos de un tipo
+fn ids_by_name<'a>(kind: &str, names: impl Iterator<Item = &'a String>) -> BTreeMap<String, Uuid> {
+    names
+        .map(|name| (name.clone(), uuid_from_str(&format!("{}-{}", kind, name))))
+        .collect()
+}
+
+/// Localiza el id del elemento referenciado por nombre desde el elemento `from`
+fn find_id(
+    ids: &BTreeMap<String, Uuid>,
+    name: &str,
+    what: &str,
+    from: &str,
+) -> Result<Uuid, Error> {
+    ids.get(name)
+        .copied()
+        .ok_or_else(|| anyhow!("{} \"{}\" de \"{}\" no encontrado", what, name, from))
+}
+
+/// Polígono cuadrado con la superficie indicada, en coordenadas de opaco
+///
+/// Se usa para los opacos que no tienen definición geométrica
+fn square_polygon(area: f32) -> Polygon {
+    let side = area.sqrt();
+    vec![
+        point![0.0, 0.0],
+        point![side, 0.0],
+        point![side, side],
+        point![0.0, side],
+    ]
+}
@@ -0,0 +1,155 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Estructura JSON antigua (cte::Model), usada también por el editor web de EnvolventeCTE
+//!
+//! Los elementos y construcciones se indexan por nombre y las referencias entre elementos usan nombres.
+//!
+//! Diferencias de criterio con el modelo:
+//! - la orientación de los opacos es geográfica (N=0, E=+90, W=-90), como en BDL
+//! - la superficie de los opacos es neta (descontados sus huecos)
+//! - la geometría de opacos y huecos es opcional y, si no se indica, solo se conoce su superficie
+//! - las construcciones de opacos se definen por su resistencia intrínseca y las de huecos por su U global
+//! - el factor de obstáculos remotos de los huecos incluye el efecto de las sombras
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::{default_1, default_true},
+    BoundaryType, Meta, Point2, Point3, Polygon, SpaceType, ThermalBridgeKind,
+};
+
+/// Modelo con la estructura antigua, con elementos indexados por nombre
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(super) struct LegacyModel {
+    #[serde(default)]
+    pub meta: Meta,
+    pub spaces: BTreeMap<String, LegacySpace>,
+    pub walls: BTreeMap<String, LegacyWall>,
+    #[serde(default)]
+    pub windows: BTreeMap<String, LegacyWindow>,
+    #[serde(default)]
+    pub thermal_bridges: BTreeMap<String, LegacyThermalBridge>,
+    pub wallcons: BTreeMap<String, LegacyWallCons>,
+    pub windowcons: BTreeMap<String, LegacyWinCons>,
+}
+
+/// Espacio
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct LegacySpace {
+    /// Superficie útil [m²]
+    /// Es un dato informativo, que no se usa en la importación
+    #[serde(default)]
+    pub area: f32,
+    /// Altura bruta (suelo a suelo) [m]
+    pub height: f32,
+    /// Cota del suelo del espacio [m]
+    #[serde(default)]
+    pub z: f32,
+    #[serde(default = "default_1")]
+    pub multiplier: f32,
+    #[serde(rename = "type", default)]
+    pub kind: SpaceType,
+    #[serde(default = "default_true")]
+    pub inside_tenv: bool,
+    /// Tasa de renovación de aire [1/h]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n_v: Option<f32>,
+}
+
+/// Opaco
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct LegacyWall {
+    /// Superficie neta, sin huecos [m²]
+    #[serde(rename = "A")]
+    pub a: f32,
+    pub bounds: BoundaryType,
+    /// Nombre de la construcción
+    pub cons: String,
+    /// Nombre del espacio al que pertenece
+    pub space: String,
+    /// Nombre del espacio adyacente
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nextto: Option<String>,
+    /// Inclinación [º]
+    pub tilt: f32,
+    /// Orientación geográfica (N=0, E=+90, W=-90) [º]
+    pub azimuth: f32,
+    /// Posición del opaco, en coordenadas de espacio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Point3>,
+    /// Polígono del opaco, en coordenadas de opaco
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub polygon: Option<Polygon>,
+}
+
+/// Hueco
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct LegacyWindow {
+    /// Superficie del hueco [m²]
+    #[serde(rename = "A")]
+    pub a: f32,
+    /// Nombre de la construcción
+    pub cons: String,
+    /// Nombre del opaco al que pertenece
+    pub wall: String,
+    /// Factor de obstáculos remotos [-]
+    #[serde(default = "default_1")]
+    pub fshobst: f32,
+    /// Posición del hueco, en coordenadas de opaco
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Point2>,
+    /// Anchura del hueco [m]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<f32>,
+    /// Altura del hueco [m]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f32>,
+    /// Retranqueo del hueco [m]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setback: Option<f32>,
+}
+
+/// Puente térmico
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct LegacyThermalBridge {
+    /// Longitud [m]
+    #[serde(rename = "L")]
+    pub l: f32,
+    /// Transmitancia térmica lineal [W/mK]
+    pub psi: f32,
+    #[serde(default)]
+    pub kind: ThermalBridgeKind,
+}
+
+/// Construcción de opaco
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct LegacyWallCons {
+    /// Espesor total [m]
+    #[serde(default)]
+    pub thickness: f32,
+    /// Resistencia intrínseca, sin resistencias superficiales [m²K/W]
+    #[serde(rename = "R_intrinsic")]
+    pub r_intrinsic: f32,
+    /// Absortividad [-]
+    pub absorptance: f32,
+}
+
+/// Construcción de hueco
+#[allow(non_snake_case)]
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct LegacyWinCons {
+    /// Transmitancia térmica global del hueco [W/m²K]
+    pub U: f32,
+    /// Fracción de marco [-]
+    pub Ff: f32,
+    /// Factor solar del hueco sin protección solar activada (g_gl;wi) [-]
+    pub gglwi: f32,
+    /// Factor solar del hueco con la protección solar activada (g_gl;sh;wi) [-]
+    pub gglshwi: f32,
+    /// Permeabilidad al aire a 100 Pa [m3/hm2]
+    pub C_100: f32,
+}
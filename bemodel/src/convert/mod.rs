@@ -4,5 +4,18 @@
 
 //! Conversión a modelos energéticos BeModel desde varios formatos:
 //! - Herramienta unificada LIDER-CALENER (HULC)
+//! - Editor web de EnvolventeCTE, archivo JSON con elementos indexados por nombre
+//!
+//! Conversión desde modelos BeModel a otros formatos:
+//! - Formato JSON antiguo (cte::Model), con elementos indexados por nombre
+//! - Herramienta unificada LIDER-CALENER (HULC), archivo .ctehexml con geometría y construcciones
 
 pub(crate) mod from_ctehexml;
+mod from_envolventecte;
+mod legacy;
+mod to_ctehexml;
+mod to_legacy;
+
+pub use from_envolventecte::from_envolventecte_json;
+pub use to_ctehexml::to_ctehexml;
+pub use to_legacy::to_legacy_json;
@@ -0,0 +1,851 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Conversión de Model a un archivo .ctehexml mínimo
+//!
+//! Se genera el bloque DatosGenerales y la entrada gráfica de LIDER (EntradaGraficaLIDER) con el BDL
+//! de la geometría y las construcciones del modelo: materiales, composiciones de capas, vidrios, marcos,
+//! huecos, plantas, espacios, opacos, ventanas, puentes térmicos y sombras.
+//!
+//! Todos los opacos se definen mediante polígono, posición, azimut e inclinación, sin usar LOCATION,
+//! y las sombras mediante sus vértices en coordenadas globales.
+//! No se incluyen los sistemas, ni las condiciones operacionales (cargas, consignas y horarios)
+//! de los espacios, que solo se referencian por nombre.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use anyhow::{format_err, Error};
+use nalgebra::point;
+
+use super::to_legacy::{unique_names, NameMap};
+use crate::{
+    utils::azimuth_model_to_bdl, BoundaryType, MatProps, Model, Point2, Point3, Space, SpaceType,
+    Tilt, Uuid, WallCons, WallGeom,
+};
+
+/// Absortividad por defecto de las construcciones de opacos en BDL [-]
+const DEFAULT_ABSORPTANCE: f32 = 0.6;
+
+/// Genera un archivo .ctehexml con la geometría y construcciones del modelo
+pub fn to_ctehexml(model: &Model) -> Result<String, Error> {
+    let bdl = to_bdl(model)?;
+    let meta = &model.meta;
+    let climate = meta.climate.to_string();
+    let tipo_vivienda = match (meta.is_dwelling, meta.num_dwellings) {
+        (true, n) if n > 1 => "Bloque",
+        (true, _) => "Unifamiliar",
+        (false, _) => "Terciario",
+    };
+
+    let mut out = String::new();
+    writeln!(out, "<?xml version=\"1.0\"?>")?;
+    writeln!(out, "<CTE-HE-XML>")?;
+    writeln!(out, "    <DatosGenerales>")?;
+    writeln!(out, "        <nomPro>{}</nomPro>", xml_escape(&meta.name))?;
+    writeln!(
+        out,
+        "        <tipoVivienda>{}</tipoVivienda>",
+        tipo_vivienda
+    )?;
+    writeln!(
+        out,
+        "        <tipoDefinicion>{}</tipoDefinicion>",
+        if meta.is_new_building {
+            "Nuevo"
+        } else {
+            "Certificacion"
+        }
+    )?;
+    writeln!(
+        out,
+        "        <zonaClimatica>{}</zonaClimatica>",
+        climate.trim_end_matches('c')
+    )?;
+    writeln!(
+        out,
+        "        <numViviendasBloque>{}</numViviendasBloque>",
+        meta.num_dwellings
+    )?;
+    writeln!(
+        out,
+        "        <valorImpulsionAire>{:.2}</valorImpulsionAire>",
+        meta.global_ventilation_l_s.unwrap_or_default()
+    )?;
+    writeln!(
+        out,
+        "        <pathArchivoMeteorologicoSeleccionado>C:\\ProgramasCTEyCEE\\DatosClimaticos\\GENERICOS\\zona{}.bin</pathArchivoMeteorologicoSeleccionado>",
+        climate
+    )?;
+    writeln!(
+        out,
+        "        <ensayoPermeabilidad>{}</ensayoPermeabilidad>",
+        if meta.n50_test_ach.is_some() {
+            "SI"
+        } else {
+            "NO"
+        }
+    )?;
+    writeln!(
+        out,
+        "        <ValorN50Medido>{}</ValorN50Medido>",
+        meta.n50_test_ach.unwrap_or_default()
+    )?;
+    writeln!(out, "    </DatosGenerales>")?;
+    writeln!(
+        out,
+        "    <EntradaGraficaLIDER><![CDATA[{}]]>    </EntradaGraficaLIDER>",
+        bdl
+    )?;
+    writeln!(out, "</CTE-HE-XML>")?;
+    Ok(out)
+}
+
+/// Nombres únicos de los elementos del modelo, ya adaptados a BDL
+struct BdlNames {
+    floors: NameMap,
+    spaces: NameMap,
+    walls: NameMap,
+    windows: NameMap,
+    tbs: NameMap,
+    shades: NameMap,
+    wallcons: NameMap,
+    wincons: NameMap,
+    materials: NameMap,
+    glasses: NameMap,
+    frames: NameMap,
+}
+
+impl BdlNames {
+    fn new(model: &Model) -> Self {
+        Self {
+            floors: bdl_names(model.floors.iter().map(|e| (e.id, e.name.as_str()))),
+            spaces: bdl_names(model.spaces.iter().map(|e| (e.id, e.name.as_str()))),
+            walls: bdl_names(model.walls.iter().map(|e| (e.id, e.name.as_str()))),
+            windows: bdl_names(model.windows.iter().map(|e| (e.id, e.name.as_str()))),
+            tbs: bdl_names(
+                model
+                    .thermal_bridges
+                    .iter()
+                    .map(|e| (e.id, e.name.as_str())),
+            ),
+            shades: bdl_names(model.shades.iter().map(|e| (e.id, e.name.as_str()))),
+            wallcons: bdl_names(model.cons.wallcons.iter().map(|e| (e.id, e.name.as_str()))),
+            wincons: bdl_names(model.cons.wincons.iter().map(|e| (e.id, e.name.as_str()))),
+            materials: bdl_names(model.cons.materials.iter().map(|e| (e.id, e.name.as_str()))),
+            glasses: bdl_names(model.cons.glasses.iter().map(|e| (e.id, e.name.as_str()))),
+            frames: bdl_names(model.cons.frames.iter().map(|e| (e.id, e.name.as_str()))),
+        }
+    }
+}
+
+/// Planta BDL, con los espacios que contiene
+struct BdlFloor<'a> {
+    name: String,
+    z: f32,
+    height: f32,
+    multiplier: f32,
+    spaces: Vec<&'a Space>,
+}
+
+/// Genera el BDL de la entrada gráfica de LIDER
+fn to_bdl(model: &Model) -> Result<String, Error> {
+    let names = BdlNames::new(model);
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "$ +----------------------------------------------------+"
+    )?;
+    writeln!(
+        out,
+        "$ |         FICHERO GENERADO CON CTE-HE-2019           |"
+    )?;
+    writeln!(
+        out,
+        "$ +----------------------------------------------------+"
+    )?;
+    writeln!(out, "$")?;
+    writeln!(out, "CAMBIO = NO")?;
+    writeln!(out, "CAMBIO-CALENER = NO")?;
+    write_general_data(&mut out, model)?;
+    writeln!(out, "TEMPLARY = USER")?;
+    write_db(&mut out, model, &names)?;
+    write_geometry(&mut out, model, &names)?;
+
+    // Puentes térmicos
+    for tb in &model.thermal_bridges {
+        write_block(
+            &mut out,
+            &names.tbs[&tb.id],
+            "THERMAL-BRIDGE",
+            &[
+                ("LONG-TOTAL", num(tb.l)),
+                ("DEFINICION", "2".into()),
+                ("TTL", num(tb.psi)),
+                ("FRSI", "0".into()),
+            ],
+        )?;
+    }
+
+    // Sombras, mediante sus vértices en coordenadas globales
+    for shade in &model.shades {
+        let vertices = match global_vertices(&shade.geometry) {
+            Some(vertices) if vertices.len() > 2 => vertices,
+            _ => continue,
+        };
+        let mut attrs = vec![("TRAN", "0".to_string()), ("REFL", "0.7".to_string())];
+        let keys: Vec<_> = (1..=vertices.len()).map(|i| format!("V{}", i)).collect();
+        for (key, v) in keys.iter().zip(vertices) {
+            attrs.push((key, format!("( {}, {}, {} )", v.x, v.y, v.z)));
+        }
+        write_block(&mut out, &names.shades[&shade.id], "BUILDING-SHADE", &attrs)?;
+    }
+
+    Ok(out)
+}
+
+/// Bloques de datos generales y parámetros del edificio
+fn write_general_data(out: &mut String, model: &Model) -> Result<(), Error> {
+    let meta = &model.meta;
+    write_block(
+        out,
+        "DATOS GENERALES",
+        "GENERAL-DATA",
+        &[
+            ("NAME-PROJECT", quoted(&meta.name)),
+            (
+                "TYPE-DEFINITION",
+                quoted(if meta.is_new_building {
+                    "Nuevo"
+                } else {
+                    "Certificacion"
+                }),
+            ),
+            ("ZONE", quoted(&meta.climate.to_string())),
+            ("NUM-VIVIENDAS", meta.num_dwellings.to_string()),
+        ],
+    )?;
+    write_block(
+        out,
+        "Edificio",
+        "BUILD-PARAMETERS",
+        &[
+            ("AZIMUTH", "0".into()),
+            ("D-AISLAMIENTO-PERIMETRAL", num(meta.d_perim_insulation)),
+            ("RA-AISLAMIENTO-PERIMETRAL", num(meta.rn_perim_insulation)),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Base de datos de materiales y construcciones
+fn write_db(out: &mut String, model: &Model, names: &BdlNames) -> Result<(), Error> {
+    let cons = &model.cons;
+
+    for mat in &cons.materials {
+        let (mut attrs, vapour_diff) = match mat.properties {
+            MatProps::Resistance {
+                resistance,
+                vapour_diff,
+                thickness,
+            } => {
+                let mut attrs = vec![("TYPE", "RESISTANCE".to_string())];
+                if let Some(thickness) = thickness {
+                    attrs.push(("THICKNESS", num(thickness)));
+                }
+                attrs.push(("RESISTANCE", num(resistance)));
+                (attrs, vapour_diff)
+            }
+            MatProps::Detailed {
+                specific_heat,
+                vapour_diff,
+                ..
+            }
+            | MatProps::MoistureDependent {
+                specific_heat,
+                vapour_diff,
+                ..
+            } => (
+                vec![
+                    ("TYPE", "PROPERTIES".to_string()),
+                    (
+                        "CONDUCTIVITY",
+                        num(mat.properties.conductivity(None).unwrap_or_default()),
+                    ),
+                    ("DENSITY", num(mat.properties.density().unwrap_or_default())),
+                    ("SPECIFIC-HEAT", num(specific_heat)),
+                ],
+                vapour_diff,
+            ),
+        };
+        if let Some(vapour_diff) = vapour_diff {
+            attrs.push(("VAPOUR-DIFFUSIVITY-FACTOR", num(vapour_diff)));
+        }
+        write_block(out, &names.materials[&mat.id], "MATERIAL", &attrs)?;
+    }
+
+    for wc in &cons.wallcons {
+        let materials = wc
+            .layers
+            .iter()
+            .map(|l| {
+                names
+                    .materials
+                    .get(&l.material)
+                    .map(|name| quoted(name))
+                    .ok_or_else(|| {
+                        format_err!(
+                            "Material {} de la construcción {} no encontrado",
+                            l.material,
+                            wc.name
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // HULC no admite capas de espesor variable, que se exportan con su espesor medio
+        let thicknesses: Vec<_> = wc.layers.iter().map(|l| num(l.e_mean())).collect();
+        write_block(
+            out,
+            &names.wallcons[&wc.id],
+            "LAYERS",
+            &[
+                ("GROUP", quoted("Capas")),
+                ("MATERIAL", format!("( {} )", materials.join(", "))),
+                ("THICKNESS", format!("( {} )", thicknesses.join(", "))),
+            ],
+        )?;
+    }
+
+    // Construcciones de opacos, compartidas por todos los opacos que las usan
+    for wc in &cons.wallcons {
+        write_block(
+            out,
+            &construction_name(names, wc),
+            "CONSTRUCTION",
+            &[
+                ("TYPE", "LAYERS".into()),
+                ("LAYERS", quoted(&names.wallcons[&wc.id])),
+                ("ABSORPTANCE", num(wc.absorptance)),
+            ],
+        )?;
+    }
+
+    for glass in &cons.glasses {
+        write_block(
+            out,
+            &names.glasses[&glass.id],
+            "GLASS-TYPE",
+            &[
+                ("GROUP", quoted("Vidrios")),
+                ("TYPE", "SHADING-COEF".into()),
+                ("SHADING-COEF", num(glass.g_gln / 0.86)),
+                ("GLASS-CONDUCTANCE", num(glass.u_value)),
+            ],
+        )?;
+    }
+
+    for frame in &cons.frames {
+        let width = cons
+            .wincons
+            .iter()
+            .find(|wc| wc.frame == frame.id)
+            .and_then(|wc| wc.frame_width)
+            .unwrap_or_default();
+        write_block(
+            out,
+            &names.frames[&frame.id],
+            "NAME-FRAME",
+            &[
+                ("GROUP", quoted("Marcos")),
+                ("FRAME-WIDTH", num(width)),
+                ("FRAME-CONDUCT", num(frame.u_value)),
+                ("FRAME-ABS", num(frame.absorptivity)),
+            ],
+        )?;
+    }
+
+    for wc in &cons.wincons {
+        let glass = names.glasses.get(&wc.glass).ok_or_else(|| {
+            format_err!(
+                "Vidrio de la construcción de hueco {} no encontrado",
+                wc.name
+            )
+        })?;
+        let frame = names.frames.get(&wc.frame).ok_or_else(|| {
+            format_err!(
+                "Marco de la construcción de hueco {} no encontrado",
+                wc.name
+            )
+        })?;
+        let name = &names.wincons[&wc.id];
+        let mut attrs = vec![
+            ("NAME", quoted(name)),
+            ("TYPE", "1".to_string()),
+            ("GROUP", quoted("Huecos")),
+            ("GROUP-GLASS", quoted("Vidrios")),
+            ("GLASS-TYPE", quoted(glass)),
+            ("GROUP-FRAME", quoted("Marcos")),
+            ("NAME-FRAME", quoted(frame)),
+            ("PORCENTAGE", num(wc.f_f * 100.0)),
+            ("INF-COEF", num(wc.c_100_effective())),
+            ("porcentajeIncrementoU", num(wc.delta_u)),
+        ];
+        if let Some(g_glshwi) = wc.g_glshwi {
+            attrs.push(("TransmisividadJulio", num(g_glshwi)));
+        }
+        write_block(out, name, "GAP", &attrs)?;
+    }
+
+    Ok(())
+}
+
+/// Polígonos, plantas, espacios, opacos y huecos
+fn write_geometry(out: &mut String, model: &Model, names: &BdlNames) -> Result<(), Error> {
+    // Plantas con sus espacios. Los espacios sin planta se sitúan en una planta propia
+    let mut floors: BTreeMap<Uuid, BdlFloor> = model
+        .floors
+        .iter()
+        .map(|f| {
+            (
+                f.id,
+                BdlFloor {
+                    name: names.floors[&f.id].clone(),
+                    z: f.z,
+                    height: f.height,
+                    multiplier: f.multiplier,
+                    spaces: vec![],
+                },
+            )
+        })
+        .collect();
+    for space in &model.spaces {
+        match space.floor.and_then(|id| floors.get_mut(&id)) {
+            Some(floor) => floor.spaces.push(space),
+            None => {
+                floors.insert(
+                    space.id,
+                    BdlFloor {
+                        name: format!("{}_Planta", names.spaces[&space.id]),
+                        z: space.z,
+                        height: space.height,
+                        multiplier: 1.0,
+                        spaces: vec![space],
+                    },
+                );
+            }
+        }
+    }
+    let mut floors: Vec<_> = floors.into_values().collect();
+    floors.sort_by(|a, b| a.z.total_cmp(&b.z));
+
+    // Polígonos de plantas, espacios y opacos
+    // Los espacios sin suelos, y las plantas sin espacios con suelos, usan un polígono unitario
+    let unit_square = vec![
+        point![0.0, 0.0],
+        point![1.0, 0.0],
+        point![1.0, 1.0],
+        point![0.0, 1.0],
+    ];
+    for floor in &floors {
+        let space_polygons: Vec<_> = floor
+            .spaces
+            .iter()
+            .map(|space| space_polygon(model, space.id))
+            .collect();
+        let floor_polygon = outline_polygon(space_polygons.iter().flatten());
+        write_polygon(
+            out,
+            &format!("{}_PolPlanta", floor.name),
+            floor_polygon.as_ref().unwrap_or(&unit_square),
+        )?;
+        for (space, polygon) in floor.spaces.iter().zip(&space_polygons) {
+            write_polygon(
+                out,
+                &format!("{}_PolEspacio", names.spaces[&space.id]),
+                polygon.as_ref().unwrap_or(&unit_square),
+            )?;
+        }
+    }
+    for wall in &model.walls {
+        write_polygon(
+            out,
+            &format!("{}_Poligono", names.walls[&wall.id]),
+            &wall.geometry.polygon,
+        )?;
+    }
+
+    let mut previous = "Ninguna".to_string();
+    for floor in &floors {
+        write_block(
+            out,
+            &floor.name,
+            "FLOOR",
+            &[
+                ("Z", num(floor.z)),
+                ("POLYGON", quoted(&format!("{}_PolPlanta", floor.name))),
+                ("FLOOR-HEIGHT", num(floor.height)),
+                ("SPACE-HEIGHT", num(floor.height)),
+                ("MULTIPLIER", num(floor.multiplier)),
+                ("SHAPE", "POLYGON".into()),
+                ("PREVIOUS", quoted(&previous)),
+            ],
+        )?;
+        previous = floor.name.clone();
+        for space in &floor.spaces {
+            write_space(out, model, names, space, floor)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Espacio, con sus opacos y huecos
+fn write_space(
+    out: &mut String,
+    model: &Model,
+    names: &BdlNames,
+    space: &Space,
+    floor: &BdlFloor,
+) -> Result<(), Error> {
+    let space_name = &names.spaces[&space.id];
+    let loads = space
+        .loads
+        .and_then(|id| model.loads.iter().find(|l| l.id == id));
+    let thermostat = space
+        .thermostat
+        .and_then(|id| model.thermostats.iter().find(|t| t.id == id));
+    let loads_name = loads.map_or("Ninguno", |l| l.name.as_str());
+    // Potencia de iluminación y VEEI que dan la iluminancia del espacio
+    let power = loads.map(|l| l.lighting).unwrap_or_default();
+    let veei_obj = match space.illuminance {
+        Some(illuminance) if illuminance > 0.0 && power > 0.0 => 100.0 * power / illuminance,
+        _ => 0.0,
+    };
+
+    let mut attrs = vec![
+        ("HEIGHT", num(space.height)),
+        ("Z", num(space.z - floor.z)),
+        ("SHAPE", "POLYGON".to_string()),
+        ("POLYGON", quoted(&format!("{}_PolEspacio", space_name))),
+        (
+            "TYPE",
+            match space.kind {
+                SpaceType::CONDITIONED => "CONDITIONED",
+                SpaceType::UNCONDITIONED => "UNCONDITIONED",
+                SpaceType::UNINHABITED => "UNHABITED",
+            }
+            .to_string(),
+        ),
+        ("SPACE-TYPE", quoted(loads_name)),
+        ("SPACE-CONDITIONS", quoted(loads_name)),
+        (
+            "SYSTEM-CONDITIONS",
+            quoted(thermostat.map_or("Ninguno", |t| t.name.as_str())),
+        ),
+        (
+            "MULTIPLIER",
+            num(space.multiplier / floor.multiplier.max(f32::EPSILON)),
+        ),
+        ("MULTIPLIED", "0".to_string()),
+        (
+            "perteneceALaEnvolventeTermica",
+            if space.inside_tenv { "SI" } else { "NO" }.to_string(),
+        ),
+        ("POWER", num(power)),
+        ("VEEI-OBJ", num(veei_obj)),
+        ("VEEI-REF", num(veei_obj)),
+    ];
+    if let Some(n_v) = space.n_v {
+        attrs.push(("AIR-CHANGES/HR", num(n_v)));
+    }
+    write_block(out, space_name, "SPACE", &attrs)?;
+
+    for wall in model.walls_of_space(space.id) {
+        let wall_name = &names.walls[&wall.id];
+        let wallcons = model
+            .cons
+            .get_wallcons(wall.cons)
+            .ok_or_else(|| format_err!("Construcción del opaco {} no encontrada", wall.name))?;
+        let cons_name = construction_name(names, wallcons);
+
+        let btype = match wall.bounds {
+            BoundaryType::EXTERIOR if Tilt::from(wall.geometry.tilt) == Tilt::TOP => "ROOF",
+            BoundaryType::EXTERIOR => "EXTERIOR-WALL",
+            BoundaryType::GROUND => "UNDERGROUND-WALL",
+            BoundaryType::INTERIOR | BoundaryType::ADIABATIC => "INTERIOR-WALL",
+        };
+        let mut attrs = vec![];
+        match wall.bounds {
+            BoundaryType::INTERIOR => {
+                attrs.push(("INT-WALL-TYPE", "STANDARD".to_string()));
+                if let Some(next_to) = wall.next_to.and_then(|id| names.spaces.get(&id)) {
+                    attrs.push(("NEXT-TO", quoted(next_to)));
+                }
+            }
+            BoundaryType::ADIABATIC => attrs.push(("INT-WALL-TYPE", "ADIABATIC".to_string())),
+            BoundaryType::EXTERIOR => attrs.push(("ABSORPTANCE", num(wallcons.absorptance))),
+            BoundaryType::GROUND => (),
+        }
+        let position = wall.geometry.position.unwrap_or_else(Point3::origin);
+        attrs.extend([
+            ("CONSTRUCTION", quoted(&cons_name)),
+            ("X", num(position.x)),
+            ("Y", num(position.y)),
+            ("Z", num(position.z - space.z)),
+            ("AZIMUTH", num(azimuth_model_to_bdl(wall.geometry.azimuth))),
+            ("TILT", num(wall.geometry.tilt)),
+            ("POLYGON", quoted(&format!("{}_Poligono", wall_name))),
+        ]);
+        write_block(out, wall_name, btype, &attrs)?;
+
+        for win in model.windows_of_wall(wall.id) {
+            let wincons = names
+                .wincons
+                .get(&win.cons)
+                .ok_or_else(|| format_err!("Construcción del hueco {} no encontrada", win.name))?;
+            let position = win.geometry.position.unwrap_or_else(Point2::origin);
+            write_block(
+                out,
+                &names.windows[&win.id],
+                "WINDOW",
+                &[
+                    ("X", num(position.x)),
+                    ("Y", num(position.y)),
+                    ("SETBACK", num(win.geometry.setback)),
+                    ("HEIGHT", num(win.geometry.height)),
+                    ("WIDTH", num(win.geometry.width)),
+                    ("GAP", quoted(wincons)),
+                ],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Nombre BDL de la construcción de un opaco
+///
+/// Las construcciones con absortividad distinta a la de por defecto siguen el criterio de nombres de HULC
+fn construction_name(names: &BdlNames, wallcons: &WallCons) -> String {
+    let layers_name = &names.wallcons[&wallcons.id];
+    if (wallcons.absorptance - DEFAULT_ABSORPTANCE).abs() < 0.005 {
+        layers_name.clone()
+    } else {
+        format!("{}{:.2}", layers_name, wallcons.absorptance)
+    }
+}
+
+/// Polígono en planta del espacio, a partir del mayor de sus suelos, en sentido antihorario
+///
+/// Se consideran suelos los opacos inferiores del espacio y los techos de otros espacios que lindan con él
+fn space_polygon(model: &Model, space_id: Uuid) -> Option<Vec<Point2>> {
+    let floor = model
+        .walls
+        .iter()
+        .filter(|w| {
+            let tilt = Tilt::from(w.geometry.tilt);
+            (w.space == space_id && tilt == Tilt::BOTTOM)
+                || (w.next_to == Some(space_id) && tilt == Tilt::TOP)
+        })
+        .max_by(|a, b| a.area().total_cmp(&b.area()))?;
+    let mut polygon: Vec<_> = global_vertices(&floor.geometry)?
+        .iter()
+        .map(|p| p.xy())
+        .collect();
+    if polygon_area(&polygon) < 0.0 {
+        polygon.reverse();
+    }
+    Some(polygon)
+}
+
+/// Contorno en planta de un conjunto de polígonos contiguos en sentido antihorario
+///
+/// Se eliminan los lados compartidos por dos polígonos y se encadenan los restantes, dividiendo antes
+/// los lados en los vértices de otros polígonos que caen sobre ellos. Si el resultado no es un único
+/// contorno cerrado (p.e. con polígonos que no se tocan) se usa el polígono de mayor superficie.
+fn outline_polygon<'a>(
+    polygons: impl Iterator<Item = &'a Vec<Point2>> + Clone,
+) -> Option<Vec<Point2>> {
+    const TOL: f32 = 1e-3;
+    let key = |p: &Point2| ((p.x / TOL).round() as i64, (p.y / TOL).round() as i64);
+
+    let vertices: Vec<Point2> = polygons.clone().flatten().copied().collect();
+    // Lados, divididos en los vértices que caen sobre ellos
+    let mut edges: Vec<(Point2, Point2)> = Vec::new();
+    for polygon in polygons.clone() {
+        for (p, q) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+            let d = q - p;
+            let len2 = d.norm_squared();
+            if len2 < TOL * TOL {
+                continue;
+            }
+            let mut splits: Vec<(f32, Point2)> = vertices
+                .iter()
+                .filter_map(|r| {
+                    let t = (r - p).dot(&d) / len2;
+                    let on_edge = t > TOL && t < 1.0 - TOL && (p + d * t - r).norm() < TOL;
+                    on_edge.then_some((t, *r))
+                })
+                .collect();
+            splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut start = *p;
+            for (_, r) in splits {
+                if key(&r) != key(&start) {
+                    edges.push((start, r));
+                    start = r;
+                }
+            }
+            edges.push((start, *q));
+        }
+    }
+    // Eliminación de lados compartidos (recorridos en sentidos opuestos)
+    let mut outline: Vec<(Point2, Point2)> = Vec::new();
+    for edge in edges {
+        match outline
+            .iter()
+            .position(|e| key(&e.0) == key(&edge.1) && key(&e.1) == key(&edge.0))
+        {
+            Some(i) => {
+                outline.swap_remove(i);
+            }
+            None => outline.push(edge),
+        }
+    }
+    // Encadenamiento de los lados del contorno
+    let largest = || {
+        polygons
+            .clone()
+            .max_by(|a, b| polygon_area(a).total_cmp(&polygon_area(b)))
+            .cloned()
+    };
+    let first = match outline.first() {
+        Some(first) => *first,
+        None => return largest(),
+    };
+    let mut chain = vec![first.0];
+    let mut current = first.1;
+    while key(&current) != key(&first.0) {
+        let mut next = outline.iter().filter(|e| key(&e.0) == key(&current));
+        match (next.next(), next.next()) {
+            (Some(e), None) if chain.len() < outline.len() => {
+                chain.push(e.0);
+                current = e.1;
+            }
+            _ => return largest(),
+        }
+    }
+    if chain.len() != outline.len() {
+        return largest();
+    }
+    // Eliminación de vértices intermedios en lados alineados
+    let n = chain.len();
+    let simplified: Vec<_> = (0..n)
+        .filter(|&i| {
+            let (a, b, c) = (chain[(i + n - 1) % n], chain[i], chain[(i + 1) % n]);
+            ((b - a).perp(&(c - b))).abs() > TOL * TOL
+        })
+        .map(|i| chain[i])
+        .collect();
+    Some(simplified)
+}
+
+/// Superficie de un polígono en planta (positiva en sentido antihorario)
+fn polygon_area(polygon: &[Point2]) -> f32 {
+    0.5 * polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(p, q)| p.x * q.y - q.x * p.y)
+        .sum::<f32>()
+}
+
+/// Vértices de la geometría en coordenadas globales
+///
+/// Se empieza por un vértice cuyos dos lados siguientes no son colineales, ya que BDL calcula
+/// la normal de las sombras definidas por vértices con los tres primeros
+fn global_vertices(geometry: &WallGeom) -> Option<Vec<Point3>> {
+    let m = geometry.to_global_coords_matrix()?;
+    let vertices: Vec<_> = geometry
+        .polygon
+        .iter()
+        .map(|p| m * point![p.x, p.y, 0.0])
+        .collect();
+    let n = vertices.len();
+    let start = (0..n)
+        .find(|&i| {
+            let (p0, p1, p2) = (vertices[i], vertices[(i + 1) % n], vertices[(i + 2) % n]);
+            (p1 - p0).cross(&(p2 - p1)).magnitude() > 1e-6
+        })
+        .unwrap_or_default();
+    Some(
+        vertices[start..]
+            .iter()
+            .chain(&vertices[..start])
+            .copied()
+            .collect(),
+    )
+}
+
+/// Escribe un polígono BDL
+fn write_polygon(out: &mut String, name: &str, polygon: &[Point2]) -> Result<(), Error> {
+    let keys: Vec<_> = (1..=polygon.len()).map(|i| format!("V{}", i)).collect();
+    let attrs: Vec<_> = keys
+        .iter()
+        .zip(polygon)
+        .map(|(key, p)| (key.as_str(), format!("( {}, {} )", p.x, p.y)))
+        .collect();
+    write_block(out, name, "POLYGON", &attrs)
+}
+
+/// Escribe un bloque BDL con sus atributos
+fn write_block<K: AsRef<str>>(
+    out: &mut String,
+    name: &str,
+    btype: &str,
+    attrs: &[(K, String)],
+) -> Result<(), Error> {
+    writeln!(out, "\"{}\" = {}", name, btype)?;
+    for (key, value) in attrs {
+        writeln!(out, "    {} = {}", key.as_ref(), value)?;
+    }
+    writeln!(out, "    ..")?;
+    Ok(())
+}
+
+/// Nombres únicos y válidos en BDL
+///
+/// Se eliminan los caracteres que no admite el formato (comillas, signos igual y dobles puntos)
+/// y se compactan los espacios en blanco
+fn bdl_names<'a>(elements: impl Iterator<Item = (Uuid, &'a str)>) -> NameMap {
+    let sanitized: Vec<_> = elements
+        .map(|(id, name)| {
+            let name = name
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace('"', "'")
+                .replace('=', "-")
+                .replace("..", ".");
+            (id, name)
+        })
+        .collect();
+    unique_names(sanitized.iter().map(|(id, name)| (*id, name.as_str())))
+}
+
+/// Cadena entre comillas
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value)
+}
+
+/// Valor numérico
+fn num(value: f32) -> String {
+    format!("{}", value)
+}
+
+/// Escapa los caracteres especiales de XML
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,172 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Conversión de Model al formato JSON antiguo (cte::Model)
+//!
+//! En la estructura antigua los elementos y construcciones se indexan por nombre
+//! y las referencias entre elementos usan nombres en lugar de identificadores UUID.
+//! Los nombres repetidos se desambiguan añadiendo un sufijo numérico (_1, _2, ...).
+//!
+//! La estructura antigua no admite todos los datos del modelo:
+//! - las sombras no se exportan, pero su efecto se incluye en el factor de obstáculos remotos de los huecos
+//! - las construcciones se reducen a su resistencia intrínseca (opacos) o a su U, F_f y factores solares (huecos)
+//! - no se exportan las cargas, horarios, consignas, sistemas ni las sustituciones de U de opacos y huecos
+
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{format_err, Error};
+
+use super::legacy::{
+    LegacyModel, LegacySpace, LegacyThermalBridge, LegacyWall, LegacyWallCons, LegacyWinCons,
+    LegacyWindow,
+};
+use crate::utils::{azimuth_model_to_bdl, fround2};
+use crate::{Model, Uuid};
+
+/// Diccionario de id a nombre único
+pub(super) type NameMap = BTreeMap<Uuid, String>;
+
+/// Genera JSON con la estructura antigua del modelo (elementos indexados por nombre)
+pub fn to_legacy_json(model: &Model) -> Result<String, Error> {
+    let spaces = unique_names(model.spaces.iter().map(|e| (e.id, e.name.as_str())));
+    let walls = unique_names(model.walls.iter().map(|e| (e.id, e.name.as_str())));
+    let windows = unique_names(model.windows.iter().map(|e| (e.id, e.name.as_str())));
+    let tbs = unique_names(
+        model
+            .thermal_bridges
+            .iter()
+            .map(|e| (e.id, e.name.as_str())),
+    );
+    let wallcons = unique_names(model.cons.wallcons.iter().map(|e| (e.id, e.name.as_str())));
+    let wincons = unique_names(model.cons.wincons.iter().map(|e| (e.id, e.name.as_str())));
+
+    let mut legacy = LegacyModel {
+        meta: model.meta.clone(),
+        ..Default::default()
+    };
+
+    for s in &model.spaces {
+        legacy.spaces.insert(
+            spaces[&s.id].clone(),
+            LegacySpace {
+                area: fround2(s.area(&model.walls)),
+                height: s.height,
+                z: s.z,
+                multiplier: s.multiplier,
+                kind: s.kind,
+                inside_tenv: s.inside_tenv,
+                n_v: s.n_v,
+            },
+        );
+    }
+
+    for w in &model.walls {
+        let nextto = match w.next_to {
+            Some(id) => Some(name_of(&spaces, id, "Espacio adyacente", &w.name)?),
+            None => None,
+        };
+        legacy.walls.insert(
+            walls[&w.id].clone(),
+            LegacyWall {
+                a: w.area_net(&model.windows),
+                bounds: w.bounds,
+                cons: name_of(&wallcons, w.cons, "Construcción", &w.name)?,
+                space: name_of(&spaces, w.space, "Espacio", &w.name)?,
+                nextto,
+                tilt: w.geometry.tilt,
+                azimuth: azimuth_model_to_bdl(w.geometry.azimuth),
+                position: w.geometry.position,
+                polygon: Some(w.geometry.polygon.clone()),
+            },
+        );
+    }
+
+    // Factores de obstáculos remotos, con el efecto de las sombras del modelo
+    let fshobst = model.compute_fshobst();
+    for w in &model.windows {
+        let fshobst = model
+            .overrides
+            .windows
+            .get(&w.id)
+            .and_then(|o| o.f_shobst)
+            .or_else(|| fshobst.get(&w.id).copied())
+            .unwrap_or(1.0);
+        legacy.windows.insert(
+            windows[&w.id].clone(),
+            LegacyWindow {
+                a: fround2(w.area()),
+                cons: name_of(&wincons, w.cons, "Construcción", &w.name)?,
+                wall: name_of(&walls, w.wall, "Opaco", &w.name)?,
+                fshobst: fround2(fshobst),
+                position: w.geometry.position,
+                width: Some(w.geometry.width),
+                height: Some(w.geometry.height),
+                setback: Some(w.geometry.setback),
+            },
+        );
+    }
+
+    for tb in &model.thermal_bridges {
+        legacy.thermal_bridges.insert(
+            tbs[&tb.id].clone(),
+            LegacyThermalBridge {
+                l: tb.l,
+                psi: tb.psi,
+                kind: tb.kind,
+            },
+        );
+    }
+
+    for wc in &model.cons.wallcons {
+        legacy.wallcons.insert(
+            wallcons[&wc.id].clone(),
+            LegacyWallCons {
+                thickness: wc.thickness(),
+                r_intrinsic: wc.resistance(&model.cons)?,
+                absorptance: wc.absorptance,
+            },
+        );
+    }
+
+    for wc in &model.cons.wincons {
+        let missing = || format_err!("Construcción de hueco {} incompleta", wc.name);
+        legacy.windowcons.insert(
+            wincons[&wc.id].clone(),
+            LegacyWinCons {
+                U: wc.u_value(&model.cons).ok_or_else(missing)?,
+                Ff: wc.f_f,
+                gglwi: wc.g_glwi(&model.cons).ok_or_else(missing)?,
+                gglshwi: wc.g_glshwi(&model.cons).ok_or_else(missing)?,
+                C_100: wc.c_100_effective(),
+            },
+        );
+    }
+
+    Ok(serde_json::to_string_pretty(&legacy)?)
+}
+
+/// Asigna nombres únicos a los elementos, añadiendo sufijos a los nombres repetidos
+pub(super) fn unique_names<'a>(elements: impl Iterator<Item = (Uuid, &'a str)>) -> NameMap {
+    let mut used = HashSet::new();
+    let mut names = NameMap::new();
+    for (id, name) in elements {
+        let mut unique = name.to_string();
+        let mut suffix = 0;
+        while used.contains(&unique) {
+            suffix += 1;
+            unique = format!("{}_{}", name, suffix);
+        }
+        used.insert(unique.clone());
+        names.insert(id, unique);
+    }
+    names
+}
+
+/// Nombre del elemento referenciado por id desde el elemento `from`
+fn name_of(names: &NameMap, id: Uuid, what: &str, from: &str) -> Result<String, Error> {
+    names
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format_err!("{} {} de \"{}\" no encontrado", what, id, from))
+}
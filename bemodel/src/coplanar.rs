@@ -0,0 +1,192 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Unión de opacos coplanares contiguos, para simplificar fachadas troceadas de modelos importados
+
+use log::info;
+use nalgebra::{IsometryMatrix2, IsometryMatrix3};
+
+use super::{
+    types::HasSurface, Model, Point2, Point3, Polygon, Uuid, Vector2, Vector3, Wall, WallGeom,
+};
+
+/// Tolerancia para la coincidencia de vértices y la coplanaridad [m]
+const TOL: f32 = 0.001;
+
+impl Model {
+    /// Une los opacos coplanares contiguos en un solo opaco
+    ///
+    /// Se unen los opacos del mismo espacio, con la misma construcción, condiciones de contorno, espacio adyacente,
+    /// cámara ventilada y aislamiento perimetral, que están en el mismo plano y comparten un lado completo.
+    /// El opaco resultante conserva los datos del primero, con el polígono unión, y recibe los huecos del resto,
+    /// que mantienen su posición, y sus superficies radiantes. Se descartan los datos de usuario (overrides) de los opacos eliminados.
+    /// Los opacos sin definición geométrica completa no se unen.
+    ///
+    /// Devuelve el número de opacos eliminados al fusionarse con otros.
+    pub fn merge_coplanar_walls(&mut self) -> usize {
+        let mut merged = 0;
+        while let Some((i, j, geometry, positions)) = self.find_coplanar_pair() {
+            let (id, removed) = (self.walls[i].id, self.walls.remove(j));
+            for (win_id, position) in positions {
+                if let Some(win) = self.windows.iter_mut().find(|w| w.id == win_id) {
+                    win.wall = id;
+                    win.geometry.position = position;
+                }
+            }
+            for rs in self
+                .radiant_surfaces
+                .iter_mut()
+                .filter(|rs| rs.wall == removed.id)
+            {
+                rs.wall = id;
+            }
+            self.walls[i].geometry = geometry;
+            self.overrides.walls.remove(&removed.id);
+            info!(
+                "Opaco {} ({}) unido al opaco coplanar {} ({})",
+                removed.id, removed.name, id, self.walls[i].name
+            );
+            merged += 1;
+        }
+        merged
+    }
+
+    /// Localiza el primer par de opacos que se pueden unir
+    ///
+    /// Devuelve sus índices, la geometría del opaco unión y las nuevas posiciones de los huecos de ambos
+    #[allow(clippy::type_complexity)]
+    fn find_coplanar_pair(&self) -> Option<(usize, usize, WallGeom, Vec<(Uuid, Option<Point2>)>)> {
+        for (i, a) in self.walls.iter().enumerate() {
+            for (j, b) in self.walls.iter().enumerate().skip(i + 1) {
+                if !same_wall_props(a, b) {
+                    continue;
+                }
+                let geometry = match merged_geometry(&a.geometry, &b.geometry) {
+                    Some(geometry) => geometry,
+                    None => continue,
+                };
+                // Reubicación de los huecos en coordenadas del opaco unión
+                let positions: Option<Vec<_>> = self
+                    .windows_of_wall(a.id)
+                    .map(|w| (w, &a.geometry))
+                    .chain(self.windows_of_wall(b.id).map(|w| (w, &b.geometry)))
+                    .map(|(w, old)| match w.geometry.position {
+                        Some(pos) => relocate(pos, old, &geometry).map(|p| (w.id, Some(p))),
+                        None => Some((w.id, None)),
+                    })
+                    .collect();
+                if let Some(positions) = positions {
+                    return Some((i, j, geometry, positions));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// ¿Comparten los opacos espacio, construcción, condiciones de contorno y propiedades adicionales?
+fn same_wall_props(a: &Wall, b: &Wall) -> bool {
+    a.space == b.space
+        && a.cons == b.cons
+        && a.bounds == b.bounds
+        && a.next_to == b.next_to
+        && a.ventilated == b.ventilated
+        && a.perim_insulation == b.perim_insulation
+}
+
+/// Geometría unión de dos opacos coplanares con un lado completo en común
+///
+/// El resultado conserva la posición, inclinación y orientación del primer opaco.
+/// Devuelve None si no son coplanares, no comparten un lado o no tienen definición geométrica completa
+fn merged_geometry(a: &WallGeom, b: &WallGeom) -> Option<WallGeom> {
+    if a.outward_normal().dot(&b.outward_normal()) < 1.0 - 1e-4 {
+        return None;
+    }
+    let to_local_a = a.to_global_coords_matrix()?.inverse();
+    let to_global_b = b.to_global_coords_matrix()?;
+
+    // Vértices del segundo opaco en coordenadas del primero, con su mismo sentido de giro
+    let mut poly_b = Polygon::with_capacity(b.polygon.len());
+    for p in &b.polygon {
+        let local = to_local_a * (to_global_b * Point3::new(p.x, p.y, 0.0));
+        if local.z.abs() > TOL {
+            return None;
+        }
+        poly_b.push(local.xy());
+    }
+    if a.polygon.normal().z * poly_b.normal().z < 0.0 {
+        poly_b.reverse();
+    }
+
+    // Lado común, recorrido en sentido opuesto en cada polígono
+    let (n, m) = (a.polygon.len(), poly_b.len());
+    let close = |p: Point2, q: Point2| (p - q).norm() < TOL;
+    let (i, j) = (0..n).find_map(|i| {
+        (0..m)
+            .find(|&j| {
+                close(a.polygon[i], poly_b[(j + 1) % m]) && close(a.polygon[(i + 1) % n], poly_b[j])
+            })
+            .map(|j| (i, j))
+    })?;
+
+    let mut polygon: Polygon = a.polygon[..=i].to_vec();
+    polygon.extend((2..m).map(|k| poly_b[(j + k) % m]));
+    polygon.extend_from_slice(&a.polygon[i + 1..]);
+    let polygon = remove_collinear(polygon);
+
+    // Descarta uniones con solapes u otros casos degenerados
+    if polygon.len() < 3
+        || (polygon.area() - a.polygon.area() - poly_b.area()).abs() > TOL * polygon.perimeter()
+    {
+        return None;
+    }
+
+    Some(WallGeom {
+        polygon,
+        ..a.clone()
+    })
+}
+
+/// Elimina los vértices repetidos y los intermedios de lados alineados
+fn remove_collinear(mut polygon: Polygon) -> Polygon {
+    let mut k = 0;
+    while k < polygon.len() && polygon.len() > 3 {
+        let n = polygon.len();
+        let prev = polygon[(k + n - 1) % n];
+        let next = polygon[(k + 1) % n];
+        let (u, v) = (polygon[k] - prev, next - polygon[k]);
+        if u.norm() < TOL || (u.perp(&v).abs() < TOL * v.norm().max(u.norm()) && u.dot(&v) > 0.0) {
+            polygon.remove(k);
+            k = k.saturating_sub(1);
+        } else {
+            k += 1;
+        }
+    }
+    polygon
+}
+
+/// Posición de un hueco en las coordenadas de una nueva geometría de opaco
+///
+/// Devuelve None si los ejes del hueco no se mantienen en la nueva geometría
+fn relocate(pos: Point2, old: &WallGeom, new: &WallGeom) -> Option<Point2> {
+    let (old_to_global, old_to_poly) = (
+        old.to_global_coords_matrix()?,
+        old.to_polygon_coords_matrix()?,
+    );
+    let (new_to_global, new_to_poly) = (
+        new.to_global_coords_matrix()?,
+        new.to_polygon_coords_matrix()?,
+    );
+    let axis = |to_global: &IsometryMatrix3<f32>, to_poly: &IsometryMatrix2<f32>| {
+        let x: Vector2 = to_poly * Vector2::x();
+        to_global * Vector3::new(x.x, x.y, 0.0)
+    };
+    if axis(&old_to_global, &old_to_poly).dot(&axis(&new_to_global, &new_to_poly)) < 1.0 - 1e-4 {
+        return None;
+    }
+    let p = old_to_poly * pos;
+    let global = old_to_global * Point3::new(p.x, p.y, 0.0);
+    let local = new_to_global.inverse() * global;
+    Some(new_to_poly.inverse() * local.xy())
+}
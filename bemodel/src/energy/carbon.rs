@@ -0,0 +1,53 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Huella de carbono incorporada en los materiales de la envolvente
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use log::warn;
+
+use crate::{Model, Uuid};
+
+impl Model {
+    /// Carbono incorporado en los materiales de los elementos opacos [kgCO2e]
+    ///
+    /// Se suma la masa de cada capa (ρ · e · A) por el factor de emisión de su material [kgCO2e/kg],
+    /// usando la superficie neta de los opacos (descontando huecos) y el multiplicador de su espacio.
+    /// Los materiales sin factor de emisión o sin densidad (definidos por su resistencia térmica) no se computan.
+    pub fn embodied_carbon(&self, factors: &BTreeMap<Uuid, f32>) -> f32 {
+        let mut missing = BTreeSet::new();
+        let mut carbon = 0.0;
+        for wall in &self.walls {
+            let cons = match self.cons.get_wallcons(wall.cons) {
+                Some(cons) => cons,
+                None => continue,
+            };
+            let multiplier = self.get_space(wall.space).map_or(1.0, |s| s.multiplier);
+            let area = wall.area_net(&self.windows) * multiplier;
+            for layer in &cons.layers {
+                let factor = match factors.get(&layer.material) {
+                    Some(factor) => factor,
+                    None => {
+                        missing.insert(layer.material);
+                        continue;
+                    }
+                };
+                let density = self
+                    .cons
+                    .get_material(layer.material)
+                    .and_then(|m| m.properties.density())
+                    .unwrap_or_default();
+                carbon += density * layer.e_mean() * area * factor;
+            }
+        }
+        for material in missing {
+            warn!(
+                "No se ha definido el factor de emisión del material {}. No se tendrá en cuenta en el cálculo del carbono incorporado",
+                material
+            );
+        }
+        carbon
+    }
+}
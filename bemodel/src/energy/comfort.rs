@@ -0,0 +1,367 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Temperatura operativa de los espacios y confort adaptativo
+//!
+//! La temperatura del aire de cada espacio se obtiene con un balance sensible horario de un nodo,
+//! con la capacidad térmica interna del espacio, la transmisión de calor por los elementos en contacto
+//! con el exterior o el terreno, la ventilación y las ganancias solares e internas.
+//! Las particiones interiores se consideran sin transmisión de calor y con su superficie a la temperatura del aire.
+//! Las superficies radiantes aportan su potencia al nodo del espacio y elevan la temperatura de su superficie.
+//! Los espacios acondicionados mantienen la temperatura del aire entre sus consignas, con la carga sensible
+//! de calefacción o refrigeración necesaria, mientras que los no acondicionados quedan en oscilación libre.
+//! La temperatura operativa es la media de la temperatura del aire y la radiante media.
+//! El confort adaptativo sigue la UNE-EN 16798-1 (categoría II).
+
+use std::collections::BTreeMap;
+
+use climate::MetData;
+
+use super::{inertia::C_M_PER_AREA, EnergyProps};
+use crate::{BoundaryType, Model, SpaceType, Uuid};
+
+/// Resistencia superficial interior [m²K/W]
+const R_SI: f32 = 0.13;
+/// Constante de la media móvil de la temperatura exterior (UNE-EN 16798-1) [-]
+const ALPHA_RM: f32 = 0.8;
+/// Límites de confort adaptativo de la categoría II respecto a la temperatura de confort [ºC]
+const COMFORT_UPPER: f32 = 3.0;
+const COMFORT_LOWER: f32 = -4.0;
+
+/// Superficie de intercambio del espacio con el exterior o el terreno
+struct ExchangeSurface {
+    /// Superficie [m²]
+    area: f32,
+    /// Transmitancia térmica [W/m²K]
+    u: f32,
+    /// En contacto con el terreno
+    ground: bool,
+    /// Transmitancia térmica horaria, para huecos con protección nocturna [W/m²K]
+    u_hourly: Option<Vec<f32>>,
+}
+
+impl ExchangeSurface {
+    /// Transmitancia térmica en la hora i [W/m²K]
+    fn u_at(&self, i: usize) -> f32 {
+        self.u_hourly
+            .as_ref()
+            .and_then(|u| u.get(i).copied())
+            .unwrap_or(self.u)
+    }
+}
+
+/// Resultado del balance sensible horario de un espacio
+pub(crate) struct SpaceBalance {
+    /// Temperatura del aire, para cada hora [ºC]
+    pub t_air: Vec<f32>,
+    /// Carga sensible para mantener las consignas, para cada hora [W]
+    /// Es positiva para calefacción y negativa para refrigeración
+    pub load: Vec<f32>,
+    /// Potencia emitida por las superficies radiantes, para cada hora [W]
+    radiant: Vec<f32>,
+    /// Superficies en contacto con el exterior o el terreno
+    surfaces: Vec<ExchangeSurface>,
+    /// Superficie total de los elementos del espacio [m²]
+    total_area: f32,
+}
+
+impl Model {
+    /// Temperatura operativa de cada espacio, para cada hora de los datos climáticos [ºC]
+    ///
+    /// Se simula el año completo dos veces, usando la primera como periodo de preacondicionamiento.
+    /// La temperatura del terreno se toma igual a la media anual de la temperatura exterior.
+    /// Los espacios sin capacidad térmica calculable usan un valor de inercia media por superficie útil.
+    pub fn operative_temperature_hourly(&self, met: &MetData) -> BTreeMap<Uuid, Vec<f32>> {
+        let t_ground = ground_temperature(met);
+        self.sensible_balance_hourly(met, false)
+            .into_iter()
+            .map(|(id, balance)| {
+                let SpaceBalance {
+                    t_air,
+                    radiant,
+                    surfaces,
+                    total_area,
+                    ..
+                } = balance;
+                // Temperatura radiante media con las superficies interiores a la temperatura del aire
+                let exchange_area: f32 = surfaces.iter().map(|s| s.area).sum();
+                let total_area = total_area.max(exchange_area);
+                let operative = met
+                    .data
+                    .iter()
+                    .zip(&t_air)
+                    .enumerate()
+                    .map(|(i, (d, &t))| {
+                        if total_area <= 0.0 {
+                            return t;
+                        }
+                        let sum_surf: f32 = surfaces
+                            .iter()
+                            .map(|s| {
+                                let t_e = if s.ground { t_ground } else { d.db_temp };
+                                s.area * (t - s.u_at(i) * R_SI * (t - t_e))
+                            })
+                            .sum();
+                        // Las superficies radiantes se calientan sobre la temperatura del aire en q · R_si
+                        let sum_radiant = radiant[i] * R_SI;
+                        let t_mr = (sum_surf + sum_radiant + (total_area - exchange_area) * t)
+                            / total_area;
+                        0.5 * (t + t_mr)
+                    })
+                    .collect();
+                (id, operative)
+            })
+            .collect()
+    }
+
+    /// Balance sensible horario de un nodo de cada espacio
+    ///
+    /// Se simula el año completo dos veces, usando la primera como periodo de preacondicionamiento.
+    /// La carga de cada hora es la necesaria para llevar la temperatura de oscilación libre a la consigna.
+    /// Con consignas constantes se usan, en todas las horas, la máxima de calefacción y la mínima de refrigeración
+    /// de los horarios del termostato.
+    pub(crate) fn sensible_balance_hourly(
+        &self,
+        met: &MetData,
+        constant_setpoints: bool,
+    ) -> BTreeMap<Uuid, SpaceBalance> {
+        let props = EnergyProps::from(self);
+        let global_n_v = self.global_ventilation_rate();
+        let solar_gains = self.solar_gains_hourly(met);
+        let n_hours = met.data.len();
+        let t_ground = ground_temperature(met);
+
+        let mut map = BTreeMap::new();
+        for (id, space) in &props.spaces {
+            // Superficies en contacto con el exterior o el terreno y superficie total del espacio
+            let mut surfaces = vec![];
+            let mut total_area = 0.0;
+            for wall in props
+                .walls
+                .values()
+                .filter(|w| w.space == *id || w.space_next == Some(*id))
+            {
+                total_area += wall.area_gross;
+                if wall.space == *id
+                    && matches!(wall.bounds, BoundaryType::EXTERIOR | BoundaryType::GROUND)
+                {
+                    surfaces.push(ExchangeSurface {
+                        area: wall.area_net,
+                        u: wall.u_value_override.or(wall.u_value).unwrap_or_default(),
+                        ground: wall.bounds == BoundaryType::GROUND,
+                        u_hourly: None,
+                    });
+                }
+            }
+            let win_ids: Vec<_> = props
+                .windows
+                .iter()
+                .filter(|(_, w)| {
+                    w.bounds == BoundaryType::EXTERIOR
+                        && props
+                            .walls
+                            .get(&w.wall)
+                            .is_some_and(|wall| wall.space == *id)
+                })
+                .map(|(win_id, w)| {
+                    let u = w.u_value_override.or(w.u_value).unwrap_or_default();
+                    let u_hourly = self
+                        .windows
+                        .iter()
+                        .find(|win| win.id == *win_id)
+                        .and_then(|win| self.window_u_value_hourly(win, u, met));
+                    surfaces.push(ExchangeSurface {
+                        area: w.area,
+                        u,
+                        ground: false,
+                        u_hourly,
+                    });
+                    *win_id
+                })
+                .collect();
+
+            let h_ext: Vec<f32> = (0..n_hours)
+                .map(|i| {
+                    surfaces
+                        .iter()
+                        .filter(|s| !s.ground)
+                        .map(|s| s.u_at(i) * s.area)
+                        .sum()
+                })
+                .collect();
+            let h_gnd: f32 = surfaces
+                .iter()
+                .filter(|s| s.ground)
+                .map(|s| s.u * s.area)
+                .sum();
+            let n_v = match space.n_v {
+                Some(n_v) => n_v,
+                None if space.kind != SpaceType::UNINHABITED => {
+                    props.design_ventilation_rate(*id).unwrap_or(global_n_v)
+                }
+                None => 0.0,
+            };
+            let h_ve = 0.34 * n_v * space.volume_net;
+            // Capacidad térmica por paso de tiempo horario [W/K]
+            let c_m = match self.space_heat_capacity(*id) {
+                c_m if c_m > 0.0 => c_m,
+                _ => C_M_PER_AREA * space.area,
+            } / 3600.0;
+
+            // Ganancias internas y solares [W]
+            let schedule = |sch: Option<Uuid>| -> Vec<f32> {
+                sch.map(|sch| self.schedules.year_values(sch))
+                    .unwrap_or_default()
+            };
+            let mut gains = vec![0.0; n_hours];
+            if let Some(loads) = space.loads.and_then(|id| props.loads.get(&id)) {
+                for (load, sch) in [
+                    (loads.people_sensible, loads.people_schedule),
+                    (loads.equipment, loads.equipment_schedule),
+                    (loads.lighting, loads.lighting_schedule),
+                ] {
+                    for (g, v) in gains.iter_mut().zip(schedule(sch)) {
+                        *g += load * v * space.area;
+                    }
+                }
+            }
+            for win_gains in win_ids.iter().filter_map(|id| solar_gains.get(id)) {
+                for (g, v) in gains.iter_mut().zip(win_gains) {
+                    *g += v;
+                }
+            }
+
+            // Potencia emitida por las superficies radiantes del espacio [W]
+            let mut radiant = vec![0.0; n_hours];
+            for (rs, wall) in self.radiant_surfaces.iter().filter_map(|rs| {
+                props
+                    .walls
+                    .get(&rs.wall)
+                    .filter(|w| w.space == *id)
+                    .map(|w| (rs, w))
+            }) {
+                let power = rs.max_power * wall.area_net;
+                match rs.schedule {
+                    Some(sch) => {
+                        for (r, v) in radiant.iter_mut().zip(self.schedules.year_values(sch)) {
+                            *r += power * v;
+                        }
+                    }
+                    None => radiant.iter_mut().for_each(|r| *r += power),
+                }
+            }
+            for (g, r) in gains.iter_mut().zip(&radiant) {
+                *g += r;
+            }
+
+            // Consignas de los espacios acondicionados [ºC]
+            let thermostat = space
+                .thermostat
+                .filter(|_| space.kind == SpaceType::CONDITIONED)
+                .and_then(|id| self.thermostats.iter().find(|t| t.id == id));
+            let mut t_min = schedule(thermostat.and_then(|t| t.temp_min));
+            let mut t_max = schedule(thermostat.and_then(|t| t.temp_max));
+            if constant_setpoints {
+                let t_min_max = t_min.iter().copied().reduce(f32::max);
+                let t_max_min = t_max.iter().copied().reduce(f32::min);
+                t_min = t_min_max.map_or_else(Vec::new, |t| vec![t; n_hours]);
+                t_max = t_max_min.map_or_else(Vec::new, |t| vec![t; n_hours]);
+            }
+
+            let mut t_air = vec![0.0; n_hours];
+            let mut load = vec![0.0; n_hours];
+            let mut t_prev = t_ground;
+            for _ in 0..2 {
+                for (i, d) in met.data.iter().enumerate() {
+                    let h = c_m + h_ext[i] + h_ve + h_gnd;
+                    let t_free = if h > 0.0 {
+                        (c_m * t_prev + gains[i] + (h_ext[i] + h_ve) * d.db_temp + h_gnd * t_ground)
+                            / h
+                    } else {
+                        d.db_temp
+                    };
+                    let mut t_new = t_free;
+                    if let Some(t_min) = t_min.get(i) {
+                        t_new = t_new.max(*t_min);
+                    }
+                    if let Some(t_max) = t_max.get(i) {
+                        t_new = t_new.min(*t_max);
+                    }
+                    t_air[i] = t_new;
+                    load[i] = h * (t_new - t_free);
+                    t_prev = t_new;
+                }
+            }
+
+            map.insert(
+                *id,
+                SpaceBalance {
+                    t_air,
+                    load,
+                    radiant,
+                    surfaces,
+                    total_area,
+                },
+            );
+        }
+        map
+    }
+
+    /// Horas fuera del rango de confort adaptativo de cada espacio, según la UNE-EN 16798-1 (categoría II) [h]
+    ///
+    /// La temperatura de confort es θ_c = 0,33·θ_rm + 18,8, con θ_rm la media móvil ponderada de las
+    /// temperaturas exteriores medias diarias, y el rango de confort es [θ_c - 4, θ_c + 3].
+    /// Fuera del rango de aplicación de la norma se limita θ_rm a [10, 30]ºC para el límite superior
+    /// y a [15, 30]ºC para el inferior. Se computan todas las horas del año.
+    pub fn adaptive_comfort_hours(&self, met: &MetData) -> BTreeMap<Uuid, usize> {
+        let t_rm = running_mean_outdoor_temperature(met);
+        self.operative_temperature_hourly(met)
+            .into_iter()
+            .map(|(id, t_op)| {
+                let hours = t_op
+                    .iter()
+                    .zip(&t_rm)
+                    .filter(|(&t, &t_rm)| {
+                        let upper = 0.33 * t_rm.clamp(10.0, 30.0) + 18.8 + COMFORT_UPPER;
+                        let lower = 0.33 * t_rm.clamp(15.0, 30.0) + 18.8 + COMFORT_LOWER;
+                        t > upper || t < lower
+                    })
+                    .count();
+                (id, hours)
+            })
+            .collect()
+    }
+}
+
+/// Temperatura del terreno, igual a la media anual de la temperatura exterior [ºC]
+fn ground_temperature(met: &MetData) -> f32 {
+    met.data.iter().map(|d| d.db_temp).sum::<f32>() / met.data.len().max(1) as f32
+}
+
+/// Media móvil ponderada de la temperatura exterior, θ_rm, para cada hora de los datos climáticos [ºC]
+///
+/// θ_rm = (1 - α)·θ_ed-1 + α·θ_rm-1, con θ_ed-1 la temperatura media del día anterior y α = 0,8.
+/// Se recorre el año dos veces para que el valor inicial no dependa del arranque.
+fn running_mean_outdoor_temperature(met: &MetData) -> Vec<f32> {
+    let daily_means: Vec<f32> = met
+        .data
+        .chunks(24)
+        .map(|day| day.iter().map(|d| d.db_temp).sum::<f32>() / day.len() as f32)
+        .collect();
+    let mut t_rm = daily_means.iter().sum::<f32>() / daily_means.len().max(1) as f32;
+    let mut daily_t_rm = vec![0.0; daily_means.len()];
+    for _ in 0..2 {
+        let mut t_prev_day = *daily_means.last().unwrap_or(&t_rm);
+        for (t_day, rm) in daily_means.iter().zip(daily_t_rm.iter_mut()) {
+            t_rm = (1.0 - ALPHA_RM) * t_prev_day + ALPHA_RM * t_rm;
+            *rm = t_rm;
+            t_prev_day = *t_day;
+        }
+    }
+    daily_t_rm
+        .iter()
+        .flat_map(|&rm| std::iter::repeat(rm).take(24))
+        .take(met.data.len())
+        .collect()
+}
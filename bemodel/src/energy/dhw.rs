@@ -0,0 +1,46 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Demanda de energía para la producción de agua caliente sanitaria (ACS)
+//!
+//! Q_ACS = ρ·c_p·V_d·N_d·(θ_ref - θ_red)·(1 + f_acum + f_dist), con V_d el consumo diario,
+//! N_d los días del mes, θ_red la temperatura del agua fría de red de ese mes y f_acum, f_dist
+//! las pérdidas de acumulación y distribución, como fracción de la energía en los puntos de consumo.
+
+use climate::MONTH_DAYS;
+
+use crate::Model;
+
+/// Capacidad calorífica volumétrica del agua (ρ·c_p) [kWh/(l·K)]
+const RHO_CP_WATER: f32 = 4.186 / 3600.0;
+
+impl Model {
+    /// Demanda mensual de energía para la producción de ACS [kWh/mes]
+    ///
+    /// Se calcula la energía necesaria para calentar el consumo diario de ACS desde la temperatura del
+    /// agua fría de red de cada mes (p.e. climatedata::cold_water_temperatures) hasta la de referencia,
+    /// incrementada con las pérdidas de acumulación y distribución.
+    /// Vale cero si el modelo no tiene definida la demanda de ACS.
+    pub fn dhw_energy_demand(&self, cold_water_temp_monthly: [f32; 12]) -> [f32; 12] {
+        let mut demand = [0.0; 12];
+        let dhw = match &self.meta.dhw {
+            Some(dhw) => dhw,
+            None => return demand,
+        };
+        let losses = 1.0 + dhw.storage_losses.max(0.0) + dhw.distribution_losses.max(0.0);
+        for (i, value) in demand.iter_mut().enumerate() {
+            let delta_t = (dhw.t_ref - cold_water_temp_monthly[i]).max(0.0);
+            *value = RHO_CP_WATER * dhw.daily_volume * MONTH_DAYS[i] as f32 * delta_t * losses;
+        }
+        demand
+    }
+
+    /// Demanda mensual de energía para la producción de ACS con el agua fría de red del emplazamiento [kWh/mes]
+    ///
+    /// La temperatura del agua fría de red es la de la provincia del emplazamiento o, si no se conoce,
+    /// la de la zona climática (Meta::cold_water_temperatures).
+    pub fn dhw_energy_demand_for_site(&self) -> [f32; 12] {
+        self.dhw_energy_demand(self.meta.cold_water_temperatures())
+    }
+}
@@ -10,7 +10,7 @@ use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::energy::EnergyProps;
-use crate::{BoundaryType, Tilt};
+use crate::{BoundaryType, ThermalBridgeKind, Tilt, Uuid};
 
 /// Reporte de cálculo de K (HE2019)
 #[allow(non_snake_case)]
@@ -32,6 +32,35 @@ pub struct KData {
     pub windows: KElementProps,
     /// Puentes térmicos
     pub tbs: KTBElements,
+    /// Desglose de H_tr por tipo de elemento
+    pub h_tr: KHtr,
+}
+
+/// Desglose de la transferencia de calor por transmisión (H_tr = Σ A·U + Σ ψ·L) por tipo de elemento
+///
+/// La suma de los aportes de opacos, huecos y puentes térmicos (total), dividida por la superficie de intercambio,
+/// es igual a K. Las particiones de la envolvente térmica en contacto con espacios exteriores a ella se indican
+/// por separado, ya que, como en HULC, no participan en el cálculo de K ni en el total.
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize)]
+pub struct KHtr {
+    /// Muros exteriores [W/K]
+    pub walls: f32,
+    /// Cubiertas exteriores [W/K]
+    pub roofs: f32,
+    /// Suelos exteriores [W/K]
+    pub floors: f32,
+    /// Elementos en contacto con el terreno [W/K]
+    pub ground: f32,
+    /// Huecos [W/K]
+    pub windows: f32,
+    /// Puentes térmicos [W/K]
+    pub tbs: f32,
+    /// Total, sin particiones [W/K]
+    pub total: f32,
+    /// Particiones de la envolvente térmica en contacto con espacios exteriores a ella [W/K]
+    /// La U de la partición incluye el efecto del espacio adyacente no acondicionado
+    #[serde(default)]
+    pub partitions: f32,
 }
 
 /// Resumen de resultados de K
@@ -102,6 +131,51 @@ pub struct KTBElementProps {
     pub psil: f32,
 }
 
+/// Aportación de un puente térmico al coeficiente de transferencia de calor por transmisión de K
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct KTBContribution {
+    /// ID del puente térmico
+    pub id: Uuid,
+    /// Tipo de puente térmico
+    pub kind: ThermalBridgeKind,
+    /// L del puente térmico, teniendo en cuenta el multiplicador de su espacio [m]
+    pub l: f32,
+    /// ψ·L del puente térmico [W/K]
+    pub psil: f32,
+    /// Porcentaje sobre el ψ·L total de los puentes térmicos [%]
+    pub percent: f32,
+}
+
+/// Aportación de cada puente térmico a K, ordenada de mayor a menor ψ·L
+///
+/// Permite identificar los encuentros que más penalizan. Como en el cálculo de K, se excluyen los puentes
+/// térmicos con longitud negativa, de modo que la suma de las aportaciones es igual a KSummary::tbs_psil.
+pub fn tb_contributions(props: &EnergyProps) -> Vec<KTBContribution> {
+    let mut contributions: Vec<_> = props
+        .thermal_bridges
+        .iter()
+        .filter(|(_, tb)| tb.l >= 0.0)
+        .map(|(id, tb)| {
+            let l = tb.l * tb.multiplier;
+            KTBContribution {
+                id: *id,
+                kind: tb.kind,
+                l,
+                psil: tb.psi * l,
+                percent: 0.0,
+            }
+        })
+        .collect();
+    let total: f32 = contributions.iter().map(|c| c.psil).sum();
+    if total.abs() > 0.001 {
+        for c in &mut contributions {
+            c.percent = 100.0 * c.psil / total;
+        }
+    }
+    contributions.sort_by(|a, b| b.psil.total_cmp(&a.psil));
+    contributions
+}
+
 impl From<&EnergyProps> for KData {
     /// Calcula la transmitancia térmica global K (W/m²K)
     /// Transmitancia media de opacos, huecos y puentes térmicos en contacto con el aire exterior o con el terreno
@@ -155,6 +229,16 @@ impl From<&EnergyProps> for KData {
             element_case.u_max = element_case.u_max.map(|v| v.max(wall_u)).or(Some(wall_u));
             element_case.u_min = element_case.u_min.map(|v| v.min(wall_u)).or(Some(wall_u));
         }
+        // Particiones de la envolvente térmica, que no computan en K
+        let partitions: f32 = props
+            .walls
+            .values()
+            .filter(|w| w.is_tenv && w.bounds == BoundaryType::INTERIOR)
+            .filter_map(|w| {
+                let wall_u = w.u_value_override.or(w.u_value)?;
+                Some(w.multiplier * w.area_net * wall_u)
+            })
+            .sum();
         // Valores medios de huecos y opacos
         if k.windows.a > 0.001 {
             k.windows.u_mean = Some(k.windows.au / k.windows.a);
@@ -174,11 +258,11 @@ impl From<&EnergyProps> for KData {
         // PTs
         for tb in props.thermal_bridges.values() {
             use crate::ThermalBridgeKind::*;
-            let l = tb.l;
             // A veces se incluyen longitudes < 0 para señalar que no se han medido
-            if l < 0.0 {
+            if tb.l < 0.0 {
                 continue;
             };
+            let l = tb.l * tb.multiplier;
             let psil = tb.psi * l;
             let mut tb_case = match tb.kind {
                 ROOF => &mut k.tbs.roof,
@@ -229,6 +313,16 @@ impl From<&EnergyProps> for KData {
             + tbs.generic.psil;
         summary.a = summary.opaques_a + summary.windows_a;
         summary.au = summary.opaques_au + summary.windows_au + summary.tbs_psil;
+        k.h_tr = KHtr {
+            walls: walls.au,
+            roofs: roofs.au,
+            floors: floors.au,
+            ground: ground.au,
+            windows: windows.au,
+            tbs: summary.tbs_psil,
+            total: summary.au,
+            partitions,
+        };
         k.K = if summary.a < 0.01 {
             0.0
         } else {
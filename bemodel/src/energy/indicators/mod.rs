@@ -10,8 +10,11 @@ mod types;
 pub mod k;
 pub mod n50;
 pub mod qsoljul;
+mod units;
 
 pub use types::EnergyIndicators;
+pub(crate) use types::h_ve;
 pub use n50::N50Data;
 pub use k::KData;
 pub use qsoljul::QSolJulData;
+pub use units::UnitSystem;
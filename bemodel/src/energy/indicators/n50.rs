@@ -28,12 +28,15 @@ pub struct N50Data {
     pub walls_c: f32,
     /// C_o · A_o de los opacos que participan en la n_50, usando valor de ensayo, si está disponible, o de referencia [m³/h]
     pub walls_c_a: f32,
-    /// Superficie (A_h) de los huecos que participan en la n_50 [m²]
+    /// Superficie (A_h) de los huecos, incluidas las puertas, que participan en la n_50 [m²]
     pub windows_a: f32,
     /// Permeabilidad (C_h) media de los huecos que participan en la n_50 [m³/h·m²]
     pub windows_c: f32,
     /// C_h · A_h de los huecos que participan en la n_50 [m³/h]
     pub windows_c_a: f32,
+    /// Caudal a 100 Pa de las infiltraciones adicionales (chimeneas, conductos, etc) [m³/h]
+    #[serde(default)]
+    pub extra_c_a: f32,
     /// Volumen interior de los espacios interiores a la envolvente térmica [m³]
     pub vol: f32,
 }
@@ -44,7 +47,8 @@ impl From<&EnergyProps> for N50Data {
     /// - las superficies opacos en contacto con el aire exterior
     /// - las permeabilidad al aire de opacos en función de si es nuevo (o permeab. mejorada) o existente
     /// - los huecos de las superficies opacas anteriores
-    /// - la permeabilidad al aire de huecos definida en su construcción
+    /// - la permeabilidad al aire de huecos definida en su construcción o, en las puertas, la propia
+    /// - las infiltraciones adicionales declaradas (chimeneas, conductos, etc)
     /// - el volumen interior de la envolvente térmica ()
     /// Los huecos sin construcción definida se consideran con la permeabilidad al aire
     /// de huecos poco estancos, C_100=100 m³/h·m² (clase 0)
@@ -63,7 +67,7 @@ impl From<&EnergyProps> for N50Data {
                 let mut win_ah = 0.0;
                 let mut win_ah_ch = 0.0;
                 for (win_id, win) in props.windows.iter().filter(|(_, win)| &win.wall == wall_id) {
-                    let win_c_100 = if let Some(win_c_100) = props.wincons.get(&win.cons).map(|wc| wc.c_100) {
+                    let win_c_100 = if let Some(win_c_100) = win.door_c_100.or_else(|| props.wincons.get(&win.cons).map(|wc| wc.c_100)) {
                         win_c_100
                     } else {
                         warn!("No se ha podido calcular el valor C_100 del hueco {}. Se usará el valor por defecto C_100 = 100 m³/h·m² en el cálculo de n_50", win_id);
@@ -72,6 +76,11 @@ impl From<&EnergyProps> for N50Data {
                     win_ah += win.area;
                     win_ah_ch += win.area * win_c_100;
                 }
+                // Descontamos los solapes entre huecos, manteniendo la permeabilidad media
+                if win_ah > 0.0 {
+                    win_ah_ch *= wall.glazed_area_net / win_ah;
+                    win_ah = wall.glazed_area_net;
+                }
                 data.walls_a += wall.area_net * multiplier;
                 data.windows_a += win_ah * multiplier;
                 data.windows_c_a += win_ah_ch * multiplier;
@@ -87,17 +96,20 @@ impl From<&EnergyProps> for N50Data {
         // NOTE: usamos is_new_building pero igual merecería la pena una variable para permeabilidad mejorada
         data.walls_c_ref = props.global.c_o_100;
         data.walls_c_a_ref = data.walls_a * data.walls_c_ref;
+        data.extra_c_a = props.global.extra_leakage_100;
 
         if data.vol > 0.001 {
             // 0.629 = (50/100)^0.67 -> factor de cambio de presiones
-            data.n50_ref = 0.629 * (data.walls_c_a_ref + data.windows_c_a) / data.vol;
+            data.n50_ref =
+                0.629 * (data.walls_c_a_ref + data.windows_c_a + data.extra_c_a) / data.vol;
         };
 
         // Si hay valor de ensayo se usa, si no usamos valor de referencia
         if let Some(n50test) = props.global.n_50_test_ach {
             data.n50 = n50test;
             if data.walls_a > 0.001 {
-                data.walls_c = ((n50test * data.vol) / 0.629 - data.windows_c_a) / data.walls_a;
+                data.walls_c = ((n50test * data.vol) / 0.629 - data.windows_c_a - data.extra_c_a)
+                    / data.walls_a;
                 data.walls_c_a = data.walls_a * data.walls_c;
             } else {
                 data.walls_c = data.walls_c_ref;
@@ -110,8 +122,8 @@ impl From<&EnergyProps> for N50Data {
         };
 
         info!(
-            "n_50={:.2} 1/h, n_50_ref={:.2} 1/h, A_o={:.2} m², C_o={:.2} m³/h·m², Σ(A_o.C_o)={:.2} m³/h, C_o_ref={:.2} m³/h·m², Σ(A_o.C_o_ref)={:.2} m³/h, A_h={:.2} m², C_h={:.2} m³/h·m², Σ(A_h.C_h)={:.2} m³/h, vol={:.2} m³",
-            data.n50, data.n50_ref, data.walls_a, data.walls_c, data.walls_c_a, data.walls_c_ref, data.walls_c_a_ref, data.windows_a, data.windows_c, data.windows_c_a, data.vol
+            "n_50={:.2} 1/h, n_50_ref={:.2} 1/h, A_o={:.2} m², C_o={:.2} m³/h·m², Σ(A_o.C_o)={:.2} m³/h, C_o_ref={:.2} m³/h·m², Σ(A_o.C_o_ref)={:.2} m³/h, A_h={:.2} m², C_h={:.2} m³/h·m², Σ(A_h.C_h)={:.2} m³/h, Q_extra={:.2} m³/h, vol={:.2} m³",
+            data.n50, data.n50_ref, data.walls_a, data.walls_c, data.walls_c_a, data.walls_c_ref, data.walls_c_a_ref, data.windows_a, data.windows_c, data.windows_c_a, data.extra_c_a, data.vol
         );
         data
     }
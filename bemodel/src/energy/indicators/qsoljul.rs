@@ -8,13 +8,16 @@
 //! - UNE-EN ISO 13770:2017 para elementos en contacto con el terremo
 #![allow(non_snake_case)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::{energy::EnergyProps, BoundaryType, Orientation};
 
+/// Resistencia superficial exterior [m²K/W]
+const R_SE: f32 = 0.04;
+
 /// Reporte de cálculo del parámetro de control solar q_sol:jul (HE2019)
 #[allow(non_snake_case)]
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -23,6 +26,9 @@ pub struct QSolJulData {
     pub q_soljul: f32,
     /// Ganancias para el mes de julio (Q_soljul) [kWh/mes]
     pub Q_soljul: f32,
+    /// Ganancias solares a través de opacos para el mes de julio [kWh/mes]
+    /// Dato informativo, no computa en el parámetro de control solar
+    pub Q_soljul_opaques: f32,
     /// Superficie total de huecos [m²]
     pub a_wp: f32,
     /// Irradiación solar acumulada, media ponderada por superficie de huecos [kWh/m²·mes]
@@ -34,7 +40,7 @@ pub struct QSolJulData {
     /// Fracción de marco, media ponderada por superficie de huecos [-]
     pub f_f_mean: f32,
     /// Datos de ganancias solares (Q_soljul) resumidos por orientaciones
-    pub detail: HashMap<Orientation, QSolJulDetail>,
+    pub detail: BTreeMap<Orientation, QSolJulDetail>,
 }
 
 /// Detalles del parámetro de control solar q_sol:jul (HE2019) por orientación
@@ -65,8 +71,11 @@ impl QSolJulData {
         let mut q_soljul_data = QSolJulData::default();
 
         let mut Q_soljul = 0.0;
+        // Las puertas opacas no computan como superficie acristalada
         for (win_id, win) in props.windows.iter().filter(|(_, w)| {
-            w.is_tenv && (w.bounds == EXTERIOR || w.bounds == GROUND)
+            w.is_tenv
+                && (w.bounds == EXTERIOR || w.bounds == GROUND)
+                && w.door_absorptance.is_none()
         }) {
             let orientation = win.orientation;
             let radjul = *totradjul.get(&orientation).unwrap();
@@ -104,6 +113,40 @@ impl QSolJulData {
             Q_soljul += Q_soljul_orient;
         }
 
+        // Ganancias solares de opacos, Q = f_vent · alpha · R_se · U · A · H_sol;jul
+        // reducidas en los opacos con cámara ventilada
+        let mut Q_soljul_opaques = 0.0;
+        for wall in props
+            .walls
+            .values()
+            .filter(|w| w.is_tenv && w.bounds == EXTERIOR)
+        {
+            let radjul = *totradjul.get(&wall.orientation).unwrap();
+            let absorptance = props
+                .wallcons
+                .get(&wall.cons)
+                .map_or(0.0, |c| c.absorptance);
+            let u_value = wall.u_value_override.or(wall.u_value).unwrap_or_default();
+            Q_soljul_opaques += wall.solar_gain_factor
+                * absorptance
+                * R_SE
+                * u_value
+                * wall.area_net
+                * wall.multiplier
+                * radjul;
+        }
+        // Ganancias solares de puertas opacas, con su absortividad y transmitancia propias
+        for door in props
+            .windows
+            .values()
+            .filter(|w| w.is_tenv && w.bounds == EXTERIOR && w.door_absorptance.is_some())
+        {
+            let radjul = *totradjul.get(&door.orientation).unwrap();
+            let absorptance = door.door_absorptance.unwrap_or_default();
+            let u_value = door.u_value_override.or(door.u_value).unwrap_or_default();
+            Q_soljul_opaques += absorptance * R_SE * u_value * door.area * door.multiplier * radjul;
+        }
+
         let a_ref = props.global.a_ref;
         let q_soljul = Q_soljul / a_ref;
         info!(
@@ -114,6 +157,7 @@ impl QSolJulData {
         // Guarda datos globales y corrige medias globales
         q_soljul_data.q_soljul = q_soljul;
         q_soljul_data.Q_soljul = Q_soljul;
+        q_soljul_data.Q_soljul_opaques = Q_soljul_opaques;
         q_soljul_data.irradiance_mean /= q_soljul_data.a_wp;
         q_soljul_data.fshobst_mean /= q_soljul_data.a_wp;
         q_soljul_data.gglshwi_mean /= q_soljul_data.a_wp;
@@ -129,3 +173,33 @@ impl QSolJulData {
         q_soljul_data
     }
 }
+
+/// Factor de obstáculos remotos medio de los huecos de cada orientación, ponderado por su área de captación solar [-]
+///
+/// El área de captación de cada hueco es A · g_gl;sh;wi · (1 - F_F), de modo que, con la irradiación de la orientación,
+/// la media reproduce las ganancias solares de julio de sus huecos.
+/// Se consideran los mismos huecos y valores efectivos que en el cálculo del q_sol;jul (F_sh;obst de usuario o calculado),
+/// con los mismos valores por defecto. Solo se incluyen las orientaciones con huecos.
+pub fn fshobst_mean_by_orientation(props: &EnergyProps) -> BTreeMap<Orientation, f32> {
+    use BoundaryType::{EXTERIOR, GROUND};
+    let mut sums: BTreeMap<Orientation, (f32, f32)> = BTreeMap::new();
+    for win in props.windows.values().filter(|w| {
+        w.is_tenv && (w.bounds == EXTERIOR || w.bounds == GROUND) && w.door_absorptance.is_none()
+    }) {
+        let (g_glshwi, f_f) = props
+            .wincons
+            .get(&win.cons)
+            .map_or((0.77, 0.20), |c| (c.g_glshwi, c.f_f));
+        let f_shobst = win.f_shobst_override.or(win.f_shobst).unwrap_or(1.0);
+        let a_cap = win.area * win.multiplier * g_glshwi * (1.0 - f_f);
+        let (sum, a_sum) = sums.entry(win.orientation).or_default();
+        *sum += f_shobst * a_cap;
+        *a_sum += a_cap;
+    }
+    sums.into_iter()
+        .map(|(orientation, (sum, a_sum))| {
+            let mean = if a_sum > 0.0 { sum / a_sum } else { 1.0 };
+            (orientation, mean)
+        })
+        .collect()
+}
@@ -6,28 +6,47 @@
 //!
 //! Tipo para la obtención de los indicadores energéticos K, n50, qsoljul, etc
 
+use std::collections::BTreeMap;
+
 use anyhow::Error;
 use serde::{Deserialize, Serialize};
 
+use super::k::{tb_contributions, KTBContribution};
+use super::qsoljul::fshobst_mean_by_orientation;
 use super::KData;
 use super::N50Data;
 use super::QSolJulData;
+use super::UnitSystem;
 
 use crate::energy::EnergyProps;
-use crate::{check, climatedata, Model, Warning};
+use crate::{check, climatedata, Model, Orientation, SpaceType, Warning};
+
+/// Capacidad calorífica volumétrica del aire [Wh/m³K]
+const RHO_C_AIR: f32 = 0.34;
 
 /// Estructura que contiene los resultados del cálculo de indicadores y parámetros energéticos
 #[allow(non_snake_case)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnergyIndicators {
+    /// Sistema de unidades en el que se expresan los indicadores
+    #[serde(default)]
+    pub units: UnitSystem,
     pub area_ref: f32,
     pub compactness: f32,
     pub vol_env_net: f32,
     pub vol_env_gross: f32,
+    /// Coeficiente de transferencia de calor por ventilación [W/K]
+    pub H_ve: f32,
     pub props: EnergyProps,
     pub K_data: KData,
+    /// Aportación de cada puente térmico a K, ordenada de mayor a menor ψ·L
+    #[serde(default)]
+    pub tb_contributions: Vec<KTBContribution>,
     pub q_soljul_data: QSolJulData,
     pub n50_data: N50Data,
+    /// Factor de obstáculos remotos medio por orientación, ponderado por el área de captación de los huecos [-]
+    #[serde(default)]
+    pub f_shobst_mean_by_orientation: BTreeMap<Orientation, f32>,
     pub warnings: Vec<Warning>,
 }
 
@@ -39,6 +58,9 @@ impl EnergyIndicators {
     }
 
     /// Calcula indicadores energéticos del modelo
+    ///
+    /// Se usa la envolvente térmica tal como se define en el modelo, es decir, la envolvente total
+    /// (ver EnergyIndicators::for_total_envelope).
     pub fn compute(model: &Model) -> Self {
         let climatezone = model.meta.climate;
         let totradjul = climatedata::total_radiation_in_july_by_orientation(&climatezone);
@@ -48,18 +70,75 @@ impl EnergyIndicators {
         let props = EnergyProps::from(model);
 
         Self {
+            units: UnitSystem::SI,
             area_ref: props.global.a_ref,
             compactness: props.global.compactness,
             vol_env_net: props.global.vol_env_net,
             vol_env_gross: props.global.vol_env_gross,
+            H_ve: h_ve(&props),
 
             K_data: KData::from(&props),
+            tb_contributions: tb_contributions(&props),
             q_soljul_data: QSolJulData::from(&props, &totradjul),
             n50_data: N50Data::from(&props),
+            f_shobst_mean_by_orientation: fshobst_mean_by_orientation(&props),
 
             props,
             // TODO: estos avisos deberían ser resultado de los cálculos, no del check general
             warnings: check(model),
         }
     }
+
+    /// Calcula indicadores energéticos para la envolvente térmica total
+    ///
+    /// Incluye todos los espacios interiores a la envolvente térmica (inside_tenv), tanto habitables como
+    /// no habitables (p.e. trasteros o garajes incluidos en la envolvente).
+    /// Es la envolvente que se usa por defecto (ver EnergyIndicators::compute).
+    pub fn for_total_envelope(model: &Model) -> Self {
+        Self::compute(model)
+    }
+
+    /// Calcula indicadores energéticos para la envolvente térmica habitable
+    ///
+    /// Solo incluye los espacios habitables interiores a la envolvente térmica. Los espacios no habitables
+    /// interiores a la envolvente se consideran exteriores a ella, de modo que sus opacos exteriores y en contacto
+    /// con el terreno dejan de computar en K, mientras que las particiones que los separan de los espacios habitables,
+    /// que no participan en el cálculo de K, se contabilizan por separado (KHtr::partitions).
+    /// El modelo original no se modifica.
+    pub fn for_habitable_envelope(model: &Model) -> Self {
+        let mut model = model.clone();
+        for space in model
+            .spaces
+            .iter_mut()
+            .filter(|s| s.kind == SpaceType::UNINHABITED)
+        {
+            space.inside_tenv = false;
+        }
+        Self::compute(&model)
+    }
+}
+
+/// Coeficiente de transferencia de calor por ventilación, H_ve = 0,34 · Σ n_v · V [W/K]
+///
+/// Se computan los espacios del interior de la envolvente térmica, teniendo en cuenta sus multiplicadores.
+/// Los espacios habitables sin tasa de ventilación definida usan la tasa de ventilación de diseño según
+/// la categoría de calidad del aire de sus cargas o, si no se define, la tasa global de ventilación del edificio.
+/// Los no habitables sin tasa definida no computan.
+pub(crate) fn h_ve(props: &EnergyProps) -> f32 {
+    let global_n_v = props.global.global_ventilation_rate;
+    props
+        .spaces
+        .iter()
+        .filter(|(_, s)| s.inside_tenv)
+        .map(|(id, s)| {
+            let n_v = match s.n_v {
+                Some(n_v) => n_v,
+                None if s.kind != SpaceType::UNINHABITED => {
+                    props.design_ventilation_rate(*id).unwrap_or(global_n_v)
+                }
+                None => 0.0,
+            };
+            RHO_C_AIR * n_v * s.volume_net * s.multiplier
+        })
+        .sum()
 }
@@ -0,0 +1,211 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Sistemas de unidades para la presentación de los indicadores energéticos
+//!
+//! Los indicadores se calculan y almacenan en unidades del SI. La conversión a unidades IP
+//! (sistema anglosajón) es solo una vista de salida que usa los factores exactos de definición:
+//! 1 ft = 0,3048 m, 1 Btu (IT) = 1055,05585262 J y 1 ºF de diferencia = 5/9 K.
+
+use serde::{Deserialize, Serialize};
+
+use super::{k::KTBContribution, EnergyIndicators, KData, N50Data, QSolJulData};
+
+/// Longitud [m/ft]
+const M_PER_FT: f64 = 0.3048;
+/// Energía [J/Btu]
+const J_PER_BTU: f64 = 1_055.055_852_62;
+/// Diferencia de temperaturas [K/ºF]
+const K_PER_F: f64 = 5.0 / 9.0;
+
+/// Sistema de unidades
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitSystem {
+    /// Sistema Internacional (m, m², m³, W/K, W/m²K, m³/h)
+    #[default]
+    SI,
+    /// Sistema anglosajón (ft, ft², ft³, Btu/h·ºF, Btu/h·ft²·ºF, cfm)
+    IP,
+}
+
+/// Factores de conversión de cada magnitud, como valor en el sistema de destino de una unidad del de origen
+#[derive(Debug, Copy, Clone)]
+struct Factors {
+    /// Longitud [m -> ft]
+    length: f32,
+    /// Superficie [m² -> ft²]
+    area: f32,
+    /// Volumen [m³ -> ft³]
+    volume: f32,
+    /// Coeficiente de transferencia de calor [W/K -> Btu/h·ºF]
+    conductance: f32,
+    /// Transmitancia térmica [W/m²K -> Btu/h·ft²·ºF]
+    u_value: f32,
+    /// Caudal [m³/h -> ft³/min]
+    flow: f32,
+    /// Caudal por unidad de superficie [m³/h·m² -> ft³/min·ft²]
+    flow_per_area: f32,
+}
+
+impl Factors {
+    /// Factores para pasar del sistema `from` al sistema `to`
+    fn new(from: UnitSystem, to: UnitSystem) -> Self {
+        // Valor IP de una unidad SI
+        let length = 1.0 / M_PER_FT;
+        let area = length * length;
+        let volume = area * length;
+        let conductance = 3600.0 / J_PER_BTU * K_PER_F;
+        let u_value = conductance / area;
+        let flow = volume / 60.0;
+        let flow_per_area = flow / area;
+
+        let si_to_ip = [
+            length,
+            area,
+            volume,
+            conductance,
+            u_value,
+            flow,
+            flow_per_area,
+        ];
+        let f = match (from, to) {
+            (UnitSystem::SI, UnitSystem::IP) => si_to_ip,
+            (UnitSystem::IP, UnitSystem::SI) => si_to_ip.map(|v| 1.0 / v),
+            _ => [1.0; 7],
+        }
+        .map(|v| v as f32);
+
+        Self {
+            length: f[0],
+            area: f[1],
+            volume: f[2],
+            conductance: f[3],
+            u_value: f[4],
+            flow: f[5],
+            flow_per_area: f[6],
+        }
+    }
+}
+
+impl EnergyIndicators {
+    /// Devuelve una copia de los indicadores expresada en el sistema de unidades indicado
+    ///
+    /// Se convierten las superficies, volúmenes, longitudes, transmitancias, coeficientes de transferencia
+    /// de calor y caudales de los indicadores. Las energías e irradiaciones (q_sol;jul), las magnitudes
+    /// adimensionales y las tasas de renovación (n50) se mantienen, así como las propiedades energéticas
+    /// del modelo (`props`), que siempre se expresan en el SI.
+    pub fn to_units(&self, system: UnitSystem) -> EnergyIndicators {
+        let f = Factors::new(self.units, system);
+        EnergyIndicators {
+            units: system,
+            area_ref: self.area_ref * f.area,
+            compactness: self.compactness * f.length,
+            vol_env_net: self.vol_env_net * f.volume,
+            vol_env_gross: self.vol_env_gross * f.volume,
+            H_ve: self.H_ve * f.conductance,
+            props: self.props.clone(),
+            K_data: k_data_to_units(&self.K_data, &f),
+            tb_contributions: self
+                .tb_contributions
+                .iter()
+                .map(|c| KTBContribution {
+                    l: c.l * f.length,
+                    psil: c.psil * f.conductance,
+                    ..*c
+                })
+                .collect(),
+            q_soljul_data: q_soljul_data_to_units(&self.q_soljul_data, &f),
+            n50_data: n50_data_to_units(&self.n50_data, &f),
+            f_shobst_mean_by_orientation: self.f_shobst_mean_by_orientation.clone(),
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+fn k_data_to_units(data: &KData, f: &Factors) -> KData {
+    let mut data = *data;
+    data.K *= f.u_value;
+
+    let s = &mut data.summary;
+    s.a *= f.area;
+    s.au *= f.conductance;
+    s.opaques_a *= f.area;
+    s.opaques_au *= f.conductance;
+    s.windows_a *= f.area;
+    s.windows_au *= f.conductance;
+    s.tbs_l *= f.length;
+    s.tbs_psil *= f.conductance;
+
+    for e in [
+        &mut data.walls,
+        &mut data.roofs,
+        &mut data.floors,
+        &mut data.ground,
+        &mut data.windows,
+    ] {
+        e.a *= f.area;
+        e.au *= f.conductance;
+        for u in [&mut e.u_max, &mut e.u_min, &mut e.u_mean]
+            .into_iter()
+            .flatten()
+        {
+            *u *= f.u_value;
+        }
+    }
+
+    let tbs = &mut data.tbs;
+    for tb in [
+        &mut tbs.roof,
+        &mut tbs.balcony,
+        &mut tbs.corner,
+        &mut tbs.intermediate_floor,
+        &mut tbs.internal_wall,
+        &mut tbs.ground_floor,
+        &mut tbs.pillar,
+        &mut tbs.window,
+        &mut tbs.generic,
+    ] {
+        tb.l *= f.length;
+        tb.psil *= f.conductance;
+    }
+
+    let h = &mut data.h_tr;
+    for v in [
+        &mut h.walls,
+        &mut h.roofs,
+        &mut h.floors,
+        &mut h.ground,
+        &mut h.windows,
+        &mut h.tbs,
+        &mut h.total,
+        &mut h.partitions,
+    ] {
+        *v *= f.conductance;
+    }
+    data
+}
+
+fn q_soljul_data_to_units(data: &QSolJulData, f: &Factors) -> QSolJulData {
+    let mut data = data.clone();
+    data.a_wp *= f.area;
+    for detail in data.detail.values_mut() {
+        detail.a *= f.area;
+    }
+    data
+}
+
+fn n50_data_to_units(data: &N50Data, f: &Factors) -> N50Data {
+    let mut data = *data;
+    data.walls_a *= f.area;
+    data.walls_c_ref *= f.flow_per_area;
+    data.walls_c_a_ref *= f.flow;
+    data.walls_c *= f.flow_per_area;
+    data.walls_c_a *= f.flow;
+    data.windows_a *= f.area;
+    data.windows_c *= f.flow_per_area;
+    data.windows_c_a *= f.flow;
+    data.extra_c_a *= f.flow;
+    data.vol *= f.volume;
+    data
+}
@@ -0,0 +1,202 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Capacidad térmica interna del edificio
+//!
+//! Se obtiene con el método simplificado de la UNE-EN ISO 13786 (Anexo A), sumando la capacidad térmica
+//! de las capas de cada cerramiento próximas a su cara interior, hasta una profundidad máxima de 10 cm.
+//! La capacidad térmica por superficie útil permite clasificar la inercia del edificio según las clases
+//! de la UNE-EN ISO 13790 (Tabla 12), usadas en el método mensual.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::EnergyProps;
+use crate::{BoundaryType, ConsDb, MatProps, Model, Tilt, Uuid, WallCons};
+
+/// Profundidad máxima de las capas que participan en la capacidad térmica interna [m]
+const MAX_DEPTH: f32 = 0.10;
+/// Capacidad térmica interna por superficie útil, para edificios de inercia media [J/m²K]
+/// Se usa cuando no puede calcularse a partir de las construcciones
+pub(crate) const C_M_PER_AREA: f32 = 165_000.0;
+
+/// Clase de inercia térmica del edificio según su capacidad térmica interna por superficie útil
+///
+/// Los límites entre clases son los valores medios entre las capacidades térmicas por defecto
+/// de clases consecutivas de la UNE-EN ISO 13790 (Tabla 12).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ThermalMassClass {
+    /// Muy ligera, C_m/A < 95 000 J/m²K
+    VERYLIGHT,
+    /// Ligera, 95 000 <= C_m/A < 137 500 J/m²K
+    LIGHT,
+    /// Media, 137 500 <= C_m/A < 212 500 J/m²K
+    MEDIUM,
+    /// Pesada, 212 500 <= C_m/A < 315 000 J/m²K
+    HEAVY,
+    /// Muy pesada, C_m/A >= 315 000 J/m²K
+    VERYHEAVY,
+}
+
+impl ThermalMassClass {
+    /// Clase de inercia para una capacidad térmica interna por superficie útil [J/m²K]
+    pub fn from_c_m_per_area(c_m_per_area: f32) -> Self {
+        match c_m_per_area {
+            c if c < 95_000.0 => Self::VERYLIGHT,
+            c if c < 137_500.0 => Self::LIGHT,
+            c if c < 212_500.0 => Self::MEDIUM,
+            c if c < 315_000.0 => Self::HEAVY,
+            _ => Self::VERYHEAVY,
+        }
+    }
+
+    /// Capacidad térmica interna por superficie útil por defecto de la clase [J/m²K]
+    pub fn c_m_per_area(&self) -> f32 {
+        match self {
+            Self::VERYLIGHT => 80_000.0,
+            Self::LIGHT => 110_000.0,
+            Self::MEDIUM => C_M_PER_AREA,
+            Self::HEAVY => 260_000.0,
+            Self::VERYHEAVY => 370_000.0,
+        }
+    }
+}
+
+impl Display for ThermalMassClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match *self {
+            Self::VERYLIGHT => "VERYLIGHT",
+            Self::LIGHT => "LIGHT",
+            Self::MEDIUM => "MEDIUM",
+            Self::HEAVY => "HEAVY",
+            Self::VERYHEAVY => "VERYHEAVY",
+        };
+        write!(f, "{}", printable)
+    }
+}
+
+impl MatProps {
+    /// Capacidad térmica volumétrica del material, ρ·c_p [J/m³K]
+    ///
+    /// Los materiales definidos por su resistencia térmica no tienen capacidad térmica (None)
+    pub fn volumetric_heat_capacity(&self) -> Option<f32> {
+        match *self {
+            MatProps::Detailed {
+                density,
+                specific_heat,
+                ..
+            }
+            | MatProps::MoistureDependent {
+                density,
+                specific_heat,
+                ..
+            } => Some(density * specific_heat),
+            MatProps::Resistance { .. } => None,
+        }
+    }
+}
+
+impl WallCons {
+    /// Capacidad térmica por superficie de las capas próximas a una de las caras de la composición [J/m²K]
+    ///
+    /// Las capas se ordenan del exterior al interior. Se suman las capas desde la cara interior
+    /// (o desde la cara exterior, si se indica `from_exterior`) hasta una profundidad de 10 cm.
+    /// Los materiales no encontrados o sin capacidad térmica no contribuyen.
+    pub fn areal_heat_capacity(&self, db: &ConsDb, from_exterior: bool) -> f32 {
+        let mut layers: Vec<_> = self.layers.iter().collect();
+        if !from_exterior {
+            layers.reverse();
+        }
+        let mut depth = 0.0;
+        let mut kappa = 0.0;
+        for layer in layers {
+            if depth >= MAX_DEPTH {
+                break;
+            }
+            let e = layer.e_mean().min(MAX_DEPTH - depth);
+            depth += e;
+            kappa += db
+                .get_material(layer.material)
+                .and_then(|m| m.properties.volumetric_heat_capacity())
+                .unwrap_or_default()
+                * e;
+        }
+        kappa
+    }
+}
+
+impl Model {
+    /// Capacidad térmica interna de los espacios de la envolvente térmica, C_m [J/K]
+    ///
+    /// Suma de κ·A de las caras de los opacos que dan a espacios del interior de la envolvente térmica,
+    /// teniendo en cuenta los multiplicadores de los espacios. Las particiones entre espacios de la envolvente
+    /// contribuyen por ambas caras. Se descuenta la superficie de huecos.
+    pub fn internal_heat_capacity(&self) -> f32 {
+        let inside_tenv = |id| self.get_space(id).is_some_and(|s| s.inside_tenv);
+        let multiplier = |id| self.get_space(id).map_or(1.0, |s| s.multiplier);
+
+        self.walls
+            .iter()
+            .filter_map(|wall| {
+                let cons = self.cons.get_wallcons(wall.cons)?;
+                let area = wall.area_net(&self.windows);
+                let mut c = 0.0;
+                if inside_tenv(wall.space) {
+                    c +=
+                        cons.areal_heat_capacity(&self.cons, false) * area * multiplier(wall.space);
+                }
+                // Cara exterior de particiones interiores en contacto con otro espacio de la envolvente
+                if let Some(next_to) = wall.next_to {
+                    if wall.bounds == BoundaryType::INTERIOR && inside_tenv(next_to) {
+                        // Los elementos horizontales se cuentan una vez por cada espacio
+                        let next_multiplier = match Tilt::from(wall) {
+                            Tilt::SIDE => multiplier(wall.space),
+                            _ => multiplier(next_to),
+                        };
+                        c += cons.areal_heat_capacity(&self.cons, true) * area * next_multiplier;
+                    }
+                }
+                Some(c)
+            })
+            .sum()
+    }
+
+    /// Clase de inercia térmica del edificio
+    ///
+    /// Se obtiene a partir de la capacidad térmica interna por superficie útil de la envolvente térmica.
+    /// Si no puede calcularse (sin superficie útil o sin capacidad térmica de las construcciones)
+    /// se supone inercia media.
+    pub fn thermal_mass_class(&self) -> ThermalMassClass {
+        let a_ref = EnergyProps::from(self).global.a_ref;
+        let c_m = self.internal_heat_capacity();
+        if a_ref < f32::EPSILON || c_m <= 0.0 {
+            return ThermalMassClass::MEDIUM;
+        }
+        ThermalMassClass::from_c_m_per_area(c_m / a_ref)
+    }
+
+    /// Capacidad térmica interna de un espacio, sin tener en cuenta su multiplicador [J/K]
+    ///
+    /// Suma de κ·A de las caras de los opacos que dan al espacio, descontando la superficie de huecos.
+    pub(crate) fn space_heat_capacity(&self, space_id: Uuid) -> f32 {
+        self.walls
+            .iter()
+            .filter_map(|wall| {
+                let from_exterior = if wall.space == space_id {
+                    false
+                } else if wall.next_to == Some(space_id) && wall.bounds == BoundaryType::INTERIOR {
+                    true
+                } else {
+                    return None;
+                };
+                let cons = self.cons.get_wallcons(wall.cons)?;
+                Some(
+                    cons.areal_heat_capacity(&self.cons, from_exterior)
+                        * wall.area_net(&self.windows),
+                )
+            })
+            .sum()
+    }
+}
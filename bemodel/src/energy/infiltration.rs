@@ -0,0 +1,82 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Cálculo horario de la tasa de infiltración dependiente del viento y la temperatura
+//!
+//! Usa un modelo simplificado de tipo AIM-2 (Walker y Wilson, 1993), sin chimeneas ni conductos,
+//! en el que los caudales debidos al efecto chimenea y al viento se obtienen a partir del
+//! coeficiente de flujo de la envolvente y se combinan en cuadratura (superposición LBL).
+
+use climate::MetData;
+
+use super::{indicators::N50Data, EnergyProps};
+use crate::Model;
+
+/// Exponente de flujo de la envolvente [-]
+const N_FLOW: f32 = 0.67;
+/// Fracción de la superficie de fugas situada en suelos y techos [-]
+const R_LEAKAGE: f32 = 0.5;
+/// Coeficiente de protección frente al viento (entorno con obstáculos moderados) [-]
+const SHELTER: f32 = 0.7;
+/// Densidad del aire [kg/m³]
+const RHO_AIR: f32 = 1.2;
+/// Aceleración de la gravedad [m/s²]
+const G: f32 = 9.81;
+/// Temperatura interior de referencia [ºC]
+const T_INT: f32 = 20.0;
+/// Altura de medida del viento en la estación meteorológica [m]
+const H_MET: f32 = 10.0;
+
+impl Model {
+    /// Tasa horaria de infiltración del edificio, para cada hora de los datos climáticos [1/h]
+    ///
+    /// Se usa el coeficiente de flujo de la envolvente obtenido a partir de las permeabilidades
+    /// de opacos (C_o) y huecos (C_h) a 100 Pa, junto a las infiltraciones adicionales declaradas, la velocidad del viento del archivo climático
+    /// corregida a la altura del edificio y la diferencia entre la temperatura exterior y una
+    /// temperatura interior de referencia de 20ºC.
+    ///
+    /// Con viento nulo y sin diferencia de temperaturas la infiltración es nula (valor mínimo).
+    pub fn infiltration_rate_hourly(&self, met: &MetData) -> Vec<f32> {
+        let props = EnergyProps::from(self);
+        let n50data = N50Data::from(&props);
+        let vol = n50data.vol;
+        if vol < 0.001 {
+            return vec![0.0; met.data.len()];
+        }
+
+        // Coeficiente de flujo de la envolvente, Q = c · ΔP^n [m³/h·Paⁿ]
+        let c_flow =
+            (n50data.walls_c_a + n50data.windows_c_a + n50data.extra_c_a) / 100_f32.powf(N_FLOW);
+
+        // Altura de la envolvente térmica [m]
+        let tenv_spaces = self.spaces.iter().filter(|s| s.inside_tenv);
+        let z_min = tenv_spaces
+            .clone()
+            .map(|s| s.z)
+            .fold(f32::INFINITY, f32::min);
+        let z_max = tenv_spaces
+            .map(|s| s.z + s.height)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let height = (z_max - z_min).max(0.0);
+
+        // Factores de efecto chimenea y viento sin chimeneas (X = 0)
+        let f_s = (1.0 + N_FLOW * R_LEAKAGE / 2.0) / (N_FLOW + 1.0) * 0.5_f32.powf(N_FLOW + 1.0);
+        let f_w = 0.19 * (2.0 - N_FLOW) * (1.0 - (R_LEAKAGE / 2.0).powf(1.5));
+        // Corrección de la velocidad del viento a la altura del edificio
+        let wind_factor = (height.max(1.0) / H_MET).powf(0.25);
+
+        met.data
+            .iter()
+            .map(|d| {
+                let delta_t = (T_INT - d.db_temp).abs();
+                let p_stack = RHO_AIR * G * height * delta_t / (T_INT + 273.15);
+                let wind_speed = SHELTER * d.wind_speed.max(0.0) * wind_factor;
+                let p_wind = 0.5 * RHO_AIR * wind_speed * wind_speed;
+                let q_stack = c_flow * f_s * p_stack.powf(N_FLOW);
+                let q_wind = c_flow * f_w * p_wind.powf(N_FLOW);
+                (q_stack * q_stack + q_wind * q_wind).sqrt() / vol
+            })
+            .collect()
+    }
+}
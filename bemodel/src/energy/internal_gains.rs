@@ -0,0 +1,49 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Ganancias internas horarias de los espacios por ocupación, equipos e iluminación
+//!
+//! Cada carga (SpaceLoads) se modula con su horario anual de fracciones de carga.
+
+use crate::{Model, Uuid};
+
+/// Número de horas del año
+const HOURS: usize = 8760;
+
+impl Model {
+    /// Ganancias internas sensibles de un espacio para cada hora del año (8760 valores) [W]
+    ///
+    /// Suma las cargas sensibles de ocupación, equipos e iluminación del espacio, cada una multiplicada
+    /// por su fracción de carga horaria, por la superficie del espacio y por su multiplicador.
+    /// Las cargas sin horario definido no computan y los espacios inexistentes
+    /// o sin cargas definidas tienen ganancias nulas.
+    pub fn internal_gains_hourly(&self, space_id: Uuid) -> Vec<f32> {
+        let mut gains = vec![0.0; HOURS];
+        let space = match self.get_space(space_id) {
+            Some(space) => space,
+            None => return gains,
+        };
+        let loads = match space
+            .loads
+            .and_then(|id| self.loads.iter().find(|l| l.id == id))
+        {
+            Some(loads) => loads,
+            None => return gains,
+        };
+        let factor = space.area(&self.walls) * space.multiplier;
+        for (load, sch) in [
+            (loads.people_sensible, loads.people_schedule),
+            (loads.equipment, loads.equipment_schedule),
+            (loads.lighting, loads.lighting_schedule),
+        ] {
+            let Some(sch) = sch else {
+                continue;
+            };
+            for (g, v) in gains.iter_mut().zip(self.schedules.year_values(sch)) {
+                *g += load * v * factor;
+            }
+        }
+        gains
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Cálculo horario de la carga latente de los espacios acondicionados
+//!
+//! Se consideran las aportaciones de humedad de los ocupantes, según la carga latente de ocupación
+//! y su horario, y la entrada de humedad con el aire de ventilación, según la diferencia entre la
+//! humedad específica exterior y una humedad interior de referencia.
+
+use std::collections::BTreeMap;
+
+use climate::MetData;
+
+use super::EnergyProps;
+use crate::{Model, SpaceType, Uuid};
+
+/// Densidad del aire [kg/m³]
+const RHO_AIR: f32 = 1.2;
+/// Calor latente de vaporización del agua [J/kg]
+const H_FG: f32 = 2_501_000.0;
+/// Humedad específica interior de referencia (24ºC y 50% de humedad relativa) [kg_agua/kg_aire_seco]
+const X_INT: f32 = 0.0093;
+
+impl Model {
+    /// Carga latente de cada espacio acondicionado, para cada hora de los datos climáticos [W]
+    ///
+    /// La carga no incluye el multiplicador del espacio y no puede ser negativa.
+    /// Los espacios sin ocupación declarada solo tienen carga latente por ventilación, que es nula
+    /// cuando el aire exterior es más seco que el interior.
+    /// Para los espacios sin tasa de ventilación definida se usa la tasa de ventilación de diseño según la
+    /// categoría de calidad del aire de sus cargas o, si no se define, la tasa global de ventilación del edificio.
+    pub fn latent_load_hourly(&self, met: &MetData) -> BTreeMap<Uuid, Vec<f32>> {
+        let props = EnergyProps::from(self);
+        let global_n_v = self.global_ventilation_rate();
+
+        let mut map = BTreeMap::new();
+        for (id, space) in props
+            .spaces
+            .iter()
+            .filter(|(_, s)| s.kind == SpaceType::CONDITIONED)
+        {
+            // Carga latente de ocupación [W]
+            let occupancy = space
+                .loads
+                .and_then(|id| props.loads.get(&id))
+                .and_then(|loads| {
+                    let people_latent = loads.people_latent * space.area;
+                    loads.people_schedule.map(|sch| {
+                        self.schedules
+                            .year_values(sch)
+                            .iter()
+                            .map(|v| v * people_latent)
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .unwrap_or_default();
+
+            // Caudal de ventilación [kg/s]
+            let n_v = space
+                .n_v
+                .or_else(|| props.design_ventilation_rate(*id))
+                .unwrap_or(global_n_v);
+            let m_v = RHO_AIR * n_v * space.volume_net / 3600.0;
+
+            let latent = met
+                .data
+                .iter()
+                .enumerate()
+                .map(|(i, d)| {
+                    let q_occ = occupancy.get(i).copied().unwrap_or_default();
+                    let q_vent = m_v * H_FG * (d.abs_humidity - X_INT);
+                    (q_occ + q_vent).max(0.0)
+                })
+                .collect();
+            map.insert(*id, latent);
+        }
+        map
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Potencia instalada y consumo de iluminación de los espacios
+//!
+//! Se usan las cargas de iluminación de los espacios (SpaceLoads) y su horario anual de fracciones de carga.
+
+use std::collections::BTreeMap;
+
+use crate::{Model, Uuid};
+
+impl Model {
+    /// Densidad de potencia de iluminación de cada espacio [W/m²]
+    ///
+    /// Los espacios sin cargas definidas tienen una densidad de potencia nula.
+    pub fn lighting_power_density(&self) -> BTreeMap<Uuid, f32> {
+        self.spaces
+            .iter()
+            .map(|s| {
+                let power = s
+                    .loads
+                    .and_then(|id| self.loads.iter().find(|l| l.id == id))
+                    .map(|l| l.lighting)
+                    .unwrap_or_default();
+                (s.id, power)
+            })
+            .collect()
+    }
+
+    /// Consumo anual de iluminación del edificio [kWh/año]
+    ///
+    /// Se obtiene para cada espacio como producto de su densidad de potencia de iluminación,
+    /// su superficie y la suma anual de las fracciones de carga de su horario de iluminación,
+    /// teniendo en cuenta los multiplicadores de los espacios.
+    /// Los espacios sin iluminación o sin horario de iluminación definidos no computan.
+    pub fn lighting_energy_annual(&self) -> f32 {
+        self.spaces
+            .iter()
+            .filter_map(|s| {
+                let loads = s
+                    .loads
+                    .and_then(|id| self.loads.iter().find(|l| l.id == id))?;
+                let hours: f32 = self
+                    .schedules
+                    .year_values(loads.lighting_schedule?)
+                    .iter()
+                    .sum();
+                Some(loads.lighting * s.area(&self.walls) * s.multiplier * hours / 1000.0)
+            })
+            .sum()
+    }
+}
@@ -6,18 +6,47 @@
 //!
 //! Cálculo de propiedades e indicadores energéticos del modelo y sus elementos
 
+mod carbon;
+mod comfort;
+mod dhw;
 mod indicators;
+mod inertia;
+mod infiltration;
+mod internal_gains;
+mod latent;
+mod lighting;
+mod night_insulation;
+mod peak_loads;
+mod pick;
 mod props;
 mod radiation;
 mod raytracing;
+mod seasonal;
+mod sol_air;
+mod solar_aperture;
+mod solar_gains;
+pub mod thermal_bridge_2d;
 mod transmittance;
+mod ventilation;
+mod view_factors;
 
-pub use indicators::EnergyIndicators;
+pub use indicators::{EnergyIndicators, UnitSystem};
+pub use inertia::ThermalMassClass;
+pub use peak_loads::{PeakLoad, PeakLoads};
 pub use props::EnergyProps;
-pub use radiation::ray_dir_to_sun;
-pub use raytracing::{Bounded, Intersectable, Ray, AABB, BVH};
+pub(crate) use radiation::F_W;
+pub use radiation::{ray_dir_to_sun, BackfaceCulling, FshobstOptions, OccludersBVH};
+pub use raytracing::{Bounded, Intersectable, Occluder, Ray, AABB, BVH};
+pub use seasonal::SeasonalDemand;
 
-use crate::Model;
+use std::collections::BTreeMap;
+
+use log::warn;
+
+use crate::{
+    checks::WINDOW_OVERLAP_MIN_AREA, climatedata::monthly_radiation_for_surface, BoundaryType,
+    Model, Orientation, Tilt, Uuid, Window,
+};
 
 impl Model {
     /// Calcula indicadores energéticos
@@ -25,6 +54,44 @@ impl Model {
         EnergyIndicators::compute(self)
     }
 
+    /// Calcula indicadores energéticos para distintos valores de la U de los huecos, para estudios de sensibilidad
+    ///
+    /// Para cada valor [W/m²K] se fija la U de todos los huecos del modelo, con prioridad sobre los valores
+    /// calculados y los de usuario, y se calculan los indicadores. El modelo original no se modifica.
+    pub fn sensitivity_window_u(&self, values: &[f32]) -> Vec<EnergyIndicators> {
+        values
+            .iter()
+            .map(|u_value| {
+                let mut model = self.clone();
+                for win in &self.windows {
+                    model.overrides.windows.entry(win.id).or_default().u_value = Some(*u_value);
+                }
+                model.energy_indicators()
+            })
+            .collect()
+    }
+
+    /// Factor de obstáculos remotos medio necesario, por orientación, para alcanzar un valor objetivo de q_sol;jul [-]
+    ///
+    /// Las ganancias de los huecos son proporcionales a su F_sh;obst, por lo que se reducen las de todas las orientaciones
+    /// en la misma proporción, q_sol;jul,obj / q_sol;jul. El valor de cada orientación es la media actual,
+    /// ponderada por área de captación (EnergyIndicators::f_shobst_mean_by_orientation), escalada en esa proporción,
+    /// de modo que al asignarlo a todos los huecos de la orientación se alcanza el objetivo.
+    /// Si el modelo ya cumple el objetivo se devuelven los valores medios actuales.
+    pub fn required_shading(&self, target_qsoljul: f32) -> BTreeMap<Orientation, f32> {
+        let ind = self.energy_indicators();
+        let q_soljul = ind.q_soljul_data.q_soljul;
+        let ratio = if q_soljul > 0.0 {
+            (target_qsoljul / q_soljul).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        ind.f_shobst_mean_by_orientation
+            .into_iter()
+            .map(|(orientation, f_shobst)| (orientation, ratio * f_shobst))
+            .collect()
+    }
+
     /// Tasa global de ventilación del edificio (1/h)
     pub fn global_ventilation_rate(&self) -> f32 {
         use crate::{utils::fround2, SpaceType};
@@ -56,4 +123,154 @@ impl Model {
             .map(|n_v_g| 3.6 * n_v_g / vol_env_inh_net)
             .unwrap_or_default()
     }
+
+    /// Superficie de intercambio térmico de la envolvente térmica [m²]
+    ///
+    /// Suma de las superficies brutas de los opacos de la envolvente térmica en contacto con el aire exterior
+    /// o el terreno, incluidos sus huecos, teniendo en cuenta los multiplicadores de los espacios.
+    /// Es la superficie usada para el cálculo de la compacidad V/A.
+    pub fn envelope_surface_area(&self) -> f32 {
+        EnergyProps::from(self)
+            .walls
+            .values()
+            .filter(|w| {
+                w.is_tenv && matches!(w.bounds, BoundaryType::EXTERIOR | BoundaryType::GROUND)
+            })
+            .map(|w| w.area_gross * w.multiplier)
+            .sum()
+    }
+
+    /// Radiación solar total mensual sobre los opacos exteriores y sus huecos [kWh/m²·mes]
+    ///
+    /// Se obtiene para la zona climática del modelo interpolando los datos mensuales de radiación
+    /// según la inclinación y orientación reales de cada opaco, que comparten sus huecos.
+    /// No se tienen en cuenta las sombras.
+    pub fn monthly_radiation_on_surfaces(&self) -> BTreeMap<Uuid, [f32; 12]> {
+        let mut map = BTreeMap::new();
+        for wall in self
+            .walls
+            .iter()
+            .filter(|w| w.bounds == BoundaryType::EXTERIOR)
+        {
+            let radiation = match monthly_radiation_for_surface(
+                &self.meta.climate,
+                wall.geometry.tilt,
+                wall.geometry.azimuth,
+            ) {
+                Some(radiation) => radiation,
+                None => continue,
+            };
+            map.insert(wall.id, radiation);
+            for win in self.windows_of_wall(wall.id) {
+                map.insert(win.id, radiation);
+            }
+        }
+        map
+    }
+
+    /// Superficie de huecos del modelo, descontando los solapes entre huecos de un mismo opaco [m²]
+    ///
+    /// Tiene en cuenta los multiplicadores de los espacios.
+    /// No incluye las puertas opacas.
+    /// Avisa de los opacos con solapes significativos entre sus huecos.
+    pub fn glazed_area_net(&self) -> f32 {
+        let glazed_windows = self.glazed_windows();
+        self.walls
+            .iter()
+            .map(|wall| {
+                let multiplier = self
+                    .get_space(wall.space)
+                    .map(|s| s.multiplier)
+                    .unwrap_or(1.0);
+                let glazed_area = wall.glazed_area_net(&glazed_windows);
+                let overlap =
+                    wall.windows(&glazed_windows).map(|w| w.area()).sum::<f32>() - glazed_area;
+                if overlap > WINDOW_OVERLAP_MIN_AREA {
+                    warn!(
+                        "Opaco {} ({}) con huecos solapados en {:.2} m²",
+                        wall.id, wall.name, overlap
+                    );
+                }
+                glazed_area * multiplier
+            })
+            .sum()
+    }
+
+    /// Huecos acristalados del modelo (excluye las puertas opacas)
+    fn glazed_windows(&self) -> Vec<Window> {
+        self.windows
+            .iter()
+            .filter(|w| !w.is_opaque_door())
+            .cloned()
+            .collect()
+    }
+
+    /// Superficie construida de cada planta [m²]
+    ///
+    /// Suma de la proyección horizontal de los suelos de los espacios de cada planta,
+    /// teniendo en cuenta los multiplicadores de los espacios.
+    /// Es la superficie construida bruta, distinta de la superficie útil.
+    /// Los espacios sin planta asignada se agrupan con un nombre de planta vacío.
+    pub fn built_area_by_floor(&self) -> BTreeMap<String, f32> {
+        let mut areas: BTreeMap<String, f32> = BTreeMap::new();
+        for space in &self.spaces {
+            let floor_name = space
+                .floor
+                .and_then(|id| self.get_floor(id))
+                .map(|f| f.name.clone())
+                .unwrap_or_default();
+            let projected_area = self
+                .walls
+                .iter()
+                .filter(|w| w.space == space.id && Tilt::from(*w) == Tilt::BOTTOM)
+                .map(|w| w.area() * w.geometry.tilt.to_radians().cos().abs())
+                .sum::<f32>();
+            *areas.entry(floor_name).or_default() += projected_area * space.multiplier;
+        }
+        areas
+    }
+
+    /// Superficie construida total del edificio [m²]
+    ///
+    /// Suma de las superficies construidas de todas las plantas
+    pub fn built_area(&self) -> f32 {
+        self.built_area_by_floor().values().sum()
+    }
+
+    /// Porcentaje de huecos por fachada (WWR) para cada orientación [-]
+    ///
+    /// Relación entre la superficie de huecos y la superficie bruta (opaco + huecos) de los muros
+    /// exteriores de cada orientación, teniendo en cuenta los multiplicadores de los espacios.
+    /// Los solapes entre huecos de un mismo muro se cuentan una sola vez.
+    /// Se excluyen suelos y cubiertas, y las puertas opacas no computan como superficie de huecos.
+    pub fn wwr_by_orientation(&self) -> BTreeMap<Orientation, f32> {
+        let glazed_windows = self.glazed_windows();
+        let mut areas: BTreeMap<Orientation, (f32, f32)> = BTreeMap::new();
+        for wall in self
+            .walls
+            .iter()
+            .filter(|w| w.bounds == BoundaryType::EXTERIOR && Tilt::from(*w) == Tilt::SIDE)
+        {
+            let multiplier = self
+                .get_space(wall.space)
+                .map(|s| s.multiplier)
+                .unwrap_or(1.0);
+            let win_area = wall.glazed_area_net(&glazed_windows);
+            let entry = areas.entry(Orientation::from(wall)).or_default();
+            entry.0 += win_area * multiplier;
+            entry.1 += wall.area() * multiplier;
+        }
+
+        areas
+            .into_iter()
+            .map(|(orientation, (win_area, wall_area))| {
+                let wwr = if wall_area > 0.0 {
+                    win_area / wall_area
+                } else {
+                    0.0
+                };
+                (orientation, wwr)
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,85 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Transmisión horaria de calor por los huecos con protección nocturna (persianas o contraventanas)
+//!
+//! El cierre de la protección añade una resistencia térmica ΔR a la del hueco, de modo que la
+//! transmitancia en cada hora es U_h = (1 - f)·U + f·U_cerr, con U_cerr = 1 / (1 / U + ΔR)
+//! y f la fracción de cierre de la protección en esa hora.
+
+use std::collections::BTreeMap;
+
+use climate::MetData;
+
+use super::EnergyProps;
+use crate::{BoundaryType, Model, Uuid, Window};
+
+/// Temperatura interior de referencia [ºC]
+const T_INT: f32 = 20.0;
+
+impl Model {
+    /// Transmitancia térmica horaria de los huecos con protección nocturna [W/m²K]
+    ///
+    /// Devuelve None si el hueco no tiene protección nocturna, en cuyo caso se usa la transmitancia del hueco.
+    /// La fracción de cierre de cada hora se toma del horario de la protección o, si no se define,
+    /// vale 1 en las horas sin sol de los datos climáticos y 0 en el resto.
+    pub fn window_u_value_hourly(
+        &self,
+        window: &Window,
+        u_value: f32,
+        met: &MetData,
+    ) -> Option<Vec<f32>> {
+        let delta_r = window.night_insulation.filter(|r| *r > 0.0)?;
+        let u_closed = if u_value > 0.0 {
+            1.0 / (1.0 / u_value + delta_r)
+        } else {
+            0.0
+        };
+        let closed: Vec<f32> = match window.night_insulation_schedule {
+            Some(sch) => self.schedules.year_values(sch),
+            None => met
+                .data
+                .iter()
+                .map(|d| if d.zenith >= 90.0 { 1.0 } else { 0.0 })
+                .collect(),
+        };
+        Some(
+            (0..met.data.len())
+                .map(|i| {
+                    let f = closed.get(i).copied().unwrap_or_default().clamp(0.0, 1.0);
+                    (1.0 - f) * u_value + f * u_closed
+                })
+                .collect(),
+        )
+    }
+
+    /// Pérdidas horarias de calor por transmisión de los huecos exteriores de la envolvente térmica [W]
+    ///
+    /// Se calculan como U_h·A·(θ_int - θ_ext), con una temperatura interior de referencia de 20ºC,
+    /// teniendo en cuenta el multiplicador de los espacios y el cierre de las protecciones nocturnas.
+    /// Los valores negativos corresponden a ganancias de calor.
+    pub fn windows_transmission_losses_hourly(&self, met: &MetData) -> BTreeMap<Uuid, Vec<f32>> {
+        let props = EnergyProps::from(self);
+        self.windows
+            .iter()
+            .filter_map(|win| {
+                let wp = props.windows.get(&win.id)?;
+                if !wp.is_tenv || wp.bounds != BoundaryType::EXTERIOR {
+                    return None;
+                }
+                let u_value = wp.u_value_override.or(wp.u_value).unwrap_or_default();
+                let area = wp.area * wp.multiplier;
+                let u_hourly = self
+                    .window_u_value_hourly(win, u_value, met)
+                    .unwrap_or_else(|| vec![u_value; met.data.len()]);
+                let losses = u_hourly
+                    .iter()
+                    .zip(&met.data)
+                    .map(|(u, d)| u * area * (T_INT - d.db_temp))
+                    .collect();
+                Some((win.id, losses))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Cargas máximas (pico) de calefacción y refrigeración, para el dimensionado de equipos
+//!
+//! Se obtienen del balance sensible horario de los espacios (ver Model::operative_temperature_hourly)
+//! y de su carga latente (ver Model::latent_load_hourly), a lo largo de todas las horas de los datos climáticos.
+
+use std::collections::BTreeMap;
+
+use climate::MetData;
+use serde::{Deserialize, Serialize};
+
+use crate::{Model, Uuid};
+
+/// Carga máxima y hora en la que se produce
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeakLoad {
+    /// Potencia máxima [W]
+    pub power: f32,
+    /// Posición de la hora en los datos climáticos
+    pub index: usize,
+    /// Mes (1 a 12)
+    pub month: u32,
+    /// Día (1 a 31)
+    pub day: u32,
+    /// Hora (1 a 24)
+    pub hour: f32,
+}
+
+/// Cargas máximas de calefacción y refrigeración del edificio y de sus espacios
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeakLoads {
+    /// Carga máxima de calefacción del edificio, teniendo en cuenta los multiplicadores de los espacios [W]
+    pub heating: PeakLoad,
+    /// Carga máxima de refrigeración del edificio, teniendo en cuenta los multiplicadores de los espacios [W]
+    pub cooling: PeakLoad,
+    /// Carga máxima de calefacción de cada espacio acondicionado, sin su multiplicador [W]
+    pub heating_by_space: BTreeMap<Uuid, PeakLoad>,
+    /// Carga máxima de refrigeración de cada espacio acondicionado, sin su multiplicador [W]
+    pub cooling_by_space: BTreeMap<Uuid, PeakLoad>,
+}
+
+impl Model {
+    /// Cargas máximas (pico) de calefacción y refrigeración del edificio y de cada espacio [W]
+    ///
+    /// La carga de calefacción es la carga sensible necesaria para mantener la consigna de calefacción.
+    /// La de refrigeración es la carga sensible necesaria para mantener la consigna de refrigeración más
+    /// la carga latente, que se suma en las horas sin demanda de calefacción.
+    /// Se usan consignas constantes, la máxima de calefacción y la mínima de refrigeración de los horarios,
+    /// para que los picos no reflejen la puesta a régimen tras los periodos de consigna reducida o sin acondicionar.
+    /// Las cargas del edificio son las máximas de la suma horaria de las de los espacios, que no tienen por qué
+    /// coincidir en la misma hora. Solo se incluyen los espacios acondicionados.
+    pub fn peak_loads(&self, met: &MetData) -> PeakLoads {
+        let latent = self.latent_load_hourly(met);
+        let n_hours = met.data.len();
+
+        let mut peaks = PeakLoads::default();
+        let mut heating_total = vec![0.0; n_hours];
+        let mut cooling_total = vec![0.0; n_hours];
+        for (id, balance) in self.sensible_balance_hourly(met, true) {
+            // Solo los espacios acondicionados tienen carga latente
+            let latent = match latent.get(&id) {
+                Some(latent) => latent,
+                None => continue,
+            };
+            let multiplier = self.get_space(id).map_or(1.0, |s| s.multiplier);
+            let heating: Vec<f32> = balance.load.iter().map(|l| l.max(0.0)).collect();
+            let cooling: Vec<f32> = balance
+                .load
+                .iter()
+                .zip(latent)
+                .map(|(&l, &lat)| if l > 0.0 { 0.0 } else { lat - l })
+                .collect();
+            for (total, v) in heating_total.iter_mut().zip(&heating) {
+                *total += v * multiplier;
+            }
+            for (total, v) in cooling_total.iter_mut().zip(&cooling) {
+                *total += v * multiplier;
+            }
+            peaks.heating_by_space.insert(id, peak(&heating, met));
+            peaks.cooling_by_space.insert(id, peak(&cooling, met));
+        }
+        peaks.heating = peak(&heating_total, met);
+        peaks.cooling = peak(&cooling_total, met);
+        peaks
+    }
+}
+
+/// Valor máximo de una serie horaria y hora en la que se produce
+///
+/// Si hay varias horas con el valor máximo se devuelve la primera
+fn peak(values: &[f32], met: &MetData) -> PeakLoad {
+    let (mut index, mut power) = (0, 0.0);
+    for (i, &v) in values.iter().enumerate() {
+        if v > power {
+            (index, power) = (i, v);
+        }
+    }
+    let (month, day, hour) = met
+        .data
+        .get(index)
+        .map_or((0, 0, 0.0), |d| (d.month, d.day, d.hour));
+    PeakLoad {
+        power,
+        index,
+        month,
+        day,
+        hour,
+    }
+}
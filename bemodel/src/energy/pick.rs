@@ -0,0 +1,60 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Selección de elementos del modelo mediante un rayo (p.e. al pulsar sobre un visor 3D)
+
+use super::raytracing::{Bounded, Occluder, Ray, BVH};
+use crate::{types::HasSurface, Model, Uuid, WallGeom};
+
+/// Tolerancia para considerar coincidentes dos intersecciones [m]
+const TOL: f32 = 1e-4;
+
+impl Model {
+    /// Elemento (opaco o hueco) más cercano que intersecta el rayo, y distancia a la intersección desde su origen [m]
+    ///
+    /// Se consideran todos los opacos y huecos con definición geométrica completa, por cualquiera de sus caras.
+    /// Los huecos están en el plano de su opaco, por lo que, a igual distancia, se selecciona el hueco.
+    /// La BVH de los elementos se construye en cada consulta.
+    /// Devuelve None si el rayo no intersecta ningún elemento
+    pub fn pick(&self, ray: Ray) -> Option<(Uuid, f32)> {
+        let occluder = |id: Uuid, linked_to_id: Option<Uuid>, geometry: &WallGeom| Occluder {
+            id,
+            linked_to_id,
+            normal: geometry.polygon.normal(),
+            trans_matrix: geometry.to_global_coords_matrix().map(|m| m.inverse()),
+            polygon: geometry.polygon.clone(),
+            aabb: geometry.aabb(),
+            transmittance: 0.0,
+            backface_culling: false,
+        };
+
+        let mut elements: Vec<_> = self
+            .walls
+            .iter()
+            .filter(|w| w.geometry.position.is_some() && w.geometry.polygon.len() >= 3)
+            .map(|w| occluder(w.id, None, &w.geometry))
+            .collect();
+        // Los huecos se enlazan a su opaco
+        elements.extend(self.windows.iter().filter_map(|win| {
+            let geometry = win.global_geometry(&self.get_wall(win.wall)?.geometry)?;
+            Some(occluder(win.id, Some(win.wall), &geometry))
+        }));
+        if elements.is_empty() {
+            return None;
+        }
+
+        let bvh = BVH::build(elements, 30);
+        bvh.intersections(&ray)
+            .into_iter()
+            .min_by(|(a, t_a), (b, t_b)| {
+                if (t_a - t_b).abs() < TOL {
+                    // Preferencia de los huecos frente a los opacos
+                    b.linked_to_id.is_some().cmp(&a.linked_to_id.is_some())
+                } else {
+                    t_a.total_cmp(t_b)
+                }
+            })
+            .map(|(oc, t)| (oc.id, t))
+    }
+}
@@ -11,7 +11,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 use crate::{
-    utils::fround2, BoundaryType, Model, Orientation, SpaceType, ThermalBridgeKind, Tilt, Uuid,
+    utils::fround2, AirQuality, BoundaryType, Model, Orientation, SpaceType, ThermalBridgeKind,
+    Tilt, Uuid,
 };
 
 /// Reporte de cálculo de propiedades térmicas y geométricas del modelo
@@ -114,6 +115,7 @@ impl From<&Model> for EnergyProps {
                 equipment_schedule: s.equipment_schedule,
                 lighting: s.lighting,
                 lighting_schedule: s.lighting_schedule,
+                air_quality: s.air_quality,
                 loads_avg,
             };
             loads.insert(s.id, e);
@@ -130,6 +132,7 @@ impl From<&Model> for EnergyProps {
             let wcp = WallConsProps {
                 thickness: wc.thickness(),
                 resistance: wc.resistance(&model.cons).ok(),
+                absorptance: wc.absorptance,
             };
             wallcons.insert(wc.id, wcp);
         }
@@ -142,7 +145,7 @@ impl From<&Model> for EnergyProps {
             let g_glwi = wc.g_glwi(&model.cons).unwrap_or(0.77);
             let g_glshwi = wc.g_glshwi(&model.cons).unwrap_or(g_glwi);
             let wcp = WinConsProps {
-                c_100: wc.c_100,
+                c_100: wc.c_100_effective(),
                 u_value: wc.u_value(&model.cons),
                 g_glwi,
                 g_glshwi,
@@ -219,10 +222,12 @@ impl From<&Model> for EnergyProps {
                 tilt: Tilt::from(w),
                 area_gross: w.area(),
                 area_net: w.area_net(&model.windows),
+                glazed_area_net: w.glazed_area_net(&model.windows),
                 multiplier: spaces.get(&w.space).map_or(1.0, |sp| sp.multiplier),
                 is_tenv: tenv_wall_ids.contains(&w.id),
                 u_value: w.u_value(model),
                 u_value_override: wall_override.and_then(|o| o.u_value),
+                solar_gain_factor: w.solar_gain_factor(),
             };
             walls.insert(w.id, wp);
         }
@@ -242,10 +247,12 @@ impl From<&Model> for EnergyProps {
                 multiplier: wall.map_or(1.0, |wp| wp.multiplier),
                 bounds: wall.map(|w| w.bounds).unwrap_or_default(),
                 is_tenv: tenv_wall_ids.contains(&w.wall),
-                u_value: wincons.get(&w.cons).and_then(|c| c.u_value),
+                u_value: w.u_value_adjusted(model),
                 u_value_override: win_override.and_then(|o| o.u_value),
                 f_shobst: fshobstmap.get(&w.id).copied(),
                 f_shobst_override: win_override.and_then(|o| o.f_shobst),
+                door_c_100: w.door_c_100,
+                door_absorptance: w.opaque_door.map(|d| d.absorptance),
             };
             windows.insert(w.id, wp);
         }
@@ -257,6 +264,10 @@ impl From<&Model> for EnergyProps {
                 kind: tb.kind,
                 l: tb.l,
                 psi: tb.psi,
+                multiplier: tb
+                    .space
+                    .and_then(|id| spaces.get(&id))
+                    .map_or(1.0, |sp| sp.multiplier),
             };
             thermal_bridges.insert(tb.id, tbp);
         }
@@ -314,7 +325,7 @@ impl From<&Model> for EnergyProps {
             spaces
                 .values()
                 .map(|s| {
-                    if !s.inside_tenv && s.kind != SpaceType::UNINHABITED {
+                    if s.inside_tenv && s.kind != SpaceType::UNINHABITED {
                         s.area * s.height_net * s.multiplier
                     } else {
                         0.0
@@ -442,6 +453,7 @@ impl From<&Model> for EnergyProps {
             global_ventilation_rate,
             n_50_test_ach: model.meta.n50_test_ach,
             c_o_100,
+            extra_leakage_100: model.meta.extra_leakage.iter().map(|e| e.flow_100).sum(),
             occ_spaces_hours_in_use,
             occ_spaces_average_load,
         };
@@ -494,6 +506,8 @@ pub struct GlobalProps {
     /// NOTE: usamos is_new_building pero igual merecería la pena una variable
     /// para permeabilidad mejorada
     pub c_o_100: f32,
+    /// Caudal de las infiltraciones adicionales a las de opacos y huecos a 100 Pa [m³/h]
+    pub extra_leakage_100: f32,
     /// Tiempo total de ocupación de los espacios habitables en el interior de
     /// la envolvente térmica, h
     /// Se computa la ocupación de cualquier espacio a cada hora, de modo que el máximo
@@ -552,6 +566,8 @@ pub struct WallProps {
     pub area_gross: f32,
     /// Superficie neta del opaco, [m²]
     pub area_net: f32,
+    /// Superficie de huecos del opaco, descontando solapes entre huecos, [m²]
+    pub glazed_area_net: f32,
     /// Multiplicador del espacio, [-]
     pub multiplier: f32,
     /// ¿Pertenece este opaco a la envolvente térmica?
@@ -560,6 +576,8 @@ pub struct WallProps {
     pub u_value: Option<f32>,
     /// U de opaco (usuario), [W/m²K]
     pub u_value_override: Option<f32>,
+    /// Factor de reducción de la ganancia solar por cámara ventilada, [-]
+    pub solar_gain_factor: f32,
 }
 
 /// Propiedades de huecos
@@ -581,7 +599,7 @@ pub struct WinProps {
     pub bounds: BoundaryType,
     /// ¿Pertenece a la envolvente térmica el opaco en el que se sitúa el hueco?
     pub is_tenv: bool,
-    /// U de huecos (calculado), [W/m²K]
+    /// U de huecos (calculado, con el factor b en huecos hacia espacios no acondicionados), [W/m²K]
     pub u_value: Option<f32>,
     /// U de huecos (usuario), [W/m²K]
     pub u_value_override: Option<f32>,
@@ -589,6 +607,10 @@ pub struct WinProps {
     pub f_shobst: Option<f32>,
     /// Factor de obstrucción de obstáculos remotos (usuario), [-]
     pub f_shobst_override: Option<f32>,
+    /// Permeabilidad al aire a 100 Pa de las puertas, [m³/hm²]
+    pub door_c_100: Option<f32>,
+    /// Absortividad de las puertas opacas (None en los huecos acristalados), [-]
+    pub door_absorptance: Option<f32>,
 }
 
 /// Propiedades de puentes térmicos
@@ -601,6 +623,8 @@ pub struct TbProps {
     pub l: f32,
     /// Transmitancia térmica lineal del puente térmico (W/mK)
     pub psi: f32,
+    /// Multiplicador del espacio al que pertenece o 1.0 si no está asociado a un espacio, [-]
+    pub multiplier: f32,
 }
 
 /// Propiedades de sombras
@@ -621,6 +645,8 @@ pub struct WallConsProps {
     pub thickness: f32,
     // Resistencia térmica de la construcción (excluyendo resistencias superficiales), [m²K/W]
     pub resistance: Option<f32>,
+    /// Absortividad solar de la cara exterior, [-]
+    pub absorptance: f32,
 }
 
 /// Propiedades de construcciones de opacos
@@ -634,7 +660,7 @@ pub struct WinConsProps {
     pub g_glshwi: f32,
     /// U de construcción de hueco, [W/m²K]
     pub u_value: Option<f32>,
-    /// Permeabilidad al aire del hueco a 100 Pa, [m³/h·m²]
+    /// Permeabilidad al aire efectiva del hueco a 100 Pa, [m³/h·m²]
     pub c_100: f32,
     /// Fracción de marco del hueco, [-]
     pub f_f: f32,
@@ -697,6 +723,8 @@ pub struct LoadsProps {
     pub lighting: f32,
     /// Horario anual de fracciones de carga de iluminación
     pub lighting_schedule: Option<Uuid>,
+    /// Categoría de calidad del aire interior
+    pub air_quality: Option<AirQuality>,
     /// Carga interna media (ocupación sensible, iluminación, equipos), W/m²
     pub loads_avg: f32,
 }
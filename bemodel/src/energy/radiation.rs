@@ -5,24 +5,145 @@
 //! Implementación del cálculo del factor de obstáculos remotos de los huecos. Usa raytracing
 //! sobre una malla de puntos del hueco y una estructura BVH para acelerar el cálculo.
 
-use std::collections::BTreeMap;
+use std::{borrow::Borrow, collections::BTreeMap};
 
 use log::{debug, warn};
+use rayon::prelude::*;
 
-use climate::{nday_from_md, radiation_for_surface, SolarRadiation};
+use climate::{nday_from_ymd, radiation_for_surface, MetData, SolarRadiation};
 
 use crate::{
-    climatedata::{RadData, CLIMATEMETADATA, JULYRADDATA},
+    climatedata::{synthesize_hourly, RadData, JULYRADDATA},
     energy::raytracing::{Bounded, Intersectable, Occluder, Ray, AABB, BVH},
     point,
     types::HasSurface,
-    utils::fround2,
+    utils::{fround2, uuid_from_obj},
     vector,
     BoundaryType::{ADIABATIC, EXTERIOR},
     ConsDb, Model, Point3, Shade, Uuid, Vector3, WallGeom, WinCons, Window,
 };
 
+/// Opciones del cálculo de los factores de obstáculos remotos de los huecos
+///
+/// Los valores por defecto reproducen el cálculo de compute_fshobst:
+/// muestreo automático del hueco, todas las horas de sol, solo el mes de julio y cálculo en paralelo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FshobstOptions {
+    /// Número de puntos de muestreo por dimensión del hueco
+    /// Con None se usan entre 5 y 10 puntos según el tamaño del hueco
+    pub samples_per_window: Option<usize>,
+    /// Paso entre los instantes de cálculo, en horas [h]
+    /// Con 1 se evalúan todas las horas con sol
+    pub time_step: usize,
+    /// Meses del cálculo [1, 12]
+    /// Se evalúa el día 1 de cada mes
+    pub months: Vec<u32>,
+    /// Cálculo en paralelo de los huecos
+    /// Con false se fuerza el cálculo secuencial (p.e. para depuración)
+    pub parallel: bool,
+    /// Tipos de elementos que solo ocluyen por su cara exterior
+    pub backface_culling: BackfaceCulling,
+}
+
+/// Tipos de elementos oclusores que solo bloquean la radiación por su cara exterior (back-face culling)
+///
+/// Por defecto todos los elementos son de doble cara. Las sombras de retranqueos y lamas de los huecos
+/// son siempre de doble cara.
+/// La cara exterior es la definida por la normal del polígono del elemento.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackfaceCulling {
+    /// Los opacos solo ocluyen por su cara exterior
+    pub walls: bool,
+    /// Las sombras solo ocluyen por su cara exterior
+    pub shades: bool,
+}
+
+impl Default for FshobstOptions {
+    fn default() -> Self {
+        Self {
+            samples_per_window: None,
+            time_step: 1,
+            months: vec![7],
+            parallel: true,
+            backface_culling: BackfaceCulling::default(),
+        }
+    }
+}
+
+/// BVH de los oclusores del modelo, reutilizable entre cálculos
+///
+/// Incluye los oclusores opacos y, aparte, las sombras con horario de transmisividad, cuyo efecto varía en cada hora.
+/// Guarda una huella de los elementos del modelo con los que se construyó, para detectar si ha quedado obsoleta
+/// tras cambiar la geometría del modelo.
+pub struct OccludersBVH {
+    /// BVH de los oclusores opacos
+    pub bvh: BVH<Occluder>,
+    /// Oclusores con horario de transmisividad y sus valores horarios (8760 valores)
+    pub scheduled: Vec<(Occluder, Vec<f32>)>,
+    /// Tipos de elementos con oclusión solo por su cara exterior
+    pub culling: BackfaceCulling,
+    /// Huella de los elementos del modelo usados en la construcción
+    key: Uuid,
+}
+
+impl OccludersBVH {
+    /// Comprueba si la BVH corresponde a los elementos actuales del modelo
+    pub fn is_valid_for(&self, model: &Model) -> bool {
+        self.key == model.occluders_key()
+    }
+
+    /// Oclusores con horario de transmisividad, con su transmisividad en la hora del año indicada [0, 8759]
+    fn scheduled_at(&self, idx: usize) -> Vec<Occluder> {
+        self.scheduled
+            .iter()
+            .map(|(oc, values)| {
+                let mut oc = oc.clone();
+                oc.transmittance = values.get(idx).copied().unwrap_or(0.0).clamp(0.0, 1.0);
+                oc
+            })
+            .collect()
+    }
+}
+
 impl Model {
+    /// Construye la BVH de los oclusores del modelo, para reutilizarla en varios cálculos
+    ///
+    /// Debe reconstruirse si cambia la geometría del modelo (ver OccludersBVH::is_valid_for)
+    pub fn build_bvh(&self) -> OccludersBVH {
+        self.build_bvh_with_culling(BackfaceCulling::default())
+    }
+
+    /// Construye la BVH de los oclusores del modelo, con oclusión solo por la cara exterior en los tipos indicados
+    pub fn build_bvh_with_culling(&self, culling: BackfaceCulling) -> OccludersBVH {
+        let shade_schedules: Vec<_> = self
+            .shades
+            .iter()
+            .filter_map(|s| Some((s.id, self.schedules.year_values(s.schedule?))))
+            .collect();
+        let (scheduled, opaque): (Vec<_>, Vec<_>) = self
+            .collect_occluders_with_culling(culling)
+            .into_iter()
+            .partition(|oc| shade_schedules.iter().any(|(id, _)| *id == oc.id));
+        let scheduled = scheduled
+            .into_iter()
+            .filter_map(|oc| {
+                let (_, values) = shade_schedules.iter().find(|(id, _)| *id == oc.id)?;
+                Some((oc, values.clone()))
+            })
+            .collect();
+        OccludersBVH {
+            bvh: BVH::build(opaque, 30),
+            scheduled,
+            culling,
+            key: self.occluders_key(),
+        }
+    }
+
+    /// Huella de los elementos del modelo que intervienen en la generación de oclusores
+    fn occluders_key(&self) -> Uuid {
+        uuid_from_obj(&(&self.walls, &self.windows, &self.shades, &self.schedules))
+    }
+
     /// Recalcula los factores de obstáculos remotos para los huecos
     ///
     /// Considera el sombreamiento de elementos de opaco y sombra sobre el hueco
@@ -31,6 +152,99 @@ impl Model {
     /// sin calcularlos a partir de la visión del cielo o el terreno y las reflexiones.
     /// Por esto, tiende a sobreestimar el valor respecto a un método con backwards raytracing completo.
     pub fn compute_fshobst(&self) -> BTreeMap<Uuid, f32> {
+        self.compute_fshobst_with_options(&FshobstOptions::default())
+    }
+
+    /// Calcula los factores de obstáculos remotos para los huecos con las opciones indicadas
+    ///
+    /// Permite equilibrar precisión y velocidad de cálculo en modelos grandes, reduciendo el número de
+    /// puntos de muestreo de los huecos o de instantes de cálculo.
+    /// Para julio se usan los datos de radiación del 1 de julio de la zona climática (como en compute_fshobst)
+    /// y, para el resto de meses, los del día 1 del mes de los datos horarios sintéticos de la zona.
+    /// El valor de cada hueco es el promedio de los instantes de todos los meses.
+    pub fn compute_fshobst_with_options(&self, options: &FshobstOptions) -> BTreeMap<Uuid, f32> {
+        self.compute_fshobst_with_bvh(
+            &self.build_bvh_with_culling(options.backface_culling),
+            options,
+        )
+    }
+
+    /// Calcula los factores de obstáculos remotos para los huecos con una BVH de oclusores ya construida
+    ///
+    /// Si la BVH no corresponde a la geometría actual del modelo o a las opciones de back-face culling
+    /// se reconstruye.
+    pub fn compute_fshobst_with_bvh(
+        &self,
+        bvh: &OccludersBVH,
+        options: &FshobstOptions,
+    ) -> BTreeMap<Uuid, f32> {
+        let rebuilt;
+        let bvh = if bvh.is_valid_for(self) && bvh.culling == options.backface_culling {
+            bvh
+        } else {
+            warn!("BVH de oclusores obsoleta. Se reconstruye con la geometría actual del modelo");
+            rebuilt = self.build_bvh_with_culling(options.backface_culling);
+            &rebuilt
+        };
+        let latitude = self.meta.site_latitude();
+        let mut raddata = vec![];
+        if options.months.contains(&7) {
+            if let Some(julyraddata) = JULYRADDATA.lock().unwrap().get(&self.meta.climate) {
+                raddata.extend(julyraddata.iter().cloned());
+            }
+        }
+        if options.months.iter().any(|m| *m != 7) {
+            match synthesize_hourly(&self.meta.climate) {
+                Ok(met) => {
+                    for month in options.months.iter().filter(|m| **m != 7) {
+                        raddata.extend(raddata_for_day(&met, *month, 1));
+                    }
+                }
+                Err(e) => warn!(
+                    "No se han podido generar los datos climáticos horarios: {}",
+                    e
+                ),
+            }
+        }
+        let raddata: Vec<_> = raddata
+            .into_iter()
+            .step_by(options.time_step.max(1))
+            .collect();
+        self.compute_fshobst_for_raddata(&raddata, latitude, options, bvh)
+    }
+
+    /// Calcula los factores de obstáculos remotos para los huecos en el día indicado, con los datos climáticos dados
+    ///
+    /// Permite evaluar las sombras en otros periodos distintos del de julio, como el invierno,
+    /// lo que es relevante con sombras estacionales (p.e. vegetación de hoja caduca).
+    /// Se usan las horas del día con el sol sobre el horizonte.
+    pub fn compute_fshobst_for_day(
+        &self,
+        met: &MetData,
+        month: u32,
+        day: u32,
+    ) -> BTreeMap<Uuid, f32> {
+        let raddata = raddata_for_day(met, month, day);
+        self.compute_fshobst_for_raddata(
+            &raddata,
+            met.meta.latitude,
+            &FshobstOptions::default(),
+            &self.build_bvh(),
+        )
+    }
+
+    /// Calcula los factores de obstáculos remotos para los huecos con los datos de radiación indicados
+    ///
+    /// La transmisividad de las sombras con horario se evalúa en cada hora de los datos.
+    /// Los huecos son independientes entre sí y se calculan en paralelo, salvo que se indique lo contrario en las opciones.
+    /// De las opciones solo se usan el muestreo de los huecos y el cálculo en paralelo.
+    fn compute_fshobst_for_raddata(
+        &self,
+        raddata: &[RadData],
+        latitude: f32,
+        options: &FshobstOptions,
+        bvh: &OccludersBVH,
+    ) -> BTreeMap<Uuid, f32> {
         /// Estructura interna de datos para el soporte del cálculo de fshobst de huecos
         #[derive(Default, Debug)]
         struct ObstData {
@@ -42,30 +256,25 @@ impl Model {
             dif: Vec<f32>,
         }
 
-        let occluders = self.collect_occluders();
+        if raddata.is_empty() {
+            return BTreeMap::new();
+        }
 
-        let mut map: BTreeMap<Uuid, ObstData> = BTreeMap::new();
-        let mut fshobstmap: BTreeMap<Uuid, f32> = BTreeMap::new();
+        // Sombras con horario de transmisividad para cada hora
+        let hourly_scheduled: Vec<Vec<Occluder>> = raddata
+            .iter()
+            .map(|d| {
+                let nday = nday_from_ymd(2001, d.month, d.day) as usize;
+                bvh.scheduled_at((nday - 1) * 24 + (d.hour.ceil() as usize).clamp(1, 24) - 1)
+            })
+            .collect();
 
-        let latitude = CLIMATEMETADATA
-            .lock()
-            .unwrap()
-            .get(&self.meta.climate)
-            .unwrap()
-            .latitude;
-        let julyraddata = JULYRADDATA.lock().unwrap();
-        let raddata = match julyraddata.get(&self.meta.climate) {
-            Some(data) => data,
-            None => return fshobstmap,
-        };
-        for window in &self.windows {
-            // if window.name != "P01_E01_PE004_V" {continue};
-            let window_wall = match self.get_wall(window.wall) {
-                None => continue,
-                Some(wall) => wall,
-            };
-            let ray_origins = self.ray_origins_for_window(window);
-            for d in raddata {
+        let window_obst_data = |window: &Window| {
+            let window_wall = self.get_wall(window.wall)?;
+            let ray_origins =
+                self.ray_origins_for_window_with_samples(window, options.samples_per_window);
+            let mut windata = ObstData::default();
+            for (i, d) in raddata.iter().enumerate() {
                 let RadData {
                     month,
                     day,
@@ -77,7 +286,7 @@ impl Model {
                     ..
                 } = *d;
                 let ray_dir = ray_dir_to_sun(azimuth, altitude);
-                let nday = nday_from_md(month, day);
+                let nday = nday_from_ymd(2001, month, day);
                 let rad_on_win = radiation_for_surface(
                     nday,
                     hour,
@@ -87,13 +296,29 @@ impl Model {
                     window_wall.geometry.azimuth,
                     0.2,
                 );
-                let fshdir = self.sunlit_fraction(window, &ray_origins, &ray_dir, &occluders);
-                let windata = map.entry(window.id).or_default();
+                let fshdir = self.sunlit_fraction_with_bvh(
+                    window,
+                    &ray_origins,
+                    &ray_dir,
+                    &bvh.bvh,
+                    &hourly_scheduled[i],
+                );
                 windata.fshdir.push(fshdir);
                 windata.dir.push(rad_on_win.dir);
                 windata.dif.push(rad_on_win.dif);
             }
-        }
+            Some((window.id, windata))
+        };
+        let map: BTreeMap<Uuid, ObstData> = if options.parallel {
+            self.windows
+                .par_iter()
+                .filter_map(window_obst_data)
+                .collect()
+        } else {
+            self.windows.iter().filter_map(window_obst_data).collect()
+        };
+
+        let mut fshobstmap: BTreeMap<Uuid, f32> = BTreeMap::new();
         for (id, d) in map.iter() {
             let nvalues = d.fshdir.len();
             let mut fshobst_sum = 0.0;
@@ -124,6 +349,37 @@ impl Model {
         ray_dir: &Vector3,
         occluders: &[Occluder],
     ) -> f32 {
+        // Los elementos parcialmente transparentes bloquean la fracción de radiación que no transmiten
+        let (opaque, translucent): (Vec<_>, Vec<_>) = occluders
+            .iter()
+            .filter(|oc| oc.transmittance < 1.0)
+            .partition(|oc| oc.transmittance <= 0.0);
+        let translucent: Vec<_> = translucent.into_iter().cloned().collect();
+        self.sunlit_fraction_with_bvh(
+            window,
+            ray_origins,
+            ray_dir,
+            &BVH::build(opaque, 30),
+            &translucent,
+        )
+    }
+
+    /// Fracción del hueco con radiación solar directa para la posición solar dada, con una BVH ya construida [0.0 - 1.0]
+    ///
+    /// Permite reutilizar la BVH de los oclusores opacos (ver build_bvh) para distintas posiciones solares,
+    /// descartando en cada consulta el opaco del hueco y las sombras de retranqueo y lamas de otros huecos.
+    /// translucent: oclusores parcialmente transparentes, que bloquean la fracción de radiación que no transmiten
+    pub fn sunlit_fraction_with_bvh<T>(
+        &self,
+        window: &Window,
+        ray_origins: &[Point3],
+        ray_dir: &Vector3,
+        bvh: &BVH<T>,
+        translucent: &[Occluder],
+    ) -> f32
+    where
+        T: Bounded + Intersectable + Borrow<Occluder>,
+    {
         let window_wall = match self.get_wall(window.wall) {
             None => {
                 warn!(
@@ -137,7 +393,7 @@ impl Model {
 
         // Elementos sin definición geométrica completa. No podemos calcular las obstrucciones
         let geometry = &window_wall.geometry;
-        if geometry.position.is_none() {
+        if geometry.position.is_none() || ray_origins.is_empty() {
             warn!(
                 "Hueco {} (id: {}) sin definición geométrica completa. Se considera superficie soleada al 100%",
                 window.name, window.id
@@ -147,39 +403,81 @@ impl Model {
 
         // Comprobamos que la normal del opaco y el rayo hacia el sol no son opuestos (backface culling)
         // Si no, el rayo iría al interior del hueco, está en sombra, y devolvemos 0.0
-        if window_wall.geometry.normal().dot(ray_dir) < 0.01 {
+        // Usamos la normal según la inclinación y orientación del opaco, como en el cálculo de la radiación,
+        // ya que el sentido de giro de los polígonos de cubiertas no siempre es coherente con ellas
+        if window_wall.geometry.outward_normal().dot(ray_dir) < 0.01 {
             return 0.0;
         }
 
-        let candidate_occluders: Vec<_> = occluders
-            .iter()
-            .filter(|oc| {
-                // Descartamos el opaco al que pertenece el hueco
-                if oc.id == window_wall.id {
+        let is_candidate = |oc: &Occluder| {
+            // Descartamos los elementos transparentes
+            if oc.transmittance >= 1.0 {
+                return false;
+            };
+            // Descartamos el opaco al que pertenece el hueco
+            if oc.id == window_wall.id {
+                return false;
+            };
+            // Descartamos las sombras de retranqueo y lamas que no provienen del hueco
+            if let Some(id) = &oc.linked_to_id {
+                if *id != window.id {
                     return false;
                 };
-                // Descartamos las sombras de retranqueo que no provienen del hueco
-                if let Some(id) = &oc.linked_to_id {
-                    if *id != window.id {
-                        return false;
-                    };
-                };
-                true
-            })
-            .collect();
+            };
+            true
+        };
+        let translucent: Vec<_> = translucent.iter().filter(|oc| is_candidate(oc)).collect();
 
         let rays = ray_origins.iter().map(|origin| Ray::new(*origin, *ray_dir));
         let num_rays = rays.len();
-        let mut num_intersects = 0;
+        let mut blocked = 0.0;
 
-        let bvh = BVH::build(candidate_occluders, 30);
         for ray in rays {
-            if bvh.intersects(&ray).is_some() {
-                num_intersects += 1;
+            if bvh
+                .intersects_filtered(&ray, |oc| is_candidate(oc.borrow()))
+                .is_some()
+            {
+                blocked += 1.0;
+            } else {
+                blocked += translucent
+                    .iter()
+                    .filter(|oc| oc.intersects(&ray).is_some())
+                    .map(|oc| 1.0 - oc.transmittance)
+                    .fold(0.0, f32::max);
             }
         }
 
-        1.0 - num_intersects as f32 / num_rays as f32
+        1.0 - blocked / num_rays as f32
+    }
+
+    /// Fracción del hueco con radiación solar directa para cada hora de los datos climáticos [0.0 - 1.0]
+    ///
+    /// Usa la misma BVH de oclusores en todas las horas, evaluando las sombras con horario de transmisividad
+    /// en cada hora. En las horas sin sol la fracción es 0.0.
+    pub fn sunlit_fraction_hourly(
+        &self,
+        window: &Window,
+        met: &MetData,
+        bvh: &OccludersBVH,
+    ) -> Vec<f32> {
+        let ray_origins = self.ray_origins_for_window(window);
+        met.data
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                if d.zenith >= 90.0 {
+                    return 0.0;
+                }
+                let ray_dir = ray_dir_to_sun(d.azimuth, 90.0 - d.zenith);
+                self.sunlit_fraction_with_bvh(
+                    window,
+                    &ray_origins,
+                    &ray_dir,
+                    &bvh.bvh,
+                    &bvh.scheduled_at(i),
+                )
+            })
+            .collect()
     }
 
     /// Genera todas las sombras de retranqueo de los huecos del modelo
@@ -195,10 +493,47 @@ impl Model {
             .collect()
     }
 
-    /// Genera lista de elementos oclusores a partir de muros, sombras y sombras de retranqueo
+    /// Genera todas las sombras de las lamas de protección solar de los huecos del modelo
+    pub(crate) fn windows_louver_shades(&self) -> Vec<(Uuid, Shade)> {
+        self.windows
+            .iter()
+            .filter_map(|window| {
+                self.get_wall(window.wall)
+                    .map(|wall| window.shades_for_louvers(&wall.geometry))
+            })
+            .flatten()
+            .flatten()
+            .collect()
+    }
+
+    /// Caja alineada con los ejes que contiene toda la geometría del modelo, en coordenadas globales
+    ///
+    /// Incluye los opacos, las sombras y los retranqueos y lamas de los huecos, que, por lo demás,
+    /// quedan contenidos en el plano de su opaco.
+    /// Los elementos sin definición geométrica completa no se consideran y, si no hay ninguno,
+    /// se devuelve la AABB vacía por defecto.
+    pub fn bounding_box(&self) -> AABB {
+        let mut window_shades = self.windows_setback_shades();
+        window_shades.extend(self.windows_louver_shades());
+        self.walls
+            .iter()
+            .map(|w| &w.geometry)
+            .chain(self.shades.iter().map(|s| &s.geometry))
+            .chain(window_shades.iter().map(|(_, s)| &s.geometry))
+            .filter(|g| g.position.is_some() && !g.polygon.is_empty())
+            .fold(AABB::default(), |aabb, g| aabb.join(g.aabb()))
+    }
+
+    /// Genera lista de elementos oclusores a partir de muros, sombras, sombras de retranqueo y lamas
     /// Guarda el nombre del oclusor, su id y la geometría
     pub fn collect_occluders(&self) -> Vec<Occluder> {
-        let setback_shades = self.windows_setback_shades();
+        self.collect_occluders_with_culling(BackfaceCulling::default())
+    }
+
+    /// Genera lista de elementos oclusores, con oclusión solo por la cara exterior en los tipos de elementos indicados
+    pub fn collect_occluders_with_culling(&self, culling: BackfaceCulling) -> Vec<Occluder> {
+        let mut window_shades = self.windows_setback_shades();
+        window_shades.extend(self.windows_louver_shades());
         let mut occluders: Vec<_> = self
             .walls
             .iter()
@@ -214,6 +549,8 @@ impl Model {
                 trans_matrix: e.geometry.to_global_coords_matrix().map(|m| m.inverse()),
                 polygon: e.geometry.polygon.clone(),
                 aabb: e.geometry.aabb(),
+                transmittance: 0.0,
+                backface_culling: culling.walls,
             })
             .collect();
         occluders.extend(
@@ -227,24 +564,68 @@ impl Model {
                     trans_matrix: e.geometry.to_global_coords_matrix().map(|m| m.inverse()),
                     polygon: e.geometry.polygon.clone(),
                     aabb: e.geometry.aabb(),
+                    transmittance: 0.0,
+                    backface_culling: culling.shades,
                 }),
         );
-        occluders.extend(setback_shades.iter().map(|(wid, e)| Occluder {
+        occluders.extend(window_shades.iter().map(|(wid, e)| Occluder {
             id: e.id,
             linked_to_id: Some(*wid),
             normal: e.geometry.polygon.normal(),
             trans_matrix: e.geometry.to_global_coords_matrix().map(|m| m.inverse()),
             polygon: e.geometry.polygon.clone(),
             aabb: e.geometry.aabb(),
+            transmittance: 0.0,
+            backface_culling: false,
         }));
         occluders
     }
 
+    /// Malla de los elementos oclusores del cálculo de sombras en formato Wavefront OBJ
+    ///
+    /// Incluye exactamente los oclusores usados en el cálculo de sombras (ver collect_occluders),
+    /// como un objeto por oclusor con el nombre de su id (y el del elemento del que proviene, si lo hay),
+    /// para la depuración visual de la geometría de los obstáculos (p.e. duplicados o mal situados).
+    pub fn occluders_as_obj(&self) -> String {
+        let mut obj = String::from("# Oclusores del cálculo de sombras\n");
+        let mut n_vertices = 0;
+        for oc in self.collect_occluders() {
+            let vertices = oc.global_vertices();
+            if vertices.is_empty() {
+                continue;
+            }
+            match oc.linked_to_id {
+                Some(linked) => obj.push_str(&format!("o {}_{}\n", oc.id, linked)),
+                None => obj.push_str(&format!("o {}\n", oc.id)),
+            }
+            for v in &vertices {
+                obj.push_str(&format!("v {:.3} {:.3} {:.3}\n", v.x, v.y, v.z));
+            }
+            let face: Vec<_> = (1..=vertices.len())
+                .map(|i| (n_vertices + i).to_string())
+                .collect();
+            obj.push_str(&format!("f {}\n", face.join(" ")));
+            n_vertices += vertices.len();
+        }
+        obj
+    }
+
     /// Calcula los puntos de origen en el hueco para el cálculo de fracción sombreada
     ///
     /// Parte de una retícula dividida entre 5 y 10 partes por dimensión
     /// - en cada rectángulo el punto de muestreo podría ser aleatorio y no el punto central
     pub fn ray_origins_for_window(&self, window: &Window) -> Vec<Point3> {
+        self.ray_origins_for_window_with_samples(window, None)
+    }
+
+    /// Calcula los puntos de origen en el hueco con el número de puntos por dimensión indicado
+    ///
+    /// Con samples igual a None se usa el muestreo automático de ray_origins_for_window
+    pub fn ray_origins_for_window_with_samples(
+        &self,
+        window: &Window,
+        samples: Option<usize>,
+    ) -> Vec<Point3> {
         let wall = match self.get_wall(window.wall) {
             None => return vec![],
             Some(wall) => wall,
@@ -253,8 +634,13 @@ impl Model {
 
         // Definimos el número de puntos muestreados para que cada dimensión
         // se divida en fragmentos de 20cm aprox, sin pasar de 10 ni menos de 5
-        let n_x: usize = 10.min((wg.width / 20.0).round() as usize).max(5);
-        let n_y: usize = 10.min((wg.height / 20.0).round() as usize).max(5);
+        let (n_x, n_y) = match samples {
+            Some(n) => (n.max(1), n.max(1)),
+            None => (
+                10.min((wg.width / 20.0).round() as usize).max(5),
+                10.min((wg.height / 20.0).round() as usize).max(5),
+            ),
+        };
 
         let (x, y) = match wg.position {
             Some(p) => (p.x, p.y),
@@ -293,12 +679,15 @@ impl Model {
     }
 }
 
+/// Factor de corrección del factor solar del vidrio para incidencia no normal, F_w = g_glwi / g_gln [-]
+pub(crate) const F_W: f32 = 0.90;
+
 impl WinCons {
-    /// Transmitancia térmica total del acristalmiento (g_glwi = g_gln * 0.90) [-]
+    /// Transmitancia térmica total del acristalmiento (g_glwi = g_gln * F_W) [-]
     /// Corresponde al factor solar sin protección solar activada
     pub fn g_glwi(&self, db: &ConsDb) -> Option<f32> {
         let glass = db.get_glass(self.glass)?;
-        Some(fround2(glass.g_gln * 0.90))
+        Some(fround2(glass.g_gln * F_W))
     }
 
     /// Transmitancia térmica del acristalamiento con protecciones solares activadas, g_glshwi [-]
@@ -308,6 +697,23 @@ impl WinCons {
     }
 }
 
+/// Datos de radiación de las horas con sol del día indicado
+fn raddata_for_day(met: &MetData, month: u32, day: u32) -> Vec<RadData> {
+    met.data
+        .iter()
+        .filter(|d| d.month == month && d.day == day && d.zenith < 90.0)
+        .map(|d| RadData {
+            month: d.month,
+            day: d.day,
+            hour: d.hour,
+            azimuth: d.azimuth,
+            altitude: 90.0 - d.zenith,
+            dir: d.rdirhor,
+            dif: d.rdifhor,
+        })
+        .collect()
+}
+
 /// Vector orientado en la dirección del sol
 ///
 /// sun_azimuth: azimuth solar [-180.0,+180.0] (E+, W-, S=0)
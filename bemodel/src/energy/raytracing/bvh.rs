@@ -79,9 +79,9 @@ impl<T: Bounded> BVH<T> {
         let mut pending: Vec<TreeElement<T>> = Vec::new();
         // Nodos procesados (2*n-1 nodos con n terminales)
         let expected_num_nodes = if elements.is_empty() {
-            2 * (elements.len() / max_num_elements) - 1
-        } else {
             0
+        } else {
+            2 * (elements.len() / max_num_elements).max(1) - 1
         };
         let mut node_list: Vec<TreeElement<T>> = Vec::with_capacity(expected_num_nodes);
 
@@ -136,6 +136,14 @@ impl<T: Bounded> BVH<T> {
         // Al final del proceso contiene el nodo raíz
         let mut completed: BTreeMap<NodeId, BVHNode<T>> = BTreeMap::new();
 
+        // Con un único nodo terminal este es el nodo raíz
+        if node_list.len() == 1 {
+            if let Some(TreeElement(_, Leaf, _, _, Some(elements))) = node_list.pop() {
+                let aabb = elements.aabb();
+                return Self::new(Some(BVHNode::Leaf { aabb, elements }));
+            }
+        }
+
         // Vamos añadiendo los nodos que tenemos a sus elementos padre y
         // a medida que los completamos los añadimos a sus respectivos padres
         while node_list.len() > 1 {
@@ -218,16 +226,19 @@ impl<T: Bounded> BVH<T> {
     }
 }
 
-impl<T> Intersectable for BVH<T>
+impl<T> BVH<T>
 where
     T: Bounded + Intersectable,
 {
-    fn intersects(&self, ray: &Ray) -> Option<f32> {
+    /// Comprueba la intersección del rayo con los elementos que cumplen la condición indicada
+    ///
+    /// Permite reutilizar una misma BVH descartando algunos de sus elementos en cada consulta
+    pub fn intersects_filtered<F: Fn(&T) -> bool>(&self, ray: &Ray, filter: F) -> Option<f32> {
         let hits_iter = self
             .iter_with_ray(ray)
             .filter(|e| matches!(e, BVHNode::Leaf { .. }));
         for e in hits_iter {
-            for occ in e.elements()? {
+            for occ in e.elements()?.iter().filter(|occ| filter(occ)) {
                 if let intersect_opt @ Some(_) = occ.intersects(ray) {
                     return intersect_opt;
                 }
@@ -235,6 +246,27 @@ where
         }
         None
     }
+
+    /// Elementos con los que colisiona el rayo, con la distancia desde su origen a la intersección
+    ///
+    /// A diferencia de intersects, que se detiene en la primera colisión, recorre todos los elementos
+    /// cuya AABB colisiona con el rayo, y permite localizar el elemento más cercano
+    pub fn intersections(&self, ray: &Ray) -> Vec<(&T, f32)> {
+        self.iter_with_ray(ray)
+            .filter_map(|e| e.elements())
+            .flatten()
+            .filter_map(|e| e.intersects(ray).map(|t| (e, t)))
+            .collect()
+    }
+}
+
+impl<T> Intersectable for BVH<T>
+where
+    T: Bounded + Intersectable,
+{
+    fn intersects(&self, ray: &Ray) -> Option<f32> {
+        self.intersects_filtered(ray, |_| true)
+    }
 }
 
 /// Nodos de la BVH. Puede ser un nodo terminal o intermedio
@@ -6,13 +6,16 @@ use nalgebra::IsometryMatrix3;
 
 use super::{Bounded, Intersectable, Ray, AABB};
 
-use crate::{Polygon, Vector3, Uuid};
+use crate::{Point3, Polygon, Uuid, Vector3};
 
 /// Elemento oclusor, con información geométrica e identificación
 ///
 /// - el id permite excluir el opaco de un hueco
 /// - el origin_id permite excluir las geometrías de retranqueo que no son del hueco analizado
 /// - normal y trans_matrix permiten cachear resultados para cálculo de intersecciones con el polígono 2D transformando un rayo
+/// - la transmisividad permite considerar elementos que no bloquean por completo la radiación
+/// - el back-face culling permite considerar elementos que solo bloquean por una de sus caras
+#[derive(Clone)]
 pub struct Occluder {
     /// Id del elemento
     pub id: Uuid,
@@ -26,15 +29,56 @@ pub struct Occluder {
     pub polygon: Polygon,
     /// AABB (min, max)
     pub aabb: AABB,
+    /// Transmisividad del elemento a la radiación solar [0, 1]
+    /// Con 0.0 el elemento es opaco y con 1.0 completamente transparente
+    pub transmittance: f32,
+    /// Oclusión solo por la cara exterior (back-face culling)
+    /// Con false el elemento es de doble cara y bloquea los rayos que le llegan por cualquiera de sus caras.
+    /// Con true no bloquea los rayos que inciden por su cara trasera (los que siguen el sentido de su normal)
+    pub backface_culling: bool,
 }
 
-impl Intersectable for &Occluder {
+impl Occluder {
+    /// Vértices del polígono del oclusor en coordenadas globales
+    ///
+    /// Devuelve una lista vacía si no hay matriz de transformación
+    pub fn global_vertices(&self) -> Vec<Point3> {
+        match self.trans_matrix.map(|m| m.inverse()) {
+            Some(to_global) => self
+                .polygon
+                .iter()
+                .map(|p| to_global * Point3::new(p.x, p.y, 0.0))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Intersectable for Occluder {
     fn intersects(&self, ray: &Ray) -> Option<f32> {
         self.aabb.intersects(ray)?;
+        if self.backface_culling {
+            let local_dir = self.trans_matrix.as_ref()? * ray.dir;
+            if self.normal.dot(&local_dir) > 0.0 {
+                return None;
+            }
+        }
         ray.intersects_with_data(&self.polygon, self.trans_matrix.as_ref(), &self.normal)
     }
 }
 
+impl Bounded for Occluder {
+    fn aabb(&self) -> AABB {
+        self.aabb
+    }
+}
+
+impl Intersectable for &Occluder {
+    fn intersects(&self, ray: &Ray) -> Option<f32> {
+        (*self).intersects(ray)
+    }
+}
+
 impl Bounded for &Occluder {
     fn aabb(&self) -> AABB {
         self.aabb
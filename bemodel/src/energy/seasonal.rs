@@ -0,0 +1,267 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Estimación de la demanda de calefacción y refrigeración con un método mensual cuasiestacionario
+//!
+//! Sigue el esquema del método mensual de la UNE-EN ISO 13790 usado en el Documento de Apoyo del DB-HE1,
+//! a partir de la transmisión de calor de la envolvente (K), la ventilación (H_ve), las ganancias solares
+//! (con los factores usados en q_sol;jul) y las ganancias internas medias, sin necesidad de simulación horaria.
+//! La constante de tiempo del edificio se obtiene a partir de su capacidad térmica interna.
+//! Las temperaturas exteriores mensuales son las aproximadas para la zona climática.
+
+use climate::MONTH_DAYS;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    indicators::{h_ve, KData, QSolJulData},
+    inertia::C_M_PER_AREA,
+    EnergyProps,
+};
+use crate::{
+    climatedata::{
+        monthly_temperatures, total_radiation_by_orientation_and_month,
+        total_radiation_in_july_by_orientation, ClimateZone,
+    },
+    Model,
+};
+
+/// Temperatura de consigna de calefacción [ºC]
+const T_SET_HEATING: f32 = 20.0;
+/// Temperatura de consigna de refrigeración [ºC]
+const T_SET_COOLING: f32 = 25.0;
+/// Parámetros numéricos del factor de utilización (UNE-EN ISO 13790, método mensual)
+const A_H0: f32 = 1.0;
+const TAU_H0: f32 = 15.0;
+
+/// Demanda de calefacción y refrigeración estimada con el método mensual
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeasonalDemand {
+    /// Demanda anual de calefacción [kWh/m²·año]
+    pub heating: f32,
+    /// Demanda anual de refrigeración [kWh/m²·año]
+    pub cooling: f32,
+    /// Demanda mensual de calefacción [kWh/m²·mes]
+    pub heating_monthly: Vec<f32>,
+    /// Demanda mensual de refrigeración [kWh/m²·mes]
+    pub cooling_monthly: Vec<f32>,
+    /// Coeficiente de transferencia de calor por transmisión [W/K]
+    pub h_tr: f32,
+    /// Coeficiente de transferencia de calor por ventilación [W/K]
+    pub h_ve: f32,
+    /// Constante de tiempo del edificio [h]
+    pub tau: f32,
+}
+
+/// Balance mensual de ganancias y pérdidas del edificio
+struct MonthlyBalance {
+    /// Superficie útil de referencia [m²]
+    a_ref: f32,
+    /// Coeficiente de transferencia de calor por transmisión [W/K]
+    h_tr: f32,
+    /// Coeficiente de transferencia de calor por ventilación [W/K]
+    h_ve: f32,
+    /// Constante de tiempo del edificio [h]
+    tau: f32,
+    /// Ganancias solares e internas [kWh]
+    q_gn: [f32; 12],
+    /// Pérdidas por transmisión y ventilación con la consigna de calefacción [kWh]
+    q_ht_heating: [f32; 12],
+    /// Pérdidas por transmisión y ventilación con la consigna de refrigeración [kWh]
+    q_ht_cooling: [f32; 12],
+}
+
+impl MonthlyBalance {
+    /// Parámetro numérico del factor de utilización, a_H [-]
+    fn a(&self) -> f32 {
+        A_H0 + self.tau / TAU_H0
+    }
+}
+
+impl Model {
+    /// Demanda de calefacción y refrigeración estimada con el método mensual simplificado para una zona climática
+    ///
+    /// Para cada mes se calculan las pérdidas por transmisión y ventilación respecto a la consigna,
+    /// Q_ht = (H_tr + H_ve) · (θ_set - θ_e) · t, y las ganancias solares a través de huecos e internas, Q_gn.
+    /// La demanda es Q_H = Q_ht - η_H · Q_gn en calefacción y Q_C = Q_gn - η_C · Q_ht en refrigeración,
+    /// con los factores de utilización del método mensual según la constante de tiempo del edificio.
+    /// Las ganancias solares usan el factor solar con las protecciones solares móviles activadas.
+    pub fn seasonal_demand(&self, climate: &ClimateZone) -> SeasonalDemand {
+        let balance = match self.monthly_balance(climate) {
+            Some(balance) => balance,
+            None => {
+                return SeasonalDemand {
+                    heating_monthly: vec![0.0; 12],
+                    cooling_monthly: vec![0.0; 12],
+                    ..Default::default()
+                }
+            }
+        };
+        let a = balance.a();
+        let a_ref = balance.a_ref;
+
+        let mut heating_monthly = Vec::with_capacity(12);
+        let mut cooling_monthly = Vec::with_capacity(12);
+        let months = balance
+            .q_gn
+            .iter()
+            .zip(&balance.q_ht_heating)
+            .zip(&balance.q_ht_cooling);
+        for ((&q_gn, &q_ht_h), &q_ht_c) in months {
+            let q_h = (q_ht_h - heating_utilization(q_gn, q_ht_h, a) * q_gn).max(0.0);
+            let q_c = (q_gn - cooling_utilization(q_gn, q_ht_c, a) * q_ht_c).max(0.0);
+            heating_monthly.push(q_h / a_ref);
+            cooling_monthly.push(q_c / a_ref);
+        }
+
+        SeasonalDemand {
+            heating: heating_monthly.iter().sum(),
+            cooling: cooling_monthly.iter().sum(),
+            heating_monthly,
+            cooling_monthly,
+            h_tr: balance.h_tr,
+            h_ve: balance.h_ve,
+            tau: balance.tau,
+        }
+    }
+
+    /// Factor de utilización mensual de las ganancias para calefacción, η_H [-]
+    ///
+    /// Depende de la relación entre ganancias y pérdidas de cada mes, γ = Q_gn / Q_ht, y de la constante
+    /// de tiempo del edificio, τ = C_m / (H_tr + H_ve), obtenida a partir de la capacidad térmica interna.
+    /// Los meses sin pérdidas tienen un factor nulo.
+    pub fn gain_utilisation_factor(&self, climate: &ClimateZone) -> [f32; 12] {
+        let mut factors = [0.0; 12];
+        if let Some(balance) = self.monthly_balance(climate) {
+            let a = balance.a();
+            for (m, f) in factors.iter_mut().enumerate() {
+                *f = heating_utilization(balance.q_gn[m], balance.q_ht_heating[m], a);
+            }
+        }
+        factors
+    }
+
+    /// Fracción de la demanda bruta de calefacción cubierta por las ganancias solares aprovechables [-]
+    ///
+    /// Para cada mes con pérdidas se obtienen las ganancias solares a partir del área de captación
+    /// solar efectiva de los huecos y la radiación mensual de cada orientación, Q_sol = Σ A_sol · H_sol,
+    /// y se aplica el factor de utilización de las ganancias de calefacción, η_H.
+    /// La fracción es Σ η_H · Q_sol / Σ Q_ht, con Q_ht las pérdidas por transmisión y ventilación
+    /// respecto a la consigna de calefacción, y está acotada entre 0 y 1.
+    pub fn passive_solar_fraction(&self, climate: &ClimateZone) -> f32 {
+        let balance = match self.monthly_balance(climate) {
+            Some(balance) => balance,
+            None => return 0.0,
+        };
+        let a = balance.a();
+        let aperture = self.solar_aperture_by_orientation();
+        let radiation = total_radiation_by_orientation_and_month(climate);
+
+        let mut q_sol_used = 0.0;
+        let mut q_ht = 0.0;
+        for m in (0..12).filter(|m| balance.q_ht_heating[*m] > 0.0) {
+            let q_sol: f32 = aperture
+                .iter()
+                .map(|(orientation, a_sol)| {
+                    a_sol * radiation.get(orientation).map_or(0.0, |r| r[m])
+                })
+                .sum();
+            q_sol_used += heating_utilization(balance.q_gn[m], balance.q_ht_heating[m], a) * q_sol;
+            q_ht += balance.q_ht_heating[m];
+        }
+        if q_ht > 0.0 {
+            (q_sol_used / q_ht).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Balance mensual de ganancias y pérdidas para una zona climática
+    ///
+    /// Devuelve None si el modelo no tiene superficie útil
+    fn monthly_balance(&self, climate: &ClimateZone) -> Option<MonthlyBalance> {
+        let props = EnergyProps::from(self);
+        let a_ref = props.global.a_ref;
+        if a_ref < f32::EPSILON {
+            return None;
+        }
+
+        let h_tr = KData::from(&props).summary.au;
+        let h_ve = h_ve(&props);
+        let h = h_tr + h_ve;
+        // Capacidad térmica interna [J/K], con un valor de inercia media si no puede calcularse
+        let c_m = match self.internal_heat_capacity() {
+            c_m if c_m > 0.0 => c_m,
+            _ => C_M_PER_AREA * a_ref,
+        };
+        // Constante de tiempo [h]
+        let tau = if h > f32::EPSILON {
+            c_m / 3600.0 / h
+        } else {
+            0.0
+        };
+
+        // Superficie solar equivalente de los huecos por orientación [m²]
+        let radjul = total_radiation_in_july_by_orientation(climate);
+        let q_soljul_data = QSolJulData::from(&props, &radjul);
+        let radiation = total_radiation_by_orientation_and_month(climate);
+        let temperatures = monthly_temperatures(climate);
+        // Ganancias internas medias [W]
+        let q_int = props.global.occ_spaces_average_load * a_ref;
+
+        let mut balance = MonthlyBalance {
+            a_ref,
+            h_tr,
+            h_ve,
+            tau,
+            q_gn: [0.0; 12],
+            q_ht_heating: [0.0; 12],
+            q_ht_cooling: [0.0; 12],
+        };
+        for (m, &ndays) in MONTH_DAYS.iter().enumerate() {
+            // Horas del mes [h]
+            let t = 24.0 * ndays as f32;
+            // Ganancias solares e internas [kWh]
+            let q_sol: f32 = q_soljul_data
+                .detail
+                .iter()
+                .filter(|(_, d)| d.irradiance > 0.0)
+                .map(|(orientation, d)| {
+                    let rad = radiation.get(orientation).map_or(0.0, |r| r[m]);
+                    d.gains / d.irradiance * rad
+                })
+                .sum();
+            balance.q_gn[m] = q_sol + q_int * t / 1000.0;
+            // Pérdidas por transmisión y ventilación [kWh]
+            balance.q_ht_heating[m] = h * (T_SET_HEATING - temperatures[m]) * t / 1000.0;
+            balance.q_ht_cooling[m] = h * (T_SET_COOLING - temperatures[m]) * t / 1000.0;
+        }
+        Some(balance)
+    }
+}
+
+/// Factor de utilización de las ganancias en calefacción, η_H [-]
+fn heating_utilization(q_gn: f32, q_ht: f32, a: f32) -> f32 {
+    if q_ht <= 0.0 {
+        return 0.0;
+    }
+    let gamma = q_gn / q_ht;
+    if (gamma - 1.0).abs() < 1e-4 {
+        a / (a + 1.0)
+    } else {
+        (1.0 - gamma.powf(a)) / (1.0 - gamma.powf(a + 1.0))
+    }
+}
+
+/// Factor de utilización de las pérdidas en refrigeración, η_C [-]
+fn cooling_utilization(q_gn: f32, q_ht: f32, a: f32) -> f32 {
+    if q_ht <= 0.0 || q_gn <= 0.0 {
+        return 1.0;
+    }
+    let lambda = q_ht / q_gn;
+    if (lambda - 1.0).abs() < 1e-4 {
+        a / (a + 1.0)
+    } else {
+        (1.0 - lambda.powf(-a)) / (1.0 - lambda.powf(-(a + 1.0)))
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Cálculo horario de la temperatura sol-aire de los elementos opacos
+//!
+//! La temperatura sol-aire es la temperatura exterior equivalente que produce el mismo flujo de calor
+//! en la superficie exterior del opaco que la combinación de temperatura del aire, radiación solar absorbida
+//! y pérdidas por radiación de onda larga hacia el cielo (UNE-EN ISO 52016-1).
+
+use climate::{period_radiation_for_surface, MetData};
+
+use crate::{Model, Wall};
+
+/// Resistencia superficial exterior [m²K/W]
+const R_SE: f32 = 0.04;
+/// Emisividad de onda larga por defecto de la superficie exterior [-]
+const EMISSIVITY: f32 = 0.9;
+/// Constante de Stefan-Boltzmann [W/m²K⁴]
+const SIGMA: f32 = 5.67e-8;
+/// Albedo (reflectancia solar) del terreno [-]
+const ALBEDO: f32 = 0.2;
+
+impl Model {
+    /// Temperatura sol-aire del opaco para cada hora de los datos climáticos [ºC]
+    ///
+    /// La radiación solar absorbida se reduce con el factor de la cámara ventilada del opaco, si existe.
+    /// El intercambio de onda larga con el cielo usa la emisividad de la construcción (0.9 por defecto)
+    /// y la temperatura de cielo estimada a partir de la temperatura y humedad del aire, supuesto cielo despejado,
+    /// por lo que las superficies con vista al cielo (cubiertas) pueden quedar por debajo de la temperatura del aire.
+    /// Si no se encuentra la construcción del opaco se considera una absortividad nula.
+    pub fn sol_air_temperature_hourly(&self, wall: &Wall, met: &MetData) -> Vec<f32> {
+        let (absorptance, emissivity) = self
+            .cons
+            .get_wallcons(wall.cons)
+            .map_or((0.0, EMISSIVITY), |c| {
+                (c.absorptance, c.emissivity.unwrap_or(EMISSIVITY))
+            });
+        let f_vent = wall.solar_gain_factor();
+        let tilt = wall.geometry.tilt;
+        // Factor de visión del cielo
+        let f_sky = 0.5 * (1.0 + tilt.to_radians().cos());
+
+        let radiation = period_radiation_for_surface(
+            &met.data,
+            met.meta.latitude,
+            tilt,
+            wall.geometry.azimuth,
+            ALBEDO,
+        );
+
+        met.data
+            .iter()
+            .zip(radiation)
+            .map(|(d, rad)| {
+                let t_sky = d.estimated_sky_temp();
+                // Coeficiente de transmisión de calor por radiación de onda larga, W/m²K
+                let t_mean = 0.5 * (d.db_temp + t_sky) + 273.15;
+                let h_r = 4.0 * emissivity * SIGMA * t_mean.powi(3);
+                let q_sol = f_vent * absorptance * (rad.dir + rad.dif);
+                let q_sky = f_sky * h_r * (d.db_temp - t_sky);
+                d.db_temp + R_SE * (q_sol - q_sky)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Área de captación solar efectiva de los huecos
+//!
+//! Resume la capacidad de ganancia solar del edificio como A_sol = Σ A · g_gl;wi · F_sh;obst · (1 - F_F),
+//! para los huecos de la envolvente térmica en contacto con el aire exterior.
+
+use std::collections::BTreeMap;
+
+use super::EnergyProps;
+use crate::{BoundaryType, Model, Orientation};
+
+impl Model {
+    /// Área de captación solar efectiva de los huecos de una orientación [m²]
+    pub fn solar_aperture(&self, orientation: Orientation) -> f32 {
+        self.solar_aperture_by_orientation()
+            .get(&orientation)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Área de captación solar efectiva de los huecos, por orientaciones [m²]
+    ///
+    /// Se usa el factor de obstáculos remotos de usuario o, si no está definido, el calculado,
+    /// y se tienen en cuenta los multiplicadores de los espacios.
+    /// Los huecos sin construcción definida usan los valores por defecto del q_sol;jul:
+    /// g_gl;wi = 0.77 (vidrio sencillo), F_F = 0.20, y sin factor de obstáculos remotos F_sh;obst = 1.0.
+    /// Las puertas opacas no se consideran. Solo se incluyen las orientaciones con huecos.
+    pub fn solar_aperture_by_orientation(&self) -> BTreeMap<Orientation, f32> {
+        let props = EnergyProps::from(self);
+        let mut map = BTreeMap::new();
+        for win in props.windows.values().filter(|w| {
+            w.is_tenv && w.bounds == BoundaryType::EXTERIOR && w.door_absorptance.is_none()
+        }) {
+            let (g_glwi, f_f) = props
+                .wincons
+                .get(&win.cons)
+                .map_or((0.77, 0.20), |c| (c.g_glwi, c.f_f));
+            let f_shobst = win.f_shobst_override.or(win.f_shobst).unwrap_or(1.0);
+            *map.entry(win.orientation).or_default() +=
+                win.area * win.multiplier * g_glwi * f_shobst * (1.0 - f_f);
+        }
+        map
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Cálculo horario de las ganancias solares a través de los huecos
+//!
+//! La ganancia por radiación directa tiene en cuenta el factor de obstáculos remotos y la variación del
+//! factor solar del vidrio con el ángulo de incidencia, si está definida. La ganancia por radiación
+//! difusa usa el factor solar nominal.
+
+use std::collections::BTreeMap;
+
+use climate::{
+    nday_from_ymd, period_radiation_for_surface,
+    solar::{angle_sol_surf, declination_from_nday, hourangle_from_tsol},
+    MetData,
+};
+
+use super::EnergyProps;
+use crate::{BoundaryType, Glass, Model, Uuid};
+
+/// Albedo (reflectancia solar) del terreno [-]
+const ALBEDO: f32 = 0.2;
+
+impl Glass {
+    /// Factor de corrección del factor solar del vidrio para un ángulo de incidencia dado, en grados [-]
+    ///
+    /// Se interpola linealmente en la curva g_angular y fuera de su rango se usa el valor del extremo más cercano.
+    /// Sin curva definida el factor es 1.0 (factor solar constante).
+    pub fn g_angle_factor(&self, angle: f32) -> f32 {
+        let curve = match &self.g_angular {
+            Some(curve) if !curve.is_empty() => curve,
+            _ => return 1.0,
+        };
+        let (first, last) = (curve[0], curve[curve.len() - 1]);
+        if angle <= first.0 {
+            return first.1;
+        }
+        curve
+            .windows(2)
+            .find(|w| angle <= w[1].0)
+            .map(|w| {
+                let ((a0, f0), (a1, f1)) = (w[0], w[1]);
+                if (a1 - a0).abs() < f32::EPSILON {
+                    f1
+                } else {
+                    f0 + (f1 - f0) * (angle - a0) / (a1 - a0)
+                }
+            })
+            .unwrap_or(last.1)
+    }
+}
+
+impl Model {
+    /// Ganancias solares de cada hueco en contacto con el exterior, para cada hora de los datos climáticos [W]
+    ///
+    /// Q_sol = A · (1 - F_F) · g_gl;wi · (F_sh;obst · f(θ) · I_dir + I_dif)
+    ///
+    /// donde f(θ) es la corrección del factor solar del vidrio para el ángulo de incidencia de la radiación directa.
+    /// No incluye el multiplicador del espacio ni el efecto de las protecciones solares móviles.
+    /// Los huecos sin construcción o sin opaco definidos y las puertas opacas se ignoran.
+    pub fn solar_gains_hourly(&self, met: &MetData) -> BTreeMap<Uuid, Vec<f32>> {
+        let props = EnergyProps::from(self);
+        let latitude = met.meta.latitude;
+
+        // Declinación y ángulo horario para cada hora
+        let sun_angles: Vec<_> = met
+            .data
+            .iter()
+            .map(|d| {
+                let nday = nday_from_ymd(2001, d.month, d.day);
+                (declination_from_nday(nday), hourangle_from_tsol(d.hour))
+            })
+            .collect();
+
+        let mut map = BTreeMap::new();
+        for win in self.windows.iter().filter(|w| !w.is_opaque_door()) {
+            let (wall, winprops, wincons) = match (
+                self.get_wall(win.wall),
+                props.windows.get(&win.id),
+                props.wincons.get(&win.cons),
+            ) {
+                (Some(wall), Some(winprops), Some(wincons))
+                    if winprops.bounds == BoundaryType::EXTERIOR =>
+                {
+                    (wall, winprops, wincons)
+                }
+                _ => continue,
+            };
+            let glass = self
+                .cons
+                .get_wincons(win.cons)
+                .and_then(|c| self.cons.get_glass(c.glass));
+            let f_shobst = winprops
+                .f_shobst_override
+                .or(winprops.f_shobst)
+                .unwrap_or(1.0);
+            let tilt = wall.geometry.tilt;
+            let azimuth = wall.geometry.azimuth;
+            // Superficie acristalada por factor solar [m²]
+            let a_g = winprops.area * (1.0 - wincons.f_f) * wincons.g_glwi;
+
+            let radiation =
+                period_radiation_for_surface(&met.data, latitude, tilt, azimuth, ALBEDO);
+            let gains = radiation
+                .iter()
+                .zip(&sun_angles)
+                .map(|(rad, (declination, hourangle))| {
+                    let f_angle = glass.map_or(1.0, |g| {
+                        let angle =
+                            angle_sol_surf(*declination, *hourangle, latitude, tilt, azimuth);
+                        g.g_angle_factor(angle)
+                    });
+                    a_g * (f_shobst * f_angle * rad.dir + rad.dif)
+                })
+                .collect();
+            map.insert(win.id, gains);
+        }
+        map
+    }
+}
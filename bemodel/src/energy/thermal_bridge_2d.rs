@@ -0,0 +1,233 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Estimación de la transmitancia térmica lineal de puentes térmicos mediante cálculo 2D simplificado
+//!
+//! Se resuelve la conducción estacionaria en la sección del encuentro con un método de volúmenes finitos
+//! sobre una malla rectangular no uniforme, ajustada a los límites de las capas, siguiendo el esquema
+//! de la UNE-EN ISO 10211: ΔT = 1 K entre interior y exterior, resistencias superficiales
+//! Rsi = 0,13 m²K/W y Rse = 0,04 m²K/W y planos de corte adiabáticos.
+//! La transmitancia térmica lineal se obtiene como ψ = L_2D - Σ U·l, usando dimensiones exteriores.
+//!
+//! No sustituye a un programa de cálculo específico, pero permite estimar valores para encuentros sin dato de catálogo.
+
+/// Resistencia superficial interior [m²K/W]
+const R_SI: f64 = 0.13;
+/// Resistencia superficial exterior [m²K/W]
+const R_SE: f64 = 0.04;
+/// Tamaño máximo de las celdas en el espesor del muro [m]
+const MAX_CELL_WALL: f64 = 0.01;
+/// Tamaño máximo de las celdas en el resto de direcciones [m]
+const MAX_CELL: f64 = 0.02;
+/// Máxima variación de temperatura entre iteraciones para considerar convergida la solución [K]
+const TOLERANCE: f64 = 1e-9;
+/// Número máximo de iteraciones del solver
+const MAX_ITERATIONS: usize = 50_000;
+/// Factor de sobrerrelajación [-]
+const OMEGA: f64 = 1.9;
+
+/// Capa homogénea de la sección
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SectionLayer {
+    /// Espesor [m]
+    pub e: f32,
+    /// Conductividad térmica [W/mK]
+    pub conductivity: f32,
+}
+
+/// Sección de un encuentro de fachada con forjado intermedio
+///
+/// El muro es continuo en vertical y el forjado, horizontal, acomete a él desde el interior,
+/// sustituyendo en su canto a las capas interiores del muro que interrumpe.
+/// El encuentro se modeliza con una longitud de muro por encima y por debajo del forjado
+/// y una longitud de forjado, desde la cara interior del muro, iguales a la longitud de flanco.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacadeSlabJunction {
+    /// Capas del muro, de interior a exterior
+    pub wall_layers: Vec<SectionLayer>,
+    /// Canto del forjado [m]
+    pub slab_thickness: f32,
+    /// Conductividad térmica del forjado [W/mK]
+    pub slab_conductivity: f32,
+    /// Número de capas del muro, desde el interior, interrumpidas por el forjado
+    pub slab_interrupted_layers: usize,
+    /// Longitud de los elementos de flanco [m]
+    ///
+    /// La UNE-EN ISO 10211 indica usar al menos 1 m.
+    pub flanking_length: f32,
+}
+
+/// Resultado del cálculo del encuentro
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct JunctionResult {
+    /// Coeficiente de acoplamiento térmico 2D de la sección, L_2D [W/mK]
+    pub l_2d: f32,
+    /// Transmitancia térmica del muro, U [W/m²K]
+    pub u_wall: f32,
+    /// Longitud de muro, según dimensiones exteriores, l [m]
+    pub l_wall: f32,
+    /// Transmitancia térmica lineal, ψ = L_2D - U·l [W/mK]
+    pub psi: f32,
+}
+
+impl FacadeSlabJunction {
+    /// Transmitancia térmica del muro fuera del encuentro, U [W/m²K]
+    pub fn u_wall(&self) -> f32 {
+        let r_layers: f32 = self.wall_layers.iter().map(|l| l.e / l.conductivity).sum();
+        1.0 / (R_SI as f32 + r_layers + R_SE as f32)
+    }
+
+    /// Calcula el flujo de calor en la sección y la transmitancia térmica lineal del encuentro
+    pub fn compute(&self) -> JunctionResult {
+        let wall_e: f64 = self.wall_layers.iter().map(|l| l.e as f64).sum();
+        let flank = self.flanking_length as f64;
+        let slab_e = self.slab_thickness as f64;
+        let interrupted = self.slab_interrupted_layers.min(self.wall_layers.len());
+
+        // Límites de las capas en horizontal, desde la cara exterior (x = 0) hacia el interior
+        let mut x_bounds = vec![0.0];
+        for layer in self.wall_layers.iter().rev() {
+            x_bounds.push(x_bounds.last().unwrap() + layer.e as f64);
+        }
+        let xs = mesh_lines(&x_bounds, MAX_CELL_WALL);
+        let xs_slab = mesh_lines(&[wall_e, wall_e + flank], MAX_CELL);
+        let xs = [xs, xs_slab[1..].to_vec()].concat();
+        // Límites en vertical, desde el corte inferior (y = 0)
+        let ys = mesh_lines(
+            &[0.0, flank, flank + slab_e, 2.0 * flank + slab_e],
+            MAX_CELL,
+        );
+        // Cara exterior de la primera capa interrumpida por el forjado
+        let x_slab_start = wall_e
+            - self.wall_layers[..interrupted]
+                .iter()
+                .map(|l| l.e as f64)
+                .sum::<f64>();
+
+        // Conductividad de cada celda, o None si es aire interior
+        let (nx, ny) = (xs.len() - 1, ys.len() - 1);
+        let mut conductivity = vec![None; nx * ny];
+        for j in 0..ny {
+            let yc = 0.5 * (ys[j] + ys[j + 1]);
+            let in_slab = yc > flank && yc < flank + slab_e;
+            for i in 0..nx {
+                let xc = 0.5 * (xs[i] + xs[i + 1]);
+                conductivity[j * nx + i] = if in_slab && xc > x_slab_start {
+                    Some(self.slab_conductivity as f64)
+                } else if xc < wall_e {
+                    let mut x_layer = wall_e;
+                    self.wall_layers
+                        .iter()
+                        .find(|l| {
+                            x_layer -= l.e as f64;
+                            xc > x_layer
+                        })
+                        .map(|l| l.conductivity as f64)
+                } else {
+                    None
+                };
+            }
+        }
+
+        // Conductancias entre celdas y con los ambientes interior (T = 1) y exterior (T = 0) [W/mK]
+        let dx = |i: usize| xs[i + 1] - xs[i];
+        let dy = |j: usize| ys[j + 1] - ys[j];
+        let mut cells = vec![Cell::default(); nx * ny];
+        for j in 0..ny {
+            for i in 0..nx {
+                let idx = j * nx + i;
+                let Some(k) = conductivity[idx] else {
+                    continue;
+                };
+                // Superficie exterior del muro
+                if i == 0 {
+                    cells[idx].g_ext = dy(j) / (0.5 * dx(i) / k + R_SE);
+                }
+                // Vecinos, con el tamaño de la celda y del vecino en la dirección de la cara común y la longitud de esta.
+                // Los bordes de la malla, salvo la cara exterior, son planos de corte adiabáticos
+                let neighbours = [
+                    (i > 0).then(|| (idx - 1, dx(i), dx(i - 1), dy(j))),
+                    (i + 1 < nx).then(|| (idx + 1, dx(i), dx(i + 1), dy(j))),
+                    (j > 0).then(|| (idx - nx, dy(j), dy(j - 1), dx(i))),
+                    (j + 1 < ny).then(|| (idx + nx, dy(j), dy(j + 1), dx(i))),
+                ];
+                for (n_idx, d, n_d, face) in neighbours.into_iter().flatten() {
+                    match conductivity[n_idx] {
+                        Some(n_k) => {
+                            let g = face / (0.5 * d / k + 0.5 * n_d / n_k);
+                            cells[idx].neighbours.push((n_idx, g));
+                        }
+                        // Superficie interior
+                        None => cells[idx].g_int += face / (0.5 * d / k + R_SI),
+                    }
+                }
+            }
+        }
+
+        // Resolución por Gauss-Seidel con sobrerrelajación
+        let mut temps = vec![0.5; nx * ny];
+        for _ in 0..MAX_ITERATIONS {
+            let mut max_delta: f64 = 0.0;
+            for idx in 0..cells.len() {
+                let cell = &cells[idx];
+                let g_total =
+                    cell.g_int + cell.g_ext + cell.neighbours.iter().map(|(_, g)| g).sum::<f64>();
+                if g_total <= 0.0 {
+                    continue;
+                }
+                let sum = cell.g_int
+                    + cell
+                        .neighbours
+                        .iter()
+                        .map(|(n, g)| g * temps[*n])
+                        .sum::<f64>();
+                let delta = OMEGA * (sum / g_total - temps[idx]);
+                temps[idx] += delta;
+                max_delta = max_delta.max(delta.abs());
+            }
+            if max_delta < TOLERANCE {
+                break;
+            }
+        }
+
+        // Flujo de calor hacia el exterior con ΔT = 1 K
+        let l_2d = cells
+            .iter()
+            .zip(&temps)
+            .map(|(c, t)| c.g_ext * t)
+            .sum::<f64>() as f32;
+        let u_wall = self.u_wall();
+        let l_wall = (2.0 * flank + slab_e) as f32;
+        JunctionResult {
+            l_2d,
+            u_wall,
+            l_wall,
+            psi: l_2d - u_wall * l_wall,
+        }
+    }
+}
+
+/// Celda de la malla, con sus conductancias a las celdas vecinas y a los ambientes [W/mK]
+#[derive(Debug, Clone, Default)]
+struct Cell {
+    neighbours: Vec<(usize, f64)>,
+    g_int: f64,
+    g_ext: f64,
+}
+
+/// Líneas de la malla que contienen los límites indicados, con celdas de tamaño no mayor que max_size
+///
+/// Se omiten los intervalos de longitud nula.
+fn mesh_lines(bounds: &[f64], max_size: f64) -> Vec<f64> {
+    let mut lines = vec![bounds[0]];
+    for pair in bounds.windows(2) {
+        let len = pair[1] - pair[0];
+        if len <= 1e-6 {
+            continue;
+        }
+        let n = (len / max_size).ceil() as usize;
+        lines.extend((1..=n).map(|k| pair[0] + len * k as f64 / n as f64));
+    }
+    lines
+}
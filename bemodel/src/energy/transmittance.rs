@@ -14,13 +14,14 @@
 
 use std::f32::consts::PI;
 
-use anyhow::{format_err, Error};
+use anyhow::{bail, format_err, Error};
 use log::{debug, info, warn};
 
 use crate::types::HasSurface;
 use crate::{
     utils::{fround2, fround3},
-    BoundaryType, ConsDb, Layer, MatProps, Model, Space, SpaceType, Tilt, Wall, WallCons, WinCons,
+    BoundaryType, ConsDb, Layer, MatProps, Model, PerimeterInsulation, Space, SpaceType, Tilt,
+    Uuid, Wall, WallCons, WinCons, Window,
 };
 
 // Resistencias superficiales UNE-EN ISO 6946 [m2·K/W]
@@ -181,21 +182,20 @@ impl Space {
     /// # Argumentos
     ///
     /// * `d_t` - Espesor total equivalente de solera (suelo de sótano), m
-    /// * `model` - modelo del edificio
-    /// TODO: ¿debería usar datos de aislamiento por solera en lugar de global del modelo?
-    fn slab_psi_gnd_ext(&self, d_t: f32, model: &Model) -> f32 {
+    /// * `insulation` - aislamiento perimetral de la solera
+    fn slab_psi_gnd_ext(&self, d_t: f32, insulation: PerimeterInsulation) -> f32 {
         // d': Espesor equivalente adicional resultante del aislamiento perimetral
         // - R_n: Resistencia del aislamiento perimetral, m²K/W
         // - λ: coeficiente de transmisión del terreno, W/mK
         // - λ_ins: coeficiente de transmisión del aislamiento perimetral, W/mK
         // - d_n: espesor aislamiento perimetral, m = R_n * λ_ins
         // - d' = R'.λ = (R_n - d_n / λ)·λ = R_n·λ - d_n = R_n·λ - R_n·λ_ins = R_n·(λ - λ_ins)
-        let d_1 = model.meta.rn_perim_insulation * (LAMBDA_GND - LAMBDA_INS);
+        let d_1 = insulation.rn * (LAMBDA_GND - LAMBDA_INS);
         // Suponemos aislamiento perimetral horizontal (B.5)
         // D: profundidad / ancho de la banda de aislamiento perimetral.
         // Para aislamiento horizontal, D es el ancho de la banda perimetral de aislameinto (B.5)
         // Para aislamiento vertical, D es el doble de la profundidad de la banda de aislamiento (B.6)
-        let D = model.meta.d_perim_insulation;
+        let D = insulation.d;
         debug!(
             "Aislamiento perimetral en espacio {} ({}): d_1 = {}, D = {}",
             self.name, self.id, d_1, D
@@ -219,7 +219,7 @@ impl Space {
                     .windows(&model.windows)
                     .filter_map(|win| {
                         // Si no está definida la construcción, el hueco no participa de la envolvente
-                        let u = &model.cons.get_wincons(win.cons)?.u_value(&model.cons)?;
+                        let u = win.u_value(&model.cons)?;
                         Some(win.area() * u)
                     })
                     .sum::<f32>();
@@ -228,34 +228,250 @@ impl Space {
             .sum::<f32>();
         UA_e_k
     }
+
+    /// Caudal de ventilación entre el espacio no acondicionado y el exterior, q_ue [m³/h]
+    ///
+    /// UNE-EN ISO 13789:2017 (12). Se usa la tasa de ventilación del espacio o, si no está definida, la global del edificio.
+    fn ventilation_flow_to_exterior(&self, model: &Model) -> f32 {
+        // En los no habitables debe estar definido n_v pero en los no acondicionados no
+        // Se puede obtener n_v a partir de la Tabla 6 de la UNE-EN ISO 13789:2017 y n_50/20.
+        // Para sótanos no calefactados la 13370:2007 (9.4) dice que se podría usar n_v = 0.30
+        let volume = self.area(&model.walls) * self.height_net(&model.walls, &model.cons);
+        let n_v = self.n_v.unwrap_or_else(|| model.global_ventilation_rate());
+        if n_v.abs() < f32::EPSILON {
+            // Espacio mal definido (ni tiene n_v ni hay definición global de ventilación)
+            warn!(
+                "Nivel de ventilación (1/h) nulo o casi nulo del espacio no acondicionado {} ({})",
+                self.id, self.name
+            );
+        }
+        // m^3 * 1/h
+        volume * n_v
+    }
+}
+
+impl Model {
+    /// Temperatura de un espacio no acondicionado en régimen estacionario [ºC]
+    ///
+    /// Resuelve el balance θ_u = (H_iu·θ_int + H_ue·θ_ext) / (H_iu + H_ue), según UNE-EN ISO 13789:2017,
+    /// donde H_iu es el coeficiente de transferencia de calor de los elementos interiores que separan el espacio
+    /// de los espacios acondicionados y H_ue el de los elementos exteriores o en contacto con el terreno
+    /// más la ventilación con el exterior. Equivale a un factor de ajuste b = H_ue / (H_iu + H_ue).
+    /// Los huecos de las particiones interiores se incluyen en H_iu con su transmitancia térmica.
+    /// Si el espacio no existe o no tiene intercambio de calor se devuelve la temperatura exterior.
+    pub fn unconditioned_space_temperature(
+        &self,
+        space_id: Uuid,
+        temp_int: f32,
+        temp_ext: f32,
+    ) -> f32 {
+        use SpaceType::CONDITIONED;
+        use Tilt::{BOTTOM, TOP};
+
+        let space = match self.get_space(space_id) {
+            Some(space) => space,
+            None => {
+                warn!("No se encuentra el espacio {}", space_id);
+                return temp_ext;
+            }
+        };
+        let is_conditioned = |id| self.get_space(id).is_some_and(|s| s.kind == CONDITIONED);
+
+        // Elementos interiores con espacios acondicionados
+        let H_iu: f32 = self
+            .walls
+            .iter()
+            .filter(|w| w.bounds == BoundaryType::INTERIOR)
+            .filter_map(|w| {
+                // ¿Pertenece el opaco al espacio acondicionado?
+                let from_cond = if w.space == space_id && w.next_to.is_some_and(is_conditioned) {
+                    false
+                } else if w.next_to == Some(space_id) && is_conditioned(w.space) {
+                    true
+                } else {
+                    return None;
+                };
+                let resistance = self
+                    .cons
+                    .get_wallcons(w.cons)?
+                    .resistance(&self.cons)
+                    .ok()?;
+                // Flujo desde el espacio acondicionado al no acondicionado
+                let rsi = match (from_cond, Tilt::from(w)) {
+                    (true, BOTTOM) | (false, TOP) => RSI_DESCENDENTE,
+                    (true, TOP) | (false, BOTTOM) => RSI_ASCENDENTE,
+                    _ => RSI_HORIZONTAL,
+                };
+                // Huecos de la partición, con su transmitancia sin corregir
+                let win_axu = w
+                    .windows(&self.windows)
+                    .filter_map(|win| Some(win.area() * win.u_value(&self.cons)?))
+                    .sum::<f32>();
+                Some(w.area_net(&self.windows) / (resistance + 2.0 * rsi) + win_axu)
+            })
+            .sum();
+        // Elementos exteriores y en contacto con el terreno y ventilación
+        let H_ue = space.ua_of_external_and_ground_surfaces(self)
+            + 0.33 * space.ventilation_flow_to_exterior(self);
+
+        let H = H_iu + H_ue;
+        if H < f32::EPSILON {
+            return temp_ext;
+        }
+        (H_iu * temp_int + H_ue * temp_ext) / H
+    }
+
+    /// Factor de ajuste b de un espacio no acondicionado [-]
+    ///
+    /// Es la fracción de la diferencia de temperaturas entre el interior y el exterior que se da entre
+    /// el interior y el espacio no acondicionado, b = (θ_int - θ_u) / (θ_int - θ_ext) = H_ue / (H_iu + H_ue),
+    /// con la temperatura del espacio calculada en régimen estacionario.
+    pub fn adjustment_factor_b(&self, space_id: Uuid) -> f32 {
+        1.0 - self.unconditioned_space_temperature(space_id, 1.0, 0.0)
+    }
 }
 
 impl WallCons {
     /// Resistencia térmica intrínseca (sin resistencias superficiales) de una composición de capas [W/m²K]
+    /// Los materiales con conductividad dependiente de la humedad usan su contenido de humedad de referencia
     /// TODO: convertir errores a logging y devolver Option<f32>
     pub fn resistance(&self, db: &ConsDb) -> Result<f32, Error> {
+        self.resistance_with_moisture(db, None)
+    }
+
+    /// Resistencia térmica intrínseca (sin resistencias superficiales) de una composición de capas [W/m²K]
+    /// para un contenido de humedad másico dado (kg/kg) en los materiales con conductividad dependiente de la humedad
+    /// Si no se indica el contenido de humedad se usa el de referencia de cada material
+    /// Las capas de espesor variable computan con su espesor medio
+    pub fn resistance_with_moisture(
+        &self,
+        db: &ConsDb,
+        moisture_content: Option<f32>,
+    ) -> Result<f32, Error> {
+        let (r_min, r_max) = self.resistance_range_with_moisture(db, moisture_content)?;
+        Ok(0.5 * (r_min + r_max))
+    }
+
+    /// Resistencias térmicas intrínsecas mínima y máxima (sin resistencias superficiales) de una composición de capas [W/m²K]
+    ///
+    /// Son distintas cuando hay capas de espesor variable, que computan con su espesor mínimo y máximo
+    pub fn resistance_range(&self, db: &ConsDb) -> Result<(f32, f32), Error> {
+        self.resistance_range_with_moisture(db, None)
+    }
+
+    /// Resistencias térmicas intrínsecas mínima y máxima para un contenido de humedad másico dado (kg/kg)
+    fn resistance_range_with_moisture(
+        &self,
+        db: &ConsDb,
+        moisture_content: Option<f32>,
+    ) -> Result<(f32, f32), Error> {
         let mut total_resistance = 0.0;
-        for Layer { material, e } in &self.layers {
+        let mut extra_resistance = 0.0;
+        for Layer { material, e, e_max } in &self.layers {
             match db.get_material(*material) {
-                None => return Err(format_err!(
-                    "No se encuentra el material \"{}\" de la composición de capas \"{}\"",
-                    material,
-                    self.name
-                )),
+                None => {
+                    return Err(format_err!(
+                        "No se encuentra el material \"{}\" de la composición de capas \"{}\"",
+                        material,
+                        self.name
+                    ))
+                }
                 Some(mat) => {
-                    match mat.properties {
-                        MatProps::Detailed{ conductivity, .. } if conductivity > 0.0 => total_resistance += e / conductivity,
-                        MatProps::Resistance{ resistance, ..} => total_resistance += resistance,
-                        MatProps::Detailed { .. } => return Err(format_err!(
+                    match (mat.properties, mat.properties.conductivity(moisture_content)) {
+                        (_, Some(conductivity)) if conductivity > 0.0 => {
+                            total_resistance += e / conductivity;
+                            extra_resistance += (e_max.unwrap_or(*e) - e).max(0.0) / conductivity;
+                        }
+                        (MatProps::Resistance{ resistance, ..}, _) => total_resistance += resistance,
+                        _ => return Err(format_err!(
                             "Material \"{}\" de la composición de capas \"{}\" con conductividad nula o casi nula",
                             mat.name,
                             self.name
                         ))
                     }
-                },
+                }
             }
         }
-        Ok(total_resistance)
+        Ok((total_resistance, total_resistance + extra_resistance))
+    }
+
+    /// Resistencia térmica intrínseca (sin resistencias superficiales) de la composición de capas, R_intr [m²K/W]
+    ///
+    /// Suma e/λ de las capas con conductividad definida y la resistencia de las capas definidas por resistencia.
+    /// Las capas con material no encontrado o con conductividad nula se ignoran, avisando de ello
+    pub fn r_intrinsic(&self, db: &ConsDb) -> f32 {
+        self.resistance(db).unwrap_or_else(|e| {
+            warn!("{}. Se ignoran las capas incorrectas", e);
+            self.layers
+                .iter()
+                .filter_map(|layer| {
+                    let mat = db.get_material(layer.material)?;
+                    match (mat.properties, mat.properties.conductivity(None)) {
+                        (_, Some(conductivity)) if conductivity > 0.0 => {
+                            Some(layer.e_mean() / conductivity)
+                        }
+                        (MatProps::Resistance { resistance, .. }, _) => Some(resistance),
+                        _ => None,
+                    }
+                })
+                .sum()
+        })
+    }
+
+    /// Transmitancia térmica de la composición de capas con las resistencias superficiales dadas, U [W/m²K]
+    ///
+    /// U = 1 / (R_si + R_intr + R_se)
+    /// Con capas de espesor variable se obtiene la U media ponderada por superficie (ver u_value_tapered)
+    pub fn u_value(&self, db: &ConsDb, rsi: f32, rse: f32) -> f32 {
+        match self.resistance_range(db) {
+            Ok((r_min, r_max)) => u_value_tapered(rsi + r_min + rse, r_max - r_min),
+            Err(_) => 1.0 / (rsi + self.r_intrinsic(db) + rse),
+        }
+    }
+
+    /// Composición de capas con una capa de aislamiento de espesor calculado para alcanzar la U objetivo
+    ///
+    /// Equivale al "aislamiento a determinar por el usuario" de HULC. La capa del material aislante
+    /// se añade tras las capas base, con el espesor redondeado al milímetro superior, y la U se calcula
+    /// con las resistencias superficiales de un cerramiento vertical (R_si = 0,13 m²K/W y R_se = 0,04 m²K/W).
+    /// Si las capas base ya alcanzan la U objetivo no se añade la capa de aislamiento.
+    /// Falla si la U objetivo no es positiva, si no se encuentran los materiales o si el aislante no tiene conductividad.
+    pub fn with_target_u(
+        base_layers: Vec<Layer>,
+        insulation_material: Uuid,
+        target_u: f32,
+        db: &ConsDb,
+    ) -> Result<WallCons, Error> {
+        if target_u <= 0.0 {
+            bail!("La transmitancia objetivo debe ser positiva ({})", target_u);
+        }
+        let mut cons = WallCons {
+            name: format!("Composición con U = {:.2} W/m²K", target_u),
+            layers: base_layers,
+            ..Default::default()
+        };
+        let insulation = db.get_material(insulation_material).ok_or_else(|| {
+            format_err!(
+                "No se encuentra el material aislante \"{}\"",
+                insulation_material
+            )
+        })?;
+        let conductivity = match insulation.properties.conductivity(None) {
+            Some(conductivity) if conductivity > 0.0 => conductivity,
+            _ => bail!(
+                "El material aislante \"{}\" no tiene definida la conductividad",
+                insulation.name
+            ),
+        };
+        let r_missing = 1.0 / target_u - (RSI_HORIZONTAL + cons.resistance(db)? + RSE);
+        if r_missing > 0.0 {
+            cons.layers.push(Layer {
+                material: insulation_material,
+                e: (r_missing * conductivity * 1000.0).ceil() / 1000.0,
+                e_max: None,
+            });
+        }
+        Ok(cons)
     }
 }
 
@@ -263,18 +479,115 @@ impl WinCons {
     /// Transmitancia térmica total de la construcción de hueco, U_W, en una posición dada, en W/m2K
     ///
     /// Incluye el efecto del marco, vidrio y efecto de intercalarios y/o cajones de persiana
+    /// No incluye el cajón de persiana definido en la construcción (shutter_box), que depende de la superficie del hueco
     ///
     /// Notas:
     /// - los valores de U de acristalamiento y marco son para su posición final
     /// - los valores de acristalamiento y marco ya deben incluir las resistencias superficiales
     ///   (U_g se calcula con resistencias superficiales y U_w es una ponderación)
     pub fn u_value(&self, db: &ConsDb) -> Option<f32> {
+        self.u_value_without_box(db).map(fround2)
+    }
+
+    /// Transmitancia térmica total de la construcción de hueco para un hueco de superficie dada, en W/m2K
+    ///
+    /// Si la construcción define un cajón de persiana, la transmitancia se pondera por superficies
+    /// entre la parte acristalada (vidrio y marco) y el cajón, que se considera incluido en el hueco.
+    /// Sin cajón de persiana coincide con la transmitancia de la construcción (WinCons::u_value).
+    pub fn u_value_for_area(&self, db: &ConsDb, area: f32) -> Option<f32> {
+        let u_w = self.u_value_without_box(db)?;
+        let u = match self.shutter_box {
+            Some(shutter_box) if area > 0.0 => {
+                let f_box = (shutter_box.area() / area).min(1.0);
+                u_w * (1.0 - f_box) + shutter_box.u_value * f_box
+            }
+            _ => u_w,
+        };
+        Some(fround2(u))
+    }
+
+    /// Transmitancia térmica de vidrio y marco, incluyendo el incremento delta_u, en W/m2K
+    fn u_value_without_box(&self, db: &ConsDb) -> Option<f32> {
         let glass = db.get_glass(self.glass)?;
         let frame = db.get_frame(self.frame)?;
-        Some(fround2(
+        Some(
             (1.0 + self.delta_u / 100.0)
                 * (frame.u_value * self.f_f + glass.u_value * (1.0 - self.f_f)),
-        ))
+        )
+    }
+}
+
+impl Window {
+    /// Transmitancia térmica del hueco, en W/m2K
+    ///
+    /// Incluye el efecto del cajón de persiana de la construcción, si existe
+    ///
+    /// En las dobles ventanas se combinan las resistencias de las dos hojas en serie con la de la cámara,
+    /// descontando las resistencias superficiales de las caras que dan a la cámara (UNE-EN ISO 10077-1):
+    /// U_w = 1 / (1 / U_w1 - R_si + R_s - R_se + 1 / U_w2)
+    /// con U_w1 la de la hoja exterior, que incluye el cajón de persiana, y U_w2 la de la hoja interior.
+    ///
+    /// Las puertas opacas usan su transmitancia propia.
+    pub fn u_value(&self, db: &ConsDb) -> Option<f32> {
+        if let Some(door) = self.opaque_door {
+            return Some(door.u_value);
+        }
+        let u_outer = db
+            .get_wincons(self.cons)?
+            .u_value_for_area(db, self.area())?;
+        match self.double_window {
+            None => Some(u_outer),
+            Some(double_window) => {
+                let u_inner = db
+                    .get_wincons(double_window.cons)?
+                    .u_value_without_box(db)?;
+                if u_outer <= 0.0 || u_inner <= 0.0 {
+                    return None;
+                }
+                let r_total =
+                    1.0 / u_outer - RSI_HORIZONTAL + double_window.r_cavity - RSE + 1.0 / u_inner;
+                Some(fround2(1.0 / r_total))
+            }
+        }
+    }
+}
+
+impl Window {
+    /// Transmitancia térmica del hueco corregida según su posición en la envolvente, en W/m2K
+    ///
+    /// Los huecos en particiones interiores entre un espacio acondicionado y otro no acondicionado
+    /// (p.e. hacia una galería acristalada) usan la transmitancia del hueco multiplicada por el factor
+    /// de ajuste b del espacio no acondicionado, como equivalente a su transmisión al exterior.
+    /// El resto de huecos usan la transmitancia del hueco.
+    pub fn u_value_adjusted(&self, model: &Model) -> Option<f32> {
+        use SpaceType::CONDITIONED;
+
+        let u_value = self.u_value(&model.cons)?;
+        let wall = match model.get_wall(self.wall) {
+            Some(wall) if wall.bounds == BoundaryType::INTERIOR => wall,
+            _ => return Some(u_value),
+        };
+        let (space, next_space) = match (
+            model.get_space(wall.space),
+            wall.next_to.and_then(|id| model.get_space(id)),
+        ) {
+            (Some(space), Some(next_space)) => (space, next_space),
+            _ => return Some(u_value),
+        };
+        let uncond_space = match (space.kind == CONDITIONED, next_space.kind == CONDITIONED) {
+            (true, false) => next_space,
+            (false, true) => space,
+            _ => return Some(u_value),
+        };
+        let b = model.adjustment_factor_b(uncond_space.id);
+        debug!(
+            "{} (hueco acond-no acond) U={:.2} (U_w={:.2}, b={:.2})",
+            self.name,
+            b * u_value,
+            u_value,
+            b
+        );
+        Some(fround2(b * u_value))
     }
 }
 
@@ -290,18 +603,18 @@ impl Wall {
         use SpaceType::CONDITIONED;
         use Tilt::{BOTTOM, SIDE, TOP};
 
-        let resistance = model
-            .cons
-            .get_wallcons(self.cons)?
-            .resistance(&model.cons)
-            .ok();
+        let wallcons = model.cons.get_wallcons(self.cons)?;
+        let resistance = wallcons.resistance(&model.cons).ok();
+        // Las capas de espesor variable se tienen en cuenta con su U media en los elementos exteriores y adiabáticos
+        // y con su espesor medio en el resto
+        let resistance_range = wallcons.resistance_range(&model.cons).ok();
         match self.bounds {
             // Elementos adiabáticos -----------------------------
             // Transmitancia térmica de una composición de cerramiento adiabático, en una posición dada, en W/m²K
             // Notas:
             // - los elementos adiabáticos se reportan con el valor del elemento exterior (para poder comprobar U de particiones)
             ADIABATIC => {
-                let u = self.u_value_exterior(resistance);
+                let u = self.u_value_exterior_tapered(resistance_range);
                 debug!(
                     "{} ({}, adiabático) U={:.2}",
                     self.name,
@@ -312,7 +625,7 @@ impl Wall {
             }
             // Elementos en contacto con el exterior -------------
             EXTERIOR => {
-                let u = self.u_value_exterior(resistance);
+                let u = self.u_value_exterior_tapered(resistance_range);
                 debug!(
                     "{} ({}) U={:.2}",
                     self.name,
@@ -336,7 +649,12 @@ impl Wall {
                 // d_t: espesor equivalente total de solera (suelo del sótano) (10)
                 let d_t = space.slab_d_t(&model.walls, &model.cons)?;
                 // transmitancia térmica lineal como efecto del aislamiento perimetral, psi_gnd_ext
-                let psi_gnd_ext = space.slab_psi_gnd_ext(d_t, model);
+                // Se usa el aislamiento perimetral de la solera o, si no está definido, el general del edificio
+                let insulation = self.perim_insulation.unwrap_or(PerimeterInsulation {
+                    d: model.meta.d_perim_insulation,
+                    rn: model.meta.rn_perim_insulation,
+                });
+                let psi_gnd_ext = space.slab_psi_gnd_ext(d_t, insulation);
                 // Suponemos valor cuando se calcule en espacios sin solera (no podría pasar)
                 let char_dim = space
                     .slab_char_dim(&model.walls, &model.spaces)
@@ -446,22 +764,7 @@ impl Wall {
                         // Calculamos el A.U de los elementos del espacio que dan al exterior o al terreno (excluye interiores))
                         let UA_e_k = uncondspace.ua_of_external_and_ground_surfaces(model);
                         // Flow rate between the unheated space and the external environment 13789, (12), m³/h
-                        // En los no habitables debe estar definido n_v pero en los no acondicionados no
-                        // Se puede obtener n_v a partir de la Tabla 6 de la UNE-EN ISO 13789:2017 y n_50/20.
-                        // Para sótanos no calefactados la 13370:2007 (9.4) dice que se podría usar n_v = 0.30
-                        let q_ue = {
-                            let volume = uncondspace.area(&model.walls)
-                                * uncondspace.height_net(&model.walls, &model.cons);
-                            let n_v = uncondspace
-                                .n_v
-                                .unwrap_or_else(|| model.global_ventilation_rate());
-                            if n_v.abs() < f32::EPSILON {
-                                // Espacio mal definido (ni tiene n_v ni hay definición global de ventilación)
-                                warn!("Nivel de ventilación (1/h) nulo o casi nulo del espacio no acondicionado {} ({})", uncondspace.id, uncondspace.name);
-                            }
-                            // m^3 * 1/h
-                            volume * n_v
-                        };
+                        let q_ue = uncondspace.ventilation_flow_to_exterior(model);
                         self.u_value_interior_cond_uncond(A_i, R_f, UA_e_k, q_ue)
                     }
                 }
@@ -479,13 +782,25 @@ impl Wall {
     ///
     /// * `resistance`: Resistencia térmica del elemento opaco (excluyendo resistencias superficiales), en W/m²K
     pub fn u_value_exterior(&self, resistance: Option<f32>) -> Option<f32> {
-        let r = resistance?;
+        self.u_value_exterior_tapered(resistance.map(|r| (r, r)))
+    }
+
+    /// Transmitancia térmica media de un cerramiento exterior con capas de espesor variable, en W/m2K
+    ///
+    /// Es la U media ponderada por superficie (ver u_value_tapered), con las resistencias superficiales
+    /// de la posición del elemento. Con resistencias mínima y máxima iguales coincide con u_value_exterior.
+    ///
+    /// # Argumentos
+    ///
+    /// * `resistance_range`: Resistencias térmicas mínima y máxima del elemento opaco (excluyendo resistencias superficiales), en W/m²K
+    pub fn u_value_exterior_tapered(&self, resistance_range: Option<(f32, f32)>) -> Option<f32> {
+        let (r_min, r_max) = resistance_range?;
         let rsi = match Tilt::from(self) {
             Tilt::BOTTOM => RSI_DESCENDENTE,
             Tilt::TOP => RSI_ASCENDENTE,
             Tilt::SIDE => RSI_HORIZONTAL,
         };
-        Some(fround2(1.0 / (r + rsi + RSE)))
+        Some(fround2(u_value_tapered(r_min + rsi + RSE, r_max - r_min)))
     }
 
     /// Transmitancia térmica de cerramiento interior entre espacio acondicionado y no acondicionado, en W/m2K
@@ -655,3 +970,17 @@ fn position_to_name<'a>(position: Tilt) -> &'a str {
         Tilt::SIDE => "muro",
     }
 }
+
+/// Transmitancia media ponderada por superficie de un elemento con una resistencia que varía linealmente, U [W/m²K]
+///
+/// Caso de superficie rectangular con capas de espesor variable (formación de pendientes) de UNE-EN ISO 6946:2017 (anexo E):
+/// U = ln(1 + R_2 / R_0) / R_2
+/// donde R_0 es la resistencia total mínima (incluidas las superficiales) y R_2 el incremento máximo de resistencia
+/// debido a la variación de espesor. Sin variación de espesor se tiene U = 1 / R_0.
+pub(crate) fn u_value_tapered(r_0: f32, r_2: f32) -> f32 {
+    if r_2 > 1e-6 {
+        (1.0 + r_2 / r_0).ln() / r_2
+    } else {
+        1.0 / r_0
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Caudal de ventilación de diseño de los espacios según su categoría de calidad del aire interior (RITE)
+//!
+//! El caudal de aire exterior se obtiene por el método indirecto por persona, según la ocupación del espacio,
+//! o, en los espacios sin ocupación, por el método de caudal por unidad de superficie.
+
+use std::collections::BTreeMap;
+
+use super::EnergyProps;
+use crate::{Model, Uuid};
+
+impl EnergyProps {
+    /// Caudal de ventilación de diseño del espacio [l/s]
+    ///
+    /// Devuelve None si el espacio no tiene cargas con categoría de calidad del aire interior
+    /// o si no tiene ocupación y la categoría no admite el cálculo por superficie (IDA 1).
+    /// No tiene en cuenta el multiplicador del espacio.
+    pub fn design_ventilation_flow(&self, space_id: Uuid) -> Option<f32> {
+        let space = self.spaces.get(&space_id)?;
+        let loads = self.loads.get(&space.loads?)?;
+        let air_quality = loads.air_quality?;
+        if loads.area_per_person > 0.0 {
+            Some(space.area / loads.area_per_person * air_quality.flow_per_person())
+        } else {
+            air_quality.flow_per_area().map(|q| q * space.area)
+        }
+    }
+
+    /// Tasa de ventilación de diseño del espacio [ren/h]
+    ///
+    /// Corresponde al caudal de ventilación de diseño referido al volumen neto del espacio
+    pub fn design_ventilation_rate(&self, space_id: Uuid) -> Option<f32> {
+        let volume = self.spaces.get(&space_id)?.volume_net;
+        if volume <= 0.0 {
+            return None;
+        }
+        self.design_ventilation_flow(space_id)
+            .map(|q| 3.6 * q / volume)
+    }
+}
+
+impl Model {
+    /// Caudales de ventilación de diseño de los espacios con categoría de calidad del aire interior [l/s]
+    pub fn design_ventilation_flows(&self) -> BTreeMap<Uuid, f32> {
+        let props = EnergyProps::from(self);
+        self.spaces
+            .iter()
+            .filter_map(|s| Some((s.id, props.design_ventilation_flow(s.id)?)))
+            .collect()
+    }
+}
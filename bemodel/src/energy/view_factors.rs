@@ -0,0 +1,116 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Factores de forma aproximados entre las superficies interiores de un espacio
+//!
+//! Se usa un reparto por áreas ponderadas, sin cálculo de radiosidad: cada superficie ve al resto
+//! de superficies del espacio que no están en su mismo plano, en proporción a su superficie.
+//! Sirven para repartir la radiación solar que entra por los huecos entre las superficies interiores.
+
+use std::collections::BTreeMap;
+
+use crate::{Model, Uuid, Vector3};
+
+/// Tolerancia para considerar dos superficies en el mismo plano [m]
+const TOL: f32 = 0.01;
+
+/// Superficie interior de un espacio (opaco o hueco)
+struct Surface {
+    id: Uuid,
+    /// Superficie [m²]
+    area: f32,
+    /// Normal exterior, en coordenadas globales
+    normal: Vector3,
+    /// Distancia del plano al origen, si el opaco tiene definición geométrica completa [m]
+    offset: Option<f32>,
+}
+
+impl Surface {
+    /// ¿Están las superficies en el mismo plano?
+    ///
+    /// Sin definición geométrica completa se consideran coplanares las superficies con igual orientación e inclinación
+    fn is_coplanar(&self, other: &Surface) -> bool {
+        let cos = self.normal.dot(&other.normal);
+        match (self.offset, other.offset) {
+            (Some(a), Some(b)) if cos.abs() > 1.0 - 1e-4 => (a - b * cos.signum()).abs() < TOL,
+            _ => cos > 1.0 - 1e-4,
+        }
+    }
+}
+
+impl Model {
+    /// Factores de forma aproximados entre las superficies interiores del espacio, F_ij [-]
+    ///
+    /// Las superficies son los opacos del espacio y los de otros espacios que lindan con él (next_to),
+    /// con su superficie neta, y sus huecos. El factor de forma de la superficie i a la j es:
+    ///
+    /// F_ij = A_j / Σ A_k
+    ///
+    /// donde la suma incluye las superficies que no están en el plano de i, y F_ij = 0 para las que sí lo están.
+    /// Así, los factores de cada superficie suman 1, salvo que no vea ninguna otra superficie.
+    /// Se omiten las superficies sin área.
+    pub fn space_view_factors(&self, space_id: Uuid) -> BTreeMap<Uuid, BTreeMap<Uuid, f32>> {
+        let mut surfaces = Vec::new();
+        for wall in self
+            .walls
+            .iter()
+            .filter(|w| w.space == space_id || w.next_to == Some(space_id))
+        {
+            let normal = wall.geometry.outward_normal();
+            let offset = wall.geometry.position.map(|p| normal.dot(&p.coords));
+            surfaces.push(Surface {
+                id: wall.id,
+                area: wall.area_net(&self.windows),
+                normal,
+                offset,
+            });
+            surfaces.extend(self.windows_of_wall(wall.id).map(|win| Surface {
+                id: win.id,
+                area: win.area(),
+                normal,
+                offset,
+            }));
+        }
+        surfaces.retain(|s| s.area > 0.0);
+
+        surfaces
+            .iter()
+            .map(|i| {
+                let visible: Vec<_> = surfaces.iter().filter(|j| !i.is_coplanar(j)).collect();
+                let total_area: f32 = visible.iter().map(|j| j.area).sum();
+                let factors = visible
+                    .iter()
+                    .map(|j| (j.id, j.area / total_area))
+                    .collect();
+                (i.id, factors)
+            })
+            .collect()
+    }
+
+    /// Reparto de las ganancias solares de los huecos entre las superficies interiores de su espacio [W]
+    ///
+    /// La ganancia de cada hueco (p.e. la de una hora de Model::solar_gains_hourly) se reparte entre las superficies
+    /// del espacio al que pertenece su opaco según los factores de forma del hueco (Model::space_view_factors).
+    /// Se ignoran los huecos sin opaco y los que no ven ninguna otra superficie.
+    pub fn distribute_solar_gains(&self, gains: &BTreeMap<Uuid, f32>) -> BTreeMap<Uuid, f32> {
+        let mut factors_by_space = BTreeMap::new();
+        let mut distributed = BTreeMap::new();
+        for (win_id, gain) in gains {
+            let space_id = match self
+                .get_window(*win_id)
+                .and_then(|win| self.get_wall(win.wall))
+            {
+                Some(wall) => wall.space,
+                None => continue,
+            };
+            let factors = factors_by_space
+                .entry(space_id)
+                .or_insert_with(|| self.space_view_factors(space_id));
+            for (surface_id, factor) in factors.get(win_id).into_iter().flatten() {
+                *distributed.entry(*surface_id).or_default() += gain * factor;
+            }
+        }
+        distributed
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Regeneración determinista de los identificadores (UUID) del modelo
+//!
+//! Los nuevos identificadores se obtienen a partir de una semilla, el tipo de elemento,
+//! su nombre y el número de orden entre los elementos del mismo tipo con igual nombre,
+//! de modo que el resultado es reproducible para una misma semilla.
+
+use std::collections::HashMap;
+
+use crate::{utils::uuid_from_str, Model, Uuid};
+
+/// Diccionario de (tipo de elemento, id antiguo) a id nuevo
+///
+/// Se distingue el tipo de elemento para resolver bien las referencias aunque
+/// haya colisiones de ids entre elementos de distinto tipo.
+type IdMap = HashMap<(&'static str, Uuid), Uuid>;
+
+impl Model {
+    /// Regenera de forma determinista los identificadores de todos los elementos,
+    /// construcciones, horarios, cargas y consignas del modelo, y actualiza las referencias
+    ///
+    /// Las referencias a elementos inexistentes se mantienen sin cambios.
+    pub fn regenerate_ids(&mut self, seed: &str) {
+        let mut ids = IdGenerator::new(seed);
+
+        // Elementos
+        for e in &mut self.floors {
+            e.id = ids.update("floor", &e.name, e.id);
+        }
+        for e in &mut self.spaces {
+            e.id = ids.update("space", &e.name, e.id);
+        }
+        for e in &mut self.zones {
+            e.id = ids.update("zone", &e.name, e.id);
+        }
+        for e in &mut self.walls {
+            e.id = ids.update("wall", &e.name, e.id);
+        }
+        for e in &mut self.windows {
+            e.id = ids.update("window", &e.name, e.id);
+        }
+        for e in &mut self.thermal_bridges {
+            e.id = ids.update("thermal_bridge", &e.name, e.id);
+        }
+        for e in &mut self.shades {
+            e.id = ids.update("shade", &e.name, e.id);
+        }
+        for e in &mut self.radiant_surfaces {
+            e.id = ids.update("radiant_surface", &e.name, e.id);
+        }
+        // Construcción
+        for e in &mut self.cons.wallcons {
+            e.id = ids.update("wallcons", &e.name, e.id);
+        }
+        for e in &mut self.cons.wincons {
+            e.id = ids.update("wincons", &e.name, e.id);
+        }
+        for e in &mut self.cons.materials {
+            e.id = ids.update("material", &e.name, e.id);
+        }
+        for e in &mut self.cons.glasses {
+            e.id = ids.update("glass", &e.name, e.id);
+        }
+        for e in &mut self.cons.frames {
+            e.id = ids.update("frame", &e.name, e.id);
+        }
+        // Uso
+        for e in &mut self.schedules.year {
+            e.id = ids.update("schedule_year", &e.name, e.id);
+        }
+        for e in &mut self.schedules.week {
+            e.id = ids.update("schedule_week", &e.name, e.id);
+        }
+        for e in &mut self.schedules.day {
+            e.id = ids.update("schedule_day", &e.name, e.id);
+        }
+        for e in &mut self.loads {
+            e.id = ids.update("loads", &e.name, e.id);
+        }
+        for e in &mut self.thermostats {
+            e.id = ids.update("thermostat", &e.name, e.id);
+        }
+
+        // Actualización de referencias
+        let map = ids.map;
+        let new_id = |kind: &'static str, id: &mut Uuid| {
+            if let Some(new) = map.get(&(kind, *id)) {
+                *id = *new;
+            }
+        };
+        let new_opt_id = |kind: &'static str, id: &mut Option<Uuid>| {
+            if let Some(id) = id {
+                new_id(kind, id);
+            }
+        };
+
+        for space in &mut self.spaces {
+            new_opt_id("floor", &mut space.floor);
+            new_opt_id("loads", &mut space.loads);
+            new_opt_id("thermostat", &mut space.thermostat);
+        }
+        for zone in &mut self.zones {
+            for id in &mut zone.spaces {
+                new_id("space", id);
+            }
+        }
+        for wall in &mut self.walls {
+            new_id("space", &mut wall.space);
+            new_opt_id("space", &mut wall.next_to);
+            new_id("wallcons", &mut wall.cons);
+        }
+        for window in &mut self.windows {
+            new_id("wall", &mut window.wall);
+            new_id("wincons", &mut window.cons);
+            if let Some(double_window) = &mut window.double_window {
+                new_id("wincons", &mut double_window.cons);
+            }
+            new_opt_id("schedule_year", &mut window.night_insulation_schedule);
+        }
+        for tb in &mut self.thermal_bridges {
+            new_opt_id("space", &mut tb.space);
+        }
+        for shade in &mut self.shades {
+            new_opt_id("schedule_year", &mut shade.schedule);
+        }
+        for radiant in &mut self.radiant_surfaces {
+            new_id("wall", &mut radiant.wall);
+            new_opt_id("schedule_year", &mut radiant.schedule);
+        }
+        for cons in &mut self.cons.wallcons {
+            for layer in &mut cons.layers {
+                new_id("material", &mut layer.material);
+            }
+        }
+        for cons in &mut self.cons.wincons {
+            new_id("glass", &mut cons.glass);
+            new_id("frame", &mut cons.frame);
+        }
+        for sch in &mut self.schedules.year {
+            for (id, _) in &mut sch.values {
+                new_id("schedule_week", id);
+            }
+        }
+        for sch in &mut self.schedules.week {
+            for (id, _) in &mut sch.values {
+                new_id("schedule_day", id);
+            }
+        }
+        for loads in &mut self.loads {
+            new_opt_id("schedule_year", &mut loads.people_schedule);
+            new_opt_id("schedule_year", &mut loads.equipment_schedule);
+            new_opt_id("schedule_year", &mut loads.lighting_schedule);
+        }
+        for thermostat in &mut self.thermostats {
+            new_opt_id("schedule_year", &mut thermostat.temp_max);
+            new_opt_id("schedule_year", &mut thermostat.temp_min);
+        }
+        if let Some(extra) = &mut self.extra {
+            for e in extra {
+                new_opt_id("space", &mut e.nextspace);
+                new_id("wallcons", &mut e.cons);
+            }
+        }
+
+        // Overrides indexados por id
+        self.overrides.walls = std::mem::take(&mut self.overrides.walls)
+            .into_iter()
+            .map(|(id, o)| (*map.get(&("wall", id)).unwrap_or(&id), o))
+            .collect();
+        self.overrides.windows = std::mem::take(&mut self.overrides.windows)
+            .into_iter()
+            .map(|(id, o)| (*map.get(&("window", id)).unwrap_or(&id), o))
+            .collect();
+    }
+}
+
+/// Generador de ids deterministas que registra la correspondencia con los ids antiguos
+struct IdGenerator<'a> {
+    seed: &'a str,
+    /// Número de elementos ya vistos para cada tipo y nombre
+    counts: HashMap<(&'static str, String), usize>,
+    map: IdMap,
+}
+
+impl<'a> IdGenerator<'a> {
+    fn new(seed: &'a str) -> Self {
+        Self {
+            seed,
+            counts: HashMap::new(),
+            map: IdMap::new(),
+        }
+    }
+
+    /// Genera el nuevo id de un elemento y guarda su correspondencia con el id antiguo
+    fn update(&mut self, kind: &'static str, name: &str, old: Uuid) -> Uuid {
+        let count = self.counts.entry((kind, name.to_string())).or_insert(0);
+        let new = uuid_from_str(&format!("{}-{}-{}-{}", self.seed, kind, name, count));
+        *count += 1;
+        self.map.insert((kind, old), new);
+        new
+    }
+}
@@ -5,7 +5,14 @@
 //! Datos climáticos, modelo del edificio y rutinas para cálculo energético
 
 mod checks;
+mod coplanar;
+mod ids;
+mod merge;
 mod purge;
+mod reference;
+mod report;
+mod rotate;
+mod submodel;
 mod types;
 
 pub mod climatedata;
@@ -15,13 +22,16 @@ pub mod utils;
 
 pub use checks::check;
 pub use purge::purge_unused;
+pub use reference::ULimits;
 pub use types::{
-    point, vector, BoundaryType, ConsDb, ConsDbGroups, ExtraData, Frame, Glass, Layer, Library,
-    MatProps, Material, Meta, Model, Orientation, Point2, Point3, Polygon, PropsOverrides,
-    Schedule, ScheduleDay, ScheduleWeek, SchedulesDb, Shade, Space, SpaceLoads, Thermostat,
-    SpaceType, ThermalBridge, ThermalBridgeKind, Tilt, Uuid, Vector2, Vector3, Wall, WallCons,
-    WallGeom, WallPropsOverrides, Warning, WarningLevel, WinCons, WinGeom, WinPropsOverrides,
-    Window,
+    point, vector, AirLeakage, AirQuality, BoundaryType, CavityVentilation, ConsDb, ConsDbGroups,
+    DhwDemand, DoubleWindow, ExtraData, Floor, Frame, Glass, Layer, Library, LouverOrientation,
+    LouverShade, MatProps, Material, Meta, Model, OpaqueDoor, Orientation, PerimeterInsulation,
+    Point2, Point3, Polygon, PropsOverrides, RadiantSurface, RollerShutterBox, Schedule,
+    ScheduleDay, ScheduleWeek, SchedulesDb, Shade, Space, SpaceLoads, SpaceType, ThermalBridge,
+    ThermalBridgeKind, ThermalZone, Thermostat, Tilt, Uuid, Vector2, Vector3, VentilatedCavity,
+    Wall, WallCons, WallGeom, WallPropsOverrides, Warning, WarningLevel, WinCons, WinGeom,
+    WinPermeabilityParts, WinPropsOverrides, Window,
 };
 
 /// Versión del programa
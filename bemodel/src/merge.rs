@@ -0,0 +1,190 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Unión de modelos para formar un edificio con varios bloques
+
+use std::collections::HashMap;
+
+use log::warn;
+use serde::Serialize;
+
+use super::{Model, Uuid, Vector3};
+
+impl Model {
+    /// Incorpora al modelo los elementos de otro modelo, desplazando su geometría
+    ///
+    /// Se añaden las plantas, espacios, zonas térmicas, opacos, huecos, puentes térmicos, sombras y superficies
+    /// radiantes del otro modelo, con sus posiciones y cotas desplazadas según offset [m], y con nuevos ids para evitar colisiones.
+    /// Las construcciones, horarios, cargas y consignas equivalentes a las del modelo (con igual definición,
+    /// salvo el id) se sustituyen por estas y el resto se añaden al modelo.
+    /// Se conservan los metadatos del modelo, avisando si difieren de los del otro modelo.
+    pub fn merge(&mut self, other: &Model, offset: Vector3) {
+        if serde_json::to_value(&self.meta).ok() != serde_json::to_value(&other.meta).ok() {
+            warn!(
+                "Los metadatos de los modelos unidos difieren. Se usarán los del modelo principal"
+            );
+        }
+
+        let mut other = other.clone();
+        other.regenerate_ids(&Uuid::new_v4().to_string());
+
+        // Construcción
+        let map = merge_equivalent(&mut self.cons.materials, other.cons.materials, |e| e.id);
+        for cons in &mut other.cons.wallcons {
+            for layer in &mut cons.layers {
+                update_id(&map, &mut layer.material);
+            }
+        }
+        let map = merge_equivalent(&mut self.cons.glasses, other.cons.glasses, |e| e.id);
+        for cons in &mut other.cons.wincons {
+            update_id(&map, &mut cons.glass);
+        }
+        let map = merge_equivalent(&mut self.cons.frames, other.cons.frames, |e| e.id);
+        for cons in &mut other.cons.wincons {
+            update_id(&map, &mut cons.frame);
+        }
+        let map = merge_equivalent(&mut self.cons.wallcons, other.cons.wallcons, |e| e.id);
+        for wall in &mut other.walls {
+            update_id(&map, &mut wall.cons);
+        }
+        for e in other.extra.iter_mut().flatten() {
+            update_id(&map, &mut e.cons);
+        }
+        let map = merge_equivalent(&mut self.cons.wincons, other.cons.wincons, |e| e.id);
+        for window in &mut other.windows {
+            update_id(&map, &mut window.cons);
+            if let Some(double_window) = &mut window.double_window {
+                update_id(&map, &mut double_window.cons);
+            }
+        }
+
+        // Uso
+        let map = merge_equivalent(&mut self.schedules.day, other.schedules.day, |e| e.id);
+        for sch in &mut other.schedules.week {
+            for (id, _) in &mut sch.values {
+                update_id(&map, id);
+            }
+        }
+        let map = merge_equivalent(&mut self.schedules.week, other.schedules.week, |e| e.id);
+        for sch in &mut other.schedules.year {
+            for (id, _) in &mut sch.values {
+                update_id(&map, id);
+            }
+        }
+        let map = merge_equivalent(&mut self.schedules.year, other.schedules.year, |e| e.id);
+        for loads in &mut other.loads {
+            for id in [
+                &mut loads.people_schedule,
+                &mut loads.equipment_schedule,
+                &mut loads.lighting_schedule,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                update_id(&map, id);
+            }
+        }
+        for thermostat in &mut other.thermostats {
+            for id in [&mut thermostat.temp_max, &mut thermostat.temp_min]
+                .into_iter()
+                .flatten()
+            {
+                update_id(&map, id);
+            }
+        }
+        for id in other
+            .windows
+            .iter_mut()
+            .map(|w| &mut w.night_insulation_schedule)
+            .chain(other.shades.iter_mut().map(|s| &mut s.schedule))
+            .chain(other.radiant_surfaces.iter_mut().map(|r| &mut r.schedule))
+            .flatten()
+        {
+            update_id(&map, id);
+        }
+        let loads_map = merge_equivalent(&mut self.loads, other.loads, |e| e.id);
+        let thermostats_map = merge_equivalent(&mut self.thermostats, other.thermostats, |e| e.id);
+        for space in &mut other.spaces {
+            if let Some(id) = &mut space.loads {
+                update_id(&loads_map, id);
+            }
+            if let Some(id) = &mut space.thermostat {
+                update_id(&thermostats_map, id);
+            }
+        }
+
+        // Elementos, con la geometría desplazada
+        for floor in &mut other.floors {
+            floor.z += offset.z;
+        }
+        for space in &mut other.spaces {
+            space.z += offset.z;
+        }
+        let positions = other
+            .walls
+            .iter_mut()
+            .map(|w| &mut w.geometry.position)
+            .chain(other.shades.iter_mut().map(|s| &mut s.geometry.position));
+        for position in positions.flatten() {
+            *position += offset;
+        }
+
+        self.floors.extend(other.floors);
+        self.spaces.extend(other.spaces);
+        self.zones.extend(other.zones);
+        self.walls.extend(other.walls);
+        self.windows.extend(other.windows);
+        self.thermal_bridges.extend(other.thermal_bridges);
+        self.shades.extend(other.shades);
+        self.radiant_surfaces.extend(other.radiant_surfaces);
+        self.overrides.walls.extend(other.overrides.walls);
+        self.overrides.windows.extend(other.overrides.windows);
+        if let Some(extra) = other.extra {
+            self.extra.get_or_insert_with(Vec::new).extend(extra);
+        }
+    }
+}
+
+/// Añade a la lista los elementos sin otro equivalente en ella y devuelve la correspondencia
+/// de ids de los elementos sustituidos por su equivalente
+///
+/// Dos elementos son equivalentes cuando tienen la misma definición, salvo el id
+fn merge_equivalent<T: Serialize>(
+    target: &mut Vec<T>,
+    items: Vec<T>,
+    get_id: fn(&T) -> Uuid,
+) -> HashMap<Uuid, Uuid> {
+    let key = |e: &T| {
+        let mut value = serde_json::to_value(e).ok();
+        if let Some(serde_json::Value::Object(map)) = &mut value {
+            map.remove("id");
+        }
+        value
+    };
+    let mut keys: Vec<_> = target.iter().map(|e| (key(e), get_id(e))).collect();
+    let mut map = HashMap::new();
+    for item in items {
+        let item_key = key(&item);
+        match keys
+            .iter()
+            .find(|(k, _)| item_key.is_some() && *k == item_key)
+        {
+            Some((_, id)) => {
+                map.insert(get_id(&item), *id);
+            }
+            None => {
+                keys.push((item_key, get_id(&item)));
+                target.push(item);
+            }
+        }
+    }
+    map
+}
+
+/// Sustituye el id por su equivalente, si lo hay
+fn update_id(map: &HashMap<Uuid, Uuid>, id: &mut Uuid) {
+    if let Some(new) = map.get(id) {
+        *id = *new;
+    }
+}
@@ -99,6 +99,9 @@ pub(crate) fn purge_unused_spaces(model: &mut Model) {
         .cloned()
         .filter(|v| spaces_used_ids.contains(&v.id))
         .collect();
+    for zone in &mut model.zones {
+        zone.spaces.retain(|id| spaces_used_ids.contains(id));
+    }
 }
 
 /// Elimina puentes térmicos con longitud nula
@@ -125,7 +128,11 @@ pub(crate) fn purge_unused_wallcons(model: &mut Model) {
 
 /// Elimina construcciones de huecos no usadas en los huecos
 pub(crate) fn purge_unused_wincons(model: &mut Model) {
-    let wincons_used_ids: HashSet<_> = model.windows.iter().map(|v| v.cons).collect();
+    let wincons_used_ids: HashSet<_> = model
+        .windows
+        .iter()
+        .flat_map(|v| std::iter::once(v.cons).chain(v.double_window.map(|d| d.cons)))
+        .collect();
     model.cons.wincons = model
         .cons
         .wincons
@@ -199,9 +206,9 @@ pub(crate) fn purge_unused_thermostats(model: &mut Model) {
         .collect();
 }
 
-/// Elimina definiciones de horarios no usadas en las definiciones de cargas o consignas
+/// Elimina definiciones de horarios no usadas en las definiciones de cargas, consignas, huecos, sombras o superficies radiantes
 pub(crate) fn purge_unused_schedules(model: &mut Model) {
-    // Eliminar perfiles no usados en cargas o consignas
+    // Eliminar perfiles no usados en cargas, consignas, huecos, sombras o superficies radiantes
     let loads_ids = model
         .loads
         .iter()
@@ -212,8 +219,19 @@ pub(crate) fn purge_unused_schedules(model: &mut Model) {
         .iter()
         .flat_map(|v| [v.temp_max, v.temp_min])
         .flatten();
+    let windows_ids = model
+        .windows
+        .iter()
+        .filter_map(|v| v.night_insulation_schedule);
+    let shades_ids = model.shades.iter().filter_map(|v| v.schedule);
+    let radiant_ids = model.radiant_surfaces.iter().filter_map(|v| v.schedule);
     // Horarios anuales - elimina no usados
-    let year_used_ids: HashSet<_> = loads_ids.chain(thermostats_ids).collect();
+    let year_used_ids: HashSet<_> = loads_ids
+        .chain(thermostats_ids)
+        .chain(windows_ids)
+        .chain(shades_ids)
+        .chain(radiant_ids)
+        .collect();
     // Elimina horarios anuales no usados
     model.schedules.year = model
         .schedules
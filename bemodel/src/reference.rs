@@ -0,0 +1,104 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Edificio de referencia del DB-HE, para la comparación de indicadores con el modelo
+
+use super::{climatedata::ClimateZone, energy::EnergyProps, BoundaryType, Model, Tilt};
+
+// Valores límite de transmitancia térmica, U_lim [W/m²K] (DB-HE 2019, tabla 3.1.1.a-HE1)
+// por zona climática de invierno: α, A, B, C, D, E
+
+/// U_lim de muros y suelos en contacto con el aire exterior (U_S, U_M) [W/m²K]
+const U_LIM_WALLS: [f32; 6] = [0.80, 0.70, 0.56, 0.49, 0.41, 0.37];
+/// U_lim de cubiertas en contacto con el aire exterior (U_C) [W/m²K]
+const U_LIM_ROOFS: [f32; 6] = [0.55, 0.50, 0.44, 0.40, 0.35, 0.33];
+/// U_lim de elementos en contacto con espacios no habitables o con el terreno (U_T) [W/m²K]
+const U_LIM_GROUND: [f32; 6] = [0.90, 0.80, 0.75, 0.70, 0.65, 0.59];
+/// U_lim de huecos (U_H) [W/m²K]
+const U_LIM_WINDOWS: [f32; 6] = [3.2, 2.7, 2.3, 2.1, 1.8, 1.8];
+/// U_lim de puertas con superficie semitransparente igual o inferior al 50% [W/m²K]
+const U_LIM_DOORS: f32 = 5.7;
+
+/// Transmitancias límite de los elementos de la envolvente térmica para una zona climática [W/m²K]
+///
+/// Son las de la tabla 3.1.1.a-HE1 del DB-HE, iguales para edificios de uso residencial y terciario.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ULimits {
+    /// Muros y suelos en contacto con el aire exterior (U_S, U_M) [W/m²K]
+    pub walls: f32,
+    /// Cubiertas en contacto con el aire exterior (U_C) [W/m²K]
+    pub roofs: f32,
+    /// Elementos en contacto con espacios no habitables o con el terreno (U_T) [W/m²K]
+    pub ground: f32,
+    /// Huecos (U_H) [W/m²K]
+    pub windows: f32,
+    /// Puertas opacas (superficie semitransparente no superior al 50%) [W/m²K]
+    pub doors: f32,
+}
+
+impl ULimits {
+    /// Transmitancias límite según la severidad climática de invierno de la zona climática
+    pub fn for_climate(climate: &ClimateZone) -> Self {
+        let zone = climate.to_string();
+        let idx = if zone.starts_with("Alfa") {
+            0
+        } else {
+            match zone.chars().next() {
+                Some('A') => 1,
+                Some('B') => 2,
+                Some('C') => 3,
+                Some('D') => 4,
+                _ => 5,
+            }
+        };
+        Self {
+            walls: U_LIM_WALLS[idx],
+            roofs: U_LIM_ROOFS[idx],
+            ground: U_LIM_GROUND[idx],
+            windows: U_LIM_WINDOWS[idx],
+            doors: U_LIM_DOORS,
+        }
+    }
+}
+
+impl Model {
+    /// Edificio de referencia del modelo
+    ///
+    /// Es una copia del modelo, con su misma geometría, en la que los opacos y huecos de la envolvente térmica
+    /// tienen como transmitancia (valor de usuario) la transmitancia límite de su zona climática:
+    /// - opacos exteriores: U_C las cubiertas y U_M el resto (muros y suelos)
+    /// - opacos en contacto con el terreno o con espacios no habitables: U_T
+    /// - huecos: U_H, o la de puertas en las puertas opacas
+    ///
+    /// El resto de elementos y datos de usuario no cambian.
+    pub fn reference_building(&self) -> Model {
+        let limits = ULimits::for_climate(&self.meta.climate);
+        let props = EnergyProps::from(self);
+        let mut model = self.clone();
+
+        for (id, wall) in props.walls.iter().filter(|(_, w)| w.is_tenv) {
+            let u_value = match (wall.bounds, wall.tilt) {
+                (BoundaryType::EXTERIOR, Tilt::TOP) => limits.roofs,
+                (BoundaryType::EXTERIOR, _) => limits.walls,
+                (BoundaryType::GROUND | BoundaryType::INTERIOR, _) => limits.ground,
+                (BoundaryType::ADIABATIC, _) => continue,
+            };
+            model.overrides.walls.entry(*id).or_default().u_value = Some(u_value);
+        }
+
+        for win in self.windows.iter() {
+            if !props.windows.get(&win.id).is_some_and(|w| w.is_tenv) {
+                continue;
+            }
+            let u_value = if win.is_opaque_door() {
+                limits.doors
+            } else {
+                limits.windows
+            };
+            model.overrides.windows.entry(win.id).or_default().u_value = Some(u_value);
+        }
+
+        model
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Informe resumen del modelo en formato Markdown, para memorias técnicas
+
+use std::fmt::Write;
+
+use crate::{energy::EnergyIndicators, Model};
+
+/// Resistencias superficiales para la transmitancia de referencia de las construcciones (flujo horizontal) [m²K/W]
+const RSI_HORIZONTAL: f32 = 0.13;
+const RSE: f32 = 0.04;
+
+impl Model {
+    /// Informe resumen del modelo en formato Markdown
+    ///
+    /// Incluye tablas de datos generales, espacios, construcciones de opacos y huecos,
+    /// indicadores energéticos (K, q_sol;jul, n50) y la lista de avisos de la validación del modelo.
+    /// La transmitancia de las construcciones de opacos es la de la composición en posición vertical.
+    pub fn to_markdown_report(&self) -> String {
+        let mut out = String::new();
+        // La escritura en un String no puede fallar
+        self.write_markdown_report(&mut out)
+            .expect("Error al escribir el informe");
+        out
+    }
+
+    /// Escribe el informe resumen del modelo en formato Markdown
+    fn write_markdown_report(&self, out: &mut String) -> std::fmt::Result {
+        let ind = EnergyIndicators::compute(self);
+
+        writeln!(out, "# {}\n", self.meta.name)?;
+
+        writeln!(out, "## Datos generales\n")?;
+        writeln!(out, "| Dato | Valor |")?;
+        writeln!(out, "|---|---|")?;
+        writeln!(out, "| Zona climática | {} |", self.meta.climate)?;
+        writeln!(
+            out,
+            "| Tipo de edificio | {} |",
+            if self.meta.is_new_building {
+                "Nuevo"
+            } else {
+                "Existente"
+            }
+        )?;
+        writeln!(
+            out,
+            "| Uso | {} |",
+            if self.meta.is_dwelling {
+                "Residencial"
+            } else {
+                "Terciario"
+            }
+        )?;
+        writeln!(out, "| Número de viviendas | {} |", self.meta.num_dwellings)?;
+        writeln!(out, "| Superficie útil [m²] | {:.2} |", ind.area_ref)?;
+        writeln!(out, "| Volumen bruto [m³] | {:.2} |", ind.vol_env_gross)?;
+        writeln!(out, "| Compacidad [m³/m²] | {:.2} |", ind.compactness)?;
+        writeln!(out)?;
+
+        writeln!(out, "## Espacios\n")?;
+        writeln!(
+            out,
+            "| Nombre | Tipo | Superficie [m²] | Volumen neto [m³] | Multiplicador | Dentro de la envolvente |"
+        )?;
+        writeln!(out, "|---|---|---|---|---|---|")?;
+        for space in &self.spaces {
+            let Some(sp) = ind.props.spaces.get(&space.id) else {
+                continue;
+            };
+            writeln!(
+                out,
+                "| {} | {} | {:.2} | {:.2} | {} | {} |",
+                space.name,
+                sp.kind,
+                sp.area,
+                sp.volume_net,
+                sp.multiplier,
+                if sp.inside_tenv { "Sí" } else { "No" }
+            )?;
+        }
+        writeln!(out)?;
+
+        writeln!(out, "## Construcciones\n")?;
+        writeln!(out, "### Opacos\n")?;
+        writeln!(out, "| Nombre | U [W/m²K] | Composición |")?;
+        writeln!(out, "|---|---|---|")?;
+        for cons in &self.cons.wallcons {
+            let layers = cons
+                .layers
+                .iter()
+                .map(|layer| {
+                    let name = self
+                        .cons
+                        .get_material(layer.material)
+                        .map_or("-", |m| m.name.as_str());
+                    match layer.e_max {
+                        Some(e_max) => format!("{} ({:.3}-{:.3} m)", name, layer.e, e_max),
+                        None => format!("{} ({:.3} m)", name, layer.e),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                out,
+                "| {} | {:.2} | {} |",
+                cons.name,
+                cons.u_value(&self.cons, RSI_HORIZONTAL, RSE),
+                layers
+            )?;
+        }
+        writeln!(out)?;
+        writeln!(out, "### Huecos\n")?;
+        writeln!(
+            out,
+            "| Nombre | U [W/m²K] | Vidrio | Marco | Fracción de marco |"
+        )?;
+        writeln!(out, "|---|---|---|---|---|")?;
+        for cons in &self.cons.wincons {
+            let glass = self
+                .cons
+                .get_glass(cons.glass)
+                .map_or("-", |g| g.name.as_str());
+            let frame = self
+                .cons
+                .get_frame(cons.frame)
+                .map_or("-", |f| f.name.as_str());
+            let u_value = cons
+                .u_value(&self.cons)
+                .map_or_else(|| "-".to_string(), |u| format!("{:.2}", u));
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {:.2} |",
+                cons.name, u_value, glass, frame, cons.f_f
+            )?;
+        }
+        writeln!(out)?;
+
+        writeln!(out, "## Indicadores\n")?;
+        writeln!(out, "| Indicador | Valor |")?;
+        writeln!(out, "|---|---|")?;
+        writeln!(
+            out,
+            "| Transmitancia térmica global, K [W/m²K] | {:.2} |",
+            ind.K_data.K
+        )?;
+        writeln!(
+            out,
+            "| Control solar, q_sol;jul [kWh/m²·mes] | {:.2} |",
+            ind.q_soljul_data.q_soljul
+        )?;
+        writeln!(
+            out,
+            "| Permeabilidad al aire, n50 [1/h] | {:.2} |",
+            ind.n50_data.n50
+        )?;
+        writeln!(
+            out,
+            "| Permeabilidad al aire de referencia, n50_ref [1/h] | {:.2} |",
+            ind.n50_data.n50_ref
+        )?;
+        writeln!(out)?;
+
+        writeln!(out, "## Avisos\n")?;
+        let warnings = self.validate();
+        if warnings.is_empty() {
+            writeln!(out, "Sin avisos.")?;
+        } else {
+            for warning in &warnings {
+                writeln!(out, "- **{}**: {}", warning.level, warning.msg)?;
+            }
+        }
+
+        Ok(())
+    }
+}
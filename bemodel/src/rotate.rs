@@ -0,0 +1,62 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Giro del modelo en planta, para el estudio de distintas orientaciones del edificio
+
+use nalgebra::{Rotation3, Translation3};
+
+use super::{utils::normalize, Model, Point3, Vector3};
+
+impl Model {
+    /// Gira el edificio en planta (muros, huecos y sombras) alrededor de su centroide, en grados
+    ///
+    /// Los ángulos positivos giran en sentido antihorario, visto desde arriba, de modo que con 90º
+    /// las fachadas sur pasan a estar orientadas al este, siguiendo el criterio de azimut del modelo.
+    /// El centroide es el centro de los opacos ponderado por su superficie y se mantiene en el giro,
+    /// por lo que girar un ángulo y luego el opuesto devuelve el modelo original.
+    /// Los huecos, definidos en coordenadas de su opaco, giran con este.
+    /// Los opacos y sombras sin posición definida solo cambian su orientación.
+    pub fn rotate(&mut self, degrees: f32) {
+        let transform = self.centroid().map(|center| {
+            Translation3::from(center.coords)
+                * Rotation3::new(Vector3::z() * degrees.to_radians())
+                * Translation3::from(-center.coords)
+        });
+        for geometry in self
+            .walls
+            .iter_mut()
+            .map(|w| &mut w.geometry)
+            .chain(self.shades.iter_mut().map(|s| &mut s.geometry))
+        {
+            geometry.azimuth = normalize(geometry.azimuth + degrees, -180.0, 180.0);
+            if let (Some(position), Some(transform)) = (&mut geometry.position, &transform) {
+                *position = transform * *position;
+            }
+        }
+    }
+
+    /// Centroide de los opacos del modelo, ponderado por su superficie, en coordenadas globales
+    ///
+    /// Devuelve None si no hay opacos con definición geométrica completa
+    fn centroid(&self) -> Option<Point3> {
+        let (sum, area) = self
+            .walls
+            .iter()
+            .filter_map(|w| {
+                let to_global = w.geometry.to_global_coords_matrix()?;
+                let n = w.geometry.polygon.len();
+                if n == 0 {
+                    return None;
+                }
+                let sum = w.geometry.polygon.iter().fold(Vector3::zeros(), |acc, p| {
+                    acc + (to_global * Point3::new(p.x, p.y, 0.0)).coords
+                });
+                Some((sum / n as f32, w.area()))
+            })
+            .fold((Vector3::zeros(), 0.0), |(sum, area), (c, a)| {
+                (sum + c * a, area + a)
+            });
+        (area > 0.0).then(|| Point3::from(sum / area))
+    }
+}
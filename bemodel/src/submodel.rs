@@ -0,0 +1,184 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Extracción de submodelos con un subconjunto de los espacios del modelo
+
+use std::collections::HashSet;
+
+use log::warn;
+
+use super::{
+    point,
+    purge::{
+        purge_unused_frames, purge_unused_glasses, purge_unused_loads, purge_unused_materials,
+        purge_unused_schedules, purge_unused_thermostats, purge_unused_wallcons,
+        purge_unused_wincons,
+    },
+    utils::normalize,
+    BoundaryType, Model, ThermalZone, Uuid, WallGeom,
+};
+
+impl Model {
+    /// Devuelve un nuevo modelo con los espacios indicados y los elementos que les corresponden
+    ///
+    /// Se conservan los opacos de esos espacios y sus huecos, las construcciones, cargas, consignas
+    /// y horarios que usan y las plantas y zonas térmicas a las que pertenecen. Las sombras se mantienen todas.
+    /// Los opacos que lindaban con espacios excluidos pasan a ser adiabáticos. Cuando el opaco pertenecía
+    /// a un espacio excluido se asigna al espacio conservado, invirtiendo su geometría, y se descartan sus huecos.
+    /// Se conservan los puentes térmicos asociados a los espacios indicados y se descartan los que
+    /// pertenecen a otros espacios o no están asociados a ningún espacio, emitiendo un aviso en este caso.
+    /// Los modelos importados de HULC no asocian los puentes térmicos a espacios, por lo que sus submodelos
+    /// no tienen puentes térmicos y su transmitancia global K queda infravalorada.
+    pub fn submodel(&self, space_ids: &[Uuid]) -> Model {
+        let kept: HashSet<_> = self
+            .spaces
+            .iter()
+            .map(|s| s.id)
+            .filter(|id| space_ids.contains(id))
+            .collect();
+
+        let mut walls = Vec::new();
+        let mut wall_ids = HashSet::new();
+        for wall in &self.walls {
+            let next_kept = wall.next_to.map(|id| kept.contains(&id));
+            let wall = if kept.contains(&wall.space) {
+                let mut wall = wall.clone();
+                if next_kept == Some(false) {
+                    wall.bounds = BoundaryType::ADIABATIC;
+                    wall.next_to = None;
+                }
+                wall_ids.insert(wall.id);
+                wall
+            } else if next_kept == Some(true) {
+                let mut wall = wall.clone();
+                wall.space = wall.next_to.unwrap();
+                wall.bounds = BoundaryType::ADIABATIC;
+                wall.next_to = None;
+                wall.geometry = reversed_geometry(&wall.geometry);
+                wall
+            } else {
+                continue;
+            };
+            walls.push(wall);
+        }
+
+        let windows: Vec<_> = self
+            .windows
+            .iter()
+            .filter(|w| wall_ids.contains(&w.wall))
+            .cloned()
+            .collect();
+        let window_ids: HashSet<_> = windows.iter().map(|w| w.id).collect();
+
+        let spaces: Vec<_> = self
+            .spaces
+            .iter()
+            .filter(|s| kept.contains(&s.id))
+            .cloned()
+            .collect();
+        let floor_ids: HashSet<_> = spaces.iter().filter_map(|s| s.floor).collect();
+
+        let mut overrides = self.overrides.clone();
+        overrides.walls.retain(|id, _| wall_ids.contains(id));
+        overrides.windows.retain(|id, _| window_ids.contains(id));
+
+        let wall_names: HashSet<_> = walls.iter().map(|w| w.name.as_str()).collect();
+        let extra = self.extra.as_ref().map(|extra| {
+            extra
+                .iter()
+                .filter(|e| wall_names.contains(e.name.as_str()))
+                .cloned()
+                .collect()
+        });
+
+        let tbs_without_space = self
+            .thermal_bridges
+            .iter()
+            .filter(|tb| tb.space.is_none())
+            .count();
+        if tbs_without_space > 0 {
+            warn!(
+                "Se descartan {} puentes térmicos sin espacio asociado al extraer el submodelo",
+                tbs_without_space
+            );
+        }
+
+        let mut model = Model {
+            meta: self.meta.clone(),
+            floors: self
+                .floors
+                .iter()
+                .filter(|f| floor_ids.contains(&f.id))
+                .cloned()
+                .collect(),
+            spaces,
+            zones: self
+                .zones
+                .iter()
+                .filter(|z| z.spaces.iter().any(|id| kept.contains(id)))
+                .map(|z| ThermalZone {
+                    spaces: z
+                        .spaces
+                        .iter()
+                        .copied()
+                        .filter(|id| kept.contains(id))
+                        .collect(),
+                    ..z.clone()
+                })
+                .collect(),
+            walls,
+            windows,
+            thermal_bridges: self
+                .thermal_bridges
+                .iter()
+                .filter(|tb| tb.space.is_some_and(|id| kept.contains(&id)))
+                .cloned()
+                .collect(),
+            shades: self.shades.clone(),
+            cons: self.cons.clone(),
+            schedules: self.schedules.clone(),
+            loads: self.loads.clone(),
+            thermostats: self.thermostats.clone(),
+            radiant_surfaces: self
+                .radiant_surfaces
+                .iter()
+                .filter(|r| wall_ids.contains(&r.wall))
+                .cloned()
+                .collect(),
+            overrides,
+            extra,
+        };
+
+        // Construcción
+        purge_unused_wallcons(&mut model);
+        purge_unused_wincons(&mut model);
+        purge_unused_materials(&mut model);
+        purge_unused_glasses(&mut model);
+        purge_unused_frames(&mut model);
+        // Uso
+        purge_unused_loads(&mut model);
+        purge_unused_thermostats(&mut model);
+        purge_unused_schedules(&mut model);
+
+        model
+    }
+}
+
+/// Geometría de la misma superficie vista desde el otro lado, con la normal invertida
+///
+/// Se gira 180º la orientación, se usa la inclinación suplementaria y se refleja el polígono
+/// respecto al eje Y local (invirtiendo el orden de los vértices para mantener el sentido de giro)
+fn reversed_geometry(geometry: &WallGeom) -> WallGeom {
+    WallGeom {
+        tilt: 180.0 - geometry.tilt,
+        azimuth: normalize(geometry.azimuth + 180.0, -180.0, 180.0),
+        position: geometry.position,
+        polygon: geometry
+            .polygon
+            .iter()
+            .rev()
+            .map(|p| point![-p.x, p.y])
+            .collect(),
+    }
+}
@@ -49,9 +49,9 @@ impl Default for BoundaryType {
 /// Posiciones de los cerramientos según su inclinación
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Tilt {
-    /// Suelo (inclinación < 60º)
+    /// Suelo (inclinación > 120º)
     BOTTOM,
-    /// Cubierta (inclinación > 120º)
+    /// Cubierta (inclinación <= 60º)
     TOP,
     /// Muro (inclinación entre 60 y 120º) (valor por defecto)
     SIDE,
@@ -93,7 +93,7 @@ impl From<f32> for Tilt {
 }
 
 /// Nombres para la orientación de un elemento, según los puntos cardinales y elemento horizontal
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Orientation {
     /// Norte
     N,
@@ -107,12 +107,18 @@ pub struct WallCons {
     pub layers: Vec<Layer>,
     /// Absortividad del elemento opaco (alpha) [0-1]
     pub absorptance: f32,
+    /// Emisividad de onda larga de la superficie exterior del elemento opaco (epsilon) [0-1]
+    /// Si no se define se usa el valor por defecto de 0.9
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emissivity: Option<f32>,
 }
 
 impl WallCons {
     /// Espesor total de una composición de capas [m]
+    ///
+    /// Las capas de espesor variable computan con su espesor medio
     pub fn thickness(&self) -> f32 {
-        fround3(self.layers.iter().map(|Layer { e, .. }| e).sum())
+        fround3(self.layers.iter().map(Layer::e_mean).sum())
     }
 }
 
@@ -123,6 +129,7 @@ impl Default for WallCons {
             name: "Construcción de opaco".to_string(),
             layers: Vec::default(),
             absorptance: 0.7,
+            emissivity: None,
         }
     }
 }
@@ -133,7 +140,19 @@ pub struct Layer {
     /// ID del material
     pub material: Uuid,
     /// Espesor, m
+    /// En capas de espesor variable es el espesor mínimo
     pub e: f32,
+    /// Espesor máximo de las capas de espesor variable (p.e. formación de pendientes o recrecidos), m
+    /// Se supone que el espesor varía linealmente entre el mínimo y el máximo sobre la superficie del elemento
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub e_max: Option<f32>,
+}
+
+impl Layer {
+    /// Espesor medio de la capa, m
+    pub fn e_mean(&self) -> f32 {
+        0.5 * (self.e + self.e_max.unwrap_or(self.e))
+    }
 }
 
 /// Definición de construcción de hueco o lucernario
@@ -150,15 +169,37 @@ pub struct WinCons {
     pub frame: Uuid,
     /// Fracción de marco [-]
     pub f_f: f32,
+    /// Ancho del marco [m]
+    /// Permite recalcular la fracción de marco a partir de la geometría del hueco
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame_width: Option<f32>,
     /// Porcentaje de U debido a intercalarios y cajón de persiana (%)
+    /// Si se define el cajón de persiana (shutter_box) solo debe recoger el efecto de los intercalarios
     /// TODO: skip if default
     pub delta_u: f32,
+    /// Cajón de persiana del hueco
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shutter_box: Option<RollerShutterBox>,
     /// Factor solar del hueco con la protección solar activada (g_gl;sh;wi) [-]
     /// Si no se define (valor None), se supone igual al factor solar sin la protección activada (g_gl;wi)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub g_glshwi: Option<f32>,
     /// Permeabilidad al aire a 100 Pa [m3/hm2]
     pub c_100: f32,
+    /// Permeabilidades al aire diferenciadas de la parte practicable y la fija del hueco
+    /// Si se define, sustituye a la permeabilidad c_100 del hueco
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub c_100_parts: Option<WinPermeabilityParts>,
+}
+
+impl WinCons {
+    /// Permeabilidad al aire efectiva a 100 Pa [m3/hm2]
+    ///
+    /// En los huecos con permeabilidades diferenciadas para la parte practicable y la fija
+    /// se pondera cada una por su fracción de superficie. En otro caso es la permeabilidad c_100.
+    pub fn c_100_effective(&self) -> f32 {
+        self.c_100_parts.map_or(self.c_100, |parts| parts.c_100())
+    }
 }
 
 impl Default for WinCons {
@@ -169,13 +210,57 @@ impl Default for WinCons {
             glass: Uuid::default(),
             frame: Uuid::default(),
             f_f: 0.20,
+            frame_width: None,
             delta_u: 0.0,
+            shutter_box: None,
             g_glshwi: None,
             c_100: 50.0,
+            c_100_parts: None,
         }
     }
 }
 
+/// Permeabilidades al aire de la parte practicable y la parte fija de un hueco
+///
+/// La infiltración de los huecos mixtos está dominada por la parte practicable
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct WinPermeabilityParts {
+    /// Fracción de la superficie del hueco que es practicable [0-1]
+    pub operable_fraction: f32,
+    /// Permeabilidad al aire a 100 Pa de la parte practicable [m3/hm2]
+    pub c_100_operable: f32,
+    /// Permeabilidad al aire a 100 Pa de la parte fija [m3/hm2]
+    pub c_100_fixed: f32,
+}
+
+impl WinPermeabilityParts {
+    /// Permeabilidad al aire a 100 Pa ponderada por superficie [m3/hm2]
+    pub fn c_100(&self) -> f32 {
+        let f_op = self.operable_fraction.clamp(0.0, 1.0);
+        f_op * self.c_100_operable + (1.0 - f_op) * self.c_100_fixed
+    }
+}
+
+/// Cajón de persiana de una construcción de hueco
+///
+/// Se considera incluido en la superficie del hueco
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct RollerShutterBox {
+    /// Transmitancia térmica del cajón [W/m²K]
+    pub u_value: f32,
+    /// Anchura del cajón [m]
+    pub width: f32,
+    /// Altura del cajón [m]
+    pub height: f32,
+}
+
+impl RollerShutterBox {
+    /// Superficie del cajón de persiana [m²]
+    pub fn area(&self) -> f32 {
+        self.width * self.height
+    }
+}
+
 /// Material de elemento opaco (muro, cubierta, suelo, partición)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Material {
@@ -216,6 +301,25 @@ pub enum MatProps {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         vapour_diff: Option<f32>,
     },
+    /// Propiedades de materiales con conductividad dependiente del contenido de humedad
+    /// λ(u) = λ_seco · exp(f_u · u), según UNE-EN ISO 10456
+    #[serde(rename = "moisture_dependent")]
+    MoistureDependent {
+        // Conductividad térmica del material seco, lambda_dry (W/mK)
+        dry_conductivity: f32,
+        // Coeficiente de conversión por humedad másica, f_u (adimensional, por kg/kg de humedad)
+        moisture_factor: f32,
+        // Contenido de humedad másico de referencia, u (kg/kg)
+        #[serde(default, skip_serializing_if = "crate::utils::is_default")]
+        moisture_content: f32,
+        // Densidad, rho (kg/m3)
+        density: f32,
+        // Calor específico, C_p (J/kg K) (valor por defecto 1000 J/kg·K)
+        specific_heat: f32,
+        // Factor de resistencia a la difusión del vapor, mu (-)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        vapour_diff: Option<f32>,
+    },
     /// Resistencia térmica (R)
     #[serde(rename = "resistance")]
     Resistance {
@@ -224,9 +328,47 @@ pub enum MatProps {
         // Factor de resistencia a la difusión del vapor, mu (-)
         #[serde(default, skip_serializing_if = "Option::is_none")]
         vapour_diff: Option<f32>,
+        /// Espesor declarado, m
+        /// Es un dato informativo (p.e. para dibujar la sección), que no interviene en el cálculo de U
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        thickness: Option<f32>,
     },
 }
 
+impl MatProps {
+    /// Conductividad térmica del material (W/mK) para un contenido de humedad másico (kg/kg)
+    ///
+    /// Si no se indica el contenido de humedad se usa el de referencia del material.
+    /// Los materiales definidos por su resistencia térmica no tienen conductividad (None)
+    pub fn conductivity(&self, moisture_content: Option<f32>) -> Option<f32> {
+        match *self {
+            MatProps::Detailed { conductivity, .. } => Some(conductivity),
+            MatProps::MoistureDependent {
+                dry_conductivity,
+                moisture_factor,
+                moisture_content: ref_moisture_content,
+                ..
+            } => Some(
+                dry_conductivity
+                    * (moisture_factor * moisture_content.unwrap_or(ref_moisture_content)).exp(),
+            ),
+            MatProps::Resistance { .. } => None,
+        }
+    }
+
+    /// Densidad del material (kg/m3)
+    ///
+    /// Los materiales definidos por su resistencia térmica no tienen densidad (None)
+    pub fn density(&self) -> Option<f32> {
+        match *self {
+            MatProps::Detailed { density, .. } | MatProps::MoistureDependent { density, .. } => {
+                Some(density)
+            }
+            MatProps::Resistance { .. } => None,
+        }
+    }
+}
+
 impl Default for MatProps {
     fn default() -> Self {
         // Caso por defecto (Fábrica 1/2' LP G > 80 del CEC)
@@ -251,6 +393,11 @@ pub struct Glass {
     pub u_value: f32,
     /// Factor solar del vidrio a incidencia normal
     pub g_gln: f32,
+    /// Curva de variación del factor solar con el ángulo de incidencia
+    /// Pares (ángulo de incidencia [º], g(θ) / g_gln [-]), ordenados por ángulo creciente
+    /// Si no se define (valor None), el factor solar no depende del ángulo de incidencia
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub g_angular: Option<Vec<(f32, f32)>>,
 }
 
 impl Default for Glass {
@@ -261,6 +408,7 @@ impl Default for Glass {
             name: "Vidrio sencillo 6mm (Vert)".to_string(),
             u_value: 5.7,
             g_gln: 0.83,
+            g_angular: None,
         }
     }
 }
@@ -72,3 +72,49 @@ impl HasSurface for Polygon {
         }
     }
 }
+
+/// Test 2D de punto en polígono usando el método de Heines
+/// http://erich.realtimerendering.com/ptinpoly/
+///
+/// Cuenta el número de cruces haciendo raycasting desde el punto para ver si está dentro (cruces impares) o fuera (cruces pares)
+/// Evita el cálculo de las intersecciones y la división por cero viendo los cambios de signo
+/// https://stackoverflow.com/questions/217578/how-can-i-determine-whether-a-2d-point-is-within-a-polygon/2922778#2922778
+/// ver https://docs.rs/geo/0.2.6/src/geo/.cargo/registry/src/github.com-1ecc6299db9ec823/geo-0.2.6/src/algorithm/contains.rs.html#9-33
+/// https://docs.rs/geo/0.18.0/geo/algorithm/contains/trait.Contains.html
+/// Ver algunos casos límite en https://stackoverflow.com/a/63436180
+/// Evita el cálculo del punto de intersección y una división localizando la condición de cruce
+///
+/// ```rust ignore
+///     use nalgebra::point;
+///     use bemodel::types::geometry::point_in_poly;
+///     let poly = vec![
+///         point![0.0, 0.0],
+///         point![9.11, 0.0],
+///         point![9.11, 3.0],
+///         point![0.0, 3.0],
+///     ];
+///     assert!(!point_in_poly(point![-9.81, -7.3], &poly));
+///     assert!(point_in_poly(point![2.0, 2.0], &poly));
+/// ```
+pub(crate) fn point_in_poly(pt: Point2, poly: &[Point2]) -> bool {
+    let x = pt.x;
+    let y = pt.y;
+    let mut inside = false;
+
+    // Empezamos con el segmento que une el punto final con el inicial
+    let mut v_j = poly[poly.len() - 1];
+    let mut y_0 = v_j.y >= y;
+    for &v_i in poly {
+        let y_1 = v_i.y >= y;
+        // primero se mira si el lado cruza la linea horizontal en pt.y
+        // y, si es así, comprobamos si se cruza también en x para detectar que se produe el cruce
+        if y_0 != y_1 && (((v_i.y - y) * (v_j.x - v_i.x) >= (v_i.x - x) * (v_j.y - v_i.y)) == y_1) {
+            inside = !inside;
+        }
+        // Avanzamos al siguiente segmento
+        y_0 = y_1;
+        v_j = v_i;
+    }
+
+    inside
+}
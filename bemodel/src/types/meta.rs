@@ -4,9 +4,12 @@
 
 //! Metadatos del modelo: Meta
 
+use climate::{Location, CTE_LATCANARIAS, CTE_LATPENINSULA};
 use serde::{Deserialize, Serialize};
 
-use crate::climatedata::ClimateZone;
+use crate::climatedata::{
+    cold_water_temperature, cold_water_temperatures, ClimateZone, CLIMATEMETADATA,
+};
 
 /// Metadatos del edificio
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +25,18 @@ pub struct Meta {
     pub num_dwellings: i32,
     /// Zona climática
     pub climate: ClimateZone,
+    /// Latitud del emplazamiento [º]
+    /// Si no se define se usa la latitud de referencia del CTE para la península o Canarias
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f32>,
+    /// Longitud del emplazamiento [º] (positiva al este)
+    /// Si no se define se usa la de la zona climática
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f32>,
+    /// Provincia del emplazamiento
+    /// Si se define, se usa para obtener la temperatura del agua fría de red
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub province: Option<String>,
     /// Ventilación global del edificio, para los espacios habitables de uso residencial, en l/s
     /// Las zonas no habitables y todas las zonas de uso terciario tienen definida su tasa
     /// de ventilación definida (en renh)
@@ -30,6 +45,13 @@ pub struct Meta {
     /// n50 medido mediante ensayo [renh]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub n50_test_ach: Option<f32>,
+    /// Infiltraciones adicionales a las de opacos y huecos (chimeneas, conductos, etc)
+    /// Se suman a la permeabilidad de la envolvente en el cálculo de n50 e infiltraciones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_leakage: Vec<AirLeakage>,
+    /// Demanda de agua caliente sanitaria (ACS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dhw: Option<DhwDemand>,
     /// Anchura o profundidad del aislamiento perimetral horizontal o vertical de la solera [m]
     /// En el caso de aislamiento vertical se debe introducir el doble de la dimensión física del aislamiento
     #[serde(default, skip_serializing_if = "is_default")]
@@ -39,6 +61,81 @@ pub struct Meta {
     pub rn_perim_insulation: f32,
 }
 
+impl Meta {
+    /// Latitud del emplazamiento [º]
+    ///
+    /// Es la latitud definida o, si no lo está, la de referencia del CTE según la zona climática
+    /// sea de Canarias o peninsular
+    pub fn site_latitude(&self) -> f32 {
+        self.latitude.unwrap_or(if self.climate.is_canarian() {
+            CTE_LATCANARIAS
+        } else {
+            CTE_LATPENINSULA
+        })
+    }
+
+    /// Localización del emplazamiento para el cálculo de la posición solar
+    ///
+    /// La longitud y el huso horario se toman de la zona climática si no se ha definido la longitud
+    pub fn site_location(&self) -> Location {
+        let (longitude, reflong) = CLIMATEMETADATA
+            .lock()
+            .unwrap()
+            .get(&self.climate)
+            .map_or((0.0, 0.0), |m| (m.longitude, m.reflong));
+        Location {
+            latitude: self.site_latitude(),
+            longitude: self.longitude.unwrap_or(longitude),
+            tz: (reflong / 15.0).round() as i32,
+        }
+    }
+
+    /// Temperaturas mensuales del agua fría de red en el emplazamiento [ºC]
+    ///
+    /// Son las de la capital de la provincia, si está definida y se reconoce, o las de la zona climática
+    pub fn cold_water_temperatures(&self) -> [f32; 12] {
+        self.province
+            .as_deref()
+            .and_then(cold_water_temperature)
+            .unwrap_or_else(|| cold_water_temperatures(&self.climate))
+    }
+}
+
+/// Elemento con infiltración de aire adicional a la de la envolvente (p.e. chimenea o conducto)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AirLeakage {
+    /// Nombre o descripción del elemento
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Caudal de aire infiltrado con una diferencia de presiones de 100 Pa [m³/h]
+    pub flow_100: f32,
+}
+
+/// Demanda de agua caliente sanitaria (ACS) del edificio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DhwDemand {
+    /// Consumo diario de ACS a la temperatura de referencia [l/día]
+    pub daily_volume: f32,
+    /// Temperatura de referencia del ACS [ºC]
+    pub t_ref: f32,
+    /// Pérdidas de acumulación, como fracción de la energía demandada en los puntos de consumo [-]
+    pub storage_losses: f32,
+    /// Pérdidas de distribución, como fracción de la energía demandada en los puntos de consumo [-]
+    pub distribution_losses: f32,
+}
+
+impl Default for DhwDemand {
+    fn default() -> Self {
+        Self {
+            daily_volume: 0.0,
+            t_ref: 60.0,
+            storage_losses: 0.0,
+            distribution_losses: 0.0,
+        }
+    }
+}
+
 /// Comprueba si el valor es igual al valor por defecto
 fn is_default<T: Default + PartialEq>(t: &T) -> bool {
     t == &Default::default()
@@ -52,8 +149,13 @@ impl Default for Meta {
             is_dwelling: true,
             num_dwellings: 1,
             climate: ClimateZone::D3,
+            latitude: None,
+            longitude: None,
+            province: None,
             global_ventilation_l_s: None,
             n50_test_ach: None,
+            extra_leakage: Vec::new(),
+            dhw: None,
             d_perim_insulation: 0.0,
             rn_perim_insulation: 0.0,
         }
@@ -18,6 +18,7 @@ mod meta;
 mod model;
 mod opaques;
 mod overrides;
+mod radiant;
 mod reporting;
 mod schedules;
 mod space;
@@ -30,18 +31,25 @@ mod window;
 use crate::utils::{fround2, uuid_from_str};
 
 pub use common::{BoundaryType, Orientation, Tilt, Uuid};
-pub use constructions::{ConsDb, Frame, Glass, Layer, MatProps, Material, WallCons, WinCons};
+pub use constructions::{
+    ConsDb, Frame, Glass, Layer, MatProps, Material, RollerShutterBox, WallCons, WinCons,
+    WinPermeabilityParts,
+};
+pub(crate) use geometry::point_in_poly;
 pub use geometry::{HasSurface, Point2, Point3, Polygon, Vector2, Vector3};
 pub use library::{ConsDbGroups, Library};
-pub use meta::Meta;
+pub use meta::{AirLeakage, DhwDemand, Meta};
 pub use model::{ExtraData, Model};
-pub use opaques::{Shade, Wall, WallGeom};
+pub use opaques::{
+    CavityVentilation, PerimeterInsulation, Shade, VentilatedCavity, Wall, WallGeom,
+};
 pub use overrides::{PropsOverrides, WallPropsOverrides, WinPropsOverrides};
+pub use radiant::RadiantSurface;
 pub use reporting::{Warning, WarningLevel};
 pub use schedules::{Schedule, ScheduleDay, ScheduleWeek, SchedulesDb};
-pub use space::{Space, SpaceType};
-pub use space_loads::SpaceLoads;
+pub use space::{Floor, Space, SpaceType, ThermalZone};
+pub use space_loads::{AirQuality, SpaceLoads};
 pub use thermostat::Thermostat;
 pub use systems::{AirFlow, ZoneSystem};
 pub use thermalbridge::{ThermalBridge, ThermalBridgeKind};
-pub use window::{WinGeom, Window};
+pub use window::{DoubleWindow, LouverOrientation, LouverShade, OpaqueDoor, WinGeom, Window};
@@ -6,13 +6,18 @@
 
 pub use nalgebra::{point, vector};
 
-use anyhow::Error;
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, format_err, Error};
+use log::warn;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    BoundaryType, ConsDb, Meta, PropsOverrides, SchedulesDb, Shade, Space, SpaceLoads,
-    Thermostat, SpaceType, ThermalBridge, Tilt, Uuid, Wall, Window,
+    BoundaryType, ConsDb, Floor, HasSurface, Meta, Point2, Point3, Polygon, PropsOverrides,
+    RadiantSurface, SchedulesDb, Shade, Space, SpaceLoads, SpaceType, ThermalBridge, ThermalZone,
+    Thermostat, Tilt, Uuid, Wall, WallCons, WallGeom, WinGeom, Window,
 };
+use crate::checks::is_empty_wallcons;
 
 // ---------- Estructura general de datos --------------
 
@@ -22,9 +27,15 @@ use super::{
 pub struct Model {
     /// Metadatos
     pub meta: Meta,
+    /// Plantas
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub floors: Vec<Floor>,
     /// Espacios
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub spaces: Vec<Space>,
+    /// Zonas térmicas
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<ThermalZone>,
     /// Opacos
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub walls: Vec<Wall>,
@@ -49,6 +60,9 @@ pub struct Model {
     /// Definición de consignas de los espacios
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub thermostats: Vec<Thermostat>,
+    /// Superficies radiantes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub radiant_surfaces: Vec<RadiantSurface>,
     /// Overrides de propiedades de elementos (opacos y huecos)
     #[serde(default, skip_serializing_if = "PropsOverrides::is_empty")]
     pub overrides: PropsOverrides,
@@ -61,11 +75,79 @@ impl Model {
     // ---------------- Conversión hacia y desde JSON
 
     /// Devuelve el modelo en formato JSON
+    ///
+    /// La salida es determinista, para facilitar el control de versiones de los modelos: las listas de elementos
+    /// mantienen el orden del modelo y los diccionarios (p.e. en overrides) son BTreeMap, ordenados por clave.
     pub fn as_json(&self) -> Result<String, Error> {
         let json = serde_json::to_string_pretty(&self)?;
         Ok(json)
     }
 
+    /// Devuelve el modelo en formato JSON, incluyendo los nombres de los elementos referenciados
+    ///
+    /// Junto a los ids, se añaden como campos auxiliares los nombres del espacio (`space_name`),
+    /// espacio adyacente (`next_to_name`) y construcción (`cons_name`) de los opacos,
+    /// del opaco (`wall_name`) y construcción (`cons_name`) de los huecos y de las cargas (`loads_name`)
+    /// y consignas (`thermostat_name`) de los espacios.
+    /// Estos campos facilitan la inspección y comparación de archivos y se ignoran al leer el modelo.
+    pub fn as_json_named(&self) -> Result<String, Error> {
+        let mut value = serde_json::to_value(self)?;
+        let space_name = |id| self.get_space(id).map(|s| s.name.as_str());
+
+        if let Some(walls) = value.get_mut("walls").and_then(|v| v.as_array_mut()) {
+            for (json, wall) in walls.iter_mut().zip(&self.walls) {
+                insert_names(
+                    json,
+                    &[
+                        ("space_name", space_name(wall.space)),
+                        ("next_to_name", wall.next_to.and_then(space_name)),
+                        (
+                            "cons_name",
+                            self.cons.get_wallcons(wall.cons).map(|c| c.name.as_str()),
+                        ),
+                    ],
+                );
+            }
+        }
+        if let Some(windows) = value.get_mut("windows").and_then(|v| v.as_array_mut()) {
+            for (json, win) in windows.iter_mut().zip(&self.windows) {
+                insert_names(
+                    json,
+                    &[
+                        (
+                            "wall_name",
+                            self.get_wall(win.wall).map(|w| w.name.as_str()),
+                        ),
+                        (
+                            "cons_name",
+                            self.cons.get_wincons(win.cons).map(|c| c.name.as_str()),
+                        ),
+                    ],
+                );
+            }
+        }
+        if let Some(spaces) = value.get_mut("spaces").and_then(|v| v.as_array_mut()) {
+            for (json, space) in spaces.iter_mut().zip(&self.spaces) {
+                let loads = space
+                    .loads
+                    .and_then(|id| self.loads.iter().find(|l| l.id == id));
+                let thermostat = space
+                    .thermostat
+                    .and_then(|id| self.thermostats.iter().find(|t| t.id == id));
+                insert_names(
+                    json,
+                    &[
+                        ("loads_name", loads.map(|l| l.name.as_str())),
+                        ("thermostat_name", thermostat.map(|t| t.name.as_str())),
+                    ],
+                );
+            }
+        }
+
+        let json = serde_json::to_string_pretty(&value)?;
+        Ok(json)
+    }
+
     /// Lee un modelo desde JSON
     pub fn from_json(data: &str) -> Result<Self, Error> {
         let model: Model = serde_json::from_str(data)?;
@@ -74,6 +156,16 @@ impl Model {
 
     // ---------------- Aceso e identificación de elementos
 
+    /// Localiza planta
+    pub fn get_floor(&self, id: Uuid) -> Option<&Floor> {
+        self.floors.iter().find(|f| f.id == id)
+    }
+
+    /// Localiza planta por nombre
+    pub fn get_floor_by_name<'a>(&'a self, name: &'a str) -> Option<&'a Floor> {
+        self.floors.iter().find(|f| f.name == name)
+    }
+
     /// Localiza espacio
     pub fn get_space(&self, id: Uuid) -> Option<&Space> {
         self.spaces.iter().find(|s| s.id == id)
@@ -84,6 +176,16 @@ impl Model {
         self.spaces.iter().find(|s| s.name == name)
     }
 
+    /// Localiza zona térmica
+    pub fn get_zone(&self, id: Uuid) -> Option<&ThermalZone> {
+        self.zones.iter().find(|z| z.id == id)
+    }
+
+    /// Localiza zona térmica por nombre
+    pub fn get_zone_by_name<'a>(&'a self, name: &'a str) -> Option<&'a ThermalZone> {
+        self.zones.iter().find(|z| z.name == name)
+    }
+
     /// Localiza opaco
     pub fn get_wall(&self, id: Uuid) -> Option<&Wall> {
         self.walls.iter().find(|w| w.id == id)
@@ -103,6 +205,338 @@ impl Model {
     pub fn get_window_by_name<'a>(&'a self, name: &'a str) -> Option<&'a Window> {
         self.windows.iter().find(|w| w.name == name)
     }
+
+    /// Opacos que pertenecen al espacio
+    ///
+    /// No incluye los opacos de otros espacios que lindan con este (next_to)
+    pub fn walls_of_space(&self, space_id: Uuid) -> impl Iterator<Item = &Wall> {
+        self.walls.iter().filter(move |w| w.space == space_id)
+    }
+
+    /// Huecos de los opacos que pertenecen al espacio
+    pub fn windows_of_space(&self, space_id: Uuid) -> impl Iterator<Item = &Window> {
+        self.windows.iter().filter(move |w| {
+            self.get_wall(w.wall)
+                .is_some_and(|wall| wall.space == space_id)
+        })
+    }
+
+    /// Huecos del opaco
+    pub fn windows_of_wall(&self, wall_id: Uuid) -> impl Iterator<Item = &Window> {
+        self.windows.iter().filter(move |w| w.wall == wall_id)
+    }
+
+    /// Espacios de la zona térmica
+    ///
+    /// Se omiten las referencias a espacios inexistentes
+    pub fn spaces_of_zone(&self, zone_id: Uuid) -> impl Iterator<Item = &Space> {
+        self.get_zone(zone_id)
+            .into_iter()
+            .flat_map(|z| z.spaces.iter().filter_map(|id| self.get_space(*id)))
+    }
+
+    /// Superficie útil de la zona térmica, teniendo en cuenta los multiplicadores de los espacios [m²]
+    pub fn zone_area(&self, zone_id: Uuid) -> f32 {
+        self.spaces_of_zone(zone_id)
+            .map(|s| s.area(&self.walls) * s.multiplier)
+            .sum()
+    }
+
+    /// Volumen neto de la zona térmica, teniendo en cuenta los multiplicadores de los espacios [m³]
+    pub fn zone_volume(&self, zone_id: Uuid) -> f32 {
+        self.spaces_of_zone(zone_id)
+            .map(|s| s.area(&self.walls) * s.height_net(&self.walls, &self.cons) * s.multiplier)
+            .sum()
+    }
+
+    // ---------------- Edición de elementos
+
+    /// Inserta un hueco en el opaco indicado y devuelve su id
+    ///
+    /// La posición (x, y) de la esquina inferior izquierda del hueco se da en coordenadas del plano
+    /// del opaco [m], y la posición en coordenadas globales se obtiene a partir de la orientación
+    /// e inclinación del opaco.
+    /// Falla si no existe el opaco o la construcción, si las dimensiones no son válidas
+    /// o si el hueco no cabe dentro del polígono del opaco.
+    pub fn add_window(
+        &mut self,
+        wall_id: Uuid,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        cons: Uuid,
+    ) -> Result<Uuid, Error> {
+        let wall = self
+            .get_wall(wall_id)
+            .ok_or_else(|| format_err!("No se encuentra el opaco {}", wall_id))?;
+        if self.cons.get_wincons(cons).is_none() {
+            bail!("No se encuentra la construcción de hueco {}", cons);
+        }
+        if width <= 0.0 || height <= 0.0 {
+            bail!(
+                "Dimensiones no válidas del hueco ({} x {}) en el opaco {}",
+                width,
+                height,
+                wall.name
+            );
+        }
+        let geometry = WinGeom {
+            position: Some(point![x, y]),
+            width,
+            height,
+            setback: 0.0,
+        };
+        match wall.geometry.contains_window(&geometry) {
+            Some(true) => (),
+            Some(false) => bail!(
+                "El hueco de {} x {} en la posición ({}, {}) no cabe en el opaco {}",
+                width,
+                height,
+                x,
+                y,
+                wall.name
+            ),
+            None => bail!(
+                "El opaco {} no tiene definición geométrica completa",
+                wall.name
+            ),
+        }
+
+        let num_windows = self.windows_of_wall(wall_id).count();
+        let window = Window {
+            name: format!("{}_V{}", wall.name, num_windows + 1),
+            cons,
+            wall: wall_id,
+            geometry,
+            ..Default::default()
+        };
+        let id = window.id;
+        self.windows.push(window);
+        Ok(id)
+    }
+
+    /// Añade un edificio colindante como sombras y devuelve los ids de las sombras creadas
+    ///
+    /// El edificio se define como un prisma con su huella en planta (polígono en coordenadas globales [m])
+    /// y su altura sobre el terreno [m]. Se crea una sombra por cada fachada y otra para la cubierta,
+    /// que se usan como elementos oclusores en el cálculo del factor de obstáculos remotos (Fshobst).
+    /// Falla si la huella tiene menos de 3 vértices o superficie nula o si la altura no es positiva.
+    pub fn add_surrounding_building(
+        &mut self,
+        footprint: Polygon,
+        height: f32,
+    ) -> Result<Vec<Uuid>, Error> {
+        if height <= 0.0 {
+            bail!("Altura no válida del edificio colindante ({})", height);
+        }
+        // Superficie con signo (positiva con vértices en sentido antihorario)
+        let signed_area = 0.5
+            * footprint
+                .iter()
+                .zip(footprint.iter().cycle().skip(1))
+                .map(|(p, q)| p.x * q.y - q.x * p.y)
+                .sum::<f32>();
+        if footprint.len() < 3 || signed_area.abs() < 0.01 {
+            bail!("Huella en planta no válida del edificio colindante");
+        }
+        // Recorremos la huella en sentido antihorario para que las fachadas miren al exterior
+        let mut footprint = footprint;
+        if signed_area < 0.0 {
+            footprint.reverse();
+        }
+
+        let num_buildings = self
+            .shades
+            .iter()
+            .filter(|s| s.name.starts_with("EDIFICIO_COLINDANTE_") && s.name.ends_with("_CUBIERTA"))
+            .count();
+        let prefix = format!("EDIFICIO_COLINDANTE_{}", num_buildings + 1);
+
+        let mut shades: Vec<_> = footprint
+            .iter()
+            .zip(footprint.iter().cycle().skip(1))
+            .enumerate()
+            .filter(|(_, (p, q))| (*q - *p).norm() > 0.001)
+            .map(|(i, (p, q))| {
+                // El eje x local de la fachada sigue el lado de la huella y el eje y es vertical
+                let side = q - p;
+                let length = side.norm();
+                Shade {
+                    name: format!("{}_F{}", prefix, i + 1),
+                    geometry: WallGeom {
+                        tilt: 90.0,
+                        azimuth: side.y.atan2(side.x).to_degrees(),
+                        position: Some(point![p.x, p.y, 0.0]),
+                        polygon: vec![
+                            point![0.0, 0.0],
+                            point![length, 0.0],
+                            point![length, height],
+                            point![0.0, height],
+                        ],
+                    },
+                    ..Default::default()
+                }
+            })
+            .collect();
+        shades.push(Shade {
+            name: format!("{}_CUBIERTA", prefix),
+            geometry: WallGeom {
+                tilt: 0.0,
+                azimuth: 0.0,
+                position: Some(point![0.0, 0.0, height]),
+                polygon: footprint,
+            },
+            ..Default::default()
+        });
+
+        let ids = shades.iter().map(|s| s.id).collect();
+        self.shades.extend(shades);
+        Ok(ids)
+    }
+
+    /// Corrige los opacos exteriores con la normal orientada hacia el interior de su espacio y devuelve cuántos se corrigieron
+    ///
+    /// Se considera que la normal exterior, definida por la inclinación y orientación del opaco, está invertida
+    /// cuando apunta hacia el centroide del espacio, calculado como media ponderada por superficie de los centros
+    /// de sus opacos. El criterio no depende del sentido de giro de los vértices, que no siempre es coherente en HULC.
+    /// La corrección reconstruye la geometría a partir de los vértices en coordenadas globales, recorridos en
+    /// sentido inverso, de modo que se invierten la inclinación y orientación sin cambiar la posición del opaco,
+    /// y recoloca sus huecos en la nueva geometría.
+    /// No se comprueban los opacos sin definición geométrica completa y en espacios muy
+    /// cóncavos el criterio del centroide puede no ser fiable.
+    pub fn fix_wall_normals(&mut self) -> usize {
+        // Centro de cada opaco en coordenadas globales y su superficie
+        let centers: HashMap<Uuid, (Point3, f32)> = self
+            .walls
+            .iter()
+            .filter_map(|w| {
+                let to_global = w.geometry.to_global_coords_matrix()?;
+                let n = w.geometry.polygon.len();
+                if n < 3 {
+                    return None;
+                }
+                let sum = w
+                    .geometry
+                    .polygon
+                    .iter()
+                    .fold(vector![0.0, 0.0, 0.0], |acc, p| {
+                        acc + (to_global * point![p.x, p.y, 0.0]).coords
+                    });
+                Some((w.id, (Point3::from(sum / n as f32), w.area())))
+            })
+            .collect();
+
+        let space_centroid = |space: Uuid| -> Option<Point3> {
+            let (sum, area) = self
+                .walls
+                .iter()
+                .filter(|w| w.space == space || w.next_to == Some(space))
+                .filter_map(|w| centers.get(&w.id))
+                .fold((vector![0.0, 0.0, 0.0], 0.0), |(sum, area), (c, a)| {
+                    (sum + c.coords * *a, area + a)
+                });
+            (area > 0.0).then(|| Point3::from(sum / area))
+        };
+
+        let inverted: Vec<(Uuid, WallGeom)> = self
+            .walls
+            .iter()
+            .filter(|w| w.bounds == BoundaryType::EXTERIOR)
+            .filter(|w| {
+                let (Some((center, _)), Some(centroid)) =
+                    (centers.get(&w.id), space_centroid(w.space))
+                else {
+                    return false;
+                };
+                w.geometry.outward_normal().dot(&(center - centroid)) < 0.0
+            })
+            .filter_map(|w| Some((w.id, flipped_geometry(&w.geometry)?)))
+            .collect();
+
+        for (wall_id, geometry) in &inverted {
+            let wall = self.walls.iter_mut().find(|w| w.id == *wall_id).unwrap();
+            warn!(
+                "Corregida la normal invertida del opaco {} ({})",
+                wall.id, wall.name
+            );
+            for win in self.windows.iter_mut().filter(|w| w.wall == *wall_id) {
+                if let Some(pos) = win.geometry.position {
+                    win.geometry.position =
+                        flipped_window_position(pos, &win.geometry, &wall.geometry, geometry);
+                }
+            }
+            wall.geometry = geometry.clone();
+        }
+        inverted.len()
+    }
+
+    /// Asigna la construcción indicada a los muros con construcción vacía y devuelve cuántos se modificaron
+    ///
+    /// Se consideran vacías la construcción por defecto "Ninguno" de los archivos BDL y las construcciones sin capas.
+    /// La construcción se añade al modelo si no existe en él otra con el mismo id.
+    pub fn assign_default_construction(&mut self, cons: WallCons) -> usize {
+        let empty_ids: Vec<Uuid> = self
+            .cons
+            .wallcons
+            .iter()
+            .filter(|c| c.id != cons.id && is_empty_wallcons(c))
+            .map(|c| c.id)
+            .collect();
+        let mut count = 0;
+        for wall in self
+            .walls
+            .iter_mut()
+            .filter(|w| empty_ids.contains(&w.cons))
+        {
+            warn!(
+                "Asignada la construcción {} al opaco {} ({}) con construcción vacía",
+                cons.name, wall.id, wall.name
+            );
+            wall.cons = cons.id;
+            count += 1;
+        }
+        if count > 0 && self.cons.get_wallcons(cons.id).is_none() {
+            self.cons.wallcons.push(cons);
+        }
+        count
+    }
+
+    /// Elimina los espacios sin ningún opaco asociado y devuelve cuántos se eliminaron
+    ///
+    /// Se consideran asociados los opacos del espacio y los de otros espacios que lo tienen como adyacente.
+    /// Los espacios con opacos pero sin superficie no se eliminan, ya que sus opacos pueden
+    /// delimitar otros espacios, y deben revisarse.
+    pub fn remove_empty_spaces(&mut self) -> usize {
+        let used: HashSet<Uuid> = self
+            .walls
+            .iter()
+            .flat_map(|w| std::iter::once(w.space).chain(w.next_to))
+            .collect();
+        let num_spaces = self.spaces.len();
+        self.spaces.retain(|s| {
+            let keep = used.contains(&s.id);
+            if !keep {
+                warn!("Eliminado el espacio {} ({}) sin opacos", s.id, s.name);
+            }
+            keep
+        });
+        for zone in &mut self.zones {
+            zone.spaces.retain(|id| used.contains(id));
+        }
+        num_spaces - self.spaces.len()
+    }
+}
+
+/// Añade al objeto JSON los nombres de elementos referenciados que estén definidos
+fn insert_names(value: &mut serde_json::Value, names: &[(&str, Option<&str>)]) {
+    if let Some(map) = value.as_object_mut() {
+        for (key, name) in names {
+            if let Some(name) = name {
+                map.insert(key.to_string(), (*name).into());
+            }
+        }
+    }
 }
 
 /// Datos adicionales para comprobación de muros
@@ -127,3 +561,52 @@ pub struct ExtraData {
     // U calculada con UNE-EN ISO 13789
     pub computed_u: f32,
 }
+
+/// Geometría de opaco con la normal exterior invertida y los mismos vértices en coordenadas globales
+///
+/// Los vértices se ordenan de modo que el primer lado coincida, en sentido inverso, con el de la geometría original,
+/// para mantener alineados con el polígono los huecos del opaco.
+fn flipped_geometry(geometry: &WallGeom) -> Option<WallGeom> {
+    let to_global = geometry.to_global_coords_matrix()?;
+    let mut vertices: Vec<Point3> = geometry
+        .polygon
+        .iter()
+        .map(|p| to_global * point![p.x, p.y, 0.0])
+        .collect();
+    // Si el polígono gira en sentido antihorario, su normal es la exterior y se invierte el recorrido
+    if geometry.polygon.normal().z > 0.0 {
+        vertices.rotate_left(1);
+        vertices.reverse();
+    }
+    WallGeom::from_global_vertices(&vertices)
+}
+
+/// Posición de un hueco en la geometría invertida de su opaco, conservando su posición en coordenadas globales
+fn flipped_window_position(
+    pos: Point2,
+    wingeom: &WinGeom,
+    old: &WallGeom,
+    new: &WallGeom,
+) -> Option<Point2> {
+    let (old_to_global, old_to_poly) = (
+        old.to_global_coords_matrix()?,
+        old.to_polygon_coords_matrix()?,
+    );
+    let (new_to_local, new_to_poly) = (
+        new.to_global_coords_matrix()?.inverse(),
+        new.to_polygon_coords_matrix()?.inverse(),
+    );
+    let (w, h) = (wingeom.width, wingeom.height);
+    let corners: Vec<Point2> = [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)]
+        .iter()
+        .map(|(dx, dy)| {
+            let p = old_to_poly * point![pos.x + dx, pos.y + dy];
+            let global = old_to_global * point![p.x, p.y, 0.0];
+            new_to_poly * (new_to_local * global).xy()
+        })
+        .collect();
+    Some(point![
+        corners.iter().map(|p| p.x).fold(f32::INFINITY, f32::min),
+        corners.iter().map(|p| p.y).fold(f32::INFINITY, f32::min)
+    ])
+}
@@ -5,13 +5,13 @@
 //! Elementos opacos: Wall, Shade y sus objetos asociados, Geometry
 
 use nalgebra::{
-    IsometryMatrix2, IsometryMatrix3, Rotation2, Rotation3, Translation2, Translation3,
+    point, IsometryMatrix2, IsometryMatrix3, Rotation2, Rotation3, Translation2, Translation3,
 };
 use serde::{Deserialize, Serialize};
 
 use super::{
-    fround2, BoundaryType, HasSurface, Orientation, Point3, Polygon, Tilt, Uuid, Vector2, Vector3,
-    Window,
+    fround2, point_in_poly, BoundaryType, HasSurface, Orientation, Point3, Polygon, Tilt, Uuid,
+    Vector2, Vector3, WinGeom, Window,
 };
 
 // Elementos -----------------------------------------------
@@ -39,6 +39,13 @@ pub struct Wall {
     pub next_to: Option<Uuid>,
     /// Geometría del elemento opaco
     pub geometry: WallGeom,
+    /// Cámara de aire ventilada exterior del elemento opaco (p.e. cubierta ventilada)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ventilated: Option<VentilatedCavity>,
+    /// Aislamiento perimetral de la solera en contacto con el terreno
+    /// Si no se define se usan los valores generales del edificio (Meta)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perim_insulation: Option<PerimeterInsulation>,
 }
 
 impl Default for Wall {
@@ -51,6 +58,8 @@ impl Default for Wall {
             space: Uuid::default(),
             next_to: None,
             geometry: WallGeom::default(),
+            ventilated: None,
+            perim_insulation: None,
         }
     }
 }
@@ -73,6 +82,47 @@ impl Wall {
         fround2(wall_gross_area - windows_area)
     }
 
+    /// Superficie de huecos del cerramiento, descontando los solapes entre huecos (m²)
+    ///
+    /// Las zonas en las que se solapan varios huecos se cuentan una sola vez.
+    /// Los huecos sin definición geométrica completa se suman sin descontar solapes.
+    pub fn glazed_area_net(&self, windows: &[Window]) -> f32 {
+        let (positioned, unpositioned): (Vec<_>, Vec<_>) = windows
+            .iter()
+            .filter(|w| w.wall == self.id)
+            .partition(|w| w.geometry.position.is_some());
+
+        // Rectángulos de los huecos (x_min, y_min, x_max, y_max)
+        let rects: Vec<_> = positioned
+            .iter()
+            .filter_map(|w| {
+                let g = &w.geometry;
+                g.position
+                    .map(|p| (p.x, p.y, p.x + g.width, p.y + g.height))
+            })
+            .collect();
+
+        // Superficie de la unión de rectángulos mediante una retícula con los bordes de los huecos
+        let mut xs: Vec<f32> = rects.iter().flat_map(|r| [r.0, r.2]).collect();
+        let mut ys: Vec<f32> = rects.iter().flat_map(|r| [r.1, r.3]).collect();
+        xs.sort_by(|a, b| a.total_cmp(b));
+        ys.sort_by(|a, b| a.total_cmp(b));
+        let mut union_area = 0.0;
+        for x in xs.windows(2) {
+            for y in ys.windows(2) {
+                let (cx, cy) = (0.5 * (x[0] + x[1]), 0.5 * (y[0] + y[1]));
+                if rects
+                    .iter()
+                    .any(|r| r.0 <= cx && cx <= r.2 && r.1 <= cy && cy <= r.3)
+                {
+                    union_area += (x[1] - x[0]) * (y[1] - y[0]);
+                }
+            }
+        }
+
+        union_area + unpositioned.iter().map(|w| w.area()).sum::<f32>()
+    }
+
     /// Perímetro del opaco, m
     #[inline]
     pub fn perimeter(&self) -> f32 {
@@ -83,6 +133,60 @@ impl Wall {
     pub fn windows<'a>(&'a self, windows: &'a [Window]) -> impl Iterator<Item = &'a Window> {
         windows.iter().filter(move |w| w.wall == self.id)
     }
+
+    /// Factor de reducción de la ganancia solar del opaco por la cámara ventilada [-]
+    /// Vale 1.0 si el opaco no tiene cámara ventilada
+    pub fn solar_gain_factor(&self) -> f32 {
+        self.ventilated
+            .as_ref()
+            .map_or(1.0, VentilatedCavity::solar_gain_factor)
+    }
+}
+
+/// Cámara de aire ventilada situada en la cara exterior de un opaco
+///
+/// Parte del calor absorbido por la hoja exterior se disipa por la ventilación de la cámara
+/// y no llega a la hoja interior
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VentilatedCavity {
+    /// Espesor (altura) de la cámara de aire [m]
+    pub thickness: f32,
+    /// Grado de ventilación de la cámara
+    pub ventilation: CavityVentilation,
+}
+
+impl VentilatedCavity {
+    /// Factor de reducción de la ganancia solar del opaco [-]
+    ///
+    /// Aproximación simplificada: la reducción máxima es de un 20% en cámaras ligeramente ventiladas
+    /// y de un 50% en cámaras muy ventiladas, y se alcanza con espesores de cámara de 10cm o más.
+    /// Para espesores menores la reducción disminuye linealmente hasta la mitad de la máxima (0cm).
+    pub fn solar_gain_factor(&self) -> f32 {
+        let max_reduction = match self.ventilation {
+            CavityVentilation::SLIGHTLY => 0.2,
+            CavityVentilation::WELL => 0.5,
+        };
+        let thickness_factor = 0.5 + 5.0 * self.thickness.clamp(0.0, 0.1);
+        1.0 - max_reduction * thickness_factor
+    }
+}
+
+/// Grado de ventilación de una cámara de aire, según UNE-EN ISO 6946
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CavityVentilation {
+    /// Cámara ligeramente ventilada (aberturas entre 500 y 1500 mm² por m de longitud o m² de superficie)
+    SLIGHTLY,
+    /// Cámara muy ventilada (aberturas mayores de 1500 mm² por m de longitud o m² de superficie)
+    WELL,
+}
+
+/// Aislamiento perimetral de una solera en contacto con el terreno (UNE-EN ISO 13370:2010 Anexo B)
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerimeterInsulation {
+    /// Ancho de la banda de aislamiento perimetral horizontal (o doble de su profundidad, si es vertical) [m]
+    pub d: f32,
+    /// Resistencia térmica del aislamiento perimetral [m²K/W]
+    pub rn: f32,
 }
 
 /// Convierte de opaco a enum Tilt
@@ -112,6 +216,11 @@ pub struct Shade {
     pub name: String,
     /// Geometría del elemento opaco
     pub geometry: WallGeom,
+    /// Horario anual de transmisividad de la sombra a la radiación solar [0, 1]
+    /// Permite modelizar sombras estacionales, como la vegetación de hoja caduca.
+    /// Si no se define, la sombra es opaca
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Uuid>,
 }
 
 impl Default for Shade {
@@ -120,6 +229,7 @@ impl Default for Shade {
             id: Uuid::new_v4(),
             name: "Sombra".to_string(),
             geometry: WallGeom::default(),
+            schedule: None,
         }
     }
 }
@@ -180,6 +290,54 @@ impl WallGeom {
         Some(trans * zrot * xrot)
     }
 
+    /// Vector unitario normal exterior, en coordenadas globales, según la inclinación y orientación de la geometría
+    ///
+    /// A diferencia de la normal de la geometría, no depende del sentido de giro de los vértices del polígono
+    pub fn outward_normal(&self) -> Vector3 {
+        let zrot = Rotation3::new(Vector3::z() * self.azimuth.to_radians());
+        let xrot = Rotation3::new(Vector3::x() * self.tilt.to_radians());
+        zrot * xrot * Vector3::z()
+    }
+
+    /// Geometría de un polígono plano a partir de sus vértices en coordenadas globales
+    ///
+    /// La normal de la geometría es la de los vértices recorridos en sentido antihorario
+    /// y la posición es la del primer vértice.
+    /// Devuelve None si hay menos de 3 vértices o la superficie es nula
+    pub(crate) fn from_global_vertices(vertices: &[Point3]) -> Option<WallGeom> {
+        if vertices.len() < 3 {
+            return None;
+        }
+        // Normal por el método de Newell
+        let n = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .fold(Vector3::zeros(), |n, (a, b)| {
+                n + Vector3::new(
+                    (a.y - b.y) * (a.z + b.z),
+                    (a.z - b.z) * (a.x + b.x),
+                    (a.x - b.x) * (a.y + b.y),
+                )
+            })
+            .try_normalize(1e-9)?;
+        // n = (sin β · sin γ, -sin β · cos γ, cos β)
+        let tilt = n.z.clamp(-1.0, 1.0).acos().to_degrees();
+        let azimuth = if n.xy().norm() < 1e-6 {
+            0.0
+        } else {
+            n.x.atan2(-n.y).to_degrees()
+        };
+        let mut geometry = WallGeom {
+            tilt,
+            azimuth,
+            position: Some(vertices[0]),
+            polygon: vec![],
+        };
+        let to_local = geometry.to_global_coords_matrix()?.inverse();
+        geometry.polygon = vertices.iter().map(|v| (to_local * v).xy()).collect();
+        Some(geometry)
+    }
+
     /// Matriz de transformación de coordenadas locales de la geometría a coordenadas de polígono interno 2D
     /// Se gira el eje X en la dirección del polígono de opaco p1 - p0 y se traslada a p0 el origen
     pub fn to_polygon_coords_matrix(&self) -> Option<IsometryMatrix2<f32>> {
@@ -194,6 +352,23 @@ impl WallGeom {
 
         Some(trans * rot)
     }
+
+    /// Comprueba si el rectángulo de un hueco queda dentro del polígono del opaco
+    ///
+    /// Las esquinas del hueco se comprueban con una tolerancia de 1mm.
+    /// Devuelve None si el hueco o el opaco no tienen definición geométrica completa
+    pub fn contains_window(&self, wingeom: &WinGeom) -> Option<bool> {
+        const TOL: f32 = 0.001;
+        let pos = wingeom.position?;
+        let to_poly = self.to_polygon_coords_matrix()?;
+        let (x0, y0) = (pos.x + TOL, pos.y + TOL);
+        let (x1, y1) = (pos.x + wingeom.width - TOL, pos.y + wingeom.height - TOL);
+        Some(
+            [(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+                .iter()
+                .all(|&(x, y)| point_in_poly(to_poly * point![x, y], &self.polygon)),
+        )
+    }
 }
 
 impl HasSurface for WallGeom {
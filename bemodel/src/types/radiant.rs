@@ -0,0 +1,30 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Superficies radiantes (suelos y techos radiantes)
+
+use serde::{Deserialize, Serialize};
+
+use super::Uuid;
+
+/// Superficie radiante, que emite una potencia controlada desde la superficie interior de un opaco
+///
+/// Modelo simplificado de suelos y techos radiantes, en el que la potencia emitida se aporta al espacio
+/// del opaco sin control por temperatura. Con potencia negativa representa una superficie refrescante.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RadiantSurface {
+    /// ID de la superficie radiante (en formato UUID)
+    pub id: Uuid,
+    /// Nombre de la superficie radiante
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Opaco desde el que se emite la potencia
+    pub wall: Uuid,
+    /// Potencia máxima emitida por superficie neta del opaco [W/m²]
+    pub max_power: f32,
+    /// Horario anual de funcionamiento, como fracción de la potencia máxima
+    /// Si no se define se emite la potencia máxima en todas las horas
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Uuid>,
+}
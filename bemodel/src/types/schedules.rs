@@ -71,15 +71,43 @@ impl SchedulesDb {
     }
 
     /// Lista de valores anuales para el horario anual con uuid dado
+    ///
+    /// No se tienen en cuenta los días festivos (ver SchedulesDb::expand)
     pub fn year_values(&self, id: Uuid) -> Vec<f32> {
-        self.get_year_as_day_sch(id)
-            .iter()
-            .flat_map(|day_id| {
-                self.get_day(*day_id)
-                    .map(|ds| ds.values.clone())
-                    .unwrap_or_default()
-            })
-            .collect::<Vec<_>>()
+        self.expand(id, &[])
+    }
+
+    /// Lista de valores anuales para el horario anual con uuid dado, aplicando un calendario de festivos
+    ///
+    /// El calendario es la lista de días del año (de 1 a 365) festivos. En ellos se usa el horario diario
+    /// de festivos del horario semanal vigente o, si no está definido, el del domingo.
+    /// El año empieza en lunes (se toma de referencia el 2001)
+    pub fn expand(&self, id: Uuid, holidays: &[u32]) -> Vec<f32> {
+        let mut values = Vec::with_capacity(8760);
+        let year = match self.get_year(id) {
+            Some(year) => year,
+            None => return values,
+        };
+        let mut day_idx = 0;
+        for (week_id, count) in &year.values {
+            let week = self.get_week(*week_id);
+            let week_days = week.map(ScheduleWeek::to_day_sch).unwrap_or_default();
+            let holiday = week
+                .and_then(|w| w.holiday)
+                .or_else(|| week_days.last().copied());
+            for _ in 0..*count {
+                let day_id = if holidays.contains(&(day_idx as u32 + 1)) {
+                    holiday
+                } else {
+                    week_days.get(day_idx % 7).copied()
+                };
+                if let Some(day) = day_id.and_then(|id| self.get_day(id)) {
+                    values.extend(&day.values);
+                }
+                day_idx += 1;
+            }
+        }
+        values
     }
 
     /// Lista de condiciones de valor distinto de (casi) cero para el horario anual con uuid dado
@@ -123,6 +151,10 @@ pub struct ScheduleWeek {
     /// Secuencia de tuplas de UUID de horarios diarios y repeticiones del día
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub values: Vec<(Uuid, u32)>,
+    /// UUID del horario diario de los días festivos
+    /// Si no se define se usa el horario del domingo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holiday: Option<Uuid>,
 }
 
 impl ScheduleWeek {
@@ -40,6 +40,9 @@ pub struct Space {
     /// Cota del espacio respecto al suelo (m)
     #[serde(default, skip_serializing_if = "is_default")]
     pub z: f32,
+    /// Planta a la que pertenece el espacio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floor: Option<Uuid>,
     /// Perfil de uso del espacio
     pub loads: Option<Uuid>,
     /// Condiciones operacionales del espacio
@@ -104,6 +107,7 @@ impl Default for Space {
             height: 3.0,
             n_v: None,
             z: 0.0,
+            floor: None,
             thermostat: None,
             loads: None,
             illuminance: None,
@@ -111,6 +115,39 @@ impl Default for Space {
     }
 }
 
+/// Planta (agrupación de espacios)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Floor {
+    /// ID de la planta (en formato UUID)
+    pub id: Uuid,
+    /// Nombre de la planta
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Cota de la planta respecto al suelo (m)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub z: f32,
+    /// Altura bruta (suelo a suelo) de la planta (m)
+    pub height: f32,
+    /// Multiplicador de la planta
+    #[serde(default = "default_1", skip_serializing_if = "multiplier_is_1")]
+    pub multiplier: f32,
+}
+
+/// Zona térmica (agrupación de espacios servidos por los mismos sistemas)
+///
+/// Los espacios que no pertenecen a ninguna zona se consideran zonas térmicas independientes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThermalZone {
+    /// ID de la zona (en formato UUID)
+    pub id: Uuid,
+    /// Nombre de la zona
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Espacios de la zona
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spaces: Vec<Uuid>,
+}
+
 /// Tipo de espacio según su nivel de acondicionamiento
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SpaceType {
@@ -4,9 +4,36 @@
 
 //! Condiciones de carga de los espacios (ocupación, equipos, iluminación, infiltraciones)
 
+use std::fmt::Display;
+
 use serde::{Deserialize, Serialize};
 
-use super::Uuid;
+use super::{Model, Schedule, ScheduleDay, ScheduleWeek, SchedulesDb, SpaceType, Uuid};
+
+/// Nombre de las cargas de uso residencial privado normalizadas del CTE
+const CTE_RESIDENTIAL_LOADS_NAME: &str = "CTE_RESIDENCIAL";
+
+/// Perfil de ocupación de uso residencial privado, días laborables (fracción de la carga máxima)
+#[rustfmt::skip]
+const CTE_RES_PEOPLE_WORKDAY: [f32; 24] = [
+    1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0,
+    0.25, 0.25, 0.25, 0.25, 0.25, 0.25, 0.25, 0.25,
+    0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5,
+    1.0,
+];
+/// Perfil de ocupación de uso residencial privado, sábados y festivos (fracción de la carga máxima)
+const CTE_RES_PEOPLE_HOLIDAY: [f32; 24] = [1.0; 24];
+/// Perfil de iluminación y equipos de uso residencial privado, todos los días (fracción de la carga máxima)
+#[rustfmt::skip]
+const CTE_RES_LIGHTING: [f32; 24] = [
+    0.2, 0.2, 0.2, 0.2, 0.2, 0.2, 0.2,
+    0.6,
+    0.2, 0.2, 0.2, 0.2, 0.2, 0.2, 0.2,
+    0.4, 0.4, 0.4,
+    0.6,
+    1.0, 1.0, 1.0, 1.0,
+    0.2,
+];
 
 /// Cargas de los espacios por ocupación, equipos e iluminación
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -39,4 +66,176 @@ pub struct SpaceLoads {
     /// Si no se define se suponen todos los valores == 0
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub lighting_schedule: Option<Uuid>,
+    /// Categoría de calidad del aire interior (RITE), para el cálculo del caudal de ventilación de diseño
+    /// Si no se define se usa la tasa de ventilación del espacio o la global del edificio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub air_quality: Option<AirQuality>,
+}
+
+impl SpaceLoads {
+    /// Cargas de uso residencial privado con los perfiles normalizados del CTE DB-HE (Anejo D)
+    ///
+    /// Carga máxima de ocupación sensible de 2.15 W/m² y latente de 1.36 W/m², con ocupación completa
+    /// por la noche, del 25% de 8 a 15h y del 50% de 16 a 23h en días laborables, y completa en sábados y festivos.
+    /// Carga máxima de iluminación y de equipos de 2.20 W/m², con el mismo perfil todos los días.
+    /// Los horarios se añaden a la base de datos de horarios, salvo que ya existan otros con el mismo nombre.
+    pub fn cte_residential_default(schedules: &mut SchedulesDb) -> Self {
+        let people_schedule = add_cte_schedule(
+            schedules,
+            "CTE_RES_OCUPACION",
+            CTE_RES_PEOPLE_WORKDAY,
+            CTE_RES_PEOPLE_HOLIDAY,
+        );
+        let lighting_schedule = add_cte_schedule(
+            schedules,
+            "CTE_RES_ILUMINACION_EQUIPOS",
+            CTE_RES_LIGHTING,
+            CTE_RES_LIGHTING,
+        );
+        Self {
+            id: Uuid::new_v4(),
+            name: CTE_RESIDENTIAL_LOADS_NAME.to_string(),
+            area_per_person: 0.0,
+            people_schedule: Some(people_schedule),
+            people_sensible: 2.15,
+            people_latent: 1.36,
+            equipment: 2.20,
+            equipment_schedule: Some(lighting_schedule),
+            lighting: 2.20,
+            lighting_schedule: Some(lighting_schedule),
+            air_quality: None,
+        }
+    }
+}
+
+/// Añade un horario anual con perfiles diarios de días laborables y de sábados y festivos
+///
+/// Si ya existe un horario anual con ese nombre se devuelve su id, sin añadir nada
+fn add_cte_schedule(
+    schedules: &mut SchedulesDb,
+    name: &str,
+    workday: [f32; 24],
+    holiday: [f32; 24],
+) -> Uuid {
+    if let Some(sch) = schedules.year.iter().find(|s| s.name == name) {
+        return sch.id;
+    }
+    let workday_id = Uuid::new_v4();
+    let holiday_id = Uuid::new_v4();
+    let week_id = Uuid::new_v4();
+    let year_id = Uuid::new_v4();
+    schedules.day.push(ScheduleDay {
+        id: workday_id,
+        name: format!("{}_LABORABLE", name),
+        values: workday.to_vec(),
+    });
+    schedules.day.push(ScheduleDay {
+        id: holiday_id,
+        name: format!("{}_FESTIVO", name),
+        values: holiday.to_vec(),
+    });
+    // La semana empieza en lunes, como el año de referencia (2001)
+    schedules.week.push(ScheduleWeek {
+        id: week_id,
+        name: name.to_string(),
+        values: vec![(workday_id, 5), (holiday_id, 2)],
+        holiday: Some(holiday_id),
+    });
+    schedules.year.push(Schedule {
+        id: year_id,
+        name: name.to_string(),
+        values: vec![(week_id, 365)],
+    });
+    year_id
+}
+
+impl Model {
+    /// Asigna las cargas normalizadas de uso residencial privado del CTE a los espacios sin cargas propias
+    ///
+    /// Solo se aplica a los espacios habitables de la envolvente térmica de los edificios residenciales
+    /// (Meta.is_dwelling). Devuelve el número de espacios a los que se han asignado cargas.
+    pub fn fill_cte_residential_loads(&mut self) -> usize {
+        if !self.meta.is_dwelling {
+            return 0;
+        }
+        let pending: Vec<usize> = self
+            .spaces
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.loads.is_none() && s.inside_tenv && s.kind != SpaceType::UNINHABITED)
+            .map(|(i, _)| i)
+            .collect();
+        if pending.is_empty() {
+            return 0;
+        }
+        let loads_id = match self
+            .loads
+            .iter()
+            .find(|l| l.name == CTE_RESIDENTIAL_LOADS_NAME)
+        {
+            Some(loads) => loads.id,
+            None => {
+                let loads = SpaceLoads::cte_residential_default(&mut self.schedules);
+                let id = loads.id;
+                self.loads.push(loads);
+                id
+            }
+        };
+        for i in &pending {
+            self.spaces[*i].loads = Some(loads_id);
+        }
+        pending.len()
+    }
+}
+
+/// Categoría de calidad del aire interior según el uso del edificio (RITE, IT 1.1.4.2.2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AirQuality {
+    /// Aire de óptima calidad (hospitales, clínicas, laboratorios, guarderías)
+    IDA1,
+    /// Aire de buena calidad (oficinas, residencias, salas de lectura, museos, aulas, piscinas)
+    IDA2,
+    /// Aire de calidad media (comercios, cines, teatros, hoteles, restaurantes, gimnasios)
+    IDA3,
+    /// Aire de calidad baja
+    IDA4,
+}
+
+impl AirQuality {
+    /// Caudal mínimo de aire exterior por persona, l/(s·pers)
+    ///
+    /// RITE, IT 1.1.4.2.3, tabla 1.4.2.1
+    pub fn flow_per_person(&self) -> f32 {
+        match self {
+            AirQuality::IDA1 => 20.0,
+            AirQuality::IDA2 => 12.5,
+            AirQuality::IDA3 => 8.0,
+            AirQuality::IDA4 => 5.0,
+        }
+    }
+
+    /// Caudal mínimo de aire exterior por unidad de superficie, l/(s·m²)
+    ///
+    /// Para locales no dedicados a ocupación humana permanente. RITE, IT 1.1.4.2.3, tabla 1.4.2.4.
+    /// No es aplicable a la categoría IDA 1
+    pub fn flow_per_area(&self) -> Option<f32> {
+        match self {
+            AirQuality::IDA1 => None,
+            AirQuality::IDA2 => Some(0.83),
+            AirQuality::IDA3 => Some(0.55),
+            AirQuality::IDA4 => Some(0.28),
+        }
+    }
+}
+
+impl Display for AirQuality {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match *self {
+            AirQuality::IDA1 => "IDA1",
+            AirQuality::IDA2 => "IDA2",
+            AirQuality::IDA3 => "IDA3",
+            AirQuality::IDA4 => "IDA4",
+        };
+        write!(f, "{}", printable)
+    }
 }
@@ -29,6 +29,10 @@ pub struct ThermalBridge {
     /// Transmitancia térmica lineal del puente térmico (W/mK)
     #[serde(default, skip_serializing_if = "is_default")]
     pub psi: f32,
+    /// Espacio al que pertenece el puente térmico, en su caso
+    /// Si se define, la longitud se multiplica por el multiplicador del espacio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub space: Option<Uuid>,
 }
 
 impl Default for ThermalBridge {
@@ -39,6 +43,7 @@ impl Default for ThermalBridge {
             kind: ThermalBridgeKind::default(),
             l: 1.0,
             psi: 0.0,
+            space: None,
         }
     }
 }
@@ -7,7 +7,10 @@
 use log::info;
 use serde::{Deserialize, Serialize};
 
-use super::{point, uuid_from_str, vector, HasSurface, Point2, Shade, Uuid, Vector3, WallGeom};
+use super::{
+    point, uuid_from_str, vector, HasSurface, Point2, Point3, Shade, Uuid, Vector3, WallGeom,
+    WinCons,
+};
 
 // Elementos -----------------------------------------------
 
@@ -25,9 +28,38 @@ pub struct Window {
     pub wall: Uuid,
     /// Geometría de hueco
     pub geometry: WinGeom,
+    /// Protección solar de lamas asociada al hueco
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub louvers: Option<LouverShade>,
+    /// Resistencia térmica adicional de la persiana o contraventana cerrada, ΔR [m²K/W]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub night_insulation: Option<f32>,
+    /// Horario anual de cierre de la persiana o contraventana (fracción de cierre) [-]
+    /// Si no se define, se cierra en las horas sin sol
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub night_insulation_schedule: Option<Uuid>,
+    /// Segunda hoja de la doble ventana, por el interior del hueco
+    /// La geometría del hueco es la de la hoja exterior (construcción cons)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub double_window: Option<DoubleWindow>,
+    /// Permeabilidad al aire a 100 Pa de la puerta [m³/hm²]
+    /// Si se define, el hueco es una puerta y se usa esta permeabilidad en lugar de la de su construcción
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub door_c_100: Option<f32>,
+    /// Puerta opaca (ciega), sin superficie acristalada
+    /// Si se define, el hueco es una puerta opaca con transmitancia y absortividad propias,
+    /// que no computa como superficie acristalada (WWR, q_sol;jul) pero sí en las pérdidas por transmisión (K)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opaque_door: Option<OpaqueDoor>,
 }
 
 impl Window {
+    /// ¿Es el hueco una puerta opaca (sin superficie acristalada)?
+    #[inline]
+    pub fn is_opaque_door(&self) -> bool {
+        self.opaque_door.is_some()
+    }
+
     /// Superficie del hueco, m²
     #[inline]
     pub fn area(&self) -> f32 {
@@ -40,7 +72,50 @@ impl Window {
         self.geometry.perimeter()
     }
 
+    /// Fracción de marco del hueco [-]
+    ///
+    /// Se usa la fracción de marco de la construcción salvo que se pida su recálculo (recompute)
+    /// y la construcción defina el ancho del marco, en cuyo caso se obtiene a partir de la geometría del hueco
+    pub fn f_f(&self, cons: &WinCons, recompute: bool) -> f32 {
+        match cons.frame_width {
+            Some(frame_width) if recompute => self.geometry.frame_fraction(frame_width),
+            _ => cons.f_f,
+        }
+    }
+
+    /// Posición del hueco (esquina inferior izquierda) en coordenadas globales
+    ///
+    /// Devuelve None si el hueco o el opaco no tienen definición geométrica completa
+    pub fn position_3d(&self, wallgeom: &WallGeom) -> Option<Point3> {
+        let pos = self.geometry.position?;
+        let to_global = wallgeom.to_global_coords_matrix()?;
+        let to_poly = wallgeom.to_polygon_coords_matrix()?;
+        let p = to_poly * pos;
+        Some(to_global * point![p.x, p.y, 0.0])
+    }
+
+    /// Geometría del hueco como polígono plano en coordenadas globales, en el plano del opaco
+    ///
+    /// Devuelve None si el hueco o el opaco no tienen definición geométrica completa
+    pub(crate) fn global_geometry(&self, wallgeom: &WallGeom) -> Option<WallGeom> {
+        let pos = self.geometry.position?;
+        let to_global = wallgeom.to_global_coords_matrix()?;
+        let to_poly = wallgeom.to_polygon_coords_matrix()?;
+        let (w, h) = (self.geometry.width, self.geometry.height);
+        let vertices: Vec<_> = [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)]
+            .iter()
+            .map(|(dx, dy)| {
+                let p = to_poly * point![pos.x + dx, pos.y + dy];
+                to_global * point![p.x, p.y, 0.0]
+            })
+            .collect();
+        WallGeom::from_global_vertices(&vertices)
+    }
+
     /// Crea elementos de sombra correpondientes el perímetro de retranqueo del hueco
+    ///
+    /// Las sombras van del plano exterior del opaco al plano del hueco, por lo que sirven
+    /// para opacos con cualquier inclinación (p.e. lucernarios en cubiertas)
     pub(crate) fn shades_for_setback(&self, wallgeom: &WallGeom) -> Option<Vec<(Uuid, Shade)>> {
         let wing = &self.geometry;
         // Si no hay retranqueo no se genera geometría
@@ -53,87 +128,161 @@ impl Window {
             _ => return Some(vec![]),
         };
 
-        let wall2world = if let Some(matrix) = wallgeom.to_global_coords_matrix() {
-            matrix
-        } else {
-            info!("El elemento opaco debe tener definición geométrica completa");
-            return None;
+        let (x0, y0) = (wpos.x, wpos.y);
+        let (x1, y1) = (wpos.x + wing.width, wpos.y + wing.height);
+        let s = -wing.setback;
+        let setback_shade = |side: &str, vertices: [Point3; 4]| {
+            shade_from_wall_coords(
+                wallgeom,
+                uuid_from_str(&format!("{}-{}_setback", self.id, side)),
+                format!("{}_{}_setback", self.name, side),
+                &vertices,
+            )
+            .map(|shade| (self.id, shade))
         };
 
-        let overhang = Shade {
-            id: uuid_from_str(&format!("{}-top_setback", self.id)),
-            name: format!("{}_top_setback", self.name),
-            geometry: WallGeom {
-                // inclinación: con 90º es perpendicular al hueco
-                tilt: wallgeom.tilt + 90.0,
-                azimuth: wallgeom.azimuth,
-                position: Some(wall2world * point![wpos.x, wpos.y + wing.height, 0.0]),
-                polygon: vec![
-                    point![0.0, 0.0],
-                    point![0.0, -wing.setback],
-                    point![wing.width, -wing.setback],
-                    point![wing.width, 0.0],
+        Some(vec![
+            setback_shade(
+                "top",
+                [
+                    point![x0, y1, 0.0],
+                    point![x0, y1, s],
+                    point![x1, y1, s],
+                    point![x1, y1, 0.0],
                 ],
-            },
-        };
-
-        let left_fin = Shade {
-            id: uuid_from_str(&format!("{}-left_setback", self.id)),
-            name: format!("{}_left_setback", self.name),
-            geometry: WallGeom {
-                tilt: wallgeom.tilt,
-                azimuth: wallgeom.azimuth + 90.0,
-                position: Some(wall2world * point![wpos.x, wpos.y + wing.height, 0.0]),
-                polygon: vec![
-                    point![0.0, 0.0],
-                    point![0.0, -wing.height],
-                    point![wing.setback, -wing.height],
-                    point![wing.setback, 0.0],
+            )?,
+            setback_shade(
+                "left",
+                [
+                    point![x0, y1, 0.0],
+                    point![x0, y0, 0.0],
+                    point![x0, y0, s],
+                    point![x0, y1, s],
                 ],
-            },
-        };
-
-        let right_fin = Shade {
-            id: uuid_from_str(&format!("{}-right_setback", self.id)),
-            name: format!("{}_right_setback", self.name),
-            geometry: WallGeom {
-                tilt: wallgeom.tilt,
-                azimuth: wallgeom.azimuth - 90.0,
-                position: Some(wall2world * point![wpos.x + wing.width, wpos.y + wing.height, 0.0]),
-                polygon: vec![
-                    point![0.0, 0.0],
-                    point![-wing.setback, 0.0],
-                    point![-wing.setback, -wing.height],
-                    point![0.0, -wing.height],
+            )?,
+            setback_shade(
+                "right",
+                [
+                    point![x1, y1, 0.0],
+                    point![x1, y1, s],
+                    point![x1, y0, s],
+                    point![x1, y0, 0.0],
                 ],
-            },
+            )?,
+            setback_shade(
+                "sill",
+                [
+                    point![x0, y0, 0.0],
+                    point![x1, y0, 0.0],
+                    point![x1, y0, s],
+                    point![x0, y0, s],
+                ],
+            )?,
+        ])
+    }
+
+    /// Crea elementos de sombra correspondientes a las lamas de la protección solar del hueco
+    ///
+    /// Las lamas se sitúan en el plano exterior del opaco y cubren todo el hueco, empezando
+    /// por su borde superior (lamas horizontales) o por su borde izquierdo (lamas verticales).
+    pub(crate) fn shades_for_louvers(&self, wallgeom: &WallGeom) -> Option<Vec<(Uuid, Shade)>> {
+        let louvers = match &self.louvers {
+            Some(louvers) if louvers.slat_width > 0.0 && louvers.slat_spacing > 0.0 => louvers,
+            // Sin lamas (o con lamas sin dimensiones válidas) no se genera geometría
+            _ => return Some(vec![]),
+        };
+        let wing = &self.geometry;
+        let wpos = match wing.position {
+            Some(pos) => pos,
+            // Si no hay definición geométrica completa no se calcula geometría
+            _ => return Some(vec![]),
         };
 
-        let sill = Shade {
-            id: uuid_from_str(&format!("{}-sill_setback", self.id)),
-            name: format!("{}_sill_setback", self.name),
-            geometry: WallGeom {
-                tilt: wallgeom.tilt - 90.0,
-                azimuth: wallgeom.azimuth,
-                position: Some(wall2world * point![wpos.x, wpos.y, 0.0]),
-                polygon: vec![
-                    point![0.0, 0.0],
-                    point![wing.width, 0.0],
-                    point![wing.width, wing.setback],
-                    point![0.0, wing.setback],
-                ],
-            },
+        let LouverShade {
+            orientation,
+            slat_width,
+            slat_spacing,
+            slat_angle,
+        } = *louvers;
+
+        let length = match orientation {
+            LouverOrientation::HORIZONTAL => wing.height,
+            LouverOrientation::VERTICAL => wing.width,
         };
+        let num_slats = (length / slat_spacing).ceil().max(1.0) as usize;
+        let (sin_a, cos_a) = slat_angle.to_radians().sin_cos();
 
-        Some(vec![
-            (self.id, overhang),
-            (self.id, left_fin),
-            (self.id, right_fin),
-            (self.id, sill),
-        ])
+        (0..num_slats)
+            .map(|i| {
+                let offset = i as f32 * slat_spacing;
+                let vertices = match orientation {
+                    // Con ángulo 0º la lama es perpendicular al opaco y con ángulo positivo
+                    // el borde exterior desciende
+                    LouverOrientation::HORIZONTAL => {
+                        let y = wpos.y + wing.height - offset;
+                        let d = slat_width * vector![0.0, -sin_a, cos_a];
+                        let p0 = point![wpos.x, y, 0.0];
+                        let p1 = point![wpos.x + wing.width, y, 0.0];
+                        [p0, p1, p1 + d, p0 + d]
+                    }
+                    // Con ángulo 0º la lama es perpendicular al opaco y con ángulo positivo
+                    // el borde exterior se desplaza hacia la derecha del hueco
+                    LouverOrientation::VERTICAL => {
+                        let x = wpos.x + offset;
+                        let d = slat_width * vector![sin_a, 0.0, cos_a];
+                        let p0 = point![x, wpos.y + wing.height, 0.0];
+                        let p1 = point![x, wpos.y, 0.0];
+                        [p0, p0 + d, p1 + d, p1]
+                    }
+                };
+                shade_from_wall_coords(
+                    wallgeom,
+                    uuid_from_str(&format!("{}-louver-{}", self.id, i)),
+                    format!("{}_louver_{}", self.name, i),
+                    &vertices,
+                )
+                .map(|shade| (self.id, shade))
+            })
+            .collect()
     }
 }
 
+/// Crea una sombra a partir de sus vértices en coordenadas del opaco
+///
+/// Los vértices se dan en coordenadas del polígono del opaco (x, y), como la posición de sus huecos,
+/// y la coordenada z es la distancia al plano del opaco en la dirección de su normal exterior.
+/// Devuelve None si el opaco no tiene definición geométrica completa
+fn shade_from_wall_coords(
+    wallgeom: &WallGeom,
+    id: Uuid,
+    name: String,
+    vertices: &[Point3],
+) -> Option<Shade> {
+    let (wall2world, to_poly) = match (
+        wallgeom.to_global_coords_matrix(),
+        wallgeom.to_polygon_coords_matrix(),
+    ) {
+        (Some(wall2world), Some(to_poly)) => (wall2world, to_poly),
+        _ => {
+            info!("El elemento opaco debe tener definición geométrica completa");
+            return None;
+        }
+    };
+    let vertices: Vec<_> = vertices
+        .iter()
+        .map(|v| {
+            let p = to_poly * point![v.x, v.y];
+            wall2world * point![p.x, p.y, v.z]
+        })
+        .collect();
+    Some(Shade {
+        id,
+        name,
+        geometry: WallGeom::from_global_vertices(&vertices)?,
+        schedule: None,
+    })
+}
+
 impl Default for Window {
     fn default() -> Self {
         Window {
@@ -142,6 +291,12 @@ impl Default for Window {
             cons: Uuid::default(),
             wall: Uuid::default(),
             geometry: WinGeom::default(),
+            louvers: None,
+            night_insulation: None,
+            night_insulation_schedule: None,
+            double_window: None,
+            door_c_100: None,
+            opaque_door: None,
         }
     }
 }
@@ -172,6 +327,35 @@ impl Default for WinGeom {
     }
 }
 
+impl WinGeom {
+    /// Superficie de solape con otro hueco del mismo opaco, m²
+    ///
+    /// Si alguno de los huecos no tiene definición geométrica completa se considera que no hay solape
+    pub fn overlap_area(&self, other: &WinGeom) -> f32 {
+        match (self.position, other.position) {
+            (Some(p), Some(q)) => {
+                let dx = (p.x + self.width).min(q.x + other.width) - p.x.max(q.x);
+                let dy = (p.y + self.height).min(q.y + other.height) - p.y.max(q.y);
+                dx.max(0.0) * dy.max(0.0)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Fracción de marco [-] para un ancho de marco dado (m)
+    ///
+    /// Relación entre la superficie del marco, que recorre el perímetro del hueco, y la superficie del hueco
+    pub fn frame_fraction(&self, frame_width: f32) -> f32 {
+        let area = self.area();
+        if area < 0.001 {
+            return 0.0;
+        }
+        let glazing_area =
+            (self.width - 2.0 * frame_width).max(0.0) * (self.height - 2.0 * frame_width).max(0.0);
+        (area - glazing_area) / area
+    }
+}
+
 impl HasSurface for WinGeom {
     /// Vector unitario normal a la geometría
     fn normal(&self) -> Vector3 {
@@ -188,3 +372,51 @@ impl HasSurface for WinGeom {
         2.0 * (self.width + self.height)
     }
 }
+
+/// Puerta opaca (ciega)
+///
+/// Sus propiedades sustituyen a las de la construcción del hueco
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct OpaqueDoor {
+    /// Transmitancia térmica de la puerta, U [W/m²K]
+    pub u_value: f32,
+    /// Absortividad de la superficie exterior de la puerta, α [-]
+    pub absorptance: f32,
+}
+
+/// Hoja interior de un hueco con doble ventana
+///
+/// Las dos hojas están separadas por una cámara de aire, que puede estar ventilada
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct DoubleWindow {
+    /// Construcción de la hoja interior
+    pub cons: Uuid,
+    /// Resistencia térmica de la cámara entre hojas, R_s [m²K/W]
+    pub r_cavity: f32,
+}
+
+/// Protección solar de lamas del hueco
+///
+/// Las lamas se disponen por el exterior del hueco, en el plano exterior del opaco
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct LouverShade {
+    /// Orientación de las lamas (horizontales o verticales)
+    pub orientation: LouverOrientation,
+    /// Ancho de lama, m
+    pub slat_width: f32,
+    /// Separación entre ejes de lamas, m
+    pub slat_spacing: f32,
+    /// Ángulo de giro de las lamas respecto a la posición perpendicular al hueco, en grados
+    /// Con 90º las lamas quedan paralelas al hueco (cerradas)
+    #[serde(default)]
+    pub slat_angle: f32,
+}
+
+/// Orientación de las lamas de una protección solar
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LouverOrientation {
+    /// Lamas horizontales
+    HORIZONTAL,
+    /// Lamas verticales
+    VERTICAL,
+}
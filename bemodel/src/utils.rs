@@ -26,6 +26,26 @@ pub fn normalize(value: f32, start: f32, end: f32) -> f32 {
     (offset - (f32::floor(offset / width) * width)) + start
 }
 
+/// Convierte el azimuth desde el criterio BDL al criterio del modelo (UNE-EN ISO 52016-1) y normaliza a [-180, 180)
+///
+/// - BDL: ángulo respecto al norte (N=0, E=+90, W=-90)
+/// - UNE-EN ISO 52016-1: ángulo respecto al sur (S=0, E=+90, W=-90)
+///
+/// La orientación norte se devuelve como -180
+pub fn azimuth_bdl_to_model(azimuth: f32) -> f32 {
+    normalize(180.0 - azimuth, -180.0, 180.0)
+}
+
+/// Convierte el azimuth desde el criterio del modelo (UNE-EN ISO 52016-1) al criterio BDL y normaliza a [-180, 180)
+///
+/// - UNE-EN ISO 52016-1: ángulo respecto al sur (S=0, E=+90, W=-90)
+/// - BDL: ángulo respecto al norte (N=0, E=+90, W=-90)
+///
+/// La orientación sur se devuelve como -180
+pub fn azimuth_model_to_bdl(azimuth: f32) -> f32 {
+    normalize(180.0 - azimuth, -180.0, 180.0)
+}
+
 /// Calcula UUID a partir de hash MD5 del objeto
 ///
 /// Este no es un método muy robusto pero da valores estables para los mismos objetos
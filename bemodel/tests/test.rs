@@ -54,6 +54,7 @@ fn parse() {
         height: 2.7,
         n_v: None,
         z: 0.0,
+        floor: None,
         loads: Some(uuid::Uuid::parse_str("be9422f0-9693-6c17-d5ea-d3783d9c0b74").unwrap()),
         thermostat: Some(uuid::Uuid::parse_str("af9422f0-9693-6c17-d5ea-d3783d9c0b74").unwrap()),
         illuminance: Some(100.0),
@@ -162,6 +163,399 @@ fn model_json_e4h_medianeras() {
     assert_eq!(model.schedules.day.len(), 12);
 }
 
+#[test]
+fn model_json_named() {
+    init();
+
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+    let json = model.as_json_named().unwrap();
+
+    // Nombres de los elementos referenciados
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let wall = &model.walls[0];
+    let wall_json = &value["walls"][0];
+    assert_eq!(
+        wall_json["space_name"],
+        model.get_space(wall.space).unwrap().name.as_str()
+    );
+    assert_eq!(
+        wall_json["cons_name"],
+        model.cons.get_wallcons(wall.cons).unwrap().name.as_str()
+    );
+    let win = &model.windows[0];
+    let win_json = &value["windows"][0];
+    assert_eq!(
+        win_json["wall_name"],
+        model.get_wall(win.wall).unwrap().name.as_str()
+    );
+    assert_eq!(
+        win_json["cons_name"],
+        model.cons.get_wincons(win.cons).unwrap().name.as_str()
+    );
+    assert!(value["spaces"][0].get("loads_name").is_some());
+
+    // Los campos auxiliares se ignoran al leer el modelo
+    let model2 = Model::from_json(&json).unwrap();
+    assert_eq!(model.as_json().unwrap(), model2.as_json().unwrap());
+}
+
+#[test]
+fn model_json_deterministic() {
+    init();
+
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    // Diccionarios con varias claves
+    for win in &model.windows {
+        model.overrides.windows.entry(win.id).or_default().u_value = Some(1.5);
+    }
+
+    // La serialización se repite byte a byte, también tras leer de nuevo el modelo
+    let json = model.as_json().unwrap();
+    assert_eq!(json, model.as_json().unwrap());
+    assert_eq!(json, Model::from_json(&json).unwrap().as_json().unwrap());
+    assert_eq!(
+        model.as_json_named().unwrap(),
+        model.as_json_named().unwrap()
+    );
+
+    // Indicadores, con diccionarios por orientación
+    let ind = model.energy_indicators().as_json().unwrap();
+    assert_eq!(ind, model.energy_indicators().as_json().unwrap());
+}
+
+#[test]
+fn model_from_envolventecte_json() {
+    use bemodel::{convert::from_envolventecte_json, BoundaryType, Orientation};
+    init();
+
+    let strdata = include_str!("./data/caso_a_envolventecte.json");
+    let model = from_envolventecte_json(strdata).unwrap();
+
+    assert_eq!(model.spaces.len(), 6);
+    assert_eq!(model.walls.len(), 35);
+    assert_eq!(model.windows.len(), 10);
+    assert_eq!(model.thermal_bridges.len(), 10);
+    assert_eq!(model.cons.wallcons.len(), 6);
+    assert_eq!(model.cons.wincons.len(), 1);
+
+    // Superficies: los opacos del editor web tienen superficie neta y geometría
+    let space = model.get_space_by_name("P02_E01").unwrap();
+    assert_almost_eq!(space.area(&model.walls), 149.98, 0.01);
+    let wall = model.get_wall_by_name("P02_E01_PE001").unwrap();
+    let win = get_window_by_name(&model, "P02_E01_PE001_V");
+    assert_eq!(win.wall, wall.id);
+    assert_almost_eq!(win.area(), 2.0);
+    assert_almost_eq!(
+        wall.area_net(&model.windows),
+        wall.area() - win.area(),
+        0.01
+    );
+    assert!(wall.geometry.position.is_some());
+    assert!(win.geometry.position.is_some());
+
+    // Orientación: criterio geográfico en el editor web y UNE-EN ISO 52016-1 en el modelo
+    assert_eq!(Orientation::from(wall), Orientation::W);
+    let wall_s = model.get_wall_by_name("P02_E01_PE002").unwrap();
+    assert_almost_eq!(wall_s.geometry.azimuth, 0.0);
+    assert_eq!(Orientation::from(wall_s), Orientation::S);
+
+    // Referencias por nombre resueltas a ids
+    let suelo = model.get_wall_by_name("P01_E01_FTER001").unwrap();
+    assert_eq!(suelo.bounds, BoundaryType::GROUND);
+    assert_eq!(model.cons.get_wallcons(suelo.cons).unwrap().name, "Solera");
+    assert_eq!(model.cons.get_wincons(win.cons).unwrap().name, "PVC 2");
+    assert_eq!(model.overrides.windows[&win.id].f_shobst, Some(1.0));
+
+    // Mismos indicadores que el modelo original
+    let ind = model.energy_indicators();
+    assert_almost_eq!(ind.area_ref, 399.96, 0.01);
+    assert_almost_eq!(ind.K_data.K, 0.47, 0.01);
+    assert_almost_eq!(ind.q_soljul_data.q_soljul, 4.63, 0.01);
+
+    // Opacos y huecos sin geometría: se conserva su superficie
+    let mut data: serde_json::Value = serde_json::from_str(strdata).unwrap();
+    let wall_data = data["walls"]["P02_E01_PE001"].as_object_mut().unwrap();
+    wall_data.remove("polygon");
+    wall_data.remove("position");
+    let win_data = data["windows"]["P02_E01_PE001_V"].as_object_mut().unwrap();
+    for key in ["position", "width", "height", "setback"] {
+        win_data.remove(key);
+    }
+    let model_nogeom = from_envolventecte_json(&data.to_string()).unwrap();
+    let wall_nogeom = model_nogeom.get_wall_by_name("P02_E01_PE001").unwrap();
+    assert!(wall_nogeom.geometry.position.is_none());
+    assert_almost_eq!(wall_nogeom.area(), wall.area(), 0.01);
+    assert_almost_eq!(
+        get_window_by_name(&model_nogeom, "P02_E01_PE001_V").area(),
+        2.0
+    );
+
+    // Sin construcciones de huecos
+    let mut no_wincons = data.clone();
+    no_wincons.as_object_mut().unwrap().remove("windowcons");
+    assert!(from_envolventecte_json(&no_wincons.to_string()).is_err());
+
+    // Referencias a elementos inexistentes
+    data["windows"]["P02_E01_PE001_V"]["wall"] = "P02_E01_PE999".into();
+    assert!(from_envolventecte_json(&data.to_string()).is_err());
+}
+
+#[test]
+fn model_unconditioned_space_temperature() {
+    use bemodel::BoundaryType;
+
+    init();
+
+    // Garaje (no habitable) en planta baja en contacto con el terreno y bajo las viviendas
+    let strdata = include_str!("./data/caso_a.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let garage_id = model.get_space_by_name("P01_E02").unwrap().id;
+
+    let t_u = model.unconditioned_space_temperature(garage_id, 20.0, 5.0);
+    assert!(t_u > 5.0 && t_u < 20.0);
+    // El balance es lineal en las temperaturas: el factor b no depende de ellas
+    let b = (20.0 - t_u) / (20.0 - 5.0);
+    let t_u2 = model.unconditioned_space_temperature(garage_id, 25.0, -5.0);
+    assert_almost_eq!((25.0 - t_u2) / (25.0 - -5.0), b);
+    // Sin diferencia de temperaturas el espacio está a la misma temperatura
+    assert_almost_eq!(
+        model.unconditioned_space_temperature(garage_id, 20.0, 20.0),
+        20.0
+    );
+
+    // Mayor ventilación acerca la temperatura a la exterior
+    model
+        .spaces
+        .iter_mut()
+        .find(|s| s.id == garage_id)
+        .unwrap()
+        .n_v = Some(2.0);
+    assert!(model.unconditioned_space_temperature(garage_id, 20.0, 5.0) < t_u);
+
+    // Caso límite: espacio adiabático salvo hacia el exterior
+    for wall in model
+        .walls
+        .iter_mut()
+        .filter(|w| w.next_to == Some(garage_id))
+    {
+        wall.bounds = BoundaryType::ADIABATIC;
+        wall.next_to = None;
+    }
+    for wall in model.walls.iter_mut().filter(|w| w.space == garage_id) {
+        if wall.bounds == BoundaryType::INTERIOR {
+            wall.bounds = BoundaryType::ADIABATIC;
+            wall.next_to = None;
+        }
+    }
+    assert_almost_eq!(
+        model.unconditioned_space_temperature(garage_id, 20.0, 5.0),
+        5.0
+    );
+}
+
+#[test]
+fn model_seasonal_demand_d3() {
+    use bemodel::climatedata::ClimateZone;
+
+    init();
+
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+    let demand = model.seasonal_demand(&ClimateZone::D3);
+    let ind = model.energy_indicators();
+
+    // Coeficientes de transferencia coherentes con los indicadores
+    assert_almost_eq!(demand.h_tr, ind.K_data.summary.au, 0.1);
+    assert_almost_eq!(demand.h_ve, ind.H_ve, 0.1);
+
+    // Demandas anuales [kWh/m²·año]
+    assert_almost_eq!(demand.heating, 13.4, 0.1);
+    assert_almost_eq!(demand.cooling, 16.2, 0.1);
+    assert_almost_eq!(demand.heating_monthly.iter().sum::<f32>(), demand.heating);
+    assert_almost_eq!(demand.cooling_monthly.iter().sum::<f32>(), demand.cooling);
+    // Sin calefacción en verano ni refrigeración en invierno
+    assert_almost_eq!(demand.heating_monthly[6], 0.0);
+    assert_almost_eq!(demand.cooling_monthly[0], 0.0);
+
+    // Una zona más severa en invierno y más suave en verano aumenta la calefacción y reduce la refrigeración
+    let demand_e1 = model.seasonal_demand(&ClimateZone::E1);
+    assert!(demand_e1.heating > demand.heating);
+    assert!(demand_e1.cooling < demand.cooling);
+}
+
+#[test]
+fn model_passive_solar_fraction() {
+    use bemodel::climatedata::ClimateZone;
+
+    init();
+
+    // Hueco muy grande en la fachada sur
+    let strdata = include_str!("./data/cubo.json");
+    let mut model_s = Model::from_json(strdata).unwrap();
+    let win_id = get_window_by_name(&model_s, "P01_E01_PE001_V").id;
+    let win = model_s.windows.iter_mut().find(|w| w.id == win_id).unwrap();
+    win.geometry.width *= 2.0;
+    // El mismo edificio girado, con el hueco a norte
+    let mut model_n = model_s.clone();
+    model_n.rotate(180.0);
+
+    let f_s = model_s.passive_solar_fraction(&ClimateZone::D3);
+    let f_n = model_n.passive_solar_fraction(&ClimateZone::D3);
+    assert!(f_s > 0.0 && f_s < 1.0);
+    assert!(f_n > 0.0 && f_n < f_s);
+
+    // Sin huecos no hay aportes solares
+    let mut model_0 = model_s.clone();
+    model_0.windows.clear();
+    assert_almost_eq!(model_0.passive_solar_fraction(&ClimateZone::D3), 0.0);
+}
+
+#[test]
+fn model_thermal_mass_class() {
+    use bemodel::{energy::ThermalMassClass, MatProps};
+
+    init();
+
+    // Límites entre clases
+    use ThermalMassClass::*;
+    for (c_m, class) in [
+        (0.0, VERYLIGHT),
+        (94_999.0, VERYLIGHT),
+        (95_000.0, LIGHT),
+        (137_499.0, LIGHT),
+        (137_500.0, MEDIUM),
+        (212_499.0, MEDIUM),
+        (212_500.0, HEAVY),
+        (314_999.0, HEAVY),
+        (315_000.0, VERYHEAVY),
+        (1_000_000.0, VERYHEAVY),
+    ] {
+        assert_eq!(ThermalMassClass::from_c_m_per_area(c_m), class);
+    }
+    // Los valores por defecto de cada clase pertenecen a ella
+    for class in [VERYLIGHT, LIGHT, MEDIUM, HEAVY, VERYHEAVY] {
+        assert_eq!(
+            ThermalMassClass::from_c_m_per_area(class.c_m_per_area()),
+            class
+        );
+    }
+
+    // Sin construcciones con capacidad térmica se supone inercia media
+    assert_eq!(Model::default().thermal_mass_class(), MEDIUM);
+
+    // La inercia aumenta con la densidad de los materiales
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+    let with_density_factor = |factor: f32| {
+        let mut model = model.clone();
+        for mat in &mut model.cons.materials {
+            match &mut mat.properties {
+                MatProps::Detailed { density, .. }
+                | MatProps::MoistureDependent { density, .. } => *density *= factor,
+                MatProps::Resistance { .. } => (),
+            }
+        }
+        model.thermal_mass_class()
+    };
+    assert_eq!(with_density_factor(0.01), VERYLIGHT);
+    assert_eq!(with_density_factor(10.0), VERYHEAVY);
+}
+
+#[test]
+fn model_gain_utilisation_factor() {
+    use bemodel::{climatedata::ClimateZone, MatProps};
+
+    init();
+
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+
+    // Modelos con inercia alta y baja, escalando la densidad de los materiales
+    let with_density_factor = |factor: f32| {
+        let mut model = model.clone();
+        for mat in &mut model.cons.materials {
+            match &mut mat.properties {
+                MatProps::Detailed { density, .. }
+                | MatProps::MoistureDependent { density, .. } => *density *= factor,
+                MatProps::Resistance { .. } => (),
+            }
+        }
+        model
+    };
+    let heavy = with_density_factor(2.0);
+    let light = with_density_factor(0.1);
+    assert_almost_eq!(
+        heavy.internal_heat_capacity() / light.internal_heat_capacity(),
+        20.0,
+        0.01
+    );
+
+    let eta_heavy = heavy.gain_utilisation_factor(&ClimateZone::D3);
+    let eta_light = light.gain_utilisation_factor(&ClimateZone::D3);
+    // Los edificios con más inercia aprovechan mejor las ganancias
+    for (h, l) in eta_heavy.iter().zip(&eta_light) {
+        assert!((0.0..=1.0).contains(h) && (0.0..=1.0).contains(l));
+        assert!(h >= l);
+    }
+    // Con inercia alta se aprovechan casi todas las ganancias en invierno
+    assert!(eta_heavy[0] > 0.99);
+    assert!(eta_heavy[0] - eta_light[0] > 0.1);
+    assert!(eta_heavy[3] - eta_light[3] > 0.1);
+    // Sin pérdidas de calefacción en verano el factor es nulo
+    assert_almost_eq!(eta_heavy[6], 0.0);
+    assert!(
+        heavy.seasonal_demand(&ClimateZone::D3).heating
+            < light.seasonal_demand(&ClimateZone::D3).heating
+    );
+}
+
+#[test]
+fn model_slab_perimeter_insulation() {
+    use bemodel::PerimeterInsulation;
+
+    init();
+
+    let strdata = include_str!("./data/caso_a.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    // Aislamiento perimetral general: D = 1.0 m, R_n = 1.0 m²K/W
+    let u_slab = |model: &Model, name: &str| {
+        model
+            .get_wall_by_name(name)
+            .unwrap()
+            .u_value(model)
+            .unwrap()
+    };
+    let u1_global = u_slab(&model, "P01_E01_FTER001");
+    let u2_global = u_slab(&model, "P01_E02_TER002");
+
+    // Solera sin aislamiento perimetral y solera con más aislamiento que el general
+    let set_insulation = |model: &mut Model, name: &str, d: f32, rn: f32| {
+        let wall = model.walls.iter_mut().find(|w| w.name == name).unwrap();
+        wall.perim_insulation = Some(PerimeterInsulation { d, rn });
+    };
+    set_insulation(&mut model, "P01_E01_FTER001", 0.0, 0.0);
+    set_insulation(&mut model, "P01_E02_TER002", 2.0, 2.0);
+    let u1 = u_slab(&model, "P01_E01_FTER001");
+    let u2 = u_slab(&model, "P01_E02_TER002");
+    assert!(u1 > u1_global);
+    assert!(u2 < u2_global);
+
+    // Los valores de la solera prevalecen sobre los generales
+    model.meta.d_perim_insulation = 0.0;
+    model.meta.rn_perim_insulation = 0.0;
+    assert_almost_eq!(u_slab(&model, "P01_E01_FTER001"), u1);
+    assert_almost_eq!(u_slab(&model, "P01_E02_TER002"), u2);
+
+    // Sin aislamiento perimetral de solera se usan los valores generales
+    model
+        .walls
+        .iter_mut()
+        .for_each(|w| w.perim_insulation = None);
+    assert_almost_eq!(u_slab(&model, "P01_E01_FTER001"), u1);
+}
+
 #[test]
 fn model_json_ejemploviv_unif() {
     init();
@@ -224,6 +618,15 @@ fn model_json_ejemploviv_unif() {
     assert_almost_eq!(ind.area_ref, 102.33, 0.1);
     assert_almost_eq!(ind.compactness, 1.36, 0.01);
     assert_almost_eq!(ind.K_data.K, 0.62, 0.01);
+    // Desglose de H_tr por tipo de elemento
+    let h_tr = ind.K_data.h_tr;
+    assert_almost_eq!(
+        h_tr.walls + h_tr.roofs + h_tr.floors + h_tr.ground + h_tr.windows + h_tr.tbs,
+        h_tr.total,
+        0.01
+    );
+    assert_almost_eq!(h_tr.total / ind.K_data.summary.a, ind.K_data.K, 0.001);
+    assert!(h_tr.windows > 0.0 && h_tr.tbs > 0.0);
     assert_almost_eq!(ind.q_soljul_data.q_soljul, 0.55, 0.01); // HULC 0.54
 
     assert_almost_eq!(ind.n50_data.n50, 6.89, 0.01);
@@ -241,143 +644,3997 @@ fn model_json_ejemploviv_unif() {
 }
 
 #[test]
-fn intersections() {
+fn check_spaces_tenv() {
     init();
 
-    // Horizontal
-    let geom1 = WallGeom {
-        tilt: 0.0,
-        azimuth: 0.0,
-        position: Some(point![0.0, 0.0, 0.0]),
-        polygon: vec![
-            point![0.0, 0.0],
-            point![2.0, 0.0],
-            point![2.0, 2.0],
-            point![0.0, 2.0],
-        ],
-    };
-
-    // Vertical a Sur
-    let geom2 = WallGeom {
-        tilt: 90.0,
-        azimuth: 0.0,
-        position: Some(point![0.0, 0.0, 0.0]),
-        polygon: vec![
-            point![0.0, 0.0],
-            point![2.0, 0.0],
-            point![2.0, 2.0],
-            point![0.0, 2.0],
-        ],
-    };
-
-    // Vertical a Este
-    let geom3 = WallGeom {
-        tilt: 90.0,
-        azimuth: 90.0,
-        position: Some(point![0.0, 0.0, 0.0]),
-        polygon: vec![
-            point![0.0, 0.0],
-            point![2.0, 0.0],
-            point![2.0, 2.0],
-            point![0.0, 2.0],
-        ],
-    };
-
-    // Vertical sur
-    let geom4 = WallGeom {
-        tilt: 90.0,
-        azimuth: 0.0,
-        position: Some(point![9.11, 0.0, 9.5]),
-        polygon: vec![
-            point![0.0, 0.0],
-            point![9.11, 0.0],
-            point![9.11, 3.0],
-            point![0.0, 3.0],
-        ],
-    };
+    // Modelo correcto, sin avisos
+    let strdata = include_str!("./data/caso_a.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    assert!(bemodel::check(&model).is_empty());
 
-    #[allow(clippy::approx_constant)]
-    let testcases = vec![
-        (
-            &geom1,
-            Ray::new(point![1.0, 1.0, 2.0], vector![0.0, 0.0, -1.0]),
-            true,
-        ),
-        (
-            &geom2,
-            Ray::new(point![1.0, 1.0, 2.0], vector![0.0, 0.0, -1.0]),
-            false,
-        ),
-        (
-            &geom2,
-            Ray::new(point![1.0, 2.0, 1.0], vector![0.0, -1.0, 0.0]),
-            true,
-        ),
-        (
-            &geom3,
-            Ray::new(point![1.0, 1.0, 2.0], vector![0.0, 0.0, -1.0]),
-            false,
-        ),
-        (
-            &geom3,
-            Ray::new(point![2.0, 1.0, 1.0], vector![-1.0, 0.0, 0.0]),
-            true,
-        ),
-        (
-            &geom3,
-            Ray::new(point![-2.0, 1.0, 1.0], vector![1.0, 0.0, 0.0]),
-            true,
-        ),
-        (
-            &geom3,
-            Ray::new(point![-2.0, 1.0, 1.0], vector![-1.0, 0.0, 0.0]),
-            false,
-        ),
-        (
-            &geom4,
-            Ray::new(
-                point![-0.709, 2.1975, 0.0],
-                vector![0.0, -0.707106, 0.707106],
-            ),
-            false,
-        ),
-    ];
+    // Espacio acondicionado fuera de la ET con muros exteriores
+    let space_id = model.get_space_by_name("P01_E02").unwrap().id;
+    model
+        .spaces
+        .iter_mut()
+        .find(|s| s.id == space_id)
+        .unwrap()
+        .kind = bemodel::SpaceType::CONDITIONED;
+    let warnings = bemodel::check(&model);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].id, Some(space_id));
 
-    for (geo, r, res) in testcases {
-        info!("Inclinación: {}, azimuth: {}", geo.tilt, geo.azimuth);
-        info!("Polígono: {:?}", geo.polygon);
-        info!("Posición: {:?}", geo.position);
-        info!("Rayo: {}, {}", r.origin, r.dir);
-        let result = &geo.intersects(&r);
-        info!("Intersección con rayo: {:?}", result);
-        assert!(res == result.is_some());
+    // Espacio dentro de la ET con todos sus muros adiabáticos
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    assert!(bemodel::check(&model).is_empty());
+    for wall in model.walls.iter_mut() {
+        wall.bounds = bemodel::BoundaryType::ADIABATIC;
     }
+    let warnings = bemodel::check(&model);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].id,
+        Some(model.get_space_by_name("P01_E01").unwrap().id)
+    );
 }
 
 #[test]
-fn aabb_intersections() {
-    let aabb1 = AABB {
-        min: point![1.0, 1.0, 1.0],
-        max: point![5.0, 5.0, 5.0],
-    };
+fn indicators_k_h_tr_breakdown() {
+    use bemodel::BoundaryType;
 
-    assert!(aabb1
-        .intersects(&Ray::new(point![0.0, 0.0, 0.0], vector![1.0, 1.0, 1.0]))
-        .is_some());
+    init();
 
-    assert!(aabb1
-        .intersects(&Ray::new(point![6.0, 6.0, 6.0], vector![1.0, 1.0, 1.0]))
-        .is_none());
+    let strdata = include_str!("./data/caso_a.json");
+    let model = Model::from_json(strdata).unwrap();
+    let ind = model.energy_indicators();
+    let k_data = &ind.K_data;
+    let h_tr = k_data.h_tr;
 
-    assert!(aabb1
-        .intersects(&Ray::new(point![6.0, 6.0, 6.0], vector![-1.0, -1.0, -1.0]))
-        .is_some());
+    // La suma de los aportes, dividida por la superficie de intercambio, es K
+    let sum = h_tr.walls + h_tr.roofs + h_tr.floors + h_tr.ground + h_tr.windows + h_tr.tbs;
+    assert_almost_eq!(sum, h_tr.total, 0.01);
+    assert_almost_eq!(sum, k_data.summary.au, 0.01);
+    assert_almost_eq!(sum / k_data.summary.a, k_data.K, 0.001);
+    assert_almost_eq!(h_tr.walls, k_data.walls.au, 0.01);
+    assert_almost_eq!(h_tr.ground, k_data.ground.au, 0.01);
+    assert_almost_eq!(h_tr.tbs, k_data.summary.tbs_psil, 0.01);
+
+    // Las particiones con el garaje, exterior a la envolvente, se indican aparte
+    let partitions: f32 = ind
+        .props
+        .walls
+        .values()
+        .filter(|w| w.is_tenv && w.bounds == BoundaryType::INTERIOR)
+        .map(|w| w.multiplier * w.area_net * w.u_value.unwrap())
+        .sum();
+    assert!(h_tr.partitions > 0.0);
+    assert_almost_eq!(h_tr.partitions, partitions, 0.01);
+}
+
+#[test]
+fn indicators_habitable_and_total_envelope() {
+    init();
+
+    // Modelo mixto: garaje no habitable incluido en la envolvente térmica
+    let strdata = include_str!("./data/caso_a.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let garage_id = model.get_space_by_name("P01_E02").unwrap().id;
+    model
+        .spaces
+        .iter_mut()
+        .find(|s| s.id == garage_id)
+        .unwrap()
+        .inside_tenv = true;
+
+    let total = bemodel::energy::EnergyIndicators::for_total_envelope(&model);
+    let habitable = bemodel::energy::EnergyIndicators::for_habitable_envelope(&model);
+    // La envolvente total es la usada por defecto
+    assert_almost_eq!(total.K_data.K, model.energy_indicators().K_data.K);
+    assert_almost_eq!(total.K_data.K, 0.460, 0.001);
+    assert_almost_eq!(habitable.K_data.K, 0.466, 0.001);
+    assert!(total.K_data.summary.a > habitable.K_data.summary.a);
+    // Las particiones con el garaje no computan en K y se indican aparte
+    assert!(habitable.K_data.h_tr.partitions > total.K_data.h_tr.partitions);
+    assert!(!habitable.props.spaces[&garage_id].inside_tenv);
+    assert!(model.get_space(garage_id).unwrap().inside_tenv);
+
+    // Sin espacios no habitables en la envolvente ambas variantes coinciden
+    let strdata = include_str!("./data/caso_a.json");
+    let model = Model::from_json(strdata).unwrap();
+    assert_almost_eq!(
+        bemodel::energy::EnergyIndicators::for_total_envelope(&model)
+            .K_data
+            .K,
+        bemodel::energy::EnergyIndicators::for_habitable_envelope(&model)
+            .K_data
+            .K
+    );
+}
+
+#[test]
+fn model_wwr_by_orientation() {
+    use bemodel::{BoundaryType, Orientation, Space, Uuid, Wall, WinGeom};
+
+    init();
+
+    let space = Space {
+        id: Uuid::new_v4(),
+        name: "P01_E01".to_string(),
+        multiplier: 2.0,
+        height: 3.0,
+        ..Default::default()
+    };
+    let rect = |w: f32, h: f32| {
+        vec![
+            point![0.0, 0.0],
+            point![w, 0.0],
+            point![w, h],
+            point![0.0, h],
+        ]
+    };
+    let wall = |name: &str, tilt: f32, azimuth: f32| Wall {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        bounds: BoundaryType::EXTERIOR,
+        cons: Uuid::default(),
+        space: space.id,
+        next_to: None,
+        geometry: WallGeom {
+            tilt,
+            azimuth,
+            position: None,
+            polygon: rect(10.0, 3.0),
+        },
+        ventilated: None,
+        perim_insulation: None,
+    };
+    let window = |name: &str, wall: &Wall, x: f32, width: f32, height: f32| Window {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        cons: Uuid::default(),
+        wall: wall.id,
+        geometry: WinGeom {
+            position: Some(point![x, 0.5]),
+            width,
+            height,
+            setback: 0.0,
+        },
+        louvers: None,
+        night_insulation: None,
+        night_insulation_schedule: None,
+        double_window: None,
+        door_c_100: None,
+        opaque_door: None,
+    };
+    // Fachada sur con un 40% de huecos, fachada norte sin huecos y cubierta con lucernario
+    let south = wall("MURO_S", 90.0, 0.0);
+    let north = wall("MURO_N", 90.0, 180.0);
+    let roof = wall("CUBIERTA", 0.0, 0.0);
+    let windows = vec![
+        window("HUECO_S1", &south, 1.0, 4.0, 2.0),
+        window("HUECO_S2", &south, 6.0, 2.0, 2.0),
+        window("LUCERNARIO", &roof, 1.0, 1.0, 1.0),
+    ];
+    let model = Model {
+        spaces: vec![space],
+        walls: vec![south, north, roof],
+        windows,
+        ..Default::default()
+    };
+
+    let wwr = model.wwr_by_orientation();
+    assert_eq!(wwr.len(), 2);
+    assert_almost_eq!(wwr[&Orientation::S], 0.40);
+    assert_almost_eq!(wwr[&Orientation::N], 0.0);
+    assert!(!wwr.contains_key(&Orientation::HZ));
+}
+
+#[test]
+fn model_glazed_area_net() {
+    use bemodel::{Orientation, Space, Uuid, Wall, WinGeom};
+
+    init();
+
+    let space = Space::default();
+    // Fachada sur de 10 x 3 m
+    let wall = Wall {
+        space: space.id,
+        geometry: WallGeom {
+            tilt: 90.0,
+            azimuth: 0.0,
+            position: Some(point![0.0, 0.0, 0.0]),
+            polygon: vec![
+                point![0.0, 0.0],
+                point![10.0, 0.0],
+                point![10.0, 3.0],
+                point![0.0, 3.0],
+            ],
+        },
+        ..Default::default()
+    };
+    // Dos huecos de 2 x 2 m solapados a medias (2 m²)
+    let window = |name: &str, x: f32| Window {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        wall: wall.id,
+        geometry: WinGeom {
+            position: Some(point![x, 0.5]),
+            width: 2.0,
+            height: 2.0,
+            setback: 0.0,
+        },
+        ..Default::default()
+    };
+    let windows = vec![window("HUECO_1", 1.0), window("HUECO_2", 2.0)];
+    assert_almost_eq!(windows[0].geometry.overlap_area(&windows[1].geometry), 2.0);
+    let model = Model {
+        spaces: vec![space],
+        walls: vec![wall],
+        windows,
+        ..Default::default()
+    };
+
+    // 4 + 4 - 2 = 6 m²
+    assert_almost_eq!(model.glazed_area_net(), 6.0);
+    assert_almost_eq!(model.wwr_by_orientation()[&Orientation::S], 0.20);
+    let ind = model.energy_indicators();
+    assert_almost_eq!(ind.n50_data.windows_a, 6.0);
+
+    // Aviso de solape (además de los de construcciones no definidas)
+    let warnings: Vec<_> = bemodel::check(&model)
+        .into_iter()
+        .filter(|w| w.msg.contains("solapado"))
+        .collect();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].id, Some(model.windows[0].id));
+}
+
+#[test]
+fn model_opaque_door() {
+    use bemodel::{OpaqueDoor, Orientation, Uuid, WinGeom};
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let ind_base = model.energy_indicators();
+    let wwr_base = model.wwr_by_orientation()[&Orientation::S];
+
+    // Puerta opaca de 1 x 2 m en la fachada sur
+    let win = get_window_by_name(&model, "P01_E01_PE001_V");
+    let door = Window {
+        id: Uuid::new_v4(),
+        name: "P01_E01_PE001_P".to_string(),
+        cons: win.cons,
+        wall: win.wall,
+        geometry: WinGeom {
+            position: None,
+            width: 1.0,
+            height: 2.0,
+            setback: 0.0,
+        },
+        opaque_door: Some(OpaqueDoor {
+            u_value: 2.0,
+            absorptance: 0.6,
+        }),
+        ..Default::default()
+    };
+    assert!(door.is_opaque_door());
+    assert_almost_eq!(door.u_value(&model.cons).unwrap(), 2.0);
+    model.windows.push(door);
+    let ind = model.energy_indicators();
+
+    // No computa como superficie acristalada
+    assert_almost_eq!(model.wwr_by_orientation()[&Orientation::S], wwr_base);
+    assert_almost_eq!(ind.q_soljul_data.a_wp, ind_base.q_soljul_data.a_wp);
+    assert_almost_eq!(ind.q_soljul_data.q_soljul, ind_base.q_soljul_data.q_soljul);
+    assert!(ind.q_soljul_data.Q_soljul_opaques > ind_base.q_soljul_data.Q_soljul_opaques);
+    // Sí computa en las pérdidas por transmisión
+    assert_almost_eq!(
+        ind.K_data.summary.windows_a,
+        ind_base.K_data.summary.windows_a + 2.0
+    );
+    assert_almost_eq!(
+        ind.K_data.summary.windows_au,
+        ind_base.K_data.summary.windows_au + 4.0
+    );
+}
+
+#[test]
+fn wincons_permeability_parts() {
+    use bemodel::WinPermeabilityParts;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let ind_base = model.energy_indicators();
+    assert_almost_eq!(ind_base.n50_data.windows_c, 25.0);
+
+    // Hueco 70% fijo (3 m³/h·m²) / 30% practicable (27 m³/h·m²)
+    let cons_id = get_window_by_name(&model, "P01_E01_PE001_V").cons;
+    let cons = model
+        .cons
+        .wincons
+        .iter_mut()
+        .find(|c| c.id == cons_id)
+        .unwrap();
+    cons.c_100_parts = Some(WinPermeabilityParts {
+        operable_fraction: 0.3,
+        c_100_operable: 27.0,
+        c_100_fixed: 3.0,
+    });
+    // 0.3 · 27 + 0.7 · 3 = 10.2
+    assert_almost_eq!(cons.c_100_effective(), 10.2);
+
+    let ind = model.energy_indicators();
+    assert_almost_eq!(ind.n50_data.windows_c, 10.2);
+    assert_almost_eq!(
+        ind.n50_data.windows_c_a,
+        ind.n50_data.windows_a * 10.2,
+        0.01
+    );
+    assert!(ind.n50_data.n50 < ind_base.n50_data.n50);
+}
+
+#[test]
+fn model_built_area_by_floor() {
+    use bemodel::{Floor, Space, Wall};
+
+    init();
+
+    // Planta baja y planta tipo que se repite 3 veces, con un espacio de 10 x 8 m por planta
+    let floor = |name: &str, z: f32, multiplier: f32| Floor {
+        id: bemodel::Uuid::new_v4(),
+        name: name.to_string(),
+        z,
+        height: 3.0,
+        multiplier,
+    };
+    let floors = vec![floor("P00", 0.0, 1.0), floor("P01", 3.0, 3.0)];
+    let spaces: Vec<_> = floors
+        .iter()
+        .map(|f| Space {
+            name: format!("{}_E01", f.name),
+            floor: Some(f.id),
+            z: f.z,
+            multiplier: f.multiplier,
+            ..Default::default()
+        })
+        .collect();
+    let walls: Vec<_> = spaces
+        .iter()
+        .map(|s| Wall {
+            space: s.id,
+            geometry: WallGeom {
+                tilt: 180.0,
+                azimuth: 0.0,
+                position: Some(point![0.0, 0.0, s.z]),
+                polygon: vec![
+                    point![0.0, 0.0],
+                    point![10.0, 0.0],
+                    point![10.0, 8.0],
+                    point![0.0, 8.0],
+                ],
+            },
+            ..Default::default()
+        })
+        .collect();
+    let model = Model {
+        floors,
+        spaces,
+        walls,
+        ..Default::default()
+    };
+
+    let built_area = model.built_area_by_floor();
+    assert_eq!(built_area.len(), 2);
+    assert_almost_eq!(built_area["P00"], 80.0);
+    assert_almost_eq!(built_area["P01"], 240.0);
+    assert_almost_eq!(model.built_area(), 320.0);
+}
+
+#[test]
+fn model_ventilation_heat_transfer() {
+    use bemodel::{Space, Wall};
+
+    init();
+
+    // Espacios de 10 x 8 x 3 m (240 m³)
+    let space = |name: &str, n_v: Option<f32>, multiplier: f32, inside_tenv: bool| Space {
+        name: name.to_string(),
+        height: 3.0,
+        n_v,
+        multiplier,
+        inside_tenv,
+        ..Default::default()
+    };
+    let spaces = vec![
+        space("E01", Some(0.5), 2.0, true),
+        space("E02", None, 1.0, true),
+        space("E03", Some(1.0), 1.0, false),
+    ];
+    let walls: Vec<_> = spaces
+        .iter()
+        .map(|s| Wall {
+            space: s.id,
+            geometry: WallGeom {
+                tilt: 180.0,
+                azimuth: 0.0,
+                position: Some(point![0.0, 0.0, 0.0]),
+                polygon: vec![
+                    point![0.0, 0.0],
+                    point![10.0, 0.0],
+                    point![10.0, 8.0],
+                    point![0.0, 8.0],
+                ],
+            },
+            ..Default::default()
+        })
+        .collect();
+    let mut model = Model {
+        spaces,
+        walls,
+        ..Default::default()
+    };
+    // 100 l/s sobre 720 m³ de espacios habitables de la envolvente = 0,5 ren/h
+    model.meta.global_ventilation_l_s = Some(100.0);
+
+    let ind = model.energy_indicators();
+    // E01: 0,34 · 0,5 · 240 · 2 = 81,6 W/K
+    // E02: 0,34 · 0,5 · 240 = 40,8 W/K
+    // E03: fuera de la envolvente térmica
+    assert_almost_eq!(ind.H_ve, 122.4, 0.01);
+}
+
+#[test]
+fn model_vol_env_inh_net() {
+    use bemodel::{energy::EnergyProps, SpaceType};
+
+    init();
+
+    let strdata = include_str!("./data/caso_a.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let props = EnergyProps::from(&model);
+    // Solo cuentan los espacios habitables de la envolvente térmica
+    let vol: f32 = props
+        .spaces
+        .values()
+        .filter(|s| s.inside_tenv && s.kind != SpaceType::UNINHABITED)
+        .map(|s| s.volume_net * s.multiplier)
+        .sum();
+    assert_almost_eq!(props.global.vol_env_inh_net, vol, 0.01);
+    assert_almost_eq!(props.global.vol_env_inh_net, 1045.34, 0.01);
+    // 66,67 l/s sobre 1045,34 m³ = 0,23 ren/h, igual que Model::global_ventilation_rate
+    assert_almost_eq!(props.global.global_ventilation_rate, 0.23, 0.01);
+    assert_almost_eq!(
+        props.global.global_ventilation_rate,
+        model.global_ventilation_rate(),
+        0.001
+    );
+
+    // Un espacio habitable fuera de la envolvente no suma volumen
+    let e02 = model.get_space_by_name("P01_E02").unwrap().id;
+    model.spaces.iter_mut().find(|s| s.id == e02).unwrap().kind = SpaceType::CONDITIONED;
+    let props = EnergyProps::from(&model);
+    assert_almost_eq!(props.global.vol_env_inh_net, vol, 0.01);
+}
+
+#[test]
+fn model_elements_of_space() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let space_id = model.get_space_by_name("P01_E01").unwrap().id;
+    let wall_id = model.get_wall_by_name("P01_E01_PE001").unwrap().id;
+    let other_wall_id = model.get_wall_by_name("P01_E01_PE002").unwrap().id;
+    assert_eq!(model.walls_of_space(space_id).count(), 7);
+    assert_eq!(model.windows_of_space(space_id).count(), 1);
+    assert_eq!(
+        model.windows_of_wall(wall_id).next().unwrap().name,
+        "P01_E01_PE001_V"
+    );
+    assert_eq!(model.windows_of_wall(other_wall_id).count(), 0);
+    // Ids inexistentes o de otro tipo de elemento
+    assert_eq!(model.walls_of_space(wall_id).count(), 0);
+    assert_eq!(model.windows_of_space(wall_id).count(), 0);
+    assert_eq!(model.windows_of_wall(space_id).count(), 0);
+
+    // Todos los opacos y huecos pertenecen a algún espacio
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+    let num_walls: usize = model
+        .spaces
+        .iter()
+        .map(|s| model.walls_of_space(s.id).count())
+        .sum();
+    let num_windows: usize = model
+        .spaces
+        .iter()
+        .map(|s| model.windows_of_space(s.id).count())
+        .sum();
+    let num_windows_walls: usize = model
+        .walls
+        .iter()
+        .map(|w| model.windows_of_wall(w.id).count())
+        .sum();
+    assert_eq!(num_walls, model.walls.len());
+    assert_eq!(num_windows, model.windows.len());
+    assert_eq!(num_windows_walls, model.windows.len());
+}
+
+#[test]
+fn model_add_window() {
+    use bemodel::{Space, Wall, WinCons};
+
+    init();
+
+    let space = Space::default();
+    // Fachada este de 4 x 3 m con origen en (5, 0, 0)
+    let wall = Wall {
+        name: "MURO_E".to_string(),
+        space: space.id,
+        geometry: WallGeom {
+            tilt: 90.0,
+            azimuth: 90.0,
+            position: Some(point![5.0, 0.0, 0.0]),
+            polygon: vec![
+                point![0.0, 0.0],
+                point![4.0, 0.0],
+                point![4.0, 3.0],
+                point![0.0, 3.0],
+            ],
+        },
+        ..Default::default()
+    };
+    let wall_id = wall.id;
+    let wincons = WinCons::default();
+    let cons_id = wincons.id;
+    let mut model = Model {
+        spaces: vec![space],
+        walls: vec![wall],
+        ..Default::default()
+    };
+    model.cons.wincons.push(wincons);
+
+    let id = model
+        .add_window(wall_id, 1.0, 0.5, 1.2, 1.5, cons_id)
+        .unwrap();
+    let window = model.get_window(id).unwrap();
+    assert_eq!(window.name, "MURO_E_V1");
+    assert_eq!(window.wall, wall_id);
+    assert_almost_eq!(window.area(), 1.8);
+
+    // El eje x local del muro este apunta hacia el norte (+Y) y el eje y local hacia arriba (+Z)
+    let pos = window
+        .position_3d(&model.get_wall(wall_id).unwrap().geometry)
+        .unwrap();
+    assert_almost_eq!(pos.x, 5.0);
+    assert_almost_eq!(pos.y, 1.0);
+    assert_almost_eq!(pos.z, 0.5);
+
+    // Huecos que no caben en el muro, sin dimensiones válidas, o con muro o construcción inexistentes
+    assert!(model
+        .add_window(wall_id, 3.0, 0.5, 1.2, 1.5, cons_id)
+        .is_err());
+    assert!(model
+        .add_window(wall_id, 1.0, 2.0, 1.2, 1.5, cons_id)
+        .is_err());
+    assert!(model
+        .add_window(wall_id, -0.5, 0.5, 1.2, 1.5, cons_id)
+        .is_err());
+    assert!(model
+        .add_window(wall_id, 1.0, 0.5, 0.0, 1.5, cons_id)
+        .is_err());
+    assert!(model
+        .add_window(bemodel::Uuid::new_v4(), 1.0, 0.5, 1.2, 1.5, cons_id)
+        .is_err());
+    assert!(model
+        .add_window(wall_id, 1.0, 0.5, 1.2, 1.5, bemodel::Uuid::new_v4())
+        .is_err());
+    assert_eq!(model.windows.len(), 1);
+}
+
+#[test]
+fn model_submodel() {
+    use bemodel::{
+        energy::EnergyProps, BoundaryType, Space, ThermalBridge, Tilt, Wall, WallCons, WinCons,
+    };
+
+    init();
+
+    // Dos espacios de 10 x 8 m superpuestos. El forjado entre ambos pertenece al espacio superior
+    let e01 = Space {
+        name: "E01".to_string(),
+        ..Default::default()
+    };
+    let e02 = Space {
+        name: "E02".to_string(),
+        z: 3.0,
+        ..Default::default()
+    };
+    let floor_geom = |z: f32| WallGeom {
+        tilt: 180.0,
+        azimuth: 0.0,
+        position: Some(point![0.0, 8.0, z]),
+        polygon: vec![
+            point![0.0, 0.0],
+            point![10.0, 0.0],
+            point![10.0, 8.0],
+            point![0.0, 8.0],
+        ],
+    };
+    let facade_geom = |z: f32| WallGeom {
+        tilt: 90.0,
+        azimuth: 0.0,
+        position: Some(point![0.0, 0.0, z]),
+        polygon: vec![
+            point![0.0, 0.0],
+            point![10.0, 0.0],
+            point![10.0, 3.0],
+            point![0.0, 3.0],
+        ],
+    };
+    let cons: Vec<_> = (0..3).map(|_| WallCons::default()).collect();
+    let wincons = WinCons::default();
+    let walls = vec![
+        Wall {
+            name: "E01_SOLERA".to_string(),
+            bounds: BoundaryType::GROUND,
+            cons: cons[0].id,
+            space: e01.id,
+            geometry: floor_geom(0.0),
+            ..Default::default()
+        },
+        Wall {
+            name: "E01_FACHADA".to_string(),
+            cons: cons[1].id,
+            space: e01.id,
+            geometry: facade_geom(0.0),
+            ..Default::default()
+        },
+        Wall {
+            name: "E02_FORJADO".to_string(),
+            bounds: BoundaryType::INTERIOR,
+            cons: cons[1].id,
+            space: e02.id,
+            next_to: Some(e01.id),
+            geometry: floor_geom(3.0),
+            ..Default::default()
+        },
+        Wall {
+            name: "E02_FACHADA".to_string(),
+            cons: cons[2].id,
+            space: e02.id,
+            geometry: facade_geom(3.0),
+            ..Default::default()
+        },
+    ];
+    let fachada2_id = walls[3].id;
+    // Puentes térmicos de cada espacio y uno sin espacio asociado
+    let thermal_bridges = vec![
+        ThermalBridge {
+            name: "E01_PT".to_string(),
+            l: 10.0,
+            psi: 0.5,
+            space: Some(e01.id),
+            ..Default::default()
+        },
+        ThermalBridge {
+            name: "E02_PT".to_string(),
+            l: 10.0,
+            psi: 0.8,
+            space: Some(e02.id),
+            ..Default::default()
+        },
+        ThermalBridge {
+            name: "PT".to_string(),
+            l: 20.0,
+            psi: 0.1,
+            ..Default::default()
+        },
+    ];
+    let mut model = Model {
+        spaces: vec![e01.clone(), e02.clone()],
+        walls,
+        thermal_bridges,
+        ..Default::default()
+    };
+    model.cons.wallcons = cons.clone();
+    model.cons.wincons.push(wincons.clone());
+    model
+        .add_window(fachada2_id, 1.0, 1.0, 1.5, 1.2, wincons.id)
+        .unwrap();
+    assert_almost_eq!(EnergyProps::from(&model).global.a_ref, 160.0);
+
+    // Espacio inferior: el forjado pasa a ser su techo adiabático
+    let sub = model.submodel(&[e01.id]);
+    assert_eq!(sub.spaces.len(), 1);
+    assert_eq!(sub.walls.len(), 3);
+    assert!(sub.windows.is_empty());
+    assert!(sub.cons.wincons.is_empty());
+    assert_eq!(sub.cons.wallcons.len(), 2);
+    assert!(sub.cons.get_wallcons(cons[2].id).is_none());
+    let forjado = sub.get_wall_by_name("E02_FORJADO").unwrap();
+    assert_eq!(forjado.space, e01.id);
+    assert_eq!(forjado.next_to, None);
+    assert_eq!(forjado.bounds, BoundaryType::ADIABATIC);
+    assert_eq!(Tilt::from(forjado), Tilt::TOP);
+    assert_almost_eq!(forjado.area(), 80.0);
+    assert_almost_eq!(EnergyProps::from(&sub).global.a_ref, 80.0);
+    // Conserva solo el puente térmico del espacio, que participa en K
+    assert_eq!(sub.thermal_bridges.len(), 1);
+    assert_eq!(sub.thermal_bridges[0].name, "E01_PT");
+    let k_data = sub.energy_indicators().K_data;
+    assert_almost_eq!(k_data.summary.a, 110.0);
+    assert_almost_eq!(k_data.summary.tbs_psil, 5.0);
+    assert_almost_eq!(k_data.K, (k_data.summary.opaques_au + 5.0) / 110.0, 0.001);
+
+    // Espacio superior: conserva su suelo, ahora adiabático, y el hueco de fachada
+    let sub = model.submodel(&[e02.id]);
+    assert_eq!(sub.spaces.len(), 1);
+    assert_eq!(sub.walls.len(), 2);
+    assert_eq!(sub.windows.len(), 1);
+    assert!(sub.cons.get_wallcons(cons[0].id).is_none());
+    let forjado = sub.get_wall_by_name("E02_FORJADO").unwrap();
+    assert_eq!(forjado.bounds, BoundaryType::ADIABATIC);
+    assert_eq!(Tilt::from(forjado), Tilt::BOTTOM);
+    assert_almost_eq!(EnergyProps::from(&sub).global.a_ref, 80.0);
+}
+
+#[test]
+fn model_embodied_carbon() {
+    use bemodel::{Layer, MatProps, Material, Space, Wall, WallCons};
+    use std::collections::BTreeMap;
+
+    init();
+
+    let material = |density: f32| Material {
+        properties: MatProps::Detailed {
+            conductivity: 0.5,
+            density,
+            specific_heat: 1000.0,
+            vapour_diff: None,
+        },
+        ..Default::default()
+    };
+    let mat1 = material(1000.0);
+    let mat2 = material(50.0);
+    let mat3 = material(2000.0);
+    let cons = WallCons {
+        layers: vec![
+            Layer {
+                material: mat1.id,
+                e: 0.1,
+                e_max: None,
+            },
+            Layer {
+                material: mat2.id,
+                e: 0.05,
+                e_max: None,
+            },
+            Layer {
+                material: mat3.id,
+                e: 0.02,
+                e_max: None,
+            },
+        ],
+        ..Default::default()
+    };
+    let space = Space {
+        multiplier: 2.0,
+        ..Default::default()
+    };
+    // Opaco de 5 x 1 m
+    let wall = Wall {
+        cons: cons.id,
+        space: space.id,
+        geometry: WallGeom {
+            polygon: vec![
+                point![0.0, 0.0],
+                point![5.0, 0.0],
+                point![5.0, 1.0],
+                point![0.0, 1.0],
+            ],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut model = Model {
+        spaces: vec![space],
+        walls: vec![wall],
+        ..Default::default()
+    };
+    model.cons.materials = vec![mat1.clone(), mat2.clone(), mat3];
+    model.cons.wallcons.push(cons);
+
+    // El tercer material no tiene factor de emisión y no se computa
+    // 1000 kg/m³ · 0.1 m · 10 m² · 0.2 + 50 kg/m³ · 0.05 m · 10 m² · 3.0 = 200 + 75
+    let factors = BTreeMap::from([(mat1.id, 0.2), (mat2.id, 3.0)]);
+    assert_almost_eq!(model.embodied_carbon(&factors), 275.0);
+    assert_almost_eq!(model.embodied_carbon(&BTreeMap::new()), 0.0);
+}
+
+#[test]
+fn model_bounding_box_and_envelope_area() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+
+    // Cubo de 10 x 10 x 3 m con un voladizo de 1 m sobre el hueco sur
+    let aabb = model.bounding_box();
+    assert_almost_eq!(aabb.min.x, 0.0);
+    assert_almost_eq!(aabb.min.y, -1.0);
+    assert_almost_eq!(aabb.min.z, 0.0);
+    assert_almost_eq!(aabb.max.x, 10.0);
+    assert_almost_eq!(aabb.max.y, 10.0);
+    assert_almost_eq!(aabb.max.z, 3.0);
+    model.shades.clear();
+    let aabb = model.bounding_box();
+    assert_almost_eq!(aabb.min.y, 0.0);
+    assert_almost_eq!(aabb.center().x, 5.0);
+    assert_almost_eq!(aabb.center().y, 5.0);
+    assert_almost_eq!(aabb.center().z, 1.5);
+
+    // 4 fachadas de 30 m², suelo y cubierta de 100 m²
+    assert_almost_eq!(model.envelope_surface_area(), 320.0, 0.01);
+    let ind = model.energy_indicators();
+    assert_almost_eq!(
+        ind.compactness,
+        ind.vol_env_gross / model.envelope_surface_area(),
+        0.01
+    );
+
+    assert!(Model::default().bounding_box().min.x.is_infinite());
+}
+
+#[test]
+fn model_add_surrounding_building() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    // Hueco sur sin retranqueo ni otras sombras
+    model.shades.clear();
+    model.windows[0].geometry.setback = 0.0;
+    let win_id = get_window_by_name(&model, "P01_E01_PE001_V").id;
+    let fshobst = model.compute_fshobst()[&win_id];
+
+    // Huella en sentido horario, que se reordena para orientar las fachadas al exterior
+    let ids = model
+        .add_surrounding_building(
+            vec![
+                point![-10.0, -20.0],
+                point![-10.0, -8.0],
+                point![20.0, -8.0],
+                point![20.0, -20.0],
+            ],
+            30.0,
+        )
+        .unwrap();
+    assert_eq!(ids.len(), 5);
+    assert_eq!(model.shades.len(), 5);
+    // La fachada norte del edificio colindante mira hacia el modelo
+    let north_facade = model
+        .shades
+        .iter()
+        .find(|s| s.name == "EDIFICIO_COLINDANTE_1_F2")
+        .unwrap();
+    assert_almost_eq!(north_facade.geometry.azimuth.abs(), 180.0);
+    let corner = north_facade.geometry.polygon[2];
+    assert_almost_eq!(corner.x, 30.0);
+    assert_almost_eq!(corner.y, 30.0);
+
+    // El edificio alto al sur reduce el factor de obstáculos remotos del hueco sur
+    let fshobst_building = model.compute_fshobst()[&win_id];
+    assert!(fshobst_building < fshobst - 0.1);
+
+    assert!(model
+        .add_surrounding_building(vec![point![0.0, 0.0], point![1.0, 0.0]], 10.0)
+        .is_err());
+    assert!(model
+        .add_surrounding_building(
+            vec![point![0.0, 0.0], point![1.0, 0.0], point![1.0, 1.0]],
+            0.0
+        )
+        .is_err());
+}
+
+#[test]
+fn model_validate() {
+    use bemodel::{Space, ThermalBridge, Uuid, Wall, WarningLevel, WinGeom};
+
+    init();
+
+    let space = Space::default();
+    // Fachada sur de 4 x 3 m
+    let wall = Wall {
+        space: space.id,
+        geometry: WallGeom {
+            tilt: 90.0,
+            azimuth: 0.0,
+            position: Some(point![0.0, 0.0, 0.0]),
+            polygon: vec![
+                point![0.0, 0.0],
+                point![4.0, 0.0],
+                point![4.0, 3.0],
+                point![0.0, 3.0],
+            ],
+        },
+        ..Default::default()
+    };
+    // Hueco que sobresale del opaco y con más superficie que este
+    let window = Window {
+        wall: wall.id,
+        geometry: WinGeom {
+            position: Some(point![1.0, 0.5]),
+            width: 5.0,
+            height: 3.0,
+            setback: 0.0,
+        },
+        ..Default::default()
+    };
+    let tb = ThermalBridge {
+        id: Uuid::new_v4(),
+        name: "PT_NULO".to_string(),
+        l: 10.0,
+        psi: 0.0,
+        ..Default::default()
+    };
+    let model = Model {
+        spaces: vec![space],
+        walls: vec![wall],
+        windows: vec![window],
+        thermal_bridges: vec![tb],
+        ..Default::default()
+    };
+
+    let warnings = model.validate();
+    // Además de los avisos de check se detectan huecos fuera del opaco, opacos con exceso de huecos
+    // y puentes térmicos sin efecto
+    assert!(warnings.len() > bemodel::check(&model).len());
+    let has = |level: WarningLevel, id: Uuid| {
+        warnings
+            .iter()
+            .any(|w| w.level == level && w.id == Some(id))
+    };
+    assert!(has(WarningLevel::DANGER, model.walls[0].id));
+    assert!(has(WarningLevel::WARNING, model.windows[0].id));
+    assert!(has(WarningLevel::INFO, model.thermal_bridges[0].id));
+    // Ordenados de mayor a menor afectación
+    assert_eq!(warnings.first().unwrap().level, WarningLevel::DANGER);
+    assert_eq!(warnings.last().unwrap().level, WarningLevel::INFO);
+
+    // Serializable a JSON
+    let json = serde_json::to_string(&warnings).unwrap();
+    let parsed: Vec<bemodel::Warning> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.len(), warnings.len());
+}
+
+#[test]
+fn model_monthly_radiation_on_surfaces() {
+    use bemodel::climatedata::{
+        monthly_radiation_for_surface, total_radiation_by_orientation_and_month, ClimateZone,
+    };
+    use bemodel::Orientation;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let tabulated = total_radiation_by_orientation_and_month(&ClimateZone::D3);
+
+    // Muro vertical sur, cubierta horizontal y hueco del muro sur coinciden con los datos tabulados
+    let south_id = model.get_wall_by_name("P01_E01_PE001").unwrap().id;
+    let roof_id = model.get_wall_by_name("P01_E01C001").unwrap().id;
+    let win_id = get_window_by_name(&model, "P01_E01_PE001_V").id;
+    let radiation = model.monthly_radiation_on_surfaces();
+    // Solo los 6 opacos exteriores (sin la solera) y su hueco
+    assert_eq!(radiation.len(), 7);
+    for i in 0..12 {
+        assert_almost_eq!(radiation[&south_id][i], tabulated[&Orientation::S][i]);
+        assert_almost_eq!(radiation[&roof_id][i], tabulated[&Orientation::HZ][i]);
+        assert_almost_eq!(radiation[&win_id][i], radiation[&south_id][i]);
+    }
+
+    // Muro sur inclinado 45º, entre la horizontal y la vertical
+    model.walls[0].geometry.tilt = 45.0;
+    let radiation = model.monthly_radiation_on_surfaces();
+    for i in 0..12 {
+        let (hz, s) = (
+            tabulated[&Orientation::HZ][i],
+            tabulated[&Orientation::S][i],
+        );
+        let value = radiation[&south_id][i];
+        assert!(value >= hz.min(s) && value <= hz.max(s));
+        assert_almost_eq!(value, 0.5 * (hz + s));
+    }
+
+    // Orientación intermedia entre sur y sureste
+    let sse = monthly_radiation_for_surface(&ClimateZone::D3, 90.0, 22.5).unwrap();
+    for (i, value) in sse.iter().enumerate() {
+        assert_almost_eq!(
+            *value,
+            0.5 * (tabulated[&Orientation::S][i] + tabulated[&Orientation::SE][i])
+        );
+    }
+    // Superficie horizontal hacia abajo, sin radiación
+    let down = monthly_radiation_for_surface(&ClimateZone::D3, 180.0, 0.0).unwrap();
+    assert!(down.iter().all(|v| *v == 0.0));
+}
+
+#[test]
+fn climatedata_synthesize_hourly() {
+    use bemodel::climatedata::{synthesize_hourly, ClimateZone, MONTHLYRADDATA};
+
+    init();
+
+    let zone = ClimateZone::D3;
+    let metdata = synthesize_hourly(&zone).unwrap();
+    assert_eq!(metdata.data.len(), 8760);
+    assert_eq!(&metdata.meta.zc, "D3");
+
+    // Totales mensuales de radiación sobre superficie horizontal coherentes con los tabulados
+    let raddata = MONTHLYRADDATA
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|e| e.zone == zone && e.orientation == bemodel::Orientation::HZ)
+        .cloned()
+        .unwrap();
+    for month in 1..=12 {
+        let (dir, dif) = metdata
+            .data
+            .iter()
+            .filter(|d| d.month == month)
+            .fold((0.0, 0.0), |acc, d| (acc.0 + d.rdirhor, acc.1 + d.rdifhor));
+        assert_almost_eq!(dir / 1000.0, raddata.dir[month as usize - 1], 0.1);
+        assert_almost_eq!(dif / 1000.0, raddata.dif[month as usize - 1], 0.1);
+    }
+
+    // Sin radiación durante la noche y verano más cálido que el invierno
+    let first = &metdata.data[0];
+    assert_eq!(first.rdirhor + first.rdifhor, 0.0);
+    let mean_temp = |month: u32| {
+        let temps = metdata
+            .data
+            .iter()
+            .filter(|d| d.month == month)
+            .map(|d| d.db_temp)
+            .collect::<Vec<_>>();
+        temps.iter().sum::<f32>() / temps.len() as f32
+    };
+    assert!(mean_temp(7) > mean_temp(1) + 10.0);
+}
+
+#[test]
+fn model_infiltration_rate_hourly() {
+    use climate::{parsemet, HourlyData, MetData};
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+
+    // Año completo
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+    let n_inf = model.infiltration_rate_hourly(&met);
+    assert_eq!(n_inf.len(), 8760);
+    assert!(n_inf.iter().all(|n| *n >= 0.0));
+
+    // Sin viento ni diferencia de temperatura la infiltración es mínima (nula)
+    let hour = |db_temp: f32, wind_speed: f32| HourlyData {
+        db_temp,
+        wind_speed,
+        ..Default::default()
+    };
+    let met = MetData {
+        data: vec![
+            hour(20.0, 0.0),
+            hour(0.0, 0.0),
+            hour(20.0, 5.0),
+            hour(0.0, 5.0),
+        ],
+        ..Default::default()
+    };
+    let n_inf = model.infiltration_rate_hourly(&met);
+    assert_almost_eq!(n_inf[0], 0.0, 1e-6);
+    assert!(n_inf[1] > n_inf[0]);
+    assert!(n_inf[2] > n_inf[0]);
+    assert!(n_inf[3] > n_inf[1] && n_inf[3] > n_inf[2]);
+    // Orden de magnitud razonable frente a n50
+    let n50 = model.energy_indicators().n50_data.n50;
+    assert!(n_inf[3] < n50);
+}
+
+#[test]
+fn model_design_ventilation_flow() {
+    use bemodel::{energy::EnergyProps, AirQuality};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let space_id = model.spaces[0].id;
+    // Sin categoría de calidad del aire no hay caudal de diseño
+    assert!(model.design_ventilation_flows().is_empty());
+    let h_ve = model.energy_indicators().H_ve;
+
+    // Oficina (IDA 2) con 10 m² por ocupante
+    model.loads[0].area_per_person = 10.0;
+    model.loads[0].air_quality = Some(AirQuality::IDA2);
+    let props = EnergyProps::from(&model);
+    let space = &props.spaces[&space_id];
+    let flows = model.design_ventilation_flows();
+    assert_almost_eq!(flows[&space_id], space.area / 10.0 * 12.5);
+    assert_almost_eq!(
+        props.design_ventilation_rate(space_id).unwrap(),
+        3.6 * flows[&space_id] / space.volume_net
+    );
+    // El caudal de diseño sustituye a la tasa global de ventilación en H_ve
+    let h_ve_office = model.energy_indicators().H_ve;
+    assert!(h_ve_office > h_ve);
+    assert_almost_eq!(
+        h_ve_office,
+        0.34 * 3.6 * flows[&space_id] * space.multiplier,
+        0.01
+    );
+
+    // Sin ocupación se usa el caudal por superficie, que no es aplicable a IDA 1
+    model.loads[0].area_per_person = 0.0;
+    assert_almost_eq!(
+        model.design_ventilation_flows()[&space_id],
+        space.area * 0.83
+    );
+    model.loads[0].air_quality = Some(AirQuality::IDA1);
+    assert!(model.design_ventilation_flows().is_empty());
+
+    // La tasa de ventilación del espacio tiene prioridad
+    model.loads[0].air_quality = Some(AirQuality::IDA2);
+    model.spaces[0].n_v = Some(0.5);
+    assert_almost_eq!(
+        model.energy_indicators().H_ve,
+        0.34 * 0.5 * space.volume_net * space.multiplier,
+        0.01
+    );
+}
+
+#[test]
+fn model_latent_load_hourly() {
+    use bemodel::SpaceType;
+    use climate::parsemet;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+
+    let latent = model.latent_load_hourly(&met);
+    let n_conditioned = model
+        .spaces
+        .iter()
+        .filter(|s| s.kind == SpaceType::CONDITIONED)
+        .count();
+    assert_eq!(latent.len(), n_conditioned);
+    let (space_id, values) = latent.iter().next().unwrap();
+    assert_eq!(values.len(), 8760);
+    assert!(values.iter().all(|v| *v >= 0.0));
+
+    // Sin ocupación solo hay carga latente por ventilación
+    for loads in &mut model.loads {
+        loads.people_schedule = None;
+    }
+    let latent_vent = model.latent_load_hourly(&met);
+    let values_vent = &latent_vent[space_id];
+    assert!(values_vent.iter().zip(values).all(|(v, o)| v <= o));
+    // La carga es nula con aire exterior más seco que el interior y positiva con aire más húmedo
+    for (d, v) in met.data.iter().zip(values_vent) {
+        if d.abs_humidity < 0.0093 {
+            assert_almost_eq!(*v, 0.0);
+        } else if d.abs_humidity > 0.0094 {
+            assert!(*v > 0.0);
+        }
+    }
+}
+
+#[test]
+fn model_operative_temperature_and_adaptive_comfort() {
+    use bemodel::SpaceType;
+    use climate::parsemet;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+
+    let t_op = model.operative_temperature_hourly(&met);
+    assert_eq!(t_op.len(), model.spaces.len());
+    let (space_id, values) = t_op.iter().next().unwrap();
+    assert_eq!(values.len(), 8760);
+    let hours = model.adaptive_comfort_hours(&met);
+    assert!(hours[space_id] <= 8760);
+
+    // Sin acondicionar la temperatura oscila libremente, con más horas fuera de confort
+    model.spaces[0].kind = SpaceType::UNCONDITIONED;
+    let t_free = &model.operative_temperature_hourly(&met)[space_id];
+    let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+    let (t_jan, t_jul) = (mean(&t_free[..744]), mean(&t_free[4344..5088]));
+    assert!(t_jul > t_jan + 5.0);
+    assert!(t_free.iter().all(|t| (-15.0..60.0).contains(t)));
+    let hours_free = model.adaptive_comfort_hours(&met);
+    assert!(hours_free[space_id] > hours[space_id]);
+}
+
+#[test]
+fn model_peak_loads() {
+    use climate::parsemet;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+    let space = &model.spaces[0];
+
+    let peaks = model.peak_loads(&met);
+    assert_eq!(peaks.heating_by_space.len(), 1);
+    assert_eq!(peaks.cooling_by_space.len(), 1);
+    assert_almost_eq!(
+        peaks.heating.power,
+        peaks.heating_by_space[&space.id].power * space.multiplier
+    );
+    assert!(peaks.heating.power > 0.0 && peaks.cooling.power > 0.0);
+
+    // El pico de calefacción se produce en invierno
+    assert!([1, 2, 3, 11, 12].contains(&peaks.heating.month));
+    // El pico de refrigeración se produce en verano y en horas solares
+    let cooling = peaks.cooling;
+    assert!((6..=9).contains(&cooling.month));
+    let hour = &met.data[cooling.index];
+    assert_eq!((hour.month, hour.day), (cooling.month, cooling.day));
+    assert!(hour.rdirhor + hour.rdifhor > 0.0);
+}
+
+#[test]
+fn model_radiant_surface() {
+    use bemodel::{RadiantSurface, SpaceType, Uuid};
+    use climate::parsemet;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+    model.spaces[0].kind = SpaceType::UNCONDITIONED;
+    let space_id = model.spaces[0].id;
+    let floor_id = model
+        .walls
+        .iter()
+        .find(|w| w.name == "P01_E01_FTER001")
+        .unwrap()
+        .id;
+    let t_op = model.operative_temperature_hourly(&met)[&space_id].clone();
+
+    // Suelo radiante de 20 W/m², con potencia constante
+    model.radiant_surfaces.push(RadiantSurface {
+        id: Uuid::new_v4(),
+        name: "SUELO_RADIANTE".to_string(),
+        wall: floor_id,
+        max_power: 20.0,
+        schedule: None,
+    });
+    let model = Model::from_json(&model.as_json().unwrap()).unwrap();
+    assert_eq!(model.radiant_surfaces.len(), 1);
+    let t_op_radiant = &model.operative_temperature_hourly(&met)[&space_id];
+
+    let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+    assert!(t_op.iter().zip(t_op_radiant).all(|(t, t_r)| t_r > t));
+    assert!(mean(t_op_radiant) > mean(&t_op) + 1.0);
+}
+
+#[test]
+fn wall_ventilated_roof_solar_gains() {
+    use bemodel::{CavityVentilation, Tilt, VentilatedCavity};
+    use climate::parsemet;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+
+    let roof_idx = model
+        .walls
+        .iter()
+        .position(|w| Tilt::from(w) == Tilt::TOP && w.bounds == bemodel::BoundaryType::EXTERIOR)
+        .unwrap();
+    let mut ventilated_model = model.clone();
+    let cavity = VentilatedCavity {
+        thickness: 0.1,
+        ventilation: CavityVentilation::WELL,
+    };
+    assert_almost_eq!(cavity.solar_gain_factor(), 0.5);
+    ventilated_model.walls[roof_idx].ventilated = Some(cavity);
+
+    // Temperatura sol-aire
+    let roof = &model.walls[roof_idx];
+    let ventilated_roof = &ventilated_model.walls[roof_idx];
+    assert_almost_eq!(roof.solar_gain_factor(), 1.0);
+    let t_sa = model.sol_air_temperature_hourly(roof, &met);
+    let t_sa_vent = ventilated_model.sol_air_temperature_hourly(ventilated_roof, &met);
+    assert_eq!(t_sa.len(), 8760);
+    let max = |v: &[f32]| v.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    assert!(max(&t_sa_vent) < max(&t_sa));
+    assert!(t_sa_vent.iter().zip(&t_sa).all(|(v, nv)| v <= nv));
+    // Sin radiación solar (1 de enero a las 0h) no hay diferencias
+    assert_almost_eq!(t_sa_vent[0], t_sa[0]);
+
+    // Ganancias solares de opacos en julio
+    let q_opaques = model.energy_indicators().q_soljul_data.Q_soljul_opaques;
+    let q_opaques_vent = ventilated_model
+        .energy_indicators()
+        .q_soljul_data
+        .Q_soljul_opaques;
+    assert!(q_opaques > 0.0);
+    assert!(q_opaques_vent < q_opaques);
+}
+
+#[test]
+fn wall_sol_air_sky_radiation() {
+    use bemodel::Tilt;
+    use climate::parsemet;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+
+    let exterior = |tilt: Tilt| {
+        model
+            .walls
+            .iter()
+            .find(|w| Tilt::from(*w) == tilt && w.bounds == bemodel::BoundaryType::EXTERIOR)
+            .unwrap()
+            .clone()
+    };
+    let roof = exterior(Tilt::TOP);
+    let wall = exterior(Tilt::SIDE);
+
+    // 1 de enero a las 5h: sin sol, la cubierta se enfría por debajo del aire por radiación al cielo
+    let i = 4;
+    let t_air = met.data[i].db_temp;
+    let t_sa_roof = model.sol_air_temperature_hourly(&roof, &met);
+    let t_sa_wall = model.sol_air_temperature_hourly(&wall, &met);
+    assert!(t_sa_roof[i] < t_air - 1.0);
+    // Los muros ven menos cielo que las cubiertas
+    assert!(t_sa_roof[i] < t_sa_wall[i]);
+    assert!(t_sa_wall[i] < t_air);
+
+    // Con una superficie de baja emisividad el enfriamiento es menor
+    let cons = model
+        .cons
+        .wallcons
+        .iter_mut()
+        .find(|c| c.id == roof.cons)
+        .unwrap();
+    cons.emissivity = Some(0.2);
+    let t_sa_roof_low_e = model.sol_air_temperature_hourly(&roof, &met);
+    assert!(t_sa_roof[i] < t_sa_roof_low_e[i]);
+    assert!(t_sa_roof_low_e[i] < t_air);
+}
+#[test]
+fn window_solar_gains_incidence_angle() {
+    use climate::parsemet;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+
+    // Curva de factor solar que decae a partir de 40º de incidencia
+    let g_angular = vec![
+        (0.0, 1.0),
+        (40.0, 1.0),
+        (60.0, 0.9),
+        (80.0, 0.5),
+        (90.0, 0.0),
+    ];
+    let mut glass = model.cons.glasses[0].clone();
+    assert_almost_eq!(glass.g_angle_factor(70.0), 1.0);
+    glass.g_angular = Some(g_angular.clone());
+    assert_almost_eq!(glass.g_angle_factor(0.0), 1.0);
+    assert_almost_eq!(glass.g_angle_factor(30.0), 1.0);
+    assert_almost_eq!(glass.g_angle_factor(70.0), 0.7);
+    // La ganancia a incidencia rasante es claramente menor que a incidencia normal
+    assert!(glass.g_angle_factor(85.0) < 0.5 * glass.g_angle_factor(0.0));
+
+    let mut angular_model = model.clone();
+    for glass in &mut angular_model.cons.glasses {
+        glass.g_angular = Some(g_angular.clone());
+    }
+    let gains = model.solar_gains_hourly(&met);
+    let gains_angular = angular_model.solar_gains_hourly(&met);
+    assert!(!gains.is_empty());
+    let total = |v: &[f32]| v.iter().sum::<f32>();
+    for (id, values) in &gains {
+        let values_angular = &gains_angular[id];
+        assert_eq!(values.len(), 8760);
+        assert!(values.iter().all(|v| *v >= 0.0));
+        assert!(values_angular
+            .iter()
+            .zip(values)
+            .all(|(a, v)| *a <= *v + 0.001));
+        assert!(total(values_angular) < total(values));
+    }
+}
+
+#[test]
+fn window_frame_fraction() {
+    use bemodel::{WinCons, WinGeom};
+
+    init();
+
+    // Hueco de 1,2 x 1,5 m con marco de 6 cm
+    let window = Window {
+        geometry: WinGeom {
+            width: 1.2,
+            height: 1.5,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut cons = WinCons {
+        f_f: 0.25,
+        ..Default::default()
+    };
+    // f_f = (1,2 · 1,5 - 1,08 · 1,38) / (1,2 · 1,5) = 0,172
+    assert_almost_eq!(window.geometry.frame_fraction(0.06), 0.172, 0.001);
+
+    // Sin ancho de marco o sin pedir recálculo se respeta el valor de la construcción
+    assert_almost_eq!(window.f_f(&cons, true), 0.25);
+    cons.frame_width = Some(0.06);
+    assert_almost_eq!(window.f_f(&cons, false), 0.25);
+    assert_almost_eq!(window.f_f(&cons, true), 0.172, 0.001);
+}
+
+#[test]
+fn window_roller_shutter_box_u_value() {
+    use bemodel::{ConsDb, Frame, Glass, RollerShutterBox, WinCons, WinGeom};
+
+    init();
+
+    let glass = Glass {
+        u_value: 1.4,
+        ..Default::default()
+    };
+    let frame = Frame {
+        u_value: 2.2,
+        ..Default::default()
+    };
+    let cons = WinCons {
+        glass: glass.id,
+        frame: frame.id,
+        f_f: 0.25,
+        ..Default::default()
+    };
+    let mut db = ConsDb {
+        wincons: vec![cons],
+        glasses: vec![glass],
+        frames: vec![frame],
+        ..Default::default()
+    };
+    // Hueco de 1,2 x 1,5 m
+    let window = Window {
+        cons: db.wincons[0].id,
+        geometry: WinGeom {
+            width: 1.2,
+            height: 1.5,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Sin cajón: U = 0,25 · 2,2 + 0,75 · 1,4 = 1,60 W/m²K
+    assert_almost_eq!(db.wincons[0].u_value(&db).unwrap(), 1.60);
+    assert_almost_eq!(window.u_value(&db).unwrap(), 1.60);
+
+    // Cajón de 1,2 x 0,3 m con U = 3,0 W/m²K (20% de la superficie del hueco)
+    // U = 0,80 · 1,60 + 0,20 · 3,0 = 1,88 W/m²K
+    db.wincons[0].shutter_box = Some(RollerShutterBox {
+        u_value: 3.0,
+        width: 1.2,
+        height: 0.3,
+    });
+    assert_almost_eq!(window.u_value(&db).unwrap(), 1.88);
+    // La transmitancia de la construcción no depende del cajón
+    assert_almost_eq!(db.wincons[0].u_value(&db).unwrap(), 1.60);
+}
+
+#[test]
+fn window_louver_shades() {
+    use bemodel::{LouverOrientation, LouverShade, Wall, WinGeom};
+
+    init();
+
+    // Fachada sur de 4 x 3 m con hueco de 1,2 x 1,5 m
+    let wall = Wall {
+        geometry: WallGeom {
+            tilt: 90.0,
+            azimuth: 0.0,
+            position: Some(point![0.0, 0.0, 0.0]),
+            polygon: vec![
+                point![0.0, 0.0],
+                point![4.0, 0.0],
+                point![4.0, 3.0],
+                point![0.0, 3.0],
+            ],
+        },
+        ..Default::default()
+    };
+    let mut window = Window {
+        wall: wall.id,
+        geometry: WinGeom {
+            position: Some(point![1.4, 1.0]),
+            width: 1.2,
+            height: 1.5,
+            setback: 0.0,
+        },
+        ..Default::default()
+    };
+    let mut model = Model {
+        walls: vec![wall],
+        windows: vec![window.clone()],
+        ..Default::default()
+    };
+
+    // Mediodía de verano (21 de junio) en Madrid (latitud 40,4º): altura solar ~73º
+    let ray_dir = ray_dir_to_sun(0.0, 73.0);
+    let ray_origins = model.ray_origins_for_window(&window);
+    let occluders = model.collect_occluders();
+    assert_almost_eq!(
+        model.sunlit_fraction(&window, &ray_origins, &ray_dir, &occluders),
+        1.0
+    );
+
+    // Lamas horizontales de 10 cm cada 10 cm, inclinadas 45º
+    window.louvers = Some(LouverShade {
+        orientation: LouverOrientation::HORIZONTAL,
+        slat_width: 0.10,
+        slat_spacing: 0.10,
+        slat_angle: 45.0,
+    });
+    model.windows = vec![window.clone()];
+    let occluders = model.collect_occluders();
+    assert_eq!(occluders.len(), 1 + 15);
+    let sunlit = model.sunlit_fraction(&window, &ray_origins, &ray_dir, &occluders);
+    assert!(sunlit < 0.1);
+
+    // Con sol bajo de invierno (altura ~26º) y lamas perpendiculares al hueco (0º)
+    // pasa la mayor parte de la radiación directa
+    window.louvers.as_mut().unwrap().slat_angle = 0.0;
+    model.windows = vec![window.clone()];
+    let ray_dir = ray_dir_to_sun(0.0, 26.0);
+    let occluders = model.collect_occluders();
+    let sunlit_winter = model.sunlit_fraction(&window, &ray_origins, &ray_dir, &occluders);
+    assert!(sunlit_winter > 0.5);
+}
+
+#[test]
+fn shade_seasonal_schedule() {
+    use bemodel::{Schedule, ScheduleDay, ScheduleWeek, Uuid};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let met = climate::met::parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+    // Hueco sur sin retranqueo ni otras sombras
+    model.shades.clear();
+    model.windows[0].geometry.setback = 0.0;
+    let win_id = get_window_by_name(&model, "P01_E01_PE001_V").id;
+    let fshobst_july = model.compute_fshobst()[&win_id];
+    let fshobst_winter = model.compute_fshobst_for_day(&met, 1, 15)[&win_id];
+
+    // Arbolado de hoja caduca al sur: con hoja de junio a septiembre y sin hoja el resto del año
+    model
+        .add_surrounding_building(
+            vec![
+                point![-10.0, -20.0],
+                point![-10.0, -8.0],
+                point![20.0, -8.0],
+                point![20.0, -20.0],
+            ],
+            30.0,
+        )
+        .unwrap();
+    let fshobst_opaque = model.compute_fshobst()[&win_id];
+    assert!(fshobst_opaque < fshobst_july - 0.1);
+
+    let leaves = ScheduleDay {
+        id: Uuid::new_v4(),
+        values: vec![0.2; 24],
+        ..Default::default()
+    };
+    let no_leaves = ScheduleDay {
+        id: Uuid::new_v4(),
+        values: vec![1.0; 24],
+        ..Default::default()
+    };
+    let summer = ScheduleWeek {
+        id: Uuid::new_v4(),
+        values: vec![(leaves.id, 7)],
+        ..Default::default()
+    };
+    let winter = ScheduleWeek {
+        id: Uuid::new_v4(),
+        values: vec![(no_leaves.id, 7)],
+        ..Default::default()
+    };
+    let year = Schedule {
+        id: Uuid::new_v4(),
+        values: vec![(winter.id, 151), (summer.id, 122), (winter.id, 92)],
+        ..Default::default()
+    };
+    for shade in &mut model.shades {
+        shade.schedule = Some(year.id);
+    }
+    model.schedules.day.extend([leaves, no_leaves]);
+    model.schedules.week.extend([summer, winter]);
+    model.schedules.year.push(year);
+
+    // En julio el arbolado deja pasar parte de la radiación directa
+    let fshobst_tree = model.compute_fshobst()[&win_id];
+    assert!(fshobst_tree < fshobst_july - 0.05);
+    assert!(fshobst_tree > fshobst_opaque);
+    // En invierno, sin hoja, no hace sombra
+    let fshobst_tree_winter = model.compute_fshobst_for_day(&met, 1, 15)[&win_id];
+    assert_almost_eq!(fshobst_tree_winter, fshobst_winter);
+}
+
+#[test]
+fn material_moisture_dependent_conductivity() {
+    use bemodel::{ConsDb, Layer, MatProps, Material, Uuid, WallCons};
+
+    init();
+
+    let material: Material = serde_json::from_str(
+        r#"{
+            "id": "f5b2d1b0-5c4e-4b6b-9a3a-5e0f1c7a2b11",
+            "name": "Ladrillo hueco",
+            "dry_conductivity": 0.5,
+            "moisture_factor": 4.0,
+            "density": 1000.0,
+            "specific_heat": 1000.0
+        }"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        material.properties,
+        MatProps::MoistureDependent { .. }
+    ));
+
+    // Con humedad nula la conductividad coincide con la del material seco
+    assert_almost_eq!(material.properties.conductivity(None).unwrap(), 0.5);
+    assert_almost_eq!(material.properties.conductivity(Some(0.0)).unwrap(), 0.5);
+    // lambda = 0,5 · exp(4 · 0,05) = 0,611
+    assert_almost_eq!(
+        material.properties.conductivity(Some(0.05)).unwrap(),
+        0.611,
+        0.001
+    );
+
+    let wallcons = WallCons {
+        id: Uuid::new_v4(),
+        name: "Fábrica".to_string(),
+        layers: vec![Layer {
+            material: material.id,
+            e: 0.1,
+            e_max: None,
+        }],
+        absorptance: 0.6,
+        emissivity: None,
+    };
+    let db = ConsDb {
+        wallcons: vec![wallcons.clone()],
+        materials: vec![material],
+        ..Default::default()
+    };
+    assert_almost_eq!(wallcons.resistance(&db).unwrap(), 0.2);
+    assert_almost_eq!(
+        wallcons.resistance_with_moisture(&db, Some(0.05)).unwrap(),
+        0.1637,
+        0.001
+    );
+}
+
+#[test]
+// Usamos map_or en lugar de Option::is_none_or (Rust 1.82) para no elevar la versión mínima de Rust
+#[allow(clippy::unnecessary_map_or)]
+fn model_regenerate_ids() {
+    use bemodel::{RadiantSurface, Uuid};
+
+    init();
+
+    let strdata = include_str!("./data/caso_a.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    // Superficie radiante con horario
+    model.radiant_surfaces.push(RadiantSurface {
+        id: Uuid::new_v4(),
+        name: "SUELO_RADIANTE".to_string(),
+        wall: model.get_wall_by_name("P02_E01_FI001").unwrap().id,
+        max_power: 20.0,
+        schedule: Some(model.schedules.year[0].id),
+    });
+
+    let mut model1 = model.clone();
+    model1.regenerate_ids("semilla");
+    let mut model2 = model.clone();
+    model2.regenerate_ids("semilla");
+    let mut model3 = model.clone();
+    model3.regenerate_ids("otra semilla");
+
+    // Resultado reproducible con la misma semilla
+    assert_eq!(model1.as_json().unwrap(), model2.as_json().unwrap());
+    assert_ne!(model1.as_json().unwrap(), model3.as_json().unwrap());
+    assert_ne!(model.spaces[0].id, model1.spaces[0].id);
+    assert_eq!(model.walls.len(), model1.walls.len());
+
+    // Sin referencias colgantes
+    let m = &model1;
+    for space in &m.spaces {
+        assert!(space
+            .loads
+            .map_or(true, |id| m.loads.iter().any(|l| l.id == id)));
+        assert!(space
+            .thermostat
+            .map_or(true, |id| m.thermostats.iter().any(|t| t.id == id)));
+    }
+    for wall in &m.walls {
+        assert!(m.get_space(wall.space).is_some());
+        assert!(wall.next_to.map_or(true, |id| m.get_space(id).is_some()));
+        assert!(m.cons.get_wallcons(wall.cons).is_some());
+    }
+    for window in &m.windows {
+        assert!(m.get_wall(window.wall).is_some());
+        assert!(m.cons.get_wincons(window.cons).is_some());
+    }
+    for cons in &m.cons.wallcons {
+        for layer in &cons.layers {
+            assert!(m.cons.get_material(layer.material).is_some());
+        }
+    }
+    for cons in &m.cons.wincons {
+        assert!(m.cons.get_glass(cons.glass).is_some());
+        assert!(m.cons.get_frame(cons.frame).is_some());
+    }
+    for loads in &m.loads {
+        for sch in [
+            loads.people_schedule,
+            loads.equipment_schedule,
+            loads.lighting_schedule,
+        ]
+        .iter()
+        .flatten()
+        {
+            assert!(m.schedules.get_year(*sch).is_some());
+        }
+    }
+    for sch in &m.schedules.year {
+        for (id, _) in &sch.values {
+            assert!(m.schedules.get_week(*id).is_some());
+        }
+    }
+    for sch in &m.schedules.week {
+        for (id, _) in &sch.values {
+            assert!(m.schedules.get_day(*id).is_some());
+        }
+    }
+    for radiant in &m.radiant_surfaces {
+        assert!(m.get_wall(radiant.wall).is_some());
+        assert!(radiant
+            .schedule
+            .map_or(true, |id| m.schedules.get_year(id).is_some()));
+    }
+    for id in m.overrides.walls.keys() {
+        assert!(m.get_wall(*id).is_some());
+    }
+    for id in m.overrides.windows.keys() {
+        assert!(m.get_window(*id).is_some());
+    }
+}
+
+#[test]
+fn azimuth_bdl_model_conversion() {
+    use bemodel::utils::{azimuth_bdl_to_model, azimuth_model_to_bdl};
+
+    // (orientación, azimuth BDL, azimuth del modelo)
+    let orientations = [
+        ("N", 0.0, -180.0),
+        ("NE", 45.0, 135.0),
+        ("E", 90.0, 90.0),
+        ("SE", 135.0, 45.0),
+        ("S", -180.0, 0.0),
+        ("SW", -135.0, -45.0),
+        ("W", -90.0, -90.0),
+        ("NW", -45.0, -135.0),
+    ];
+    for (name, bdl, model) in orientations {
+        assert_almost_eq!(azimuth_bdl_to_model(bdl), model);
+        assert_almost_eq!(azimuth_model_to_bdl(model), bdl);
+        // Ida y vuelta
+        assert_almost_eq!(azimuth_model_to_bdl(azimuth_bdl_to_model(bdl)), bdl);
+        assert_almost_eq!(azimuth_bdl_to_model(azimuth_model_to_bdl(model)), model);
+        // Ángulos fuera del intervalo [-180, 180)
+        assert_almost_eq!(azimuth_bdl_to_model(bdl + 360.0), model);
+        assert_almost_eq!(azimuth_model_to_bdl(model - 360.0), bdl);
+        info!("{}: BDL {} <-> modelo {}", name, bdl, model);
+    }
+    // Norte y sur con el otro extremo del intervalo
+    assert_almost_eq!(azimuth_bdl_to_model(180.0), 0.0);
+    assert_almost_eq!(azimuth_bdl_to_model(360.0), -180.0);
+    assert_almost_eq!(azimuth_model_to_bdl(180.0), 0.0);
+}
+
+#[test]
+fn model_to_legacy_json() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    // Nombres repetidos
+    let wall_name = model.walls[0].name.clone();
+    model.walls[1].name = wall_name.clone();
+
+    let json = bemodel::convert::to_legacy_json(&model).unwrap();
+    let legacy: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    let walls = legacy["walls"].as_object().unwrap();
+    assert_eq!(walls.len(), model.walls.len());
+    assert!(walls.contains_key(&wall_name));
+    assert!(walls.contains_key(&format!("{}_1", wall_name)));
+
+    // Referencias resueltas a nombres
+    let space_name = &model.spaces[0].name;
+    let wall = &walls[&wall_name];
+    assert!(wall.get("id").is_none());
+    assert_eq!(wall["space"].as_str().unwrap(), space_name);
+    let cons_name = &model.cons.get_wallcons(model.walls[0].cons).unwrap().name;
+    assert_eq!(wall["cons"].as_str().unwrap(), cons_name);
+    assert!(legacy["wallcons"].get(cons_name).is_some());
+    // Construcciones de huecos con la clave de la estructura antigua
+    let wincons_name = &model.cons.wincons[0].name;
+    assert!(legacy.get("wincons").is_none());
+    assert!(legacy["windowcons"].get(wincons_name).is_some());
+
+    // Campos con los nombres de la estructura antigua
+    for key in ["A", "bounds", "tilt", "azimuth"] {
+        assert!(wall.get(key).is_some());
+    }
+    for key in ["geometry", "next_to"] {
+        assert!(wall.get(key).is_none());
+    }
+    let legacy_wincons = &legacy["windowcons"][wincons_name];
+    for key in ["U", "Ff", "gglwi", "gglshwi", "C_100"] {
+        assert!(legacy_wincons.get(key).is_some());
+    }
+    assert!(legacy["wallcons"][cons_name].get("R_intrinsic").is_some());
+
+    let win = &model.windows[0];
+    let legacy_win = &legacy["windows"][&win.name];
+    assert!(legacy_win.get("A").is_some());
+    assert!(legacy_win.get("fshobst").is_some());
+    let win_wall_name = &model.get_wall(win.wall).unwrap().name;
+    assert!(legacy["walls"]
+        .get(legacy_win["wall"].as_str().unwrap())
+        .is_some());
+    assert!(legacy_win["wall"]
+        .as_str()
+        .unwrap()
+        .starts_with(win_wall_name.as_str()));
+}
+
+#[test]
+fn model_to_legacy_json_roundtrip() {
+    use bemodel::convert::{from_envolventecte_json, to_legacy_json};
+    init();
+
+    // Modelo con sombras, cuyo efecto se exporta en el factor de obstáculos remotos
+    for strdata in [
+        include_str!("./data/cubo.json"),
+        include_str!("./data/caso_a.json"),
+    ] {
+        let model = Model::from_json(strdata).unwrap();
+        let imported = from_envolventecte_json(&to_legacy_json(&model).unwrap()).unwrap();
+
+        assert_eq!(imported.walls.len(), model.walls.len());
+        assert_eq!(imported.windows.len(), model.windows.len());
+
+        let ind = model.energy_indicators();
+        let ind_imported = imported.energy_indicators();
+        assert_almost_eq!(ind_imported.area_ref, ind.area_ref, 0.01);
+        assert_almost_eq!(ind_imported.K_data.K, ind.K_data.K, 0.01);
+        assert_almost_eq!(
+            ind_imported.q_soljul_data.q_soljul,
+            ind.q_soljul_data.q_soljul,
+            0.01
+        );
+    }
+}
+
+#[test]
+fn model_to_ctehexml_roundtrip() {
+    init();
+
+    for strdata in [
+        include_str!("./data/cubo.json"),
+        include_str!("./data/e4h_medianeras.json"),
+    ] {
+        let model = Model::from_json(strdata).unwrap();
+        let ctehexml = bemodel::convert::to_ctehexml(&model).unwrap();
+
+        // Construcciones sin duplicados y plantas con la geometría de sus espacios
+        let bdl = ctehexml
+            .split("<![CDATA[")
+            .nth(1)
+            .and_then(|s| s.split("]]>").next())
+            .unwrap();
+        let blocks = hulc::bdl::build_blocks(bdl).unwrap();
+        let mut construction_names: Vec<_> = blocks
+            .iter()
+            .filter(|b| b.btype == hulc::bdl::BdlBlockType::Construction)
+            .map(|b| b.name.as_str())
+            .collect();
+        let num_constructions = construction_names.len();
+        construction_names.sort_unstable();
+        construction_names.dedup();
+        assert_eq!(construction_names.len(), num_constructions);
+        let polygons_area = |suffix: &str| -> f32 {
+            blocks
+                .iter()
+                .filter(|b| b.btype == hulc::bdl::BdlBlockType::Polygon && b.name.ends_with(suffix))
+                .map(|b| hulc::bdl::Polygon::try_from(b.clone()).unwrap().area())
+                .sum()
+        };
+        assert_almost_eq!(
+            polygons_area("_PolPlanta"),
+            polygons_area("_PolEspacio"),
+            0.1
+        );
+
+        let data = hulc::ctehexml::parse(&ctehexml).unwrap();
+        let roundtrip = Model::try_from(&data).unwrap();
+
+        assert_eq!(roundtrip.spaces.len(), model.spaces.len());
+        assert_eq!(roundtrip.walls.len(), model.walls.len());
+        assert_eq!(roundtrip.windows.len(), model.windows.len());
+        for wall in &model.walls {
+            let other = roundtrip.get_wall_by_name(&wall.name).unwrap();
+            assert_eq!(other.bounds, wall.bounds);
+            assert_almost_eq!(other.area(), wall.area(), 0.01);
+            assert_almost_eq!(
+                other.area_net(&roundtrip.windows),
+                wall.area_net(&model.windows),
+                0.01
+            );
+        }
+        for win in &model.windows {
+            let other = get_window_by_name(&roundtrip, &win.name);
+            assert_almost_eq!(other.area(), win.area(), 0.01);
+        }
+        assert_almost_eq!(roundtrip.built_area(), model.built_area(), 0.1);
+        assert_almost_eq!(
+            roundtrip.energy_indicators().K_data.K,
+            model.energy_indicators().K_data.K,
+            0.001
+        );
+    }
+}
+
+#[test]
+fn intersections() {
+    init();
+
+    // Horizontal
+    let geom1 = WallGeom {
+        tilt: 0.0,
+        azimuth: 0.0,
+        position: Some(point![0.0, 0.0, 0.0]),
+        polygon: vec![
+            point![0.0, 0.0],
+            point![2.0, 0.0],
+            point![2.0, 2.0],
+            point![0.0, 2.0],
+        ],
+    };
+
+    // Vertical a Sur
+    let geom2 = WallGeom {
+        tilt: 90.0,
+        azimuth: 0.0,
+        position: Some(point![0.0, 0.0, 0.0]),
+        polygon: vec![
+            point![0.0, 0.0],
+            point![2.0, 0.0],
+            point![2.0, 2.0],
+            point![0.0, 2.0],
+        ],
+    };
+
+    // Vertical a Este
+    let geom3 = WallGeom {
+        tilt: 90.0,
+        azimuth: 90.0,
+        position: Some(point![0.0, 0.0, 0.0]),
+        polygon: vec![
+            point![0.0, 0.0],
+            point![2.0, 0.0],
+            point![2.0, 2.0],
+            point![0.0, 2.0],
+        ],
+    };
+
+    // Vertical sur
+    let geom4 = WallGeom {
+        tilt: 90.0,
+        azimuth: 0.0,
+        position: Some(point![9.11, 0.0, 9.5]),
+        polygon: vec![
+            point![0.0, 0.0],
+            point![9.11, 0.0],
+            point![9.11, 3.0],
+            point![0.0, 3.0],
+        ],
+    };
+
+    #[allow(clippy::approx_constant)]
+    let testcases = vec![
+        (
+            &geom1,
+            Ray::new(point![1.0, 1.0, 2.0], vector![0.0, 0.0, -1.0]),
+            true,
+        ),
+        (
+            &geom2,
+            Ray::new(point![1.0, 1.0, 2.0], vector![0.0, 0.0, -1.0]),
+            false,
+        ),
+        (
+            &geom2,
+            Ray::new(point![1.0, 2.0, 1.0], vector![0.0, -1.0, 0.0]),
+            true,
+        ),
+        (
+            &geom3,
+            Ray::new(point![1.0, 1.0, 2.0], vector![0.0, 0.0, -1.0]),
+            false,
+        ),
+        (
+            &geom3,
+            Ray::new(point![2.0, 1.0, 1.0], vector![-1.0, 0.0, 0.0]),
+            true,
+        ),
+        (
+            &geom3,
+            Ray::new(point![-2.0, 1.0, 1.0], vector![1.0, 0.0, 0.0]),
+            true,
+        ),
+        (
+            &geom3,
+            Ray::new(point![-2.0, 1.0, 1.0], vector![-1.0, 0.0, 0.0]),
+            false,
+        ),
+        (
+            &geom4,
+            Ray::new(
+                point![-0.709, 2.1975, 0.0],
+                vector![0.0, -0.707106, 0.707106],
+            ),
+            false,
+        ),
+    ];
+
+    for (geo, r, res) in testcases {
+        info!("Inclinación: {}, azimuth: {}", geo.tilt, geo.azimuth);
+        info!("Polígono: {:?}", geo.polygon);
+        info!("Posición: {:?}", geo.position);
+        info!("Rayo: {}, {}", r.origin, r.dir);
+        let result = &geo.intersects(&r);
+        info!("Intersección con rayo: {:?}", result);
+        assert!(res == result.is_some());
+    }
+}
+
+#[test]
+fn aabb_intersections() {
+    let aabb1 = AABB {
+        min: point![1.0, 1.0, 1.0],
+        max: point![5.0, 5.0, 5.0],
+    };
+
+    assert!(aabb1
+        .intersects(&Ray::new(point![0.0, 0.0, 0.0], vector![1.0, 1.0, 1.0]))
+        .is_some());
+
+    assert!(aabb1
+        .intersects(&Ray::new(point![6.0, 6.0, 6.0], vector![1.0, 1.0, 1.0]))
+        .is_none());
+
+    assert!(aabb1
+        .intersects(&Ray::new(point![6.0, 6.0, 6.0], vector![-1.0, -1.0, -1.0]))
+        .is_some());
 
     assert!(aabb1
         .intersects(&Ray::new(point![0.0, 2.0, 2.0], vector![1.0, 0.0, 0.0]))
         .is_some());
 
-    assert!(aabb1
-        .intersects(&Ray::new(point![0.0, 2.0, 2.0], vector![0.0, 0.0, 1.0]))
+    assert!(aabb1
+        .intersects(&Ray::new(point![0.0, 2.0, 2.0], vector![0.0, 0.0, 1.0]))
+        .is_none());
+}
+
+#[test]
+fn wallcons_r_intrinsic_and_u_value() {
+    use bemodel::{ConsDb, Layer, MatProps, Material, WallCons};
+
+    let brick = Material {
+        name: "Ladrillo".into(),
+        properties: MatProps::Detailed {
+            conductivity: 0.5,
+            density: 1000.0,
+            specific_heat: 1000.0,
+            vapour_diff: None,
+        },
+        ..Default::default()
+    };
+    let cavity = Material {
+        name: "Cámara de aire".into(),
+        properties: MatProps::Resistance {
+            resistance: 0.18,
+            vapour_diff: None,
+            thickness: None,
+        },
+        ..Default::default()
+    };
+    let insulation = Material {
+        name: "Aislante".into(),
+        properties: MatProps::Detailed {
+            conductivity: 0.04,
+            density: 30.0,
+            specific_heat: 1000.0,
+            vapour_diff: None,
+        },
+        ..Default::default()
+    };
+    let wallcons = WallCons {
+        layers: vec![
+            Layer {
+                material: brick.id,
+                e: 0.1,
+                e_max: None,
+            },
+            Layer {
+                material: cavity.id,
+                e: 0.02,
+                e_max: None,
+            },
+            Layer {
+                material: insulation.id,
+                e: 0.06,
+                e_max: None,
+            },
+        ],
+        ..Default::default()
+    };
+    let db = ConsDb {
+        materials: vec![brick, cavity, insulation],
+        ..Default::default()
+    };
+
+    // 0.1 / 0.5 + 0.18 + 0.06 / 0.04 = 1.88
+    assert_almost_eq!(wallcons.r_intrinsic(&db), 1.88);
+    assert_almost_eq!(wallcons.u_value(&db, 0.13, 0.04), 1.0 / 2.05);
+}
+
+#[test]
+fn wallcons_with_target_u() {
+    use bemodel::{ConsDb, Layer, MatProps, Material, WallCons};
+
+    let brick = Material {
+        name: "Ladrillo".into(),
+        properties: MatProps::Detailed {
+            conductivity: 0.5,
+            density: 1000.0,
+            specific_heat: 1000.0,
+            vapour_diff: None,
+        },
+        ..Default::default()
+    };
+    let cavity = Material {
+        name: "Cámara de aire".into(),
+        properties: MatProps::Resistance {
+            resistance: 0.18,
+            vapour_diff: None,
+            thickness: None,
+        },
+        ..Default::default()
+    };
+    let insulation = Material {
+        name: "Aislante".into(),
+        properties: MatProps::Detailed {
+            conductivity: 0.035,
+            density: 30.0,
+            specific_heat: 1000.0,
+            vapour_diff: None,
+        },
+        ..Default::default()
+    };
+    let base_layers = vec![
+        Layer {
+            material: brick.id,
+            e: 0.1,
+            e_max: None,
+        },
+        Layer {
+            material: cavity.id,
+            e: 0.02,
+            e_max: None,
+        },
+    ];
+    let (insulation_id, cavity_id) = (insulation.id, cavity.id);
+    let db = ConsDb {
+        materials: vec![brick, cavity, insulation],
+        ..Default::default()
+    };
+
+    // R = 1 / 0.3 - (0.13 + 0.1 / 0.5 + 0.18 + 0.04) = 2.783 m²K/W -> e = 2.783 · 0.035 = 0.0974 m
+    let cons = WallCons::with_target_u(base_layers.clone(), insulation_id, 0.3, &db).unwrap();
+    assert_eq!(cons.layers.len(), 3);
+    assert_eq!(cons.layers[2].material, insulation_id);
+    assert_almost_eq!(cons.layers[2].e, 0.098);
+    let u = cons.u_value(&db, 0.13, 0.04);
+    assert!(u <= 0.3);
+    assert_almost_eq!(u, 0.3, 0.002);
+
+    // Las capas base ya cumplen la U objetivo
+    let cons = WallCons::with_target_u(base_layers.clone(), insulation_id, 2.0, &db).unwrap();
+    assert_eq!(cons.layers.len(), 2);
+
+    // Aislante sin conductividad, material inexistente o U no válida
+    assert!(WallCons::with_target_u(base_layers.clone(), cavity_id, 0.3, &db).is_err());
+    assert!(
+        WallCons::with_target_u(base_layers.clone(), bemodel::Uuid::new_v4(), 0.3, &db).is_err()
+    );
+    assert!(WallCons::with_target_u(base_layers, insulation_id, 0.0, &db).is_err());
+}
+
+#[test]
+fn wallcons_tapered_layer() {
+    use bemodel::{ConsDb, Layer, MatProps, Material, WallCons};
+    init();
+
+    let material = |conductivity: f32| Material {
+        properties: MatProps::Detailed {
+            conductivity,
+            density: 1000.0,
+            specific_heat: 1000.0,
+            vapour_diff: None,
+        },
+        ..Default::default()
+    };
+    let slab = material(2.0);
+    let slope = material(0.2);
+    let insulation = material(0.035);
+    let layers = |e: f32, e_max: Option<f32>| {
+        vec![
+            Layer {
+                material: insulation.id,
+                e: 0.04,
+                e_max: None,
+            },
+            Layer {
+                material: slope.id,
+                e,
+                e_max,
+            },
+            Layer {
+                material: slab.id,
+                e: 0.2,
+                e_max: None,
+            },
+        ]
+    };
+    let cons = |layers| WallCons {
+        layers,
+        ..Default::default()
+    };
+    let db = ConsDb {
+        materials: vec![slab.clone(), slope.clone(), insulation.clone()],
+        ..Default::default()
+    };
+
+    // Formación de pendientes de 2 a 10 cm
+    let tapered = cons(layers(0.02, Some(0.10)));
+    let thin = cons(layers(0.02, None));
+    let thick = cons(layers(0.10, None));
+    let mean = cons(layers(0.06, None));
+    assert_almost_eq!(tapered.thickness(), 0.30);
+    let (r_min, r_max) = tapered.resistance_range(&db).unwrap();
+    assert_almost_eq!(r_min, thin.resistance(&db).unwrap());
+    assert_almost_eq!(r_max, thick.resistance(&db).unwrap());
+    assert_almost_eq!(
+        tapered.resistance(&db).unwrap(),
+        mean.resistance(&db).unwrap()
+    );
+
+    // La U media queda entre la de ambos extremos y es mayor que la del espesor medio
+    let u = |c: &WallCons| c.u_value(&db, 0.10, 0.04);
+    let u_tapered = u(&tapered);
+    assert!(u(&thick) < u_tapered && u_tapered < u(&thin));
+    assert!(u_tapered > u(&mean));
+    // U = ln(1 + R_2 / R_0) / R_2, R_0 = 0.10 + 0.04 / 0.035 + 0.02 / 0.2 + 0.2 / 2.0 + 0.04, R_2 = 0.08 / 0.2
+    assert_almost_eq!(u_tapered, 0.597, 0.001);
+
+    // En el modelo, la cubierta exterior usa la U media
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let ids = [tapered.id, thin.id, thick.id];
+    model.cons = db;
+    model.cons.wallcons = vec![tapered, thin, thick];
+    let u_roof = |cons_id| {
+        let mut roof = model.get_wall_by_name("P01_E01C001").unwrap().clone();
+        roof.cons = cons_id;
+        roof.u_value(&model).unwrap()
+    };
+    let u_model = u_roof(ids[0]);
+    assert!(u_roof(ids[2]) < u_model && u_model < u_roof(ids[1]));
+}
+
+#[test]
+fn energy_indicators_tb_contributions() {
+    init();
+
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+    let ind = model.energy_indicators();
+    let contributions = &ind.tb_contributions;
+    assert!(contributions.len() > 1);
+
+    // La suma de aportaciones coincide con el término de puentes térmicos de K
+    let psil: f32 = contributions.iter().map(|c| c.psil).sum();
+    assert_almost_eq!(psil, ind.K_data.summary.tbs_psil, 0.01);
+    assert_almost_eq!(psil, ind.K_data.h_tr.tbs, 0.01);
+    let percent: f32 = contributions.iter().map(|c| c.percent).sum();
+    assert_almost_eq!(percent, 100.0, 0.01);
+
+    // Ordenadas de mayor a menor aportación
+    assert!(contributions.windows(2).all(|w| w[0].psil >= w[1].psil));
+    let first = &contributions[0];
+    let tb = model
+        .thermal_bridges
+        .iter()
+        .find(|tb| tb.id == first.id)
+        .unwrap();
+    assert_almost_eq!(first.psil, tb.psi * first.l);
+}
+
+#[test]
+fn energy_indicators_unit_systems() {
+    use bemodel::energy::UnitSystem;
+    init();
+
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+    let si = model.energy_indicators();
+    let ip = si.to_units(UnitSystem::IP);
+
+    // 1 ft = 0,3048 m, 1 Btu/h·ft²·ºF = 5,678263 W/m²K, 1 cfm = 1,699011 m³/h
+    assert_eq!(ip.units, UnitSystem::IP);
+    assert_almost_eq!(ip.area_ref, si.area_ref / 0.09290304, 0.01);
+    assert_almost_eq!(ip.vol_env_net, si.vol_env_net / 0.028316847, 0.1);
+    assert_almost_eq!(ip.K_data.K, si.K_data.K / 5.678263);
+    assert_almost_eq!(ip.H_ve, si.H_ve / 0.5275279, 0.01);
+    assert_almost_eq!(
+        ip.n50_data.walls_c_a,
+        si.n50_data.walls_c_a / 1.699011,
+        0.01
+    );
+    assert_almost_eq!(ip.n50_data.n50, si.n50_data.n50);
+
+    // Ida y vuelta SI -> IP -> SI
+    let back = ip.to_units(UnitSystem::SI);
+    assert_eq!(back.units, UnitSystem::SI);
+    assert_almost_eq!(back.area_ref, si.area_ref, 0.01);
+    assert_almost_eq!(back.compactness, si.compactness);
+    assert_almost_eq!(back.vol_env_gross, si.vol_env_gross, 0.01);
+    assert_almost_eq!(back.K_data.K, si.K_data.K);
+    assert_almost_eq!(back.K_data.summary.au, si.K_data.summary.au, 0.01);
+    assert_almost_eq!(back.K_data.tbs.window.l, si.K_data.tbs.window.l, 0.01);
+    assert_almost_eq!(back.n50_data.walls_c, si.n50_data.walls_c);
+    assert_almost_eq!(back.q_soljul_data.a_wp, si.q_soljul_data.a_wp, 0.01);
+    assert_almost_eq!(
+        back.K_data.walls.u_mean.unwrap(),
+        si.K_data.walls.u_mean.unwrap()
+    );
+
+    // Sin cambio de sistema los valores no varían
+    assert_eq!(si.to_units(UnitSystem::SI).K_data.K, si.K_data.K);
+}
+
+#[test]
+fn model_fix_wall_normals() {
+    use bemodel::{Orientation, Tilt};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    assert_eq!(model.fix_wall_normals(), 0);
+
+    // Giramos 180º un muro exterior con hueco, que queda en el mismo plano con la normal hacia el interior
+    let win = model.get_window_by_name("P01_E01_PE001_V").unwrap().clone();
+    let wall = model.walls.iter_mut().find(|w| w.id == win.wall).unwrap();
+    let wall_id = wall.id;
+    let orientation = Orientation::from(&*wall);
+    let normal = wall.geometry.outward_normal();
+    wall.geometry.azimuth = bemodel::utils::normalize(wall.geometry.azimuth + 180.0, -180.0, 180.0);
+    assert_ne!(Orientation::from(&*wall), orientation);
+
+    assert_eq!(model.fix_wall_normals(), 1);
+    let wall = model.get_wall(wall_id).unwrap();
+    assert_eq!(Orientation::from(wall), orientation);
+    assert_eq!(Tilt::from(wall), Tilt::SIDE);
+    assert_almost_eq!((wall.geometry.outward_normal() - normal).norm(), 0.0, 0.001);
+    // El hueco se recoloca en la nueva geometría del muro
+    let new_win = model.get_window(win.id).unwrap();
+    assert_eq!(new_win.wall, wall_id);
+    assert_eq!(wall.geometry.contains_window(&new_win.geometry), Some(true));
+    assert_almost_eq!(new_win.area(), win.area());
+    assert_eq!(model.fix_wall_normals(), 0);
+
+    // Un polígono con los vértices en sentido horario no se considera invertido
+    let wall = model.walls.iter_mut().find(|w| w.id == wall_id).unwrap();
+    wall.geometry.polygon.reverse();
+    assert_eq!(model.fix_wall_normals(), 0);
+}
+
+#[test]
+fn schedules_expand_holidays() {
+    use bemodel::{Schedule, ScheduleDay, ScheduleWeek, SchedulesDb, Uuid};
+
+    // Horario fraccionario de laborables, sábados al 50% y domingos y festivos apagado
+    let workday = ScheduleDay {
+        id: Uuid::new_v4(),
+        values: (0..24)
+            .map(|h| if (8..18).contains(&h) { 1.0 } else { 0.25 })
+            .collect(),
+        ..Default::default()
+    };
+    let saturday = ScheduleDay {
+        id: Uuid::new_v4(),
+        values: vec![0.5; 24],
+        ..Default::default()
+    };
+    let sunday = ScheduleDay {
+        id: Uuid::new_v4(),
+        values: vec![0.0; 24],
+        ..Default::default()
+    };
+    let week = ScheduleWeek {
+        id: Uuid::new_v4(),
+        values: vec![(workday.id, 5), (saturday.id, 1), (sunday.id, 1)],
+        ..Default::default()
+    };
+    let year = Schedule {
+        values: vec![(week.id, 365)],
+        ..Default::default()
+    };
+    let year_id = year.id;
+    let mut db = SchedulesDb {
+        year: vec![year],
+        week: vec![week],
+        day: vec![workday, saturday, sunday],
+    };
+
+    // Sin festivos: lunes 1, sábado 6 y domingo 7 de enero
+    let values = db.year_values(year_id);
+    assert_eq!(values.len(), 8760);
+    assert_eq!(values, db.expand(year_id, &[]));
+    assert_almost_eq!(values[12], 1.0);
+    assert_almost_eq!(values[5 * 24 + 12], 0.5);
+    assert!(values[6 * 24..7 * 24].iter().all(|v| *v == 0.0));
+    // Todos los domingos del año están apagados
+    assert!((0..365)
+        .filter(|d| d % 7 == 6)
+        .all(|d| values[d * 24..(d + 1) * 24].iter().all(|v| *v == 0.0)));
+
+    // Festivos (1 y 6 de enero) sin horario de festivos: se usa el del domingo
+    let values = db.expand(year_id, &[1, 6]);
+    assert_eq!(values.len(), 8760);
+    assert!(values[0..24].iter().all(|v| *v == 0.0));
+    assert!(values[5 * 24..6 * 24].iter().all(|v| *v == 0.0));
+    assert_almost_eq!(values[24 + 12], 1.0);
+
+    // Con horario de festivos propio
+    let holiday = ScheduleDay {
+        id: Uuid::new_v4(),
+        values: vec![0.1; 24],
+        ..Default::default()
+    };
+    db.week[0].holiday = Some(holiday.id);
+    db.day.push(holiday);
+    let values = db.expand(year_id, &[1]);
+    assert_almost_eq!(values[12], 0.1);
+    assert!(values[6 * 24..7 * 24].iter().all(|v| *v == 0.0));
+}
+
+#[test]
+fn model_lighting_power_and_energy() {
+    use bemodel::{Schedule, ScheduleDay, ScheduleWeek, SpaceLoads};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+
+    // Iluminación de 10 W/m² al 50% durante 10 horas al día
+    let day = ScheduleDay {
+        values: (0..24)
+            .map(|h| if (8..18).contains(&h) { 0.5 } else { 0.0 })
+            .collect(),
+        ..Default::default()
+    };
+    let week = ScheduleWeek {
+        values: vec![(day.id, 7)],
+        ..Default::default()
+    };
+    let year = Schedule {
+        values: vec![(week.id, 365)],
+        ..Default::default()
+    };
+    let loads = SpaceLoads {
+        lighting: 10.0,
+        lighting_schedule: Some(year.id),
+        ..Default::default()
+    };
+    model.schedules.day.push(day);
+    model.schedules.week.push(week);
+    model.schedules.year.push(year);
+
+    let space = &mut model.spaces[0];
+    space.loads = Some(loads.id);
+    space.multiplier = 2.0;
+    let space_id = space.id;
+    let other_ids: Vec<_> = model.spaces[1..].iter().map(|s| s.id).collect();
+    for s in &mut model.spaces[1..] {
+        s.loads = None;
+    }
+    model.loads.push(loads);
+
+    let density = model.lighting_power_density();
+    assert_eq!(density[&space_id], 10.0);
+    assert!(other_ids.iter().all(|id| density[id] == 0.0));
+
+    // 10 W/m² · A · 2 · (365 · 10 h · 0,5) / 1000
+    let area = model.spaces[0].area(&model.walls);
+    assert_almost_eq!(
+        model.lighting_energy_annual(),
+        10.0 * area * 2.0 * 1825.0 / 1000.0,
+        0.01
+    );
+}
+
+#[test]
+fn window_double_window_u_value() {
+    use bemodel::{DoubleWindow, Uuid, WinCons};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let win_id = get_window_by_name(&model, "P01_E01_PE001_V").id;
+    let win = model.get_window(win_id).unwrap();
+    let u_outer = win.u_value(&model.cons).unwrap();
+
+    // Hoja interior con la misma construcción y sin marco
+    let inner = WinCons {
+        id: Uuid::new_v4(),
+        name: "Hoja interior".to_string(),
+        f_f: 0.0,
+        ..model.cons.get_wincons(win.cons).unwrap().clone()
+    };
+    let inner_id = inner.id;
+    let u_inner = inner.u_value(&model.cons).unwrap();
+    model.cons.wincons.push(inner);
+
+    let win = model.windows.iter_mut().find(|w| w.id == win_id).unwrap();
+    win.double_window = Some(DoubleWindow {
+        cons: inner_id,
+        r_cavity: 0.18,
+    });
+    let win = model.get_window(win_id).unwrap();
+    let u_double = win.u_value(&model.cons).unwrap();
+    // Resistencias en serie, descontando las superficiales de las caras hacia la cámara
+    assert_almost_eq!(
+        u_double,
+        1.0 / (1.0 / u_outer - 0.13 + 0.18 - 0.04 + 1.0 / u_inner),
+        0.01
+    );
+    assert!(u_double < u_outer && u_double < u_inner);
+    // Las propiedades energéticas usan la U combinada
+    let props = bemodel::energy::EnergyProps::from(&model);
+    assert_almost_eq!(props.windows[&win_id].u_value.unwrap(), u_double);
+
+    // La construcción de la hoja interior se conserva al purgar el modelo
+    bemodel::purge_unused(&mut model);
+    assert!(model.cons.get_wincons(inner_id).is_some());
+}
+
+#[test]
+fn model_internal_gains_hourly() {
+    use bemodel::{Schedule, ScheduleDay, ScheduleWeek, SpaceLoads, Uuid};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+
+    // Ocupación constante y equipos encendidos de 8 a 18h
+    let always = ScheduleDay {
+        id: Uuid::new_v4(),
+        values: vec![1.0; 24],
+        ..Default::default()
+    };
+    let office = ScheduleDay {
+        id: Uuid::new_v4(),
+        values: (0..24)
+            .map(|h| if (8..18).contains(&h) { 1.0 } else { 0.0 })
+            .collect(),
+        ..Default::default()
+    };
+    let mut schedule_ids = vec![];
+    for day in [always, office] {
+        let week = ScheduleWeek {
+            id: Uuid::new_v4(),
+            values: vec![(day.id, 7)],
+            ..Default::default()
+        };
+        let year = Schedule {
+            id: Uuid::new_v4(),
+            values: vec![(week.id, 365)],
+            ..Default::default()
+        };
+        schedule_ids.push(year.id);
+        model.schedules.day.push(day);
+        model.schedules.week.push(week);
+        model.schedules.year.push(year);
+    }
+    let loads = SpaceLoads {
+        id: Uuid::new_v4(),
+        people_sensible: 5.0,
+        people_schedule: Some(schedule_ids[0]),
+        equipment: 4.0,
+        equipment_schedule: Some(schedule_ids[1]),
+        // Sin horario de iluminación no hay ganancias por iluminación
+        lighting: 10.0,
+        ..Default::default()
+    };
+
+    let space = &mut model.spaces[0];
+    space.loads = Some(loads.id);
+    space.multiplier = 2.0;
+    let space_id = space.id;
+    model.loads.push(loads);
+    let area = model.spaces[0].area(&model.walls);
+
+    let gains = model.internal_gains_hourly(space_id);
+    assert_eq!(gains.len(), 8760);
+    assert_almost_eq!(gains[0], 5.0 * area * 2.0);
+    assert_almost_eq!(gains[12], (5.0 + 4.0) * area * 2.0);
+    // Total anual: 5 W/m² · 8760 h + 4 W/m² · 3650 h
+    assert_almost_eq!(
+        gains.iter().sum::<f32>() / 1000.0,
+        (5.0 * 8760.0 + 4.0 * 3650.0) * area * 2.0 / 1000.0,
+        0.1
+    );
+
+    // Espacio inexistente
+    assert!(model
+        .internal_gains_hourly(Uuid::new_v4())
+        .iter()
+        .all(|g| *g == 0.0));
+}
+
+#[test]
+fn skylight_horizontal_vs_vertical_south() {
+    use bemodel::{energy::ray_dir_to_sun, Orientation};
+    use climate::parsemet;
+
+    init();
+
+    // Modelo en D3 con un hueco sur de 2 x 1 m con retranqueo de 20 cm
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let met = parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+    let south_id = get_window_by_name(&model, "P01_E01_PE001_V").id;
+    let cons = get_window_by_name(&model, "P01_E01_PE001_V").cons;
+
+    // Lucernario horizontal de las mismas dimensiones y retranqueo
+    let roof_id = model.get_wall_by_name("P01_E01C001").unwrap().id;
+    let skylight_id = model.add_window(roof_id, 6.0, 1.0, 2.0, 1.0, cons).unwrap();
+    model
+        .windows
+        .iter_mut()
+        .find(|w| w.id == skylight_id)
+        .unwrap()
+        .geometry
+        .setback = 0.2;
+    let skylight = model.get_window(skylight_id).unwrap().clone();
+
+    // Con sol alto el retranqueo apenas sombrea el lucernario y con sol bajo lo sombrea en buena parte
+    let ray_origins = model.ray_origins_for_window(&skylight);
+    let occluders = model.collect_occluders();
+    let high_sun = ray_dir_to_sun(0.0, 80.0);
+    let low_sun = ray_dir_to_sun(0.0, 10.0);
+    let sunlit_high = model.sunlit_fraction(&skylight, &ray_origins, &high_sun, &occluders);
+    let sunlit_low = model.sunlit_fraction(&skylight, &ray_origins, &low_sun, &occluders);
+    assert!(sunlit_high > 0.8);
+    assert!(sunlit_low < 0.5);
+
+    // El lucernario se computa en la orientación horizontal del q_sol;jul
+    let ind = model.energy_indicators();
+    assert_almost_eq!(ind.q_soljul_data.detail[&Orientation::HZ].a, 2.0, 0.01);
+    let fshobst = model.compute_fshobst();
+    assert!(fshobst[&skylight_id] > 0.5 && fshobst[&skylight_id] <= 1.0);
+
+    // En julio la ganancia del lucernario horizontal supera a la del hueco vertical sur
+    let gains = model.solar_gains_hourly(&met);
+    let july_gains = |id| -> f32 {
+        met.data
+            .iter()
+            .zip(&gains[&id])
+            .filter(|(d, _)| d.month == 7)
+            .map(|(_, g)| g)
+            .sum()
+    };
+    assert!(july_gains(skylight_id) > 1.5 * july_gains(south_id));
+
+    // El sentido de giro del polígono de la cubierta no afecta al soleamiento del lucernario
+    model
+        .walls
+        .iter_mut()
+        .find(|w| w.id == roof_id)
+        .unwrap()
+        .geometry
+        .polygon
+        .reverse();
+    let ray_origins = model.ray_origins_for_window(&skylight);
+    let occluders = model.collect_occluders();
+    assert!(model.sunlit_fraction(&skylight, &ray_origins, &high_sun, &occluders) > 0.8);
+}
+
+#[test]
+fn model_merge_blocks() {
+    use bemodel::{RadiantSurface, Uuid};
+    use std::collections::HashSet;
+
+    init();
+
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let mut block = Model::from_json(strdata).unwrap();
+    let radiant_wall_id = block.walls[0].id;
+    block.radiant_surfaces.push(RadiantSurface {
+        id: Uuid::new_v4(),
+        name: "SUELO_RADIANTE".to_string(),
+        wall: radiant_wall_id,
+        max_power: 20.0,
+        schedule: Some(block.schedules.year[0].id),
+    });
+    let mut model = block.clone();
+    model.merge(&block, vector![100.0, 0.0, 0.0]);
+
+    // Se duplican los elementos pero no las construcciones ni los horarios equivalentes
+    assert_eq!(model.spaces.len(), 2 * block.spaces.len());
+    assert_eq!(model.walls.len(), 2 * block.walls.len());
+    assert_eq!(model.windows.len(), 2 * block.windows.len());
+    assert_eq!(model.thermal_bridges.len(), 2 * block.thermal_bridges.len());
+    assert_eq!(model.cons.wallcons.len(), block.cons.wallcons.len());
+    assert_eq!(model.cons.materials.len(), block.cons.materials.len());
+    assert_eq!(model.schedules.year.len(), block.schedules.year.len());
+    let wall_ids: HashSet<_> = model.walls.iter().map(|w| w.id).collect();
+    assert_eq!(wall_ids.len(), model.walls.len());
+
+    // Las superficies radiantes del segundo bloque apuntan a sus propios opacos y a los horarios comunes
+    assert_eq!(model.radiant_surfaces.len(), 2);
+    let radiant = &model.radiant_surfaces[1];
+    assert_ne!(radiant.wall, radiant_wall_id);
+    assert_eq!(
+        model.get_wall(radiant.wall).unwrap().name,
+        block.walls[0].name
+    );
+    assert_eq!(radiant.schedule, block.radiant_surfaces[0].schedule);
+
+    // La geometría del segundo bloque está desplazada
+    let pos = block.walls[0].geometry.position.unwrap();
+    let new_pos = model.walls[block.walls.len()].geometry.position.unwrap();
+    assert_almost_eq!(new_pos.x, pos.x + 100.0);
+    assert_almost_eq!(new_pos.y, pos.y);
+
+    // La superficie de referencia se duplica y K se mantiene
+    let ind_block = block.energy_indicators();
+    let ind = model.energy_indicators();
+    assert_almost_eq!(ind.area_ref, 2.0 * ind_block.area_ref, 0.1);
+    assert_almost_eq!(ind.K_data.K, ind_block.K_data.K, 0.01);
+}
+
+#[test]
+fn thermal_bridge_2d_facade_slab() {
+    use bemodel::energy::thermal_bridge_2d::{FacadeSlabJunction, SectionLayer};
+
+    let layer = |e, conductivity| SectionLayer { e, conductivity };
+    // Fachada con aislamiento por el exterior (SATE): enlucido, ladrillo, aislamiento y revoco
+    let etics = vec![
+        layer(0.015, 0.57),
+        layer(0.24, 0.85),
+        layer(0.06, 0.035),
+        layer(0.01, 1.0),
+    ];
+    // Fachada de doble hoja con aislamiento en cámara: enlucido, ladrillo, aislamiento, ladrillo y revoco
+    let cavity = vec![
+        layer(0.015, 0.57),
+        layer(0.07, 0.4),
+        layer(0.05, 0.035),
+        layer(0.115, 0.85),
+        layer(0.015, 1.3),
+    ];
+    let junction = |wall_layers: &Vec<SectionLayer>, slab_thickness, slab_interrupted_layers| {
+        FacadeSlabJunction {
+            wall_layers: wall_layers.clone(),
+            slab_thickness,
+            slab_conductivity: 2.3,
+            slab_interrupted_layers,
+            flanking_length: 1.0,
+        }
+        .compute()
+    };
+
+    // Sin forjado el flujo coincide con el del cálculo unidimensional
+    let res = junction(&etics, 0.0, 2);
+    assert_almost_eq!(res.u_wall, 0.454);
+    assert_almost_eq!(res.l_2d, 2.0 * res.u_wall);
+    assert_almost_eq!(res.psi, 0.0);
+
+    // Aislamiento exterior continuo: puente térmico débil
+    let res = junction(&etics, 0.30, 2);
+    assert_almost_eq!(res.l_wall, 2.3);
+    assert!(res.psi > 0.0 && res.psi < 0.15);
+
+    // Forjado que interrumpe el aislamiento y acomete a la hoja exterior.
+    // Valor por defecto de la UNE-EN ISO 14683 del orden de ψe = 0,95 W/mK, con tolerancia amplia
+    let res = junction(&cavity, 0.30, 3);
+    assert_almost_eq!(res.psi, 0.95, 0.35);
+}
+
+#[test]
+fn window_night_insulation() {
+    use bemodel::{energy::EnergyProps, Schedule, ScheduleDay, ScheduleWeek};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let met = climate::met::parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+
+    let base = model.windows_transmission_losses_hourly(&met);
+    let win_id = *base.keys().next().unwrap();
+    let night_hours: Vec<_> = met
+        .data
+        .iter()
+        .enumerate()
+        .filter(|(_, d)| d.zenith >= 90.0)
+        .map(|(i, _)| i)
+        .collect();
+    let day_hour = met.data.iter().position(|d| d.zenith < 60.0).unwrap();
+
+    // Persiana con ΔR = 0,2 m²K/W cerrada en las horas sin sol
+    let win = model.windows.iter_mut().find(|w| w.id == win_id).unwrap();
+    win.night_insulation = Some(0.2);
+    let losses = model.windows_transmission_losses_hourly(&met);
+    let (old, new) = (&base[&win_id], &losses[&win_id]);
+    assert!(!night_hours.is_empty());
+    for &i in &night_hours {
+        if old[i] > 0.0 {
+            assert!(new[i] < old[i]);
+        }
+    }
+    assert_almost_eq!(new[day_hour], old[day_hour]);
+    let night_sum = |v: &Vec<f32>| night_hours.iter().map(|&i| v[i]).sum::<f32>();
+    assert!(night_sum(new) < 0.9 * night_sum(old));
+    // La reducción corresponde a U_cerr = 1 / (1 / U + ΔR)
+    let wp = &EnergyProps::from(&model).windows[&win_id];
+    let u = wp.u_value_override.or(wp.u_value).unwrap();
+    let i = night_hours[0];
+    assert_almost_eq!(new[i] / old[i], 1.0 / (1.0 + u * 0.2));
+
+    // Con horario de cierre siempre abierto no cambian las pérdidas
+    let day = ScheduleDay {
+        values: vec![0.0; 24],
+        ..Default::default()
+    };
+    let week = ScheduleWeek {
+        values: vec![(day.id, 7)],
+        ..Default::default()
+    };
+    let year = Schedule {
+        values: vec![(week.id, 365)],
+        ..Default::default()
+    };
+    let win = model.windows.iter_mut().find(|w| w.id == win_id).unwrap();
+    win.night_insulation_schedule = Some(year.id);
+    model.schedules.day.push(day);
+    model.schedules.week.push(week);
+    model.schedules.year.push(year);
+    let losses = model.windows_transmission_losses_hourly(&met);
+    assert_almost_eq!(night_sum(&losses[&win_id]), night_sum(old), 0.01);
+
+    // La temperatura operativa se calcula con la U horaria
+    assert_eq!(
+        model.operative_temperature_hourly(&met).len(),
+        model.spaces.len()
+    );
+}
+
+#[test]
+fn model_solar_aperture() {
+    use bemodel::{
+        BoundaryType, ConsDb, Glass, Orientation, Space, Uuid, Wall, WinCons, WinGeom,
+        WinPropsOverrides,
+    };
+
+    init();
+
+    let space = Space {
+        multiplier: 2.0,
+        ..Default::default()
+    };
+    let wall = |name: &str, azimuth: f32| Wall {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        bounds: BoundaryType::EXTERIOR,
+        space: space.id,
+        geometry: WallGeom {
+            tilt: 90.0,
+            azimuth,
+            position: None,
+            polygon: vec![
+                point![0.0, 0.0],
+                point![10.0, 0.0],
+                point![10.0, 3.0],
+                point![0.0, 3.0],
+            ],
+        },
+        ..Default::default()
+    };
+    // Vidrios con g_gl;wi = 0.90 · g_gl;n = 0.63 y 0.45
+    let glass_a = Glass {
+        g_gln: 0.70,
+        ..Default::default()
+    };
+    let glass_b = Glass {
+        g_gln: 0.50,
+        ..Default::default()
+    };
+    let cons_a = WinCons {
+        glass: glass_a.id,
+        f_f: 0.25,
+        ..Default::default()
+    };
+    let cons_b = WinCons {
+        glass: glass_b.id,
+        f_f: 0.10,
+        ..Default::default()
+    };
+    let window = |wall: &Wall, cons: &WinCons, x: f32, width: f32, height: f32| Window {
+        id: Uuid::new_v4(),
+        wall: wall.id,
+        cons: cons.id,
+        geometry: WinGeom {
+            position: Some(point![x, 0.5]),
+            width,
+            height,
+            setback: 0.0,
+        },
+        ..Default::default()
+    };
+    let south = wall("MURO_S", 0.0);
+    let north = wall("MURO_N", 180.0);
+    let windows = vec![
+        window(&south, &cons_a, 0.5, 2.0, 1.5),
+        window(&south, &cons_a, 3.0, 1.0, 1.0),
+        window(&south, &cons_b, 5.0, 2.0, 2.0),
+        window(&north, &cons_a, 1.0, 1.0, 1.0),
+    ];
+    let mut model = Model {
+        spaces: vec![space],
+        walls: vec![south, north],
+        cons: ConsDb {
+            wincons: vec![cons_a, cons_b],
+            glasses: vec![glass_a, glass_b],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    // El segundo hueco sur tiene un factor de obstáculos remotos de usuario
+    model.overrides.windows.insert(
+        windows[1].id,
+        WinPropsOverrides {
+            f_shobst: Some(0.5),
+            ..Default::default()
+        },
+    );
+    model.windows = windows;
+
+    // 2 · (3 · 0.63 · 0.75 + 1 · 0.63 · 0.5 · 0.75 + 4 · 0.45 · 0.90)
+    assert_almost_eq!(model.solar_aperture(Orientation::S), 6.5475);
+    assert_almost_eq!(model.solar_aperture(Orientation::N), 0.945);
+    assert_almost_eq!(model.solar_aperture(Orientation::HZ), 0.0);
+    let by_orientation = model.solar_aperture_by_orientation();
+    assert_eq!(by_orientation.len(), 2);
+    assert_almost_eq!(by_orientation.values().sum::<f32>(), 7.4925);
+}
+
+#[test]
+fn model_empty_wall_construction() {
+    use bemodel::{Uuid, WallCons, WarningLevel};
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    assert!(bemodel::check(&model).is_empty());
+
+    // Muro con la construcción por defecto "Ninguno" de los archivos BDL
+    let empty = WallCons {
+        name: "Ninguno".to_string(),
+        ..Default::default()
+    };
+    let wall_id = model.walls[0].id;
+    model.walls[0].cons = empty.id;
+    model.cons.wallcons.push(empty);
+    let warnings = bemodel::check(&model);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].level, WarningLevel::DANGER);
+    assert_eq!(warnings[0].id, Some(wall_id));
+
+    // Sustitución por una construcción mínima
+    let default_cons = WallCons {
+        id: Uuid::new_v4(),
+        name: "Construcción por defecto".to_string(),
+        ..model.cons.wallcons[0].clone()
+    };
+    let default_id = default_cons.id;
+    assert_eq!(model.assign_default_construction(default_cons.clone()), 1);
+    assert_eq!(model.walls[0].cons, default_id);
+    assert!(model.cons.get_wallcons(default_id).is_some());
+    assert!(bemodel::check(&model).is_empty());
+    assert_eq!(model.assign_default_construction(default_cons), 0);
+}
+
+#[test]
+fn model_empty_spaces() {
+    use bemodel::{Space, Uuid, Wall, WarningLevel};
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    assert!(bemodel::check(&model).is_empty());
+    let num_spaces = model.spaces.len();
+
+    // Espacio sin muros
+    let empty = Space {
+        name: "P01_E02".to_string(),
+        ..Default::default()
+    };
+    let empty_id = empty.id;
+    // Espacio con un muro vertical pero sin suelo
+    let no_area = Space {
+        name: "P01_E03".to_string(),
+        ..Default::default()
+    };
+    let no_area_wall = Wall {
+        id: Uuid::new_v4(),
+        space: no_area.id,
+        ..model.get_wall_by_name("P01_E01_PE002").unwrap().clone()
+    };
+    let no_area_id = no_area.id;
+    model.spaces.extend([empty, no_area]);
+    model.walls.push(no_area_wall);
+
+    // El espacio sin muros tiene además un aviso por no delimitar la envolvente térmica
+    let dangers = |model: &Model| {
+        bemodel::check(model)
+            .into_iter()
+            .filter(|w| w.level == WarningLevel::DANGER)
+            .collect::<Vec<_>>()
+    };
+    let warnings = dangers(&model);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].id, Some(empty_id));
+    assert!(warnings[0].msg.contains("P01_E02"));
+    assert_eq!(warnings[1].id, Some(no_area_id));
+    assert!(warnings[1].msg.contains("P01_E03"));
+
+    // Solo se eliminan los espacios sin muros
+    assert_eq!(model.remove_empty_spaces(), 1);
+    assert_eq!(model.spaces.len(), num_spaces + 1);
+    assert!(model.get_space(empty_id).is_none());
+    assert_eq!(model.remove_empty_spaces(), 0);
+    let warnings = dangers(&model);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].id, Some(no_area_id));
+}
+
+#[test]
+fn model_merge_coplanar_walls() {
+    use bemodel::{RadiantSurface, Space, Uuid, Wall, WinGeom};
+
+    init();
+
+    let space = Space::default();
+    // Fachada sur troceada en dos muros de 5 x 3 m contiguos y un tercero separado
+    let wall = |name: &str, x: f32| Wall {
+        name: name.to_string(),
+        space: space.id,
+        geometry: WallGeom {
+            tilt: 90.0,
+            azimuth: 0.0,
+            position: Some(point![x, 0.0, 0.0]),
+            polygon: vec![
+                point![0.0, 0.0],
+                point![5.0, 0.0],
+                point![5.0, 3.0],
+                point![0.0, 3.0],
+            ],
+        },
+        ..Default::default()
+    };
+    let walls = vec![
+        wall("MURO_1", 0.0),
+        wall("MURO_2", 5.0),
+        wall("MURO_3", 20.0),
+    ];
+    let window = Window {
+        id: Uuid::new_v4(),
+        name: "HUECO_2".to_string(),
+        wall: walls[1].id,
+        geometry: WinGeom {
+            position: Some(point![1.0, 0.5]),
+            width: 2.0,
+            height: 1.5,
+            setback: 0.0,
+        },
+        ..Default::default()
+    };
+    let radiant = RadiantSurface {
+        id: Uuid::new_v4(),
+        name: "RADIANTE_2".to_string(),
+        wall: walls[1].id,
+        max_power: 50.0,
+        schedule: None,
+    };
+    let wall_1_id = walls[0].id;
+    let wall_3_id = walls[2].id;
+    let mut model = Model {
+        spaces: vec![space],
+        walls,
+        windows: vec![window],
+        radiant_surfaces: vec![radiant],
+        ..Default::default()
+    };
+    let win_pos = model.windows[0]
+        .position_3d(&model.walls[1].geometry)
+        .unwrap();
+    let area = model.walls.iter().map(|w| w.area()).sum::<f32>();
+
+    assert_eq!(model.merge_coplanar_walls(), 1);
+    assert_eq!(model.walls.len(), 2);
+    let merged = model.get_wall(wall_1_id).unwrap();
+    assert_eq!(merged.geometry.polygon.len(), 4);
+    assert_almost_eq!(merged.area(), 30.0);
+    assert_almost_eq!(model.walls.iter().map(|w| w.area()).sum::<f32>(), area);
+    assert_almost_eq!(model.get_wall(wall_3_id).unwrap().area(), 15.0);
+    // El hueco pasa al muro unión y mantiene su posición
+    let win = &model.windows[0];
+    assert_eq!(win.wall, wall_1_id);
+    let pos = win.geometry.position.unwrap();
+    assert_almost_eq!(pos.x, 6.0);
+    assert_almost_eq!(pos.y, 0.5);
+    let new_win_pos = win.position_3d(&merged.geometry).unwrap();
+    assert_almost_eq!((new_win_pos - win_pos).norm(), 0.0);
+    assert_eq!(merged.geometry.contains_window(&win.geometry), Some(true));
+    // La superficie radiante pasa al muro unión
+    assert_eq!(model.radiant_surfaces[0].wall, wall_1_id);
+
+    // No quedan más muros que unir
+    assert_eq!(model.merge_coplanar_walls(), 0);
+}
+
+#[test]
+fn model_reference_building() {
+    use bemodel::{energy::EnergyProps, BoundaryType, Tilt, ULimits};
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let reference = model.reference_building();
+
+    // Zona D3
+    let limits = ULimits::for_climate(&model.meta.climate);
+    assert_almost_eq!(limits.walls, 0.41);
+    assert_almost_eq!(limits.roofs, 0.35);
+    assert_almost_eq!(limits.ground, 0.65);
+    assert_almost_eq!(limits.windows, 1.8);
+
+    // Misma geometría y U límite en la envolvente térmica
+    assert_eq!(reference.walls.len(), model.walls.len());
+    assert_eq!(reference.windows.len(), model.windows.len());
+    assert!(model.overrides.walls.is_empty());
+    let props = EnergyProps::from(&reference);
+    for wall in props.walls.values().filter(|w| w.is_tenv) {
+        let u_lim = match (wall.bounds, wall.tilt) {
+            (BoundaryType::EXTERIOR, Tilt::TOP) => 0.35,
+            (BoundaryType::EXTERIOR, _) => 0.41,
+            _ => 0.65,
+        };
+        assert_eq!(wall.u_value_override, Some(u_lim));
+    }
+    for win in props.windows.values().filter(|w| w.is_tenv) {
+        assert_eq!(win.u_value_override, Some(1.8));
+    }
+
+    // Indicadores del edificio de referencia
+    let ind = model.energy_indicators();
+    let ind_ref = reference.energy_indicators();
+    assert_almost_eq!(
+        ind_ref.K_data.summary.opaques_a,
+        ind.K_data.summary.opaques_a
+    );
+    assert_almost_eq!(
+        ind_ref.K_data.summary.windows_au,
+        ind_ref.K_data.summary.windows_a * 1.8
+    );
+    assert!((ind_ref.K_data.K - ind.K_data.K).abs() > 0.01);
+}
+
+#[test]
+fn model_pick() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let wall = model
+        .walls
+        .iter()
+        .find(|w| w.name == "P01_E01_PE001")
+        .unwrap();
+    let win = get_window_by_name(&model, "P01_E01_PE001_V");
+
+    // Rayo desde el exterior hacia la fachada sur (plano y = 0), fuera del hueco
+    let (id, dist) = model
+        .pick(Ray::new(point![1.0, -5.0, 1.5], vector![0.0, 1.0, 0.0]))
+        .unwrap();
+    assert_eq!(id, wall.id);
+    assert_almost_eq!(dist, 5.0);
+
+    // Rayo oblicuo hacia el hueco (x = 3 a 5, z = 1 a 2)
+    let (id, dist) = model
+        .pick(Ray::new(point![4.0, -3.0, 5.5], vector![0.0, 3.0, -4.0]))
+        .unwrap();
+    assert_eq!(id, win.id);
+    assert_almost_eq!(dist, 5.0);
+
+    // Rayo desde el interior hacia el sur, por la cara interior del opaco
+    let (id, dist) = model
+        .pick(Ray::new(point![8.0, 4.0, 1.5], vector![0.0, -1.0, 0.0]))
+        .unwrap();
+    assert_eq!(id, wall.id);
+    assert_almost_eq!(dist, 4.0);
+
+    // Rayo que no alcanza el edificio
+    assert!(model
+        .pick(Ray::new(point![1.0, -5.0, 1.5], vector![0.0, -1.0, 0.0]))
         .is_none());
 }
+
+#[test]
+fn model_space_view_factors() {
+    use std::collections::BTreeMap;
+
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let space_id = model.spaces[0].id;
+    let id_of = |name: &str| model.walls.iter().find(|w| w.name == name).unwrap().id;
+    let win_id = get_window_by_name(&model, "P01_E01_PE001_V").id;
+
+    let factors = model.space_view_factors(space_id);
+    // 7 opacos y 1 hueco
+    assert_eq!(factors.len(), 8);
+    for row in factors.values() {
+        assert_almost_eq!(row.values().sum::<f32>(), 1.0);
+    }
+
+    // Las superficies no se ven a sí mismas ni a las de su mismo plano
+    let south = &factors[&id_of("P01_E01_PE001")];
+    assert!(!south.contains_key(&id_of("P01_E01_PE001")));
+    assert!(!south.contains_key(&win_id));
+    let roof = &factors[&id_of("P01_E01C001")];
+    assert!(!roof.contains_key(&id_of("P01_E01C002")));
+
+    // El suelo (100 m²) ve los muros (28 + 2 + 3 x 30 m²) y la cubierta (100 m²)
+    let floor = &factors[&id_of("P01_E01_FTER001")];
+    assert_eq!(floor.len(), 7);
+    assert_almost_eq!(floor[&win_id], 2.0 / 220.0);
+    assert_almost_eq!(floor[&id_of("P01_E01_PE003")], 30.0 / 220.0);
+
+    // El reparto de la ganancia solar del hueco conserva la energía y no llega a su opaco
+    let gains = BTreeMap::from([(win_id, 1000.0)]);
+    let distributed = model.distribute_solar_gains(&gains);
+    assert_almost_eq!(distributed.values().sum::<f32>(), 1000.0, 0.1);
+    assert!(!distributed.contains_key(&id_of("P01_E01_PE001")));
+    assert_almost_eq!(
+        distributed[&id_of("P01_E01_FTER001")],
+        1000.0 * factors[&win_id][&id_of("P01_E01_FTER001")],
+        0.1
+    );
+}
+
+#[test]
+fn model_rotate() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let ind = model.energy_indicators();
+
+    let same_geometry = |a: &WallGeom, b: &WallGeom| {
+        let d_az = (a.azimuth - b.azimuth).rem_euclid(360.0);
+        assert!(!(0.01..=359.99).contains(&d_az));
+        assert_almost_eq!(a.tilt, b.tilt);
+        match (a.position, b.position) {
+            (Some(pa), Some(pb)) => assert!((pa - pb).norm() < 0.001),
+            (pa, pb) => assert_eq!(pa, pb),
+        }
+    };
+
+    // Un giro de 90º lleva las fachadas sur al este y mantiene superficies y transmitancias
+    let mut rotated = model.clone();
+    rotated.rotate(90.0);
+    for (w, r) in model.walls.iter().zip(&rotated.walls) {
+        if w.geometry.tilt == 90.0 && w.geometry.azimuth == 0.0 {
+            assert_almost_eq!(r.geometry.azimuth, 90.0);
+        }
+        assert_almost_eq!(r.area(), w.area());
+    }
+    let rot_ind = rotated.energy_indicators();
+    assert_almost_eq!(rot_ind.area_ref, ind.area_ref);
+    assert_almost_eq!(rot_ind.K_data.K, ind.K_data.K);
+    assert!((rot_ind.q_soljul_data.q_soljul - ind.q_soljul_data.q_soljul).abs() > 0.001);
+
+    // Tras girar 360º el modelo queda igual que el original
+    let mut rotated = model.clone();
+    rotated.rotate(360.0);
+    for (w, r) in model.walls.iter().zip(&rotated.walls) {
+        same_geometry(&w.geometry, &r.geometry);
+    }
+    for (s, r) in model.shades.iter().zip(&rotated.shades) {
+        same_geometry(&s.geometry, &r.geometry);
+    }
+
+    // El giro es reversible
+    let mut rotated = model.clone();
+    rotated.rotate(37.0);
+    rotated.rotate(-37.0);
+    for (w, r) in model.walls.iter().zip(&rotated.walls) {
+        same_geometry(&w.geometry, &r.geometry);
+    }
+    assert_almost_eq!(
+        rotated.energy_indicators().q_soljul_data.q_soljul,
+        ind.q_soljul_data.q_soljul
+    );
+}
+
+#[test]
+fn model_n50_doors_and_extra_leakage() {
+    use bemodel::AirLeakage;
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let base = model.energy_indicators().n50_data;
+    let vol = base.vol;
+
+    // Chimenea con 100 m³/h a 100 Pa
+    model.meta.extra_leakage.push(AirLeakage {
+        name: "Chimenea".to_string(),
+        flow_100: 100.0,
+    });
+    let n50_data = model.energy_indicators().n50_data;
+    assert_almost_eq!(n50_data.extra_c_a, 100.0);
+    assert_almost_eq!(n50_data.n50_ref, base.n50_ref + 0.629 * 100.0 / vol);
+    assert_almost_eq!(n50_data.n50, base.n50 + 0.629 * 100.0 / vol);
+
+    // Con ensayo, la chimenea reduce la permeabilidad atribuida a los opacos
+    model.meta.n50_test_ach = Some(base.n50_ref);
+    let n50_data = model.energy_indicators().n50_data;
+    assert_almost_eq!(n50_data.n50, base.n50_ref);
+    assert_almost_eq!(n50_data.walls_c_a, base.walls_c_a_ref - 100.0, 0.01);
+    model.meta.n50_test_ach = None;
+    model.meta.extra_leakage.clear();
+
+    // El hueco pasa a ser una puerta con permeabilidad propia
+    let win = get_window_by_name(&model, "P01_E01_PE001_V");
+    let win_area = win.area();
+    let win_id = win.id;
+    let win = model.windows.iter_mut().find(|w| w.id == win_id).unwrap();
+    win.door_c_100 = Some(60.0);
+    let n50_data = model.energy_indicators().n50_data;
+    assert_almost_eq!(n50_data.windows_a, base.windows_a);
+    assert_almost_eq!(n50_data.windows_c_a, 60.0 * win_area, 0.01);
+}
+
+#[test]
+fn model_debug_occluders() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let occluders = model.collect_occluders();
+    // 6 opacos exteriores (sin la solera), 1 sombra y 4 sombras del retranqueo del hueco
+    assert_eq!(occluders.len(), 11);
+    assert_eq!(
+        occluders
+            .iter()
+            .filter(|o| o.linked_to_id.is_some())
+            .count(),
+        4
+    );
+    let win = get_window_by_name(&model, "P01_E01_PE001_V");
+    assert!(occluders
+        .iter()
+        .filter_map(|o| o.linked_to_id)
+        .all(|id| id == win.id));
+
+    // Los vértices en coordenadas globales corresponden a la geometría del opaco
+    let wall = model.get_wall_by_name("P01_E01_PE001").unwrap();
+    let oc = occluders.iter().find(|o| o.id == wall.id).unwrap();
+    let vertices = oc.global_vertices();
+    assert_eq!(vertices.len(), wall.geometry.polygon.len());
+    assert!(vertices.iter().all(|v| v.y.abs() < 0.001));
+
+    // Exportación OBJ con un objeto y una cara por oclusor
+    let obj = model.occluders_as_obj();
+    assert_eq!(obj.lines().filter(|l| l.starts_with("o ")).count(), 11);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 11);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 42);
+}
+
+#[test]
+fn model_fshobst_options() {
+    use bemodel::energy::FshobstOptions;
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let win_id = get_window_by_name(&model, "P01_E01_PE001_V").id;
+
+    // Las opciones por defecto reproducen el cálculo de compute_fshobst
+    let base = model.compute_fshobst();
+    let default = model.compute_fshobst_with_options(&FshobstOptions::default());
+    assert_eq!(base, default);
+
+    // Al aumentar el muestreo del hueco el resultado converge
+    let fshobst = |n| {
+        model.compute_fshobst_with_options(&FshobstOptions {
+            samples_per_window: Some(n),
+            ..Default::default()
+        })[&win_id]
+    };
+    let (f5, f10, f20, f40) = (fshobst(5), fshobst(10), fshobst(20), fshobst(40));
+    assert_almost_eq!(f5, base[&win_id]);
+    assert!((f40 - f20).abs() <= 0.01);
+    assert!((f40 - f10).abs() <= 0.02);
+
+    // Paso temporal y meses
+    let step = model.compute_fshobst_with_options(&FshobstOptions {
+        time_step: 2,
+        ..Default::default()
+    });
+    assert_almost_eq!(step[&win_id], base[&win_id], 0.05);
+    let winter = model.compute_fshobst_with_options(&FshobstOptions {
+        months: vec![1],
+        ..Default::default()
+    });
+    let year = model.compute_fshobst_with_options(&FshobstOptions {
+        months: (1..=12).collect(),
+        ..Default::default()
+    });
+    assert!((winter[&win_id] - base[&win_id]).abs() > 0.01);
+    assert!((0.0..=1.0).contains(&year[&win_id]));
+    assert!((year[&win_id] - base[&win_id]).abs() > 0.01);
+    assert!(model
+        .compute_fshobst_with_options(&FshobstOptions {
+            months: vec![],
+            ..Default::default()
+        })
+        .is_empty());
+}
+
+#[test]
+fn model_fshobst_parallel() {
+    use bemodel::energy::FshobstOptions;
+    init();
+
+    let strdata = include_str!("./data/e4h_medianeras.json");
+    let model = Model::from_json(strdata).unwrap();
+
+    // El cálculo en paralelo da los mismos resultados que el secuencial
+    let options = FshobstOptions {
+        samples_per_window: Some(3),
+        time_step: 3,
+        ..Default::default()
+    };
+    let parallel = model.compute_fshobst_with_options(&options);
+    let sequential = model.compute_fshobst_with_options(&FshobstOptions {
+        parallel: false,
+        ..options
+    });
+    assert_eq!(parallel.len(), model.windows.len());
+    assert_eq!(parallel, sequential);
+}
+
+#[test]
+fn model_occluders_bvh_reuse() {
+    use bemodel::energy::{ray_dir_to_sun, FshobstOptions};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let met = climate::met::parsemet(include_str!("../../climate/src/zonaD3.met")).unwrap();
+    let window = get_window_by_name(&model, "P01_E01_PE001_V");
+
+    // La BVH reutilizada da los mismos resultados que reconstruirla en cada hora
+    let bvh = model.build_bvh();
+    assert!(bvh.is_valid_for(&model));
+    let hourly = model.sunlit_fraction_hourly(window, &met, &bvh);
+    assert_eq!(hourly.len(), 8760);
+    let occluders = model.collect_occluders();
+    let ray_origins = model.ray_origins_for_window(window);
+    for (i, d) in met.data.iter().enumerate().step_by(7) {
+        if d.zenith >= 90.0 {
+            assert_eq!(hourly[i], 0.0);
+            continue;
+        }
+        let ray_dir = ray_dir_to_sun(d.azimuth, 90.0 - d.zenith);
+        let sunlit = model.sunlit_fraction(window, &ray_origins, &ray_dir, &occluders);
+        assert_almost_eq!(hourly[i], sunlit);
+    }
+    assert!(hourly.iter().any(|f| *f > 0.0 && *f < 1.0));
+
+    // Al cambiar la geometría la BVH queda obsoleta y se reconstruye
+    let mut rotated = model.clone();
+    rotated.rotate(90.0);
+    assert!(!bvh.is_valid_for(&rotated));
+    assert_eq!(
+        rotated.compute_fshobst_with_bvh(&bvh, &FshobstOptions::default()),
+        rotated.compute_fshobst()
+    );
+}
+
+#[test]
+fn model_fshobst_backface_culling() {
+    use bemodel::{
+        energy::{BackfaceCulling, FshobstOptions},
+        point, Uuid,
+    };
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let win_id = get_window_by_name(&model, "P01_E01_PE001_V").id;
+
+    // Muro a 2m frente al hueco sur, con su cara exterior hacia el sur (de espaldas al hueco)
+    let mut wall = model.get_wall_by_name("P01_E01_PE001").unwrap().clone();
+    wall.id = Uuid::new_v4();
+    wall.name = "Muro_frente".to_string();
+    wall.geometry.position = Some(point![0.0, -2.0, 0.0]);
+    model.walls.push(wall);
+
+    // Evaluamos en enero, con el sol bajo
+    let culling = |walls, shades| FshobstOptions {
+        months: vec![1],
+        backface_culling: BackfaceCulling { walls, shades },
+        ..Default::default()
+    };
+    let double_sided = model.compute_fshobst_with_options(&culling(false, false))[&win_id];
+    let walls_culled = model.compute_fshobst_with_options(&culling(true, false))[&win_id];
+    let shades_culled = model.compute_fshobst_with_options(&culling(false, true))[&win_id];
+    // Con back-face culling en opacos el muro de espaldas no bloquea la radiación
+    assert!(walls_culled > double_sided);
+    assert_almost_eq!(shades_culled, double_sided);
+
+    // Con la cara exterior hacia el hueco el muro bloquea en ambos casos
+    let wall = model.walls.last_mut().unwrap();
+    wall.geometry.azimuth = -180.0;
+    wall.geometry.position = Some(point![10.0, -2.0, 0.0]);
+    let double_sided = model.compute_fshobst_with_options(&culling(false, false))[&win_id];
+    let walls_culled = model.compute_fshobst_with_options(&culling(true, false))[&win_id];
+    assert_almost_eq!(walls_culled, double_sided);
+}
+
+#[test]
+fn model_fshobst_mean_by_orientation() {
+    use bemodel::{energy::UnitSystem, Orientation, Uuid, WinPropsOverrides};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+
+    // Segundo hueco sur, de distinto tamaño, con otro factor de obstáculos remotos
+    let win1 = get_window_by_name(&model, "P01_E01_PE001_V").clone();
+    let mut win2 = win1.clone();
+    win2.id = Uuid::new_v4();
+    win2.name = "P01_E01_PE001_V2".to_string();
+    win2.geometry.width = 2.0 * win1.geometry.width;
+    win2.geometry.position = Some(point![6.0, 1.0]);
+    model.windows.push(win2.clone());
+    for (id, f_shobst) in [(win1.id, 0.8), (win2.id, 0.4)] {
+        model.overrides.windows.insert(
+            id,
+            WinPropsOverrides {
+                f_shobst: Some(f_shobst),
+                ..Default::default()
+            },
+        );
+    }
+
+    let ind = model.energy_indicators();
+    let by_orientation = &ind.f_shobst_mean_by_orientation;
+    assert_eq!(by_orientation.len(), 1);
+    // Misma construcción, la ponderación por área de captación es por superficie: (0.8 · A + 0.4 · 2A) / 3A
+    assert_almost_eq!(by_orientation[&Orientation::S], 1.6 / 3.0);
+    assert_almost_eq!(
+        by_orientation[&Orientation::S],
+        ind.q_soljul_data.detail[&Orientation::S].fshobst_mean
+    );
+    let ind_ip = ind.to_units(UnitSystem::IP);
+    assert_eq!(&ind_ip.f_shobst_mean_by_orientation, by_orientation);
+}
+
+#[test]
+fn model_required_shading() {
+    use bemodel::WinPropsOverrides;
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let ind = model.energy_indicators();
+    let q_soljul = ind.q_soljul_data.q_soljul;
+
+    // Si ya se cumple el objetivo se mantienen los valores actuales
+    assert_eq!(
+        model.required_shading(2.0 * q_soljul),
+        ind.f_shobst_mean_by_orientation
+    );
+
+    // Al aplicar el F_sh;obst necesario a los huecos de cada orientación se alcanza el objetivo
+    let target = 0.5 * q_soljul;
+    let required = model.required_shading(target);
+    assert_eq!(required.len(), ind.f_shobst_mean_by_orientation.len());
+    for win in &model.windows {
+        let orientation = ind.props.windows[&win.id].orientation;
+        model.overrides.windows.insert(
+            win.id,
+            WinPropsOverrides {
+                f_shobst: Some(required[&orientation]),
+                ..Default::default()
+            },
+        );
+    }
+    assert_almost_eq!(model.energy_indicators().q_soljul_data.q_soljul, target);
+}
+
+#[test]
+fn model_site_latitude() {
+    use bemodel::climatedata::ClimateZone;
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+
+    // Sin latitud definida se usa la de referencia del CTE
+    model.meta.climate = ClimateZone::D3;
+    assert_almost_eq!(model.meta.site_latitude(), climate::CTE_LATPENINSULA);
+    model.meta.climate = ClimateZone::A3c;
+    assert_almost_eq!(model.meta.site_latitude(), climate::CTE_LATCANARIAS);
+
+    // La latitud explícita prevalece y se conserva al serializar el modelo
+    model.meta.latitude = Some(36.5);
+    model.meta.longitude = Some(-6.3);
+    assert_almost_eq!(model.meta.site_latitude(), 36.5);
+    let location = model.meta.site_location();
+    assert_almost_eq!(location.latitude, 36.5);
+    assert_almost_eq!(location.longitude, -6.3);
+    let model = Model::from_json(&model.as_json().unwrap()).unwrap();
+    assert_eq!(model.meta.latitude, Some(36.5));
+    assert_eq!(model.meta.longitude, Some(-6.3));
+}
+
+#[test]
+fn model_dhw_energy_demand() {
+    use bemodel::{climatedata::cold_water_temperatures, DhwDemand};
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let t_coldwater = cold_water_temperatures(&model.meta.climate);
+
+    // Sin demanda de ACS definida
+    assert_eq!(model.dhw_energy_demand(t_coldwater), [0.0; 12]);
+
+    // 4 personas con un consumo de 30 l/día·persona a 60ºC
+    model.meta.dhw = Some(DhwDemand {
+        daily_volume: 4.0 * 30.0,
+        ..Default::default()
+    });
+    let demand = model.dhw_energy_demand(t_coldwater);
+    // Enero en D3: 4.186 / 3600 · 120 l/día · 31 días · (60 - 6) K
+    assert_almost_eq!(demand[0], 233.58, 0.01);
+    // Más demanda en invierno, con agua de red más fría
+    assert!(demand[0] > demand[6]);
+
+    // Con pérdidas de acumulación y distribución
+    model.meta.dhw = Some(DhwDemand {
+        daily_volume: 4.0 * 30.0,
+        storage_losses: 0.10,
+        distribution_losses: 0.05,
+        ..Default::default()
+    });
+    let demand_with_losses = model.dhw_energy_demand(t_coldwater);
+    for (d, dl) in demand.iter().zip(demand_with_losses) {
+        assert_almost_eq!(dl, 1.15 * d, 0.01);
+    }
+}
+
+#[test]
+fn model_cold_water_temperature() {
+    use bemodel::{
+        climatedata::{cold_water_temperature, cold_water_temperatures},
+        DhwDemand,
+    };
+    init();
+
+    // Madrid (UNE 94002)
+    let madrid = cold_water_temperature("Madrid").unwrap();
+    assert_eq!(
+        madrid,
+        [8.0, 8.0, 10.0, 12.0, 14.0, 17.0, 20.0, 19.0, 17.0, 13.0, 10.0, 8.0]
+    );
+    // Sin distinguir mayúsculas ni tildes y con otras denominaciones
+    assert_eq!(cold_water_temperature(" madrid "), Some(madrid));
+    assert_eq!(
+        cold_water_temperature("Avila"),
+        cold_water_temperature("Ávila")
+    );
+    assert_eq!(
+        cold_water_temperature("Donostia"),
+        cold_water_temperature("Gipuzkoa")
+    );
+    assert!(cold_water_temperature("Desconocida").is_none());
+
+    // La provincia del emplazamiento prevalece sobre la zona climática
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    model.meta.dhw = Some(DhwDemand {
+        daily_volume: 120.0,
+        ..Default::default()
+    });
+    assert_eq!(
+        model.meta.cold_water_temperatures(),
+        cold_water_temperatures(&model.meta.climate)
+    );
+    let demand_zone = model.dhw_energy_demand_for_site();
+    model.meta.province = Some("Madrid".to_string());
+    assert_eq!(model.meta.cold_water_temperatures(), madrid);
+    // Enero en Madrid: 4.186 / 3600 · 120 l/día · 31 días · (60 - 8) K
+    let demand = model.dhw_energy_demand_for_site();
+    assert_almost_eq!(demand[0], 224.93, 0.01);
+    assert!(demand[0] < demand_zone[0]);
+    // Se conserva al serializar el modelo
+    let model = Model::from_json(&model.as_json().unwrap()).unwrap();
+    assert_eq!(model.meta.province.as_deref(), Some("Madrid"));
+}
+
+#[test]
+fn model_cte_residential_loads() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    model.meta.is_dwelling = true;
+    for space in &mut model.spaces {
+        space.loads = None;
+    }
+    let n_spaces = model.spaces.len();
+    assert_eq!(model.fill_cte_residential_loads(), n_spaces);
+    // Los espacios con cargas propias no se modifican
+    assert_eq!(model.fill_cte_residential_loads(), 0);
+
+    let loads_id = model.spaces[0].loads.unwrap();
+    let loads = model.loads.iter().find(|l| l.id == loads_id).unwrap();
+    assert_almost_eq!(loads.people_sensible, 2.15);
+    let people = model.schedules.year_values(loads.people_schedule.unwrap());
+    assert_eq!(people.len(), 8760);
+    // Lunes 1 de enero: ocupación completa a las 3h y del 25% a las 11h (punta de ausencia)
+    assert_almost_eq!(people[2], 1.0);
+    assert_almost_eq!(people[10], 0.25);
+    assert_almost_eq!(people[18], 0.5);
+    // Sábado 6 de enero: ocupación completa todo el día
+    assert_almost_eq!(people[5 * 24 + 10], 1.0);
+    // Pico de iluminación entre las 20 y las 23h
+    let lighting = model
+        .schedules
+        .year_values(loads.lighting_schedule.unwrap());
+    assert_almost_eq!(lighting[20] * loads.lighting, 2.20);
+}
+
+#[test]
+fn model_interior_window_to_unconditioned_space() {
+    use bemodel::{SpaceType, Uuid};
+    init();
+
+    // Hueco en la partición entre una vivienda y un espacio no habitable (galería acristalada)
+    let strdata = include_str!("./data/caso_a.json");
+    let mut model = Model::from_json(strdata).unwrap();
+    let wall = model.get_wall_by_name("P01_E01_Med001").unwrap().clone();
+    let uncond_id = wall.next_to.unwrap();
+    assert_eq!(
+        model.get_space(uncond_id).unwrap().kind,
+        SpaceType::UNINHABITED
+    );
+    let t_u_before = model.unconditioned_space_temperature(uncond_id, 20.0, 5.0);
+
+    let mut win = model.windows[0].clone();
+    win.id = Uuid::new_v4();
+    win.name = "Hueco_galeria".to_string();
+    win.wall = wall.id;
+    win.geometry.position = Some(point![0.5, 0.5]);
+    win.geometry.width = 1.0;
+    win.geometry.height = 1.0;
+    model.windows.push(win.clone());
+
+    let u_w = win.u_value(&model.cons).unwrap();
+    let b = model.adjustment_factor_b(uncond_id);
+    assert!(b > 0.0 && b < 1.0);
+    let u_adj = win.u_value_adjusted(&model).unwrap();
+    assert_almost_eq!(u_adj, b * u_w, 0.01);
+    assert!(u_adj < u_w);
+    // El hueco, más aislante que la partición de placas de yeso a la que sustituye, aleja la
+    // temperatura del espacio no habitable de la interior
+    assert!(model.unconditioned_space_temperature(uncond_id, 20.0, 5.0) < t_u_before);
+    // Las propiedades del hueco usan la transmitancia corregida
+    let props = bemodel::energy::EnergyProps::from(&model);
+    assert_almost_eq!(props.windows[&win.id].u_value.unwrap(), u_adj);
+    // Los huecos exteriores no se corrigen
+    let ext_win = &model.windows[0];
+    assert_eq!(
+        ext_win.u_value_adjusted(&model),
+        ext_win.u_value(&model.cons)
+    );
+}
+
+#[test]
+fn model_markdown_report() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let report = model.to_markdown_report();
+    for section in [
+        "## Datos generales",
+        "## Espacios",
+        "## Construcciones",
+        "### Opacos",
+        "### Huecos",
+        "## Indicadores",
+        "## Avisos",
+    ] {
+        assert!(report.contains(section), "Falta la sección {}", section);
+    }
+    // Incluye los espacios, las construcciones y los indicadores del modelo
+    assert!(report.contains(&format!("| {} |", model.spaces[0].name)));
+    assert!(report.contains(&format!("| {} |", model.cons.wallcons[0].name)));
+    let ind = model.energy_indicators();
+    assert!(report.contains(&format!("| {:.2} |", ind.K_data.K)));
+    assert!(report.contains(&format!("| {:.2} |", ind.n50_data.n50)));
+}
+
+#[test]
+fn model_sensitivity_window_u() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let model = Model::from_json(strdata).unwrap();
+    let k_orig = model.energy_indicators().K_data.K;
+
+    let results = model.sensitivity_window_u(&[1.0, 3.0]);
+    assert_eq!(results.len(), 2);
+    // Mayor U de huecos da mayor K
+    assert!(results[0].K_data.K < results[1].K_data.K);
+    assert_almost_eq!(results[0].K_data.windows.u_mean.unwrap(), 1.0);
+    assert_almost_eq!(results[1].K_data.windows.u_mean.unwrap(), 3.0);
+    // El modelo original no se modifica
+    assert!(model.overrides.windows.is_empty());
+    assert_almost_eq!(model.energy_indicators().K_data.K, k_orig);
+}
+
+#[test]
+fn model_multiplier_consistency() {
+    init();
+
+    let strdata = include_str!("./data/cubo.json");
+    let mut base = Model::from_json(strdata).unwrap();
+    let space_id = base.spaces[0].id;
+    for tb in &mut base.thermal_bridges {
+        tb.space = Some(space_id);
+    }
+
+    // 1 planta con multiplicador 5
+    let mut multiplied = base.clone();
+    multiplied.spaces[0].multiplier = 5.0;
+
+    // 5 plantas explícitas
+    let height = base.spaces[0].height;
+    let mut explicit = base.clone();
+    for i in 1..5 {
+        explicit.merge(&base, vector![0.0, 0.0, i as f32 * height]);
+    }
+    assert_eq!(explicit.spaces.len(), 5);
+    assert_eq!(
+        explicit.thermal_bridges.len(),
+        5 * base.thermal_bridges.len()
+    );
+
+    let ind_m = multiplied.energy_indicators();
+    let ind_e = explicit.energy_indicators();
+    assert_almost_eq!(ind_m.area_ref, ind_e.area_ref, 0.01);
+    assert_almost_eq!(ind_m.vol_env_gross, ind_e.vol_env_gross, 0.01);
+    assert_almost_eq!(ind_m.vol_env_net, ind_e.vol_env_net, 0.01);
+    assert_almost_eq!(ind_m.compactness, ind_e.compactness);
+    assert_almost_eq!(ind_m.H_ve, ind_e.H_ve, 0.01);
+    assert_almost_eq!(
+        ind_m.K_data.summary.opaques_a,
+        ind_e.K_data.summary.opaques_a,
+        0.01
+    );
+    assert_almost_eq!(
+        ind_m.K_data.summary.windows_a,
+        ind_e.K_data.summary.windows_a,
+        0.01
+    );
+    assert_almost_eq!(ind_m.K_data.summary.tbs_l, ind_e.K_data.summary.tbs_l, 0.01);
+    assert_almost_eq!(
+        ind_m.K_data.summary.tbs_l,
+        5.0 * base.energy_indicators().K_data.summary.tbs_l,
+        0.01
+    );
+    assert_almost_eq!(ind_m.K_data.K, ind_e.K_data.K);
+    assert_almost_eq!(ind_m.n50_data.n50, ind_e.n50_data.n50);
+    assert_almost_eq!(
+        ind_m.q_soljul_data.q_soljul,
+        ind_e.q_soljul_data.q_soljul,
+        0.01
+    );
+}
@@ -3,8 +3,9 @@ pub mod solar;
 
 pub use met::*;
 pub use solar::{
-    nday_from_md, nday_from_str, nday_from_ymd, radiation_for_surface, sun_position,
-    sunsurface_angles, Location, SolarRadiation, SunPosition, SunSurfaceAngles,
+    nday_from_md, nday_from_str, nday_from_ymd, pv_incidence_angle_modifier, pv_incident_energy,
+    pv_optimal_tilt, radiation_breakdown_for_surface, radiation_for_surface, sun_position,
+    sunsurface_angles, Location, RadiationBreakdown, SolarRadiation, SunPosition, SunSurfaceAngles,
 };
 
 pub const MONTH_N: [u32; 12] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
@@ -34,12 +35,12 @@ pub const CTE_LATPENINSULA: f32 = 40.7;
 pub const CTE_LATCANARIAS: f32 = 28.3;
 
 // Latitude for location ('peninsula' or 'canarias')
-pub fn cte_latitude_from_str(location: &str) -> f32 {
-    match location {
-        "peninsula" => CTE_LATPENINSULA,
-        "canarias" => CTE_LATCANARIAS,
-        // TODO: esto era un error
-        _ => 0.0,
+// Unknown locations are an error, instead of using an arbitrary latitude
+pub fn cte_latitude_from_str(location: &str) -> Result<f32, anyhow::Error> {
+    match location.trim().to_lowercase().as_str() {
+        "peninsula" | "península" => Ok(CTE_LATPENINSULA),
+        "canarias" => Ok(CTE_LATCANARIAS),
+        _ => anyhow::bail!("Localización desconocida: {}", location),
     }
 }
 
@@ -65,6 +66,35 @@ mod tests {
         };
     }
 
+    #[test]
+    fn cte_latitude_test() {
+        assert_almost_eq!(
+            cte_latitude_from_str("peninsula").unwrap(),
+            CTE_LATPENINSULA
+        );
+        assert_almost_eq!(cte_latitude_from_str("Canarias").unwrap(), CTE_LATCANARIAS);
+        assert!(cte_latitude_from_str("desconocida").is_err());
+        assert!(cte_latitude_from_str("").is_err());
+    }
+
+    #[test]
+    fn estimated_sky_temp_test() {
+        let hour = HourlyData {
+            db_temp: 7.9,
+            rel_humidity: 84.0,
+            ..Default::default()
+        };
+        assert_almost_eq!(hour.dew_point_temp(), 5.4, 0.1);
+        let t_sky = hour.estimated_sky_temp();
+        assert!(t_sky < hour.db_temp - 15.0);
+        // Con aire más seco el cielo está más frío
+        let dry_hour = HourlyData {
+            rel_humidity: 40.0,
+            ..hour
+        };
+        assert!(dry_hour.estimated_sky_temp() < t_sky);
+    }
+
     #[test]
     fn sunpos_test() {
         // Comparado con https://gml.noaa.gov/grad/solcalc/
@@ -104,6 +134,13 @@ mod tests {
         assert_almost_eq!(sunpos.azimuth, 0.0);
     }
 
+    #[test]
+    fn equation_of_time_test() {
+        // Días previos al punto de referencia de cada tramo (nday < 43 y nday < 359)
+        assert_almost_eq!(solar::t_eq(30), 13.248);
+        assert_almost_eq!(solar::t_eq(350), -4.05);
+    }
+
     #[test]
     fn sun_surface_angles_test() {
         let nday = 162;
@@ -200,4 +237,132 @@ mod tests {
         assert_almost_eq!(mdata.dir[0], 32.997);
         assert_almost_eq!(mdata.dif[0], 21.072);
     }
+
+    #[test]
+    fn monthly_radiation_table_test() {
+        let metdata = met::parsemet(METDATA).unwrap();
+
+        // Horizontal y fachada sur
+        let csv = monthly_radiation_table(&metdata, &[(0.0, 0.0, 0.2), (90.0, 0.0, 0.2)]);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "mes,S1_dir,S1_dif,S1_tot,S2_dir,S2_dif,S2_tot");
+        for (i, line) in lines[1..].iter().enumerate() {
+            let cols: Vec<_> = line.split(',').collect();
+            assert_eq!(cols.len(), 7);
+            assert_eq!(cols[0], (i + 1).to_string());
+        }
+        // Enero, superficie horizontal
+        let jan: Vec<f32> = lines[1].split(',').map(|v| v.parse().unwrap()).collect();
+        assert_almost_eq!(jan[1], 33.0);
+        assert_almost_eq!(jan[2], 21.07);
+        assert_almost_eq!(jan[3], jan[1] + jan[2], 0.011);
+    }
+
+    #[test]
+    fn met_average() {
+        let metdata = met::parsemet(METDATA).unwrap();
+
+        // Promedio de archivos idénticos
+        let avg = met::average_met(&[metdata.clone(), metdata.clone()]).unwrap();
+        assert_eq!(avg.meta.zc, "D3");
+        assert_eq!(avg.data.len(), 8760);
+        for (a, d) in avg.data.iter().zip(&metdata.data) {
+            assert_eq!((a.month, a.day, a.hour), (d.month, d.day, d.hour));
+            assert_almost_eq!(a.db_temp, d.db_temp);
+            assert_almost_eq!(a.rdirhor, d.rdirhor);
+            assert_almost_eq!(a.abs_humidity, d.abs_humidity);
+            assert_almost_eq!(a.wind_speed, d.wind_speed);
+            assert_almost_eq!(a.wind_dir, d.wind_dir);
+        }
+
+        // Media de temperaturas entre años distintos
+        let mut warmer = metdata.clone();
+        warmer.data.iter_mut().for_each(|d| d.db_temp += 2.0);
+        let avg = met::average_met(&[metdata.clone(), warmer]).unwrap();
+        assert_almost_eq!(avg.data[0].db_temp, metdata.data[0].db_temp + 1.0);
+
+        // Validación de zona y número de registros
+        let mut other_zone = metdata.clone();
+        other_zone.meta.zc = "E1".to_string();
+        assert!(met::average_met(&[metdata.clone(), other_zone]).is_err());
+        let mut short = metdata.clone();
+        short.data.truncate(24);
+        assert!(met::average_met(&[metdata, short]).is_err());
+        assert!(met::average_met(&[]).is_err());
+    }
+
+    #[test]
+    fn pv_field() {
+        let metdata = met::parsemet(METDATA).unwrap();
+
+        // Coincide con la radiación acumulada mensual de la superficie
+        let monthly = pv_incident_energy(&metdata, 30.0, 0.0, 0.2);
+        let mdata = met::monthly_radiation_for_surface(&metdata, 30.0, 0.0, 0.2);
+        for (i, m) in monthly.iter().enumerate() {
+            assert_almost_eq!(m, mdata.dir[i] + mdata.dif[i]);
+        }
+        // Más radiación en verano que en invierno
+        assert!(monthly[6] > monthly[0]);
+
+        // La inclinación óptima al sur para latitud 40º ronda los 30-40º
+        let (tilt, annual) = pv_optimal_tilt(&metdata, 0.0, 0.2);
+        assert!((25.0..=45.0).contains(&tilt));
+        assert!(
+            annual
+                >= pv_incident_energy(&metdata, 0.0, 0.0, 0.2)
+                    .iter()
+                    .sum::<f32>()
+        );
+        assert!(annual >= monthly.iter().sum::<f32>());
+
+        // Modificador del ángulo de incidencia
+        assert_almost_eq!(pv_incidence_angle_modifier(0.0), 1.0);
+        assert_almost_eq!(pv_incidence_angle_modifier(60.0), 0.95);
+        assert_almost_eq!(pv_incidence_angle_modifier(95.0), 0.0);
+    }
+
+    #[test]
+    fn radiation_breakdown() {
+        let metdata = met::parsemet(METDATA).unwrap();
+        let lat = metdata.meta.latitude;
+
+        // La suma del desglose coincide con el total, para todas las horas y varias orientaciones
+        for (tilt, azimuth) in [
+            (0.0, 0.0),
+            (90.0, 0.0),
+            (90.0, 90.0),
+            (30.0, -45.0),
+            (180.0, 0.0),
+        ] {
+            for d in &metdata.data {
+                let nday = nday_from_ymd(2001, d.month, d.day);
+                let gsol = SolarRadiation {
+                    dir: d.rdirhor,
+                    dif: d.rdifhor,
+                };
+                let total = radiation_for_surface(nday, d.hour, gsol, lat, tilt, azimuth, 0.2);
+                let br =
+                    radiation_breakdown_for_surface(nday, d.hour, gsol, lat, tilt, azimuth, 0.2);
+                assert_almost_eq!(
+                    br.dir + br.circum + br.dif_sky + br.dif_grnd,
+                    total.dir + total.dif
+                );
+                assert_almost_eq!(br.dir + br.circum, total.dir);
+                assert_almost_eq!(br.dif_sky + br.dif_grnd, total.dif);
+            }
+        }
+
+        // Valores intermedios: 21 de junio a mediodía solar (12,5h), superficie horizontal
+        let gsol = SolarRadiation {
+            dir: 800.0,
+            dif: 100.0,
+        };
+        let br = radiation_breakdown_for_surface(172, 12.5, gsol, 40.0, 0.0, 0.0, 0.2);
+        assert_almost_eq!(br.altitude, 73.44, 0.1);
+        assert_almost_eq!(br.angle, 90.0 - br.altitude, 0.01);
+        assert_almost_eq!(br.airmass, 1.043, 0.01);
+        assert_almost_eq!(br.dif_grnd, 0.0);
+        assert!(br.clearness > 1.0 && br.brightness > 0.0);
+    }
 }
@@ -114,6 +114,30 @@ pub struct HourlyData {
     pub zenith: f32,
 }
 
+impl HourlyData {
+    /// Temperatura de rocío (◦C)
+    ///
+    /// Se obtiene de la temperatura seca y la humedad relativa con la fórmula de Magnus
+    pub fn dew_point_temp(&self) -> f32 {
+        const B: f32 = 17.62;
+        const C: f32 = 243.12;
+        let gamma = (self.rel_humidity.clamp(1.0, 100.0) / 100.0).ln()
+            + B * self.db_temp / (C + self.db_temp);
+        C * gamma / (B - gamma)
+    }
+
+    /// Temperatura efectiva del cielo estimada a partir de la temperatura seca y la humedad (◦C)
+    ///
+    /// Usa la emisividad de cielo despejado de Berdahl y Martin (1984) a partir de la temperatura de rocío:
+    /// ε_sky = 0.711 + 0.56 · (T_dp / 100) + 0.73 · (T_dp / 100)², T_sky = T_db · ε_sky^0.25 (en K)
+    /// Al suponer cielo despejado, el salto con la temperatura del aire es máximo.
+    pub fn estimated_sky_temp(&self) -> f32 {
+        let t_dp = self.dew_point_temp() / 100.0;
+        let eps_sky = (0.711 + 0.56 * t_dp + 0.73 * t_dp * t_dp).clamp(0.0, 1.0);
+        (self.db_temp + 273.15) * eps_sky.powf(0.25) - 273.15
+    }
+}
+
 /// Datos de radiación y factores de reducción mensuales para una superficie
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct MonthlySurfaceRadData {
@@ -264,6 +288,84 @@ pub fn read_metdata(metdir: &str) -> HashMap<String, MetData> {
     met
 }
 
+/// Combina varios archivos climáticos de la misma zona en un año tipo promediando sus valores hora a hora
+///
+/// Se promedian la temperatura seca y de cielo, la radiación directa y difusa, la humedad específica
+/// y relativa y la velocidad del viento. La dirección del viento se promedia vectorialmente y
+/// la posición solar (azimut y cénit), que solo depende de la localización y la fecha, se toma del primer archivo,
+/// igual que los metadatos.
+/// Falla si no hay datos, si algún archivo no tiene 8760 registros horarios, si las zonas climáticas
+/// no coinciden o si las fechas y horas de los registros no se corresponden.
+pub fn average_met(files: &[MetData]) -> Result<MetData, Error> {
+    let first = match files.first() {
+        Some(first) => first,
+        None => bail!("No hay datos climáticos para promediar"),
+    };
+    for met in files {
+        if met.data.len() != 8760 {
+            bail!(
+                "Datos horarios de {} con un número de entradas distinto a 8760: {}",
+                met.meta.metname,
+                met.data.len()
+            );
+        }
+        if met.meta.zc != first.meta.zc {
+            bail!(
+                "Zonas climáticas distintas en los datos a promediar: {} y {}",
+                first.meta.zc,
+                met.meta.zc
+            );
+        }
+    }
+
+    let n = files.len() as f32;
+    let mean = |get: fn(&HourlyData) -> f32, i: usize| -> f32 {
+        files.iter().map(|met| get(&met.data[i])).sum::<f32>() / n
+    };
+    let mut data = Vec::with_capacity(8760);
+    for (i, d) in first.data.iter().enumerate() {
+        if files.iter().any(|met| {
+            let h = &met.data[i];
+            (h.month, h.day, h.hour) != (d.month, d.day, d.hour)
+        }) {
+            bail!(
+                "Registros horarios no coincidentes para el {}/{} a las {}h",
+                d.day,
+                d.month,
+                d.hour
+            );
+        }
+        // Media vectorial de la dirección del viento, en el rango [0, 360)
+        let (sin, cos) = files.iter().fold((0.0, 0.0), |(sin, cos), met| {
+            let dir = met.data[i].wind_dir.to_radians();
+            (sin + dir.sin(), cos + dir.cos())
+        });
+        let wind_dir = if files.iter().all(|met| met.data[i].wind_dir == d.wind_dir)
+            || (sin.abs() < 1e-6 && cos.abs() < 1e-6)
+        {
+            d.wind_dir
+        } else {
+            f32::atan2(sin, cos).to_degrees().rem_euclid(360.0)
+        };
+        data.push(HourlyData {
+            db_temp: mean(|h| h.db_temp, i),
+            sky_temp: mean(|h| h.sky_temp, i),
+            rdirhor: mean(|h| h.rdirhor, i),
+            rdifhor: mean(|h| h.rdifhor, i),
+            abs_humidity: mean(|h| h.abs_humidity, i),
+            rel_humidity: mean(|h| h.rel_humidity, i),
+            wind_speed: mean(|h| h.wind_speed, i),
+            wind_dir,
+            ..d.clone()
+        });
+    }
+
+    Ok(MetData {
+        meta: first.meta.clone(),
+        data,
+    })
+}
+
 /// Datos mensuales acumulados de radiación
 pub fn met_monthly_data(metdata: &HashMap<String, MetData>) -> Vec<MonthlySurfaceRadData> {
     const ALBEDO: f32 = 0.2;
@@ -429,6 +531,44 @@ pub(crate) fn monthly_radiation_for_surface(
     }
 }
 
+/// Tabla CSV de radiación acumulada mensual (directa, difusa y total) para un clima y varias superficies, kWh/m²
+///
+/// surfaces: superficies definidas por (inclinación, azimut, albedo)
+///
+/// La tabla tiene una cabecera y una fila por mes, con el número del mes en la primera columna y,
+/// para cada superficie, por orden, las columnas S{n}_dir, S{n}_dif y S{n}_tot (n = 1, 2, ...).
+/// Los valores se separan por comas, con punto decimal y dos decimales.
+pub fn monthly_radiation_table(metdata: &MetData, surfaces: &[(f32, f32, f32)]) -> String {
+    let raddata: Vec<_> = surfaces
+        .iter()
+        .map(|&(tilt, azimuth, albedo)| {
+            monthly_radiation_for_surface(metdata, tilt, azimuth, albedo)
+        })
+        .collect();
+
+    let mut header = vec!["mes".to_string()];
+    for n in 1..=surfaces.len() {
+        header.extend([
+            format!("S{}_dir", n),
+            format!("S{}_dif", n),
+            format!("S{}_tot", n),
+        ]);
+    }
+    let mut lines = vec![header.join(",")];
+    for (i, month) in MONTH_N.iter().enumerate() {
+        let mut row = vec![month.to_string()];
+        for rad in &raddata {
+            row.extend([
+                format!("{:.2}", rad.dir[i]),
+                format!("{:.2}", rad.dif[i]),
+                format!("{:.2}", rad.dir[i] + rad.dif[i]),
+            ]);
+        }
+        lines.push(row.join(","));
+    }
+    lines.join("\n") + "\n"
+}
+
 /// Datos horarios de radiación en una superficie horizontal por zona climática para el 21 de julio, W/m²
 ///
 /// metdata: datos climáticos horarios anuales por zona climática
@@ -24,7 +24,10 @@
 //
 // Authors: Rafael Villar Burke <pachi@rvburke.com>
 
-use super::MONTH_DAYS;
+use super::{
+    met::{monthly_radiation_for_surface, MetData, MonthlyRadData},
+    MONTH_DAYS,
+};
 /// # Solar functions for building science
 ///
 /// Implementation based on formulas from ISO/FDIS 52010-1:2015
@@ -173,13 +176,13 @@ pub fn t_eq(nday: u32) -> f32 {
     if nday < 21 {
         2.6 + 0.44 * nday as f32
     } else if nday < 136 {
-        5.2 + 9.0 * f32::cos((nday - 43) as f32 * 0.0357)
+        5.2 + 9.0 * f32::cos((nday as f32 - 43.0) * 0.0357)
     } else if nday < 241 {
-        1.4 - 5.0 * f32::cos((nday - 135) as f32 * 0.0449)
+        1.4 - 5.0 * f32::cos((nday as f32 - 135.0) * 0.0449)
     } else if nday < 336 {
-        -6.3 - 10.0 * f32::cos((nday - 306) as f32 * 0.036)
+        -6.3 - 10.0 * f32::cos((nday as f32 - 306.0) * 0.036)
     } else {
-        0.45 * (nday - 359) as f32
+        0.45 * (nday as f32 - 359.0)
     }
 }
 /// Time shift -> hours
@@ -389,6 +392,65 @@ pub fn radiation_for_surface(
     surf_azimuth: f32,
     albedo: f32,
 ) -> SolarRadiation {
+    radiation_breakdown_for_surface(nday, hour, gsol, latitude, surf_tilt, surf_azimuth, albedo)
+        .total()
+}
+
+/// Solar radiation on a surface split into its components, with the intermediate values used to compute it
+///
+/// The diffuse sky model (Perez) characterizes the atmosphere with the sky clearness (ε) and
+/// the sky brightness (Δ), that are used here instead of a turbidity factor.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RadiationBreakdown {
+    /// Direct (beam) irradiance on the surface (I_dir), W/m²
+    pub dir: f32,
+    /// Circumsolar irradiance on the surface (I_circum), W/m²
+    pub circum: f32,
+    /// Sky diffuse irradiance on the surface, without the circumsolar component (I_dif - I_circum), W/m²
+    pub dif_sky: f32,
+    /// Irradiance on the surface by ground reflection (I_dif;grnd), W/m²
+    pub dif_grnd: f32,
+    /// Solar angle of incidence on the surface (θ_sol;ic), degrees [0, 180]
+    pub angle: f32,
+    /// Solar altitude (α_sol), degrees
+    pub altitude: f32,
+    /// Air mass (m), dimensionless
+    pub airmass: f32,
+    /// Sky clearness (ε), dimensionless
+    pub clearness: f32,
+    /// Sky brightness (Δ), dimensionless
+    pub brightness: f32,
+}
+
+impl RadiationBreakdown {
+    /// Total direct (I_dir;tot = I_dir + I_circum) and diffuse (I_dif;tot = I_dif - I_circum + I_dif;grnd)
+    /// irradiance on the surface, W/m²
+    pub fn total(&self) -> SolarRadiation {
+        SolarRadiation {
+            dir: I_dir_tot_eq(self.dir, self.circum),
+            dif: self.dif_sky + self.dif_grnd,
+        }
+    }
+}
+
+/// Compute solar radiation available on surface, split into its components, W/m²
+///
+/// nday: day of the year (1<= n <= 366)
+/// nhour: clock time for the location, h [1.0, 24.0]
+/// gsol: solar radiation available on an horizontal surface
+/// latitude (φ_w): latitude of the weather station (degrees)
+/// surf_tilt: surface tilt angle (β_ic), degrees [0, 180]
+/// surf_azimuth: surface orientation (deviation from south, E+, W-) (γ_ic), degrees [-180, 180]
+/// albedo (ρ_sol;grnd): solar reflectivity of the ground [0.0, 1.0]
+pub fn radiation_breakdown_for_surface(
+    nday: u32,
+    hour: f32,
+    gsol: SolarRadiation,
+    latitude: f32,
+    surf_tilt: f32,
+    surf_azimuth: f32,
+    albedo: f32,
+) -> RadiationBreakdown {
     let declination = declination_from_nday(nday);
     let hourangle = hourangle_from_tsol(hour);
     let anglesolsurf = angle_sol_surf(declination, hourangle, latitude, surf_tilt, surf_azimuth);
@@ -406,12 +468,16 @@ pub fn radiation_for_surface(
     // idifgrnd: irradiance on the inclined surface by ground reflection, W/m2
     let idifgrnd = I_dif_grnd(gsolbeam, gsol.dif, altsol, surf_tilt, albedo);
 
-    let idirtot = I_dir_tot_eq(idir, icircum);
-    let idiftot = I_dif_tot_eq(idif, icircum, idifgrnd);
-
-    SolarRadiation {
-        dir: idirtot,
-        dif: idiftot,
+    RadiationBreakdown {
+        dir: idir,
+        circum: icircum,
+        dif_sky: idif - icircum,
+        dif_grnd: idifgrnd,
+        angle: anglesolsurf,
+        altitude: altsol,
+        airmass: airmass(altsol),
+        clearness: clearness(gsolbeam, gsol.dif, altsol),
+        brightness: sky_brightness(nday, gsol.dif, altsol),
     }
 }
 
@@ -601,6 +667,15 @@ pub fn I_dif(
     I_dif_eq(gsoldiff, F1, F2, a, b, betasurf)
 }
 
+/// Sky brightness parameter (Δ), adimensional
+///
+/// nday: day of the year (1<= n <= 366)
+/// gsoldiff: solar diffuse radiation on an horizontal plane, W/m2
+/// altsol (α_sol): solar altitude, degrees
+fn sky_brightness(nday: u32, gsoldiff: f32, altsol: f32) -> f32 {
+    airmass(altsol) * gsoldiff / I_ext(nday)
+}
+
 /// Diffuse radiation parameters
 ///
 /// These are needed to compute diffuse and circumsolar radiation on a surface
@@ -622,7 +697,7 @@ fn get_diffuse_params(
     let b = f32::max(cosd(85.0), cosd(zenith_sol));
     let clearness = clearness(gsolbeam, gsoldiff, altsol);
     let c = brightness_coefficients(clearness);
-    let skybr = airmass(altsol) * gsoldiff / I_ext(nday); // sky brightness param
+    let skybr = sky_brightness(nday, gsoldiff, altsol);
     let F1 = f32::max(0.0, c.f11 + c.f12 * skybr + c.f13 * zenith_sol.to_radians());
     let F2 = c.f21 + c.f22 * skybr + c.f23 * zenith_sol.to_radians();
 
@@ -752,7 +827,7 @@ pub fn I_dif_tot(
     // idifgrnd: irradiance on the inclined surface by ground reflection, W/m2
     let idifgrnd = I_dif_grnd(gsolbeam, gsoldiff, altsol, betasurf, albedo);
     // idiftotval
-    idif - icircum + idifgrnd
+    I_dif_tot_eq(idif, icircum, idifgrnd)
 }
 
 /// Total diffuse solar irradiance, I_dif;tot -> W/m2
@@ -794,3 +869,56 @@ pub fn I_tot(
         month, day, hour, gsolbeam, gsoldiff, altsol, latitude, betasurf, gammasurf, albedo,
     )
 }
+
+// ------------- Photovoltaic fields --------------------
+
+/// Incidence angle modifier for PV modules (K_θ) -> dimensionless [0.0, 1.0]
+///
+/// Fraction of the direct irradiance transmitted by the module cover, relative to normal incidence.
+/// ASHRAE model, K_θ = 1 - b0 · (1 / cos θ - 1), with b0 = 0.05
+/// anglesolsurf (θ_sol;ic): solar angle of incidence on the module surface, degrees [0, 180]
+pub fn pv_incidence_angle_modifier(anglesolsurf: f32) -> f32 {
+    const B0: f32 = 0.05;
+    if anglesolsurf >= 90.0 {
+        return 0.0;
+    }
+    f32::clamp(1.0 - B0 * (1.0 / cosd(anglesolsurf) - 1.0), 0.0, 1.0)
+}
+
+/// Monthly incident solar irradiation on the plane of a PV field -> kWh/m²·month
+///
+/// Total (direct + diffuse) irradiation on the surface, without incidence angle or other losses.
+/// met: hourly climate data
+/// tilt (β_ic): PV field tilt angle, degrees [0, 90]
+/// azimuth (γ_ic): PV field orientation (deviation from south, E+, W-), degrees [-180, 180]
+/// albedo (ρ_sol;grnd): solar reflectivity of the ground [0.0, 1.0]
+pub fn pv_incident_energy(met: &MetData, tilt: f32, azimuth: f32, albedo: f32) -> [f32; 12] {
+    let MonthlyRadData { dir, dif, .. } = monthly_radiation_for_surface(met, tilt, azimuth, albedo);
+    let mut monthly = [0.0; 12];
+    for (m, (dir, dif)) in monthly.iter_mut().zip(dir.iter().zip(&dif)) {
+        *m = dir + dif;
+    }
+    monthly
+}
+
+/// Optimal tilt of a PV field for the maximum annual incident irradiation -> (tilt in degrees, kWh/m²·year)
+///
+/// Tilts from 0º to 90º are checked in 1º steps.
+/// met: hourly climate data
+/// azimuth (γ_ic): PV field orientation (deviation from south, E+, W-), degrees [-180, 180]
+/// albedo (ρ_sol;grnd): solar reflectivity of the ground [0.0, 1.0]
+pub fn pv_optimal_tilt(met: &MetData, azimuth: f32, albedo: f32) -> (f32, f32) {
+    (0..=90)
+        .map(|tilt| {
+            let tilt = tilt as f32;
+            let annual: f32 = pv_incident_energy(met, tilt, azimuth, albedo).iter().sum();
+            (tilt, annual)
+        })
+        .fold((0.0, f32::MIN), |best, current| {
+            if current.1 > best.1 {
+                current
+            } else {
+                best
+            }
+        })
+}
@@ -48,6 +48,7 @@ fn consdb_and_groups_from_bdl(bdl: &Data) -> (ConsDb, ConsDbGroups) {
                 MatProps::Resistance {
                     resistance: material.resistance.unwrap_or_default(),
                     vapour_diff: None,
+                    thickness: material.resistance_thickness,
                 }
             },
         });
@@ -64,6 +65,7 @@ fn consdb_and_groups_from_bdl(bdl: &Data) -> (ConsDb, ConsDbGroups) {
             name: name.clone(),
             u_value: glass.conductivity,
             g_gln: glass.g_gln,
+            g_angular: None,
         });
     }
     let mut frames = Vec::new();
@@ -116,7 +118,11 @@ fn consdb_and_groups_from_bdl(bdl: &Data) -> (ConsDb, ConsDbGroups) {
             .iter()
             .copied()
             .zip(cons.thickness.iter().copied())
-            .map(|(material, e)| Layer { material, e })
+            .map(|(material, e)| Layer {
+                material,
+                e,
+                e_max: None,
+            })
             .collect();
 
         let id = uuid_from_obj(cons);
@@ -129,6 +135,7 @@ fn consdb_and_groups_from_bdl(bdl: &Data) -> (ConsDb, ConsDbGroups) {
             name: cons.name.clone(),
             layers,
             absorptance: cons.absorptance,
+            emissivity: None,
         });
     }
 
@@ -170,9 +177,17 @@ fn consdb_and_groups_from_bdl(bdl: &Data) -> (ConsDb, ConsDbGroups) {
             glass,
             frame,
             f_f: cons.framefrac,
+            frame_width: bdl
+                .db
+                .frames
+                .get(&cons.frame)
+                .map(|f| f.width)
+                .filter(|w| *w > 0.0),
             delta_u: cons.deltau,
+            shutter_box: None,
             g_glshwi: cons.gglshwi,
             c_100: cons.infcoeff,
+            c_100_parts: None,
         });
     }
 
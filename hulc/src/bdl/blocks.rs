@@ -8,7 +8,7 @@
 
 use super::AttrMap;
 
-use anyhow::{bail, Error};
+use anyhow::{bail, Context, Error};
 
 // Objetos ----------------------------------------------------------------
 
@@ -24,6 +24,9 @@ pub struct BdlBlock {
     pub parent: Option<String>,
     /// Conjunto de propiedades
     pub attrs: AttrMap,
+    /// Línea del archivo original en la que comienza el bloque (desde 1)
+    /// Vale 0 si el bloque no proviene de un archivo
+    pub line: usize,
 }
 
 impl std::str::FromStr for BdlBlock {
@@ -53,6 +56,7 @@ impl std::str::FromStr for BdlBlock {
                 btype: btype.parse()?,
                 parent: None,
                 attrs: AttrMap::new(),
+                line: 0,
             });
         }
 
@@ -87,6 +91,7 @@ impl std::str::FromStr for BdlBlock {
             btype: btype.parse()?,
             parent: None,
             attrs,
+            line: 0,
         })
     }
 }
@@ -220,13 +225,16 @@ impl std::str::FromStr for BdlBlockType {
 }
 
 /// Elimina líneas en blanco y comentarios
-fn clean_lines(input: &str) -> String {
-    input
+///
+/// Devuelve también el número de línea original (desde 1) de cada línea conservada
+fn clean_lines(input: &str) -> (String, Vec<usize>) {
+    let (nums, lines): (Vec<_>, Vec<_>) = input
         .replace("\r\n", "\n") // Normalizar saltos de línea
         .replace('ÿ', "") // Marcador de LIDER (antiguo)
         .lines()
         .map(str::trim)
-        .filter(|l| {
+        .enumerate()
+        .filter(|(_, l)| {
             !l.is_empty() // Líneas en blanco
             && !l.starts_with('$') // Comentarios
             && !l.starts_with('+') // Encabezados de LIDER (antiguo)
@@ -235,17 +243,19 @@ fn clean_lines(input: &str) -> String {
             && *l != "HUECOS"
             && *l != "PUENTES TERMICOS"
         })
-        .collect::<Vec<&str>>()
-        .join("\n")
+        .map(|(n, l)| (n + 1, l.to_string()))
+        .unzip();
+    (lines.join("\n"), nums)
 }
 
 /// Limpia y corrige datos de LIDER para tener bloques BDL bien formateados
 ///
 /// Elimina comentarios y líneas en blanco
 /// Corrige bloque de datos de LIDER mal formados
-fn sanitize_lider_data(input: &str) -> String {
+/// Devuelve también el número de línea original (desde 1) de cada línea del resultado
+fn sanitize_lider_data(input: &str) -> (String, Vec<usize>) {
     // Elimna comentarios y líneas innecesarias
-    let cleanlines = clean_lines(input);
+    let (cleanlines, nums) = clean_lines(input);
 
     // Si existe, separamos una parte inicial de atributos sueltos de LIDER,
     // sin bloque, del resto de contenido, que es BDL válido:
@@ -261,18 +271,57 @@ fn sanitize_lider_data(input: &str) -> String {
         } else if let Some(pos) = cleanlines.find("\"Defecto\" = DESCRIPTION") {
             cleanlines.split_at(pos)
         } else {
-            return cleanlines;
+            return (cleanlines, nums);
         };
-    format!(
-        "\"PARTELIDER\" = PARTELIDER\n{}\n..\n{}",
-        _lider_part, bdl_part
+    // Líneas añadidas: encabezado del bloque de LIDER, y línea vacía y fin de bloque tras él
+    let n_lider = _lider_part.matches('\n').count();
+    let first = nums.first().copied().unwrap_or(1);
+    let last_lider = nums[..n_lider].last().copied().unwrap_or(first);
+    let mut new_nums = vec![first];
+    new_nums.extend(&nums[..n_lider]);
+    new_nums.extend([last_lider, last_lider]);
+    new_nums.extend(&nums[n_lider..]);
+    (
+        format!(
+            "\"PARTELIDER\" = PARTELIDER\n{}\n..\n{}",
+            _lider_part, bdl_part
+        ),
+        new_nums,
     )
 }
 
+/// Genera la lista de bloques BDL a partir del texto de un archivo
+///
+/// Se guarda en cada bloque su línea de inicio en el texto original,
+/// que también se indica en los errores de interpretación de los bloques.
 pub fn build_blocks<T: AsRef<str>>(input: T) -> Result<Vec<BdlBlock>, Error> {
+    build_blocks_from_line(input, 1)
+}
+
+/// Genera la lista de bloques BDL a partir de un texto que comienza en la línea first_line de un archivo
+///
+/// Permite indicar las líneas de los bloques en el archivo que contiene el texto BDL (p.e. un .ctehexml).
+pub fn build_blocks_from_line<T: AsRef<str>>(
+    input: T,
+    first_line: usize,
+) -> Result<Vec<BdlBlock>, Error> {
     use BdlBlockType::*;
 
-    let cleandata = sanitize_lider_data(input.as_ref());
+    let (cleandata, nums) = sanitize_lider_data(input.as_ref());
+    // Índice de línea de cada bloque, contando los saltos de línea previos a su posición
+    let mut offset = 0;
+    let mut line_idx = 0;
+    let mut block_line = |block: &str| {
+        let block_offset = block.as_ptr() as usize - cleandata.as_ptr() as usize;
+        line_idx += cleandata[offset..block_offset].matches('\n').count();
+        offset = block_offset;
+        nums.get(line_idx)
+            .or_else(|| nums.last())
+            .copied()
+            .unwrap_or(1)
+            + first_line
+            - 1
+    };
 
     let blockstrs = cleandata
         .split("..")
@@ -294,7 +343,11 @@ pub fn build_blocks<T: AsRef<str>>(input: T) -> Result<Vec<BdlBlock>, Error> {
         {
             continue;
         };
-        let mut bdlblock: BdlBlock = block.parse()?;
+        let line = block_line(block);
+        let mut bdlblock: BdlBlock = block
+            .parse()
+            .with_context(|| format!("Error en el bloque BDL de la línea {}", line))?;
+        bdlblock.line = line;
         // Corrige el elemento madre
         let parent = match bdlblock.btype {
             // Las plantas no cuelgan de ningún elemento
@@ -45,20 +45,17 @@ impl AttrMap {
     }
 
     /// Devuelve valor como número
+    ///
+    /// Se admiten valores con unidades adjuntas (p.e. "3.5 W"), que se ignoran
     pub fn get_f32(&self, attr: &str) -> Result<f32, Error> {
-        self.0
-            .get(attr)
-            .and_then(|v| match v {
-                BdlValue::Number(num) => Some(*num),
-                _ => None,
-            })
-            .ok_or_else(|| {
-                format_err!(
-                    "Atributo '{}' no encontrado en el bloque '{:#?}'",
-                    attr,
-                    self
-                )
-            })
+        match self.0.get(attr) {
+            Some(value) => value_to_f32(attr, value),
+            None => Err(format_err!(
+                "Atributo '{}' no encontrado en el bloque '{:#?}'",
+                attr,
+                self
+            )),
+        }
     }
 
     /// Devuelve valor como número o valor por defecto (0.0)
@@ -100,20 +97,17 @@ impl AttrMap {
     }
 
     /// Elimina valor y devuelve como número
+    ///
+    /// Se admiten valores con unidades adjuntas (p.e. "3.5 W"), que se ignoran
     pub fn remove_f32(&mut self, attr: &str) -> Result<f32, Error> {
-        self.0
-            .remove(attr)
-            .and_then(|v| match v {
-                BdlValue::Number(num) => Some(num),
-                _ => None,
-            })
-            .ok_or_else(|| {
-                format_err!(
-                    "Atributo '{}' no encontrado en el bloque '{:#?}'",
-                    attr,
-                    self
-                )
-            })
+        match self.0.remove(attr) {
+            Some(value) => value_to_f32(attr, &value),
+            None => Err(format_err!(
+                "Atributo '{}' no encontrado en el bloque '{:#?}'",
+                attr,
+                self
+            )),
+        }
     }
 
     /// Elimina valor y devuelve como String
@@ -190,32 +184,87 @@ pub fn extract_namesvec<S: AsRef<str>>(input: S) -> Vec<String> {
         .collect::<Vec<_>>()
 }
 
-/// Interpreta lista de valores con formato "(num1, num2, num3, ...)"
-pub fn extract_f32vec<S: AsRef<str> + std::fmt::Debug>(input: S) -> Result<Vec<f32>, Error> {
+/// Convierte el valor de un atributo a número, ignorando las unidades adjuntas
+fn value_to_f32(attr: &str, value: &BdlValue) -> Result<f32, Error> {
+    match value {
+        BdlValue::Number(num) => Ok(*num),
+        BdlValue::String(raw) => parse_number(raw).ok_or_else(|| {
+            format_err!(
+                "Valor numérico incorrecto en el atributo '{}': '{}'",
+                attr,
+                raw
+            )
+        }),
+    }
+}
+
+/// Interpreta un número al que pueden seguir unidades (p.e. "3.5 W", "0.8W/m2K" o "25 %")
+///
+/// Las unidades deben empezar por una letra o por los símbolos '%' o 'º' y se toma como número
+/// el prefijo más largo que pueda interpretarse como tal
+fn parse_number<T: std::str::FromStr>(input: &str) -> Option<T> {
+    let input = input.trim();
+    if let Ok(num) = input.parse::<T>() {
+        return Some(num);
+    }
     input
-        .as_ref()
+        .char_indices()
+        .rev()
+        .filter(|(i, _)| *i > 0)
+        .find_map(|(i, _)| {
+            let (num, unit) = input.split_at(i);
+            if unit
+                .trim_start()
+                .starts_with(|c: char| c.is_alphabetic() || c == '%' || c == 'º' || c == '°')
+            {
+                num.trim_end().parse::<T>().ok()
+            } else {
+                None
+            }
+        })
+}
+
+/// Separa los elementos de una lista de valores con formato "(v1, v2, v3, ...)"
+///
+/// Se admiten como separadores comas, puntos y comas y espacios en blanco
+fn split_values(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .trim()
         .trim_matches(&[' ', '(', ')'] as &[_])
-        .split(',')
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+}
+
+/// Interpreta lista de valores con formato "(num1, num2, num3, ...)"
+///
+/// Se admiten como separadores comas, puntos y comas y espacios en blanco
+pub fn extract_f32vec<S: AsRef<str> + std::fmt::Debug>(input: S) -> Result<Vec<f32>, Error> {
+    split_values(input.as_ref())
         .map(|v| {
-            v.trim()
-                .parse::<f32>()
-                .map_err(|_| format_err!("Error al convertir {}", v))
+            parse_number::<f32>(v).ok_or_else(|| {
+                format_err!(
+                    "Error en la conversión numérica del valor '{}' de la lista {:?}",
+                    v,
+                    input
+                )
+            })
         })
-        .collect::<Result<Vec<f32>, _>>()
-        .map_err(|_| format_err!("Error en la conversión numérica de {:?}", input))
+        .collect()
 }
 
 /// Interpreta lista de valores con formato "(num1, num2, num3, ...)" como lista de u32
+///
+/// Se admiten como separadores comas, puntos y comas y espacios en blanco
 pub fn extract_u32vec<S: AsRef<str> + std::fmt::Debug>(input: S) -> Result<Vec<u32>, Error> {
-    input
-        .as_ref()
-        .trim_matches(&[' ', '(', ')'] as &[_])
-        .split(',')
+    split_values(input.as_ref())
         .map(|v| {
-            v.trim()
-                .parse::<u32>()
-                .map_err(|_| format_err!("Error al convertir {}", v))
+            parse_number::<u32>(v).ok_or_else(|| {
+                format_err!(
+                    "Error en la conversión numérica del valor '{}' de la lista {:?}",
+                    v,
+                    input
+                )
+            })
         })
-        .collect::<Result<Vec<u32>, _>>()
-        .map_err(|_| format_err!("Error en la conversión numérica de {:?}", input))
-}
\ No newline at end of file
+        .collect()
+}
@@ -23,6 +23,9 @@ pub struct Material {
     pub properties: Option<MaterialProperties>,
     /// Definición de resistencia térmica R (m2K/W)
     pub resistance: Option<f32>,
+    /// Espesor de los materiales definidos por su resistencia térmica, d (m)
+    /// Es un valor informativo, que no interviene en el cálculo
+    pub resistance_thickness: Option<f32>,
 }
 
 /// Definición de propiedades termofísicas y grosor
@@ -76,6 +79,12 @@ impl TryFrom<BdlBlock> for Material {
     ///         DENSITY = 30
     ///         SPECIFIC-HEAT = 1800
     ///         ..
+    ///
+    ///     "Cámara de aire sin ventilar vertical 5 cm" = MATERIAL
+    ///         TYPE              = RESISTANCE
+    ///         THICKNESS         =           0.05
+    ///         RESISTANCE        =           0.18
+    ///         ..
     /// ```
     /// TODO: Propiedades no convertidas:
     /// TODO: THICKNESS_CHANGE, THICKNESS_MAX, THICKNESS_MIN, IMAGE, NAME_CALENER, LIBRARY, UTIL, OBSOLETE
@@ -90,33 +99,37 @@ impl TryFrom<BdlBlock> for Material {
         let group = attrs
             .remove_str("GROUP")
             .unwrap_or_else(|_| "Materiales".to_string());
-        let (properties, resistance) = if &attrs.remove_str("TYPE")? == "PROPERTIES" {
-            // XXX: En LIDER antiguo no se define este valor
-            let thickness = attrs.remove_f32("THICKNESS").ok();
-            let conductivity = attrs.remove_f32("CONDUCTIVITY")?;
-            let density = attrs.remove_f32("DENSITY")?;
-            let specificheat = attrs.remove_f32("SPECIFIC-HEAT").unwrap_or(800.0);
-            // XXX: En LIDER antiguo no se define este valor
-            let vapourdiffusivity = attrs.remove_f32("VAPOUR-DIFFUSIVITY-FACTOR").ok();
-            (
-                Some(MaterialProperties {
-                    thickness,
-                    conductivity,
-                    density,
-                    specificheat,
-                    vapourdiffusivity,
-                }),
-                None,
-            )
-        } else {
-            let resistance = attrs.remove_f32("RESISTANCE")?;
-            (None, Some(resistance))
-        };
+        let (properties, resistance, resistance_thickness) =
+            if &attrs.remove_str("TYPE")? == "PROPERTIES" {
+                // XXX: En LIDER antiguo no se define este valor
+                let thickness = attrs.remove_f32("THICKNESS").ok();
+                let conductivity = attrs.remove_f32("CONDUCTIVITY")?;
+                let density = attrs.remove_f32("DENSITY")?;
+                let specificheat = attrs.remove_f32("SPECIFIC-HEAT").unwrap_or(800.0);
+                // XXX: En LIDER antiguo no se define este valor
+                let vapourdiffusivity = attrs.remove_f32("VAPOUR-DIFFUSIVITY-FACTOR").ok();
+                (
+                    Some(MaterialProperties {
+                        thickness,
+                        conductivity,
+                        density,
+                        specificheat,
+                        vapourdiffusivity,
+                    }),
+                    None,
+                    None,
+                )
+            } else {
+                let resistance = attrs.remove_f32("RESISTANCE")?;
+                let thickness = attrs.remove_f32("THICKNESS").ok();
+                (None, Some(resistance), thickness)
+            };
         Ok(Self {
             name,
             group,
             properties,
             resistance,
+            resistance_thickness,
         })
     }
 }
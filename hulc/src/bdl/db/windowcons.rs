@@ -35,6 +35,8 @@ pub struct WinCons {
     pub deltau: f32,
     /// Transmitancia total de energía del acristalameinto con los dispositivo de sombra móvil activados (g_gl;sh;wi) (-)
     pub gglshwi: Option<f32>,
+    /// ¿Es una puerta? (ISDOOR)
+    pub isdoor: bool,
 }
 
 // TODO: Llevar estas implementaciones a las WinCons de types, que es donde deberían estar
@@ -106,7 +108,7 @@ impl TryFrom<BdlBlock> for WinCons {
     ///           DEFAULT           = NO
     ///          ..
     /// ```
-    /// TODO: atributos no trasladados: ISDOOR, TRANSMITANCIA, SHADING-COEF
+    /// TODO: atributos no trasladados: TRANSMITANCIA, SHADING-COEF
     /// TODO: SHADE-COEF-SUMMER, SHADE-COEF-WINTER, MARKER-SUMMER,  MARKER-WINTER,
     /// TODO: LIBRARY (proviene de la biblioteca?), UTIL (se utiliza?), DEFAULT
     fn try_from(value: BdlBlock) -> Result<Self, Self::Error> {
@@ -126,6 +128,10 @@ impl TryFrom<BdlBlock> for WinCons {
             .remove_f32("porcentajeIncrementoU")
             .unwrap_or_default();
         let gglshwi = attrs.remove_f32("TransmisividadJulio").ok();
+        let isdoor = attrs
+            .remove_str("ISDOOR")
+            .map(|v| matches!(v.trim(), "YES" | "SI"))
+            .unwrap_or(false);
         Ok(Self {
             name,
             group,
@@ -137,6 +143,7 @@ impl TryFrom<BdlBlock> for WinCons {
             infcoeff,
             deltau,
             gglshwi,
+            isdoor,
         })
     }
 }
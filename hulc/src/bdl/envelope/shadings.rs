@@ -49,6 +49,8 @@ pub struct Shading {
     pub geometry: Option<ShadingGeometry>,
     /// Geometría por vértices
     pub vertices: Option<Vec<Point3>>,
+    /// Horario anual de transmisividad de la sombra (p.e. vegetación de hoja caduca)
+    pub schedule: Option<String>,
 }
 
 impl TryFrom<BdlBlock> for Shading {
@@ -81,6 +83,11 @@ impl TryFrom<BdlBlock> for Shading {
     ///         V3       =( 6, 27.04, 12.5 )
     ///         V4       =( 6, 25.7901, 12.5 )
     ///         ..
+    ///     "Arbol" = BUILDING-SHADE
+    ///         TRAN     =              0
+    ///         REFL     =            0.7
+    ///         SHADE-SCHEDULE = "Hoja caduca"
+    ///         ...
     /// ```
     /// TODO: atributos no trasladados:
     /// TODO: BULB-TRA, BULB-REF
@@ -90,6 +97,7 @@ impl TryFrom<BdlBlock> for Shading {
         } = value;
         let tran = attrs.remove_f32("TRAN")?;
         let refl = attrs.remove_f32("REFL")?;
+        let schedule = attrs.remove_str("SHADE-SCHEDULE").ok();
         let (geometry, vertices) = if attrs.get_f32("X").is_ok() {
             // Definición por rectángulo
             (
@@ -124,6 +132,7 @@ impl TryFrom<BdlBlock> for Shading {
             refl,
             geometry,
             vertices,
+            schedule,
         })
     }
 }
@@ -15,7 +15,7 @@ use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::path::Path;
 
-use anyhow::{bail, format_err, Error};
+use anyhow::{bail, format_err, Context, Error};
 use log::{info, warn};
 
 mod blocks;
@@ -24,13 +24,15 @@ mod db;
 mod envelope;
 mod systems;
 
-pub use blocks::{build_blocks, BdlBlock, BdlBlockType};
+pub use blocks::{build_blocks, build_blocks_from_line, BdlBlock, BdlBlockType};
 pub use common::{extract_f32vec, extract_namesvec, extract_u32vec, AttrMap};
 pub use db::{Construction, Frame, Glass, Material, MaterialProperties, WallCons, WinCons, DB};
 pub use envelope::{
     BoundaryType, Floor, Polygon, Shading, Space, ThermalBridge, Tilt, Wall, Window,
 };
-pub use systems::{DaySchedule, Schedule, WeekSchedule, YearSchedule};
+pub use systems::{
+    year_values, DaySchedule, Schedule, SpaceSysConditions, WeekSchedule, YearSchedule,
+};
 
 // ------------------------- BDL ----------------------------
 
@@ -41,6 +43,8 @@ pub struct Data {
     pub meta: BTreeMap<BdlBlockType, BdlBlock>,
     /// Base de datos de materiales, productos y composiciones constructivas
     pub db: DB,
+    /// Lista de plantas
+    pub floors: Vec<Floor>,
     /// Lista de espacios
     pub spaces: Vec<Space>,
     /// Elementos opacos de la envolvente
@@ -67,9 +71,16 @@ impl Data {
 
     /// Nuevo modelo a partir de str
     pub fn new<T: AsRef<str>>(input: T) -> Result<Self, Error> {
+        Self::new_from_line(input, 1)
+    }
+
+    /// Nuevo modelo a partir de str que comienza en la línea first_line de un archivo
+    ///
+    /// Las líneas de los bloques, y de sus errores, se cuentan desde el inicio del archivo
+    pub fn new_from_line<T: AsRef<str>>(input: T, first_line: usize) -> Result<Self, Error> {
         use BdlBlockType::*;
 
-        let blocks = build_blocks(input.as_ref())?;
+        let blocks = build_blocks_from_line(input.as_ref(), first_line)?;
 
         let mut db_blocks = Vec::new();
         let mut poly_blocks = Vec::new();
@@ -107,26 +118,26 @@ impl Data {
         for block in db_blocks {
             match block.btype {
                 Construction => {
-                    constructions.insert(block.name.clone(), db::Construction::try_from(block)?);
+                    constructions.insert(block.name.clone(), from_block(block)?);
                 }
                 Material => {
-                    let e = db::Material::try_from(block)?;
+                    let e = from_block::<db::Material>(block)?;
                     materials.insert(e.name.clone(), e);
                 }
                 NameFrame => {
-                    let e = db::Frame::try_from(block)?;
+                    let e = from_block::<db::Frame>(block)?;
                     frames.insert(e.name.clone(), e);
                 }
                 GlassType => {
-                    let e = db::Glass::try_from(block)?;
+                    let e = from_block::<db::Glass>(block)?;
                     glasses.insert(e.name.clone(), e);
                 }
                 Layers => {
-                    let e = db::WallCons::try_from(block)?;
+                    let e = from_block::<db::WallCons>(block)?;
                     layers.insert(e.name.clone(), e);
                 }
                 Gap => {
-                    let e = db::WinCons::try_from(block)?;
+                    let e = from_block::<db::WinCons>(block)?;
                     wincons.insert(e.name.clone(), e);
                 }
                 _ => unreachable!(),
@@ -183,15 +194,15 @@ impl Data {
         // luego los sustituiremos en los objetos de opacos y SPACE que los usan
         let mut polygons: BTreeMap<String, envelope::Polygon> = BTreeMap::default();
         for block in poly_blocks {
-            polygons.insert(block.name.clone(), envelope::Polygon::try_from(block)?);
+            polygons.insert(block.name.clone(), from_block(block)?);
         }
 
         // Separa plantas (FLOOR) --------------
-        // Estos objetos los eliminamos finalmente del modelo, sumando sus X,Y,Z, Azimuth a los del espacio
-        // luego los sustituiremos en los objetos SPACE que los usan
+        // Sumamos sus X,Y,Z, Azimuth a los de los objetos SPACE que los usan
+        // y las conservamos para mantener la estructura de plantas del edificio
         let mut floors: BTreeMap<String, envelope::Floor> = BTreeMap::default();
         for block in floor_blocks {
-            floors.insert(block.name.clone(), envelope::Floor::try_from(block)?);
+            floors.insert(block.name.clone(), from_block(block)?);
         }
 
         // Horarios --------------------------------------
@@ -200,19 +211,13 @@ impl Data {
         for block in schedule_blocks {
             match block.btype {
                 DaySchedulePd => {
-                    schedules.push(systems::Schedule::Day(systems::DaySchedule::try_from(
-                        block,
-                    )?));
+                    schedules.push(systems::Schedule::Day(from_block(block)?));
                 }
                 WeekSchedulePd => {
-                    schedules.push(systems::Schedule::Week(systems::WeekSchedule::try_from(
-                        block,
-                    )?));
+                    schedules.push(systems::Schedule::Week(from_block(block)?));
                 }
                 SchedulePd => {
-                    schedules.push(systems::Schedule::Year(systems::YearSchedule::try_from(
-                        block,
-                    )?));
+                    schedules.push(systems::Schedule::Year(from_block(block)?));
                 }
                 RunPeriodPd => {
                     info!("Ignorando bloque de periodo de cálculo: {}", block.name);
@@ -234,16 +239,18 @@ impl Data {
             match block.btype {
                 // Espacios -----------
                 Space => {
+                    let line = block.line;
                     let polygon_name = block.attrs.get_str("POLYGON")?;
-                    let mut space = envelope::Space::try_from(block)?;
+                    let mut space = from_block::<envelope::Space>(block)?;
                     // Insertamos el polígono -------
                     space.polygon = polygons
                         .get(&polygon_name)
                         .ok_or_else(|| {
                             format_err!(
-                                "Polígono {} no encontrado para el espacio {}",
+                                "Polígono {} no encontrado para el espacio {} (línea {})",
                                 &polygon_name,
                                 &space.name,
+                                line,
                             )
                         })?
                         .clone();
@@ -254,9 +261,10 @@ impl Data {
                     // NOTA: los espacios con cubierta inclinada podrían llegar a tener otra altura
                     let floor = floors.get(&space.floor).ok_or_else(|| {
                         format_err!(
-                            "No se ha encontrado la planta {} del espacio {}",
+                            "No se ha encontrado la planta {} del espacio {} (línea {})",
                             space.floor,
-                            space.name
+                            space.name,
+                            line
                         )
                     })?;
                     // Para la altura de espacios usamos la altura suelo-suelo de las plantas y para la neta descontamos la altura del forjado superior
@@ -269,14 +277,16 @@ impl Data {
 
                 // Cerramientos opacos de la envolvente -----------
                 ExteriorWall | Roof | InteriorWall | UndergroundWall => {
+                    let line = block.line;
                     let maybe_polygon_name = block.attrs.get_str("POLYGON").ok();
-                    let mut wall = envelope::Wall::try_from(block)?;
+                    let mut wall = from_block::<envelope::Wall>(block)?;
                     wall.polygon = if let Some(polygon_name) = maybe_polygon_name {
                         Some(polygons.remove(&polygon_name).ok_or_else(|| {
                             format_err!(
-                                "Polígono {} no encontrado para definición de opaco {}",
+                                "Polígono {} no encontrado para definición de opaco {} (línea {})",
                                 &polygon_name,
                                 &wall.name,
+                                line,
                             )
                         })?)
                     } else {
@@ -285,9 +295,10 @@ impl Data {
 
                     if !db.wallcons.contains_key(&wall.cons) {
                         bail!(
-                            "Construcción {} no encontrada para definicón de opaco {}",
+                            "Construcción {} no encontrada para definicón de opaco {} (línea {})",
                             wall.cons,
-                            wall.name
+                            wall.name,
+                            line
                         );
                     };
 
@@ -303,17 +314,17 @@ impl Data {
                 // Elementos transparentes de la envolvente -----
                 // Hueco
                 Window => {
-                    windows.push(envelope::Window::try_from(block)?);
+                    windows.push(from_block(block)?);
                 }
 
                 // Puentes térmicos ----------
                 ThermalBridge => {
-                    thermal_bridges.push(envelope::ThermalBridge::try_from(block)?);
+                    thermal_bridges.push(from_block(block)?);
                 }
 
                 // Sombras --------------------------------------
                 BuildingShade => {
-                    shadings.push(envelope::Shading::try_from(block)?);
+                    shadings.push(from_block(block)?);
                 }
 
                 _ => unreachable!(),
@@ -361,6 +372,7 @@ impl Data {
         Ok(Self {
             meta,
             db,
+            floors: floors.into_values().collect(),
             spaces,
             walls,
             windows,
@@ -386,6 +398,21 @@ impl Data {
     pub fn get_space<T: AsRef<str>>(&self, name: T) -> Option<&Space> {
         self.spaces.iter().find(|w| w.name == name.as_ref())
     }
+
+    /// Consignas de los sistemas con el nombre indicado, con sus consignas horarias resueltas
+    pub fn get_sys_conditions<T: AsRef<str>>(&self, name: T) -> Result<SpaceSysConditions, Error> {
+        let block = self
+            .system_conditions
+            .get(name.as_ref())
+            .ok_or_else(|| format_err!("Consignas {} no encontradas", name.as_ref()))?;
+        SpaceSysConditions::from_bdl(block, &self.schedules)
+    }
+}
+
+/// Convierte un bloque BDL en un elemento, indicando en los errores el nombre y la línea del bloque
+fn from_block<T: TryFrom<BdlBlock, Error = Error>>(block: BdlBlock) -> Result<T, Error> {
+    let (name, line) = (block.name.clone(), block.line);
+    T::try_from(block).with_context(|| format!("Error en el bloque {} de la línea {}", name, line))
 }
 
 /// Ángulo del opaco respecto al norte (grados sexagesimales, sentido horario, [0, 360])
@@ -0,0 +1,104 @@
+// Copyright (c) 2018-2022 Rafael Villar Burke <pachi@ietcc.csic.es>
+// Distributed under the MIT License
+// (See accompanying LICENSE file or a copy at http://opensource.org/licenses/MIT)
+
+//! Parser del Building Description Language (BDL) de DOE
+//!
+//! Consignas de los sistemas de los espacios (SYSTEM-CONDITIONS)
+
+use anyhow::{format_err, Error};
+
+use super::schedules::{year_values, Schedule};
+use crate::bdl::BdlBlock;
+
+/// Consignas y horarios de funcionamiento de los sistemas de un espacio
+///
+/// Las consignas horarias se resuelven a partir de los horarios de temperatura de consigna
+/// y de los horarios de funcionamiento de calefacción y refrigeración.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpaceSysConditions {
+    /// Nombre
+    pub name: String,
+    /// Espacio acondicionado (TYPE = CONDITIONED)
+    pub conditioned: bool,
+    /// Horario de temperatura de consigna de calefacción
+    pub heat_temp_sch: Option<String>,
+    /// Horario de temperatura de consigna de refrigeración
+    pub cool_temp_sch: Option<String>,
+    /// Horario de funcionamiento de la calefacción
+    pub heating_schedule: Option<String>,
+    /// Horario de funcionamiento de la refrigeración
+    pub cooling_schedule: Option<String>,
+    /// Consignas horarias de calefacción (8760 valores) [ºC]
+    ///
+    /// Vale None en las horas sin calefacción o en todas si el espacio no está acondicionado
+    pub heating_setpoints: Vec<Option<f32>>,
+    /// Consignas horarias de refrigeración (8760 valores) [ºC]
+    ///
+    /// Vale None en las horas sin refrigeración o en todas si el espacio no está acondicionado
+    pub cooling_setpoints: Vec<Option<f32>>,
+}
+
+impl SpaceSysConditions {
+    /// Conversión de bloque BDL a consignas de sistemas, resolviendo los horarios
+    ///
+    /// Ejemplo en BDL:
+    /// ```text
+    ///    "Res-Acondicionado" = SYSTEM-CONDITIONS
+    ///         NAME               = "Residencial"
+    ///         HEAT-TEMP-SCH      = "Horario-Calefaccion"
+    ///         COOL-TEMP-SCH      = "Horario-Refrigeracion"
+    ///         TYPE               = CONDITIONED
+    ///         HEATING-SCHEDULE   = "Funcionam-Residencia"
+    ///         COOLING-SCHEDULE   = "Funcionam-Residencia"
+    ///         ..
+    /// ```
+    /// Sin horario de funcionamiento se considera que el sistema funciona todo el año.
+    /// TODO: Propiedades no convertidas:
+    /// TODO: ZONAL-ZONE-METHOD, MAX-HEAT-RATE, MAX-COOL-RATE, FLOW/AREA, FAN-SCHEDULE, SEASON-SCH
+    pub fn from_bdl(block: &BdlBlock, schedules: &[Schedule]) -> Result<Self, Error> {
+        let attrs = &block.attrs;
+        let conditioned = attrs.get_str("TYPE").ok().as_deref() == Some("CONDITIONED");
+        let heat_temp_sch = attrs.get_str("HEAT-TEMP-SCH").ok();
+        let cool_temp_sch = attrs.get_str("COOL-TEMP-SCH").ok();
+        let heating_schedule = attrs.get_str("HEATING-SCHEDULE").ok();
+        let cooling_schedule = attrs.get_str("COOLING-SCHEDULE").ok();
+
+        let setpoints = |temp_sch: &Option<String>, on_sch: &Option<String>| {
+            if !conditioned {
+                return Ok(vec![None; 8760]);
+            }
+            let temp_sch = temp_sch.as_ref().ok_or_else(|| {
+                format_err!(
+                    "Consigna no definida en el espacio acondicionado {}",
+                    block.name
+                )
+            })?;
+            let temps = year_values(schedules, temp_sch)?;
+            let on = match on_sch {
+                Some(on_sch) => year_values(schedules, on_sch)?,
+                None => vec![1.0; 8760],
+            };
+            Ok::<_, Error>(
+                temps
+                    .into_iter()
+                    .zip(on)
+                    .map(|(t, on)| (on.abs() > 100.0 * f32::EPSILON).then_some(t))
+                    .collect(),
+            )
+        };
+        let heating_setpoints = setpoints(&heat_temp_sch, &heating_schedule)?;
+        let cooling_setpoints = setpoints(&cool_temp_sch, &cooling_schedule)?;
+
+        Ok(Self {
+            name: block.name.clone(),
+            conditioned,
+            heat_temp_sch,
+            cool_temp_sch,
+            heating_schedule,
+            cooling_schedule,
+            heating_setpoints,
+            cooling_setpoints,
+        })
+    }
+}
@@ -5,7 +5,10 @@
 //! Parser del Building Description Language (BDL) de DOE
 //!
 //! Horarios (SCHEDULE-PD, WEEK-SCHEDULE-PD, DAY-SCHEDULE-PD)
+//! y consignas de los sistemas (SYSTEM-CONDITIONS)
 
+mod conditions;
 mod schedules;
 
-pub use schedules::{DaySchedule, Schedule, WeekSchedule, YearSchedule};
+pub use conditions::SpaceSysConditions;
+pub use schedules::{year_values, DaySchedule, Schedule, WeekSchedule, YearSchedule};
@@ -24,6 +24,7 @@ pub enum Schedule {
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub enum ScheduleKind {
     /// Datos fraccionarios (0.0-1.0))
+    /// NOTE: HULC usa también este tipo para multiplicadores mayores que 1 (p.e. en horarios de ventilación)
     #[default]
     Fraction,
     /// Datos de encendido (1) / apagado (0)
@@ -100,24 +101,34 @@ pub struct WeekSchedule {
     pub name: String,
     /// Tipo de horario
     pub kind: ScheduleKind,
-    /// Horarios diarios que componen la semana
+    /// Horarios diarios que componen la semana, de lunes a domingo
+    /// Con un único horario diario, este se aplica a todos los días
     pub days: Vec<String>,
+    /// Horario diario de los días festivos
+    pub holiday: Option<String>,
+    /// Horario diario del día de diseño de calefacción
+    pub heating_design_day: Option<String>,
+    /// Horario diario del día de diseño de refrigeración
+    pub cooling_design_day: Option<String>,
 }
 
 impl TryFrom<BdlBlock> for WeekSchedule {
     type Error = Error;
 
-    /// Conversión de bloque BDL a material
+    /// Conversión de bloque BDL a horario semanal
     /// NOTE: La base de datos tiene algunos nombres con dobles espacios, que se convierten a espacios simples
     ///
+    /// La lista de horarios diarios puede tener un único elemento, que se aplica a todos los días,
+    /// o los 7 días de la semana (de lunes a domingo) seguidos, opcionalmente, de los horarios
+    /// de festivos, día de diseño de calefacción y día de diseño de refrigeración.
+    ///
     /// Ejemplo en BDL:
     /// ```text
-    ///     "HA_INF_16_A_8" = SCHEDULE-PD
+    ///     "HA26_HS0_SS_" = WEEK-SCHEDULE-PD
     ///         TYPE   = "FRACTION"
     ///         GROUP  = "Internas"
-    ///         MONTH = ( 12)
-    ///         DAY   = ( 31)
-    ///         WEEK-SCHEDULES = ( "HA26_HS0_SS_")
+    ///         DAY-SCHEDULES = ( "HA26_HD0_SS_", "HA26_HD0_SS_", "HA26_HD0_SS_", "HA26_HD0_SS_",
+    ///                           "HA26_HD0_SS_", "HA26_HD0_SS_", "HA26_HD0_SS_", "HA26_HD0_SS_")
     ///         ..
     /// ```
     /// TODO: Propiedades no convertidas:
@@ -130,16 +141,28 @@ impl TryFrom<BdlBlock> for WeekSchedule {
         } = value;
         name = name.replace("  ", " ");
         let kind = attrs.remove_str("TYPE")?.as_str().try_into()?;
-        let days = extract_namesvec(attrs.remove_str("DAY-SCHEDULES")?);
+        let mut days = extract_namesvec(attrs.remove_str("DAY-SCHEDULES")?);
 
-        if !(days.len() == 7 || days.len() == 1) {
+        if !(days.len() == 1 || (7..=10).contains(&days.len())) {
             Err(format_err!(
                 "Longitud de valores semanales incorrecta en WEEK-SCHEDULE-PD: {}",
                 name
             ))?;
         }
+        // Días especiales tras los 7 días de la semana: festivos y días de diseño
+        let mut special_days = days.split_off(days.len().min(7)).into_iter();
+        let holiday = special_days.next();
+        let heating_design_day = special_days.next();
+        let cooling_design_day = special_days.next();
 
-        Ok(Self { name, kind, days })
+        Ok(Self {
+            name,
+            kind,
+            days,
+            holiday,
+            heating_design_day,
+            cooling_design_day,
+        })
     }
 }
 
@@ -195,3 +218,75 @@ impl TryFrom<BdlBlock> for YearSchedule {
         })
     }
 }
+
+/// Días acumulados al final de cada mes en un año no bisiesto
+///
+/// HULC usa siempre el año 2001 como referencia (empieza en lunes y no es bisiesto)
+const MONTH_END_DAYS: [u32; 12] = [31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334, 365];
+
+/// Valores horarios (8760) del horario anual con el nombre indicado
+///
+/// Los horarios semanales se aplican hasta el día y mes indicados en el horario anual
+/// y la semana empieza en lunes, como el año de referencia.
+/// No se aplican los horarios de festivos ni de los días de diseño.
+pub fn year_values<T: AsRef<str>>(schedules: &[Schedule], name: T) -> Result<Vec<f32>, Error> {
+    let name = name.as_ref();
+    let find_week = |week_name: &str| {
+        schedules.iter().find_map(|s| match s {
+            Schedule::Week(w) if w.name == week_name => Some(w),
+            _ => None,
+        })
+    };
+    let find_day = |day_name: &str| {
+        schedules.iter().find_map(|s| match s {
+            Schedule::Day(d) if d.name == day_name => Some(d),
+            _ => None,
+        })
+    };
+
+    let year = schedules
+        .iter()
+        .find_map(|s| match s {
+            Schedule::Year(y) if y.name == name => Some(y),
+            _ => None,
+        })
+        .ok_or_else(|| format_err!("Horario anual {} no encontrado", name))?;
+
+    let mut values = Vec::with_capacity(8760);
+    let mut day_idx = 0;
+    for ((week_name, day), month) in year.weeks.iter().zip(&year.days).zip(&year.months) {
+        let week = find_week(week_name).ok_or_else(|| {
+            format_err!("Horario semanal {} no encontrado en {}", week_name, name)
+        })?;
+        // Último día (1 a 365) de vigencia del horario semanal
+        let end_day = match month {
+            1 => *day,
+            2..=12 => MONTH_END_DAYS[*month as usize - 2] + day,
+            _ => {
+                return Err(format_err!(
+                    "Mes {} incorrecto en el horario {}",
+                    month,
+                    name
+                ))
+            }
+        };
+        while day_idx < end_day.min(365) {
+            let day_name = &week.days[day_idx as usize % week.days.len()];
+            let day_sch = find_day(day_name).ok_or_else(|| {
+                format_err!("Horario diario {} no encontrado en {}", day_name, name)
+            })?;
+            match day_sch.values.len() {
+                1 => values.extend([day_sch.values[0]; 24]),
+                _ => values.extend(&day_sch.values),
+            }
+            day_idx += 1;
+        }
+    }
+    if values.len() != 8760 {
+        return Err(format_err!(
+            "El horario anual {} no cubre todo el año",
+            name
+        ));
+    }
+    Ok(values)
+}
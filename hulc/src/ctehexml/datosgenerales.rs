@@ -6,7 +6,7 @@
 
 use anyhow::{format_err, Error};
 
-use crate::utils::xml::{get_tag_as_f32, get_tag_as_i32, get_tag_as_str};
+use crate::utils::xml::{get_tag_as_f32, get_tag_as_i32, get_tag_as_str, get_tag_text};
 
 #[derive(Debug, Clone)]
 /// Datos del bloque DatosGenerales
@@ -48,6 +48,12 @@ pub struct DatosGenerales {
     pub zona_climatica: String,
     /// Zona climática según archivo climático (alfa1c, A1, A1c, ...)
     pub archivo_climatico: String,
+    /// Provincia del emplazamiento
+    pub provincia: String,
+    /// Latitud del emplazamiento [º], si está definida
+    pub latitud: Option<f32>,
+    /// Longitud del emplazamiento [º], si está definida
+    pub longitud: Option<f32>,
     /// Valor del ensayo de permeabilidad (solo residencial)
     pub valor_n50_medido: Option<f32>,
     /// Contenido del bloque en texto, sin parsear
@@ -64,6 +70,9 @@ impl Default for DatosGenerales {
             valor_impulsion_aire: 0.0,
             zona_climatica: "D3".into(),
             archivo_climatico: "D3".into(),
+            provincia: String::new(),
+            latitud: None,
+            longitud: None,
             valor_n50_medido: None,
             bloque_raw: String::new(),
         }
@@ -98,6 +107,9 @@ pub fn parse_datos_generales(doc: &roxmltree::Document) -> Result<DatosGenerales
                 .to_string()
             });
 
+    let latitud = get_tag_text(&datos_generales, "latitud").and_then(|v| v.trim().parse().ok());
+    let longitud = get_tag_text(&datos_generales, "longitud").and_then(|v| v.trim().parse().ok());
+
     Ok(DatosGenerales {
         nombre_proyecto: get_tag_as_str(&datos_generales, "nomPro").to_string(),
         tipo_vivienda: get_tag_as_str(&datos_generales, "tipoVivienda").to_string(),
@@ -106,6 +118,9 @@ pub fn parse_datos_generales(doc: &roxmltree::Document) -> Result<DatosGenerales
         valor_impulsion_aire: get_tag_as_f32(&datos_generales, "valorImpulsionAire")?,
         zona_climatica,
         archivo_climatico,
+        provincia: get_tag_as_str(&datos_generales, "provincia").to_string(),
+        latitud,
+        longitud,
         valor_n50_medido,
         bloque_raw: datos_generales.text().unwrap_or("").trim().to_string(),
     })
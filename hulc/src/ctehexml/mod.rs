@@ -17,7 +17,11 @@ use crate::bdl::Data;
 use crate::utils::file::{find_file_in_basedir, read_file};
 
 use datosgenerales::{parse_datos_generales, DatosGenerales};
-use systems::{parse_systems, VypSystem};
+use systems::parse_systems;
+pub use systems::{
+    EquipmentKind, GenerationEquipment, GtZoneSystem, PerformanceCurve, ThermalGenerator,
+    VypSystem, ZoneKind,
+};
 
 static LIDERCATSTRZ: &[u8] = include_bytes!("BDCatalogo.bdc.utf8.gz");
 
@@ -32,6 +36,8 @@ pub struct CtehexmlData {
     pub factores_correccion_sistemas: Vec<String>,
     /// Bloques de definición de sistemas
     pub sistemas: Vec<VypSystem>,
+    /// Zonas térmicas de los sistemas GT (ZONE)
+    pub zonas: Vec<GtZoneSystem>,
 }
 
 /// Localiza archivo .ctehexml en el directorio de proyecto basedir
@@ -85,21 +91,25 @@ pub fn parse(data: &str) -> Result<CtehexmlData, Error> {
     let datos_generales = parse_datos_generales(&doc)?;
 
     // BDL Lider
-    let entrada_grafica_lider = doc
+    // Se localiza la línea de inicio del texto para indicar las líneas de los bloques en el archivo
+    let (entrada_grafica_lider, first_line) = doc
         .descendants()
         .find(|n| n.has_tag_name("EntradaGraficaLIDER"))
-        .and_then(|e| e.text())
-        .unwrap_or("")
-        .trim()
-        .to_string();
-    let bdldata = Data::new(&entrada_grafica_lider)?;
+        .and_then(|e| e.first_child())
+        .and_then(|t| {
+            let first_line = doc.text_pos_at(t.range().start).row as usize;
+            t.text().map(|text| (text, first_line))
+        })
+        .unwrap_or(("", 1));
+    let bdldata = Data::new_from_line(entrada_grafica_lider.trim_end(), first_line)?;
 
-    let (factores_correccion_sistemas, sistemas) = parse_systems(&doc);
+    let (factores_correccion_sistemas, sistemas, zonas) = parse_systems(&doc);
 
     Ok(CtehexmlData {
         datos_generales,
         bdldata,
         factores_correccion_sistemas,
         sistemas,
+        zonas,
     })
 }
@@ -23,7 +23,10 @@ pub use vyp_types::*;
 pub use gt_types::*;
 pub use gt_types_impl::*;
 
-pub fn parse_systems(doc: &roxmltree::Document) -> (Vec<String>, Vec<VypSystem>) {
+/// Factores de corrección y sistemas de VyP y zonas térmicas de los sistemas GT
+pub fn parse_systems(
+    doc: &roxmltree::Document,
+) -> (Vec<String>, Vec<VypSystem>, Vec<GtZoneSystem>) {
     let (factores_correccion_sistemas, sistemas) = vyp_sys::parse_systems(doc);
     let gt_systems = gt_sys::parse_systems(doc);
     // let horarios = todo!();
@@ -35,5 +38,6 @@ pub fn parse_systems(doc: &roxmltree::Document) -> (Vec<String>, Vec<VypSystem>)
     println!("Sistemas VyP:\n{:#?}", sistemas);
 
     // TODO: completar sistemas GT
-    (factores_correccion_sistemas, sistemas)
+    let zonas = gt_systems.zones.into_values().collect();
+    (factores_correccion_sistemas, sistemas, zonas)
 }
@@ -4,6 +4,7 @@
 
 // Interpretación de la información de sistemas del .ctehexml de CalenerVyP
 
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
 use anyhow::{format_err, Error};
@@ -15,8 +16,9 @@ use crate::utils::xml::{
 
 use super::vyp_types::{
     CHPGenerator, CoolingParams, DhwDemand, EconomizerControl, EquipmentKind, GenerationEquipment,
-    HeatingParams, HotWaterStorageTank, PhotovoltaicGenerator, SolarThermalGenerator,
-    SystemOptions, ThermalGenerator, VypSystem, WindGenerator, ZoneEquipment,
+    HeatingParams, HotWaterStorageTank, PerformanceCurve, PhotovoltaicGenerator,
+    SolarThermalGenerator, SystemOptions, ThermalGenerator, VypSystem, WindGenerator,
+    ZoneEquipment,
 };
 
 impl TryFrom<&str> for EquipmentKind {
@@ -61,7 +63,16 @@ pub fn parse_systems(doc: &roxmltree::Document) -> (Vec<String>, Vec<VypSystem>)
         None => vec![],
     };
 
-    // println!("Factores:\n{:#?}\n\n", factores_correccion_sistemas);
+    // Definición de las curvas de corrección
+    let curves: BTreeMap<_, _> = match systems {
+        Some(sis_node) => sis_node
+            .descendants()
+            .filter(|n| n.has_tag_name("CurvaComportamiento"))
+            .filter_map(|n| build_performance_curve(&n))
+            .map(|c| (c.name.clone(), c))
+            .collect(),
+        None => BTreeMap::new(),
+    };
 
     // Definición de sistemas
     let mut sistemas = match systems {
@@ -88,9 +99,80 @@ pub fn parse_systems(doc: &roxmltree::Document) -> (Vec<String>, Vec<VypSystem>)
         sistemas.append(&mut onsiteprod);
     }
 
+    // Asignamos a los equipos la definición de sus curvas de comportamiento
+    for sistema in &mut sistemas {
+        let equipment = match sistema {
+            VypSystem::Dhw { equipment, .. }
+            | VypSystem::MultizoneHotWater { equipment, .. }
+            | VypSystem::SingleZone { equipment, .. }
+            | VypSystem::MultizoneAir { equipment, .. } => equipment,
+            _ => continue,
+        };
+        for eq in equipment {
+            if let GenerationEquipment::ThermalGenerator(generator) = eq {
+                generator.performance_curves = generator
+                    .curves
+                    .iter()
+                    .filter_map(|(_, name)| curves.get(name).cloned())
+                    .collect();
+            }
+        }
+    }
+
     (factores_correccion_sistemas, sistemas)
 }
 
+/// Curva de comportamiento de equipos a partir de su nodo XML
+///
+/// Los coeficientes y exponentes se guardan como listas separadas por ';'. Los exponentes
+/// se agrupan por términos, con un valor por cada variable independiente.
+fn build_performance_curve(node: &Node) -> Option<PerformanceCurve> {
+    let name = node.attribute("nombre")?.to_string();
+    let property = get_tag_as_str(node, "tipoPropiedad").to_string();
+    let num_vars = get_tag_as_u32_or(node, "numeroVariablesIndependientes", 1).max(1) as usize;
+    let variables = (1..=num_vars)
+        .map(|i| get_tag_as_str(node, &format!("nombreVI_{}", i)).to_string())
+        .collect();
+    let ranges = (1..=num_vars)
+        .map(|i| {
+            (
+                get_tag_as_f32_or_default(node, &format!("minVI_{}", i)),
+                get_tag_as_f32_or_default(node, &format!("maxVI_{}", i)),
+            )
+        })
+        .collect();
+    let split_values = |tag: &str| {
+        get_tag_as_str(node, tag)
+            .trim_matches('"')
+            .split(';')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let coefficients = split_values("coeficientes")
+        .iter()
+        .map(|v| v.parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    let exponents: Vec<i32> = split_values("exponentes")
+        .iter()
+        .map(|v| v.parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if exponents.len() != coefficients.len() * num_vars {
+        return None;
+    }
+    Some(PerformanceCurve {
+        name,
+        property,
+        variables,
+        ranges,
+        coefficients,
+        exponents: exponents.chunks(num_vars).map(<[i32]>::to_vec).collect(),
+    })
+}
+
 /// Genera sistema a partir de su nodo XML
 /// Podrían equivaler en E+ a:
 /// https://bigladdersoftware.com/epx/docs/9-6/input-output-reference/group-hvac-templates.html#group----hvac-templates
@@ -402,6 +484,7 @@ fn build_generation_equipment(node: roxmltree::Node) -> GenerationEquipment {
                 supply_air_flow: None,
                 multiplier,
                 curves,
+                performance_curves: vec![],
             })
         }
         CalefaccionElectrica => {
@@ -436,6 +519,7 @@ fn build_generation_equipment(node: roxmltree::Node) -> GenerationEquipment {
                 supply_air_flow: None,
                 multiplier,
                 curves,
+                performance_curves: vec![],
             })
         }
         ExpansionDirectaAireAireSf
@@ -562,6 +646,7 @@ fn build_generation_equipment(node: roxmltree::Node) -> GenerationEquipment {
                 supply_air_flow,
                 multiplier,
                 curves,
+                performance_curves: vec![],
             })
         }
         RendimientoConstante => {
@@ -612,6 +697,7 @@ fn build_generation_equipment(node: roxmltree::Node) -> GenerationEquipment {
                 supply_air_flow: None,
                 multiplier,
                 curves: vec![],
+                performance_curves: vec![],
             })
         }
         AcumuladorAguaCaliente => {
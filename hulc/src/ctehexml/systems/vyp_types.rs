@@ -121,6 +121,23 @@ pub enum VypSystem {
     CHPGenerator(CHPGenerator),
 }
 
+impl VypSystem {
+    /// Generadores térmicos del sistema
+    pub fn generators(&self) -> impl Iterator<Item = &ThermalGenerator> {
+        let equipment = match self {
+            VypSystem::Dhw { equipment, .. }
+            | VypSystem::MultizoneHotWater { equipment, .. }
+            | VypSystem::SingleZone { equipment, .. }
+            | VypSystem::MultizoneAir { equipment, .. } => equipment.as_slice(),
+            _ => &[],
+        };
+        equipment.iter().filter_map(|e| match e {
+            GenerationEquipment::ThermalGenerator(generator) => Some(generator),
+            _ => None,
+        })
+    }
+}
+
 /// Opciones en equipos / sistemas
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum SystemOptions {
@@ -333,8 +350,65 @@ pub struct ThermalGenerator {
     pub supply_air_flow: Option<f32>,
     /// Multiplicador
     pub multiplier: u32,
-    /// Curvas de rendimiento
+    /// Curvas de rendimiento (propiedad, nombre de la curva)
     pub curves: Vec<(String, String)>,
+    /// Definición de las curvas de rendimiento del equipo
+    pub performance_curves: Vec<PerformanceCurve>,
+}
+
+impl ThermalGenerator {
+    /// Curva de rendimiento del equipo para una propiedad (p.e. capTotRef_T o conRef_FCP)
+    pub fn curve(&self, property: &str) -> Option<&PerformanceCurve> {
+        self.performance_curves
+            .iter()
+            .find(|c| c.property == property)
+    }
+}
+
+/// Curva de comportamiento (factor de corrección) de un equipo
+///
+/// Polinomio en una o varias variables independientes, f(x) = Σ c_i · Π x_j^e_ij
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PerformanceCurve {
+    /// Nombre de la curva
+    pub name: String,
+    /// Propiedad que corrige (p.e. capTotRef_T, conRef_FCP)
+    pub property: String,
+    /// Nombres de las variables independientes (p.e. Temperatura seca exterior, Factor Carga Parcial)
+    pub variables: Vec<String>,
+    /// Rango de validez (mínimo, máximo) de cada variable independiente
+    pub ranges: Vec<(f32, f32)>,
+    /// Coeficientes de cada término del polinomio
+    pub coefficients: Vec<f32>,
+    /// Exponentes de cada variable independiente en cada término del polinomio
+    pub exponents: Vec<Vec<i32>>,
+}
+
+impl PerformanceCurve {
+    /// Valor de la curva para los valores de las variables independientes
+    ///
+    /// Los valores se limitan al rango de validez de cada variable
+    pub fn eval(&self, values: &[f32]) -> f32 {
+        let values: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| match self.ranges.get(i) {
+                Some(&(min, max)) if min <= max => v.clamp(min, max),
+                _ => *v,
+            })
+            .collect();
+        self.coefficients
+            .iter()
+            .zip(&self.exponents)
+            .map(|(c, exps)| {
+                c * exps
+                    .iter()
+                    .zip(&values)
+                    .map(|(e, v)| v.powi(*e))
+                    .product::<f32>()
+            })
+            .sum()
+    }
 }
 
 /// Hot Water Storage
@@ -7,11 +7,12 @@
 //! En este archivo no aparecen los elementos adiabáticos entre los cerramientos
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::BTreeMap,
     path::{Path, PathBuf},
 };
 
-use anyhow::{bail, format_err, Error};
+use anyhow::{bail, format_err, Context, Error};
+use log::warn;
 
 use crate::utils::file::{find_file_in_basedir, read_latin1_file};
 
@@ -33,6 +34,8 @@ pub struct KyGElements {
     pub thermal_bridges: BTreeMap<String, ThermalBridge>,
     /// Factores de insolación - uso desconocido
     pub hfactors: Vec<f32>,
+    /// Datos de ganancias solares de huecos en julio
+    pub solar_gains: BTreeMap<String, WindowSolarGains>,
 }
 
 /// Hueco
@@ -69,6 +72,27 @@ pub struct Window {
     pub cons: Option<String>,
 }
 
+/// Ganancias solares de un hueco en el mes de julio
+#[derive(Debug, Default)]
+pub struct WindowSolarGains {
+    /// Nombre del hueco
+    pub name: String,
+    /// Azimuth, orientación respecto al norte (N=0, NE=45, E=90) [0,360]
+    pub azimuth_n: f32,
+    /// Superficie del hueco (m2)
+    pub a: f32,
+    /// Radiación solar global en el plano del vidrio sin obstáculos (Wh/m2)
+    pub htot: f32,
+    /// Radiación solar global en el plano del vidrio tras obstáculos remotos (Wh/m2)
+    pub h1: f32,
+    /// Radiación solar global en el plano del vidrio tras obstáculos de fachada (Wh/m2)
+    pub h2: f32,
+    /// Radiación solar global en el plano del vidrio tras sombras por lamas (Wh/m2)
+    pub h3: f32,
+    /// Ganancia solar a través del hueco (Wh/m2)
+    pub gain: f32,
+}
+
 /// Elemento opaco (muro, cubierta, suelo, partición)
 #[derive(Debug, Default)]
 pub struct Wall {
@@ -103,172 +127,200 @@ pub struct ThermalBridge {
 }
 
 // Lee estructura de datos desde cadena con formato de archivo KyGananciasSolares.txt
+//
+// Los errores indican la línea del archivo en la que se producen.
+// Las líneas de elementos de tipo desconocido se ignoran. Se considera que el archivo está
+// incompleto (truncado) si no se encuentra el valor de K global o faltan los datos de ganancias
+// solares de algún hueco, y en ese caso el error indica la última línea leída.
 pub fn parse(data: &str) -> Result<KyGElements, Error> {
-    let lines = data
-        .lines()
-        .map(str::trim)
-        .collect::<Vec<&str>>()
-        .into_iter();
-
     let mut kyg = KyGElements::default();
-    let mut qsolvalues: HashMap<String, (f32, f32)> = HashMap::default();
+    let mut has_k = false;
+    let mut last_line = 0;
+
+    for (nline, line) in data.lines().map(str::trim).enumerate() {
+        parse_line(line, &mut kyg, &mut has_k).with_context(|| {
+            format!(
+                "Error en la línea {} del archivo KyGananciasSolares.txt: \"{}\"",
+                nline + 1,
+                line
+            )
+        })?;
+        last_line = nline + 1;
+    }
+
+    if !has_k {
+        bail!(
+            "Archivo KyGananciasSolares.txt incompleto tras la línea {}: no se encuentra la definición de K global",
+            last_line
+        )
+    }
 
-    for line in lines {
-        // Comentarios y líneas en blanco
-        if line.starts_with('#') || line.is_empty() {
-            continue;
+    // Actualización de valores de fshobst disponibles en el KyGananciasSolares.txt
+    for (name, hueco) in &mut kyg.windows {
+        match kyg.solar_gains.get(name) {
+            Some(gains) => {
+                hueco.azimuth_n = gains.azimuth_n;
+                hueco.fshobst = gains.h3 / gains.htot;
+            }
+            None => bail!(
+                "Archivo KyGananciasSolares.txt incompleto tras la línea {}: no se encuentran las ganancias solares del hueco {}",
+                last_line,
+                name
+            ),
         }
-        // Datos de elemento
-        if line.starts_with("Muro") || line.starts_with("Ventana") || line.starts_with("PPTT") {
-            let vv: Vec<&str> = line.split(';').map(str::trim).collect();
-            let tipo = vv[0];
-            match tipo {
-                "Ventana" => {
-                    if vv.len() < 6 {
-                        bail!("Línea de datos de hueco con formato desconocido")
-                    }
-                    let (nombre, a, u, orienta, ff) = (vv[1], vv[2], vv[3], vv[4], vv[5]);
-                    let (ggln, unknown1, unknown2, infcoeff_100, cons) = if vv.len() > 10 {
-                        (
-                            Some(vv[6].replace(',', ".").parse()?),
-                            Some(vv[7].replace(',', ".").parse()?),
-                            Some(vv[8].replace(',', ".").parse()?),
-                            Some(vv[9].replace(',', ".").parse()?),
-                            Some(vv[10].to_string()),
-                        )
-                    } else {
-                        (None, None, None, None, None)
-                    };
-                    kyg.windows.insert(
-                        nombre.to_string(),
-                        Window {
-                            name: nombre.to_string(),
-                            orientation: orienta.replace('O', "W").to_string(),
-                            azimuth_n: 0.0, // Valor temporal, se completa más abajo
-                            wall: String::default(),
-                            a: a.replace(',', ".").parse()?,
-                            u: u.replace(',', ".").parse()?,
-                            ff: ff.replace(',', ".").parse::<f32>()? / 100.0_f32,
-                            fshobst: 0.0, // Valor temporal, se completa más abajo
-                            ggln,
-                            unknown1,
-                            unknown2,
-                            infcoeff_100,
-                            cons,
-                        },
-                    );
-                }
-                "Muro" => {
-                    if vv.len() < 5 {
-                        bail!("Línea de datos de opaco con formato desconocido")
-                    }
-                    // Datos de muro
-                    let (nombre, a, u, btrx) = (vv[1], vv[2], vv[3], vv[4]);
-                    // CTEHE2019 y mayores
-                    let (wtype, orientation, cons) = if vv.len() > 7 {
-                        (
-                            Some(vv[5].to_string()),
-                            Some(vv[6].to_string()),
-                            Some(vv[7].to_string()),
-                        )
-                    } else {
-                        (None, None, None)
-                    };
+    }
 
-                    kyg.walls.insert(
-                        nombre.to_string(),
-                        Wall {
-                            name: nombre.to_string(),
-                            a: a.replace(',', ".").parse()?,
-                            u: u.replace(',', ".").parse()?,
-                            btrx: btrx.replace(',', ".").parse()?,
-                            wtype,
-                            orientation,
-                            cons,
-                        },
-                    );
-                }
-                "PPTT" => {
-                    if vv.len() < 4 {
-                        bail!("Línea de datos de hueco con formato desconocido")
-                    }
-                    // En versiones más recientes se añade el sistema dimensional como dato extra
-                    let (l, psi, nombre) = (vv[1], vv[2], vv[3]);
-                    let sisdim = (if vv.len() > 4 { vv[4] } else { "" }).to_string();
-                    kyg.thermal_bridges.insert(
-                        nombre.to_string(),
-                        ThermalBridge {
-                            name: nombre.to_string(),
-                            l: l.replace(',', ".").parse()?,
-                            psi: psi.replace(',', ".").parse()?,
-                            sisdim,
-                        },
-                    );
+    Ok(kyg)
+}
+
+/// Interpreta una línea del archivo KyGananciasSolares.txt y guarda sus datos
+fn parse_line(line: &str, kyg: &mut KyGElements, has_k: &mut bool) -> Result<(), Error> {
+    // Comentarios y líneas en blanco
+    if line.starts_with('#') || line.is_empty() {
+        return Ok(());
+    }
+    // Datos de elemento
+    if line.starts_with("Muro") || line.starts_with("Ventana") || line.starts_with("PPTT") {
+        let vv: Vec<&str> = line.split(';').map(str::trim).collect();
+        let tipo = vv[0];
+        match tipo {
+            "Ventana" => {
+                if vv.len() < 6 {
+                    bail!("Línea de datos de hueco con formato desconocido")
                 }
-                _ => println!("Desconocido"),
-            };
-        }
-        // Ganancias solares de hueco
-        else if line.starts_with('"') {
-            let vv: Vec<&str> = line.split(';').map(str::trim).collect();
-            if vv.len() < 8 {
-                bail!("Línea de datos de ganancias solares de hueco con formato desconocido")
+                let (nombre, a, u, orienta, ff) = (vv[1], vv[2], vv[3], vv[4], vv[5]);
+                let (ggln, unknown1, unknown2, infcoeff_100, cons) = if vv.len() > 10 {
+                    (
+                        Some(parse_f32(vv[6])?),
+                        Some(parse_f32(vv[7])?),
+                        Some(parse_f32(vv[8])?),
+                        Some(parse_f32(vv[9])?),
+                        Some(vv[10].to_string()),
+                    )
+                } else {
+                    (None, None, None, None, None)
+                };
+                kyg.windows.insert(
+                    nombre.to_string(),
+                    Window {
+                        name: nombre.to_string(),
+                        orientation: orienta.replace('O', "W").to_string(),
+                        azimuth_n: 0.0, // Valor temporal, se completa más abajo
+                        wall: String::default(),
+                        a: parse_f32(a)?,
+                        u: parse_f32(u)?,
+                        ff: parse_f32(ff)? / 100.0_f32,
+                        fshobst: 0.0, // Valor temporal, se completa más abajo
+                        ggln,
+                        unknown1,
+                        unknown2,
+                        infcoeff_100,
+                        cons,
+                    },
+                );
             }
-            let (name, azimuth_n, _a, htot, _h1, _h2, h3, _ganancia) = (
-                vv[0].trim_matches('"').to_string(), // name
-                vv[1].parse::<f32>()?, // azimuth (grados respecto al norte, N=0, NE=45, E=90)
-                vv[2].parse::<f32>()?, // a - area (m2)
-                vv[3].parse::<f32>()?, // htot - radiación solar global en el plano del vidrio sin obstáculos (Wh/m2)
-                vv[4].parse::<f32>()?, // _h1 - radiación solar global en el plano del vidrio tras obstáculos remotos (Wh/m2)
-                vv[5].parse::<f32>()?, // _h2 - radiación solar global en el plano del vidrio tras obstáculos de fachada (Wh/m2)
-                vv[6].parse::<f32>()?, // h3 - radiación solar global en el plano del vidrio tras sombras por lamas (Wh/m2)
-                vv[7].parse::<f32>()?, // _ganancia solar a través de este hueco (Wh/m2)
-            );
-            let fshobst = h3 / htot;
-            qsolvalues.insert(name, (azimuth_n, fshobst));
-        }
-        // K global
-        else if line.starts_with("Coeficiente K") {
-            let kval: f32 = line
-                .split(';')
-                .nth(1)
-                .ok_or_else(|| format_err!("No se encuentra la definición de K global"))?
-                .trim()
-                .replace(',', ".")
-                .parse()?;
-            kyg.k = kval;
-        }
-        // factores insolación
-        else if "012345678".contains(line.chars().next().unwrap_or('x')) {
-            let val: f32 = line
-                .split(';')
-                .nth(1)
-                .ok_or_else(|| {
-                    format_err!(
-                        "Formato inesperado de línea de factor de insolación {}",
-                        line
+            "Muro" => {
+                if vv.len() < 5 {
+                    bail!("Línea de datos de opaco con formato desconocido")
+                }
+                // Datos de muro
+                let (nombre, a, u, btrx) = (vv[1], vv[2], vv[3], vv[4]);
+                // CTEHE2019 y mayores
+                let (wtype, orientation, cons) = if vv.len() > 7 {
+                    (
+                        Some(vv[5].to_string()),
+                        Some(vv[6].to_string()),
+                        Some(vv[7].to_string()),
                     )
-                })?
-                .trim()
-                .replace(',', ".")
-                .parse()?;
-            kyg.hfactors.push(val);
-        }
-        // rg_qsolunknown
-        else {
-            continue;
-        }
-    }
+                } else {
+                    (None, None, None)
+                };
 
-    // Actualización de valores de fshobst disponibles en el KyGananciasSolares.txt
-    for (name, mut hueco) in &mut kyg.windows {
-        if let Some((azimuth_n, fshobst)) = qsolvalues.get(name) {
-            hueco.azimuth_n = *azimuth_n;
-            hueco.fshobst = *fshobst;
+                kyg.walls.insert(
+                    nombre.to_string(),
+                    Wall {
+                        name: nombre.to_string(),
+                        a: parse_f32(a)?,
+                        u: parse_f32(u)?,
+                        btrx: parse_f32(btrx)?,
+                        wtype,
+                        orientation,
+                        cons,
+                    },
+                );
+            }
+            "PPTT" => {
+                if vv.len() < 4 {
+                    bail!("Línea de datos de puente térmico con formato desconocido")
+                }
+                // En versiones más recientes se añade el sistema dimensional como dato extra
+                let (l, psi, nombre) = (vv[1], vv[2], vv[3]);
+                let sisdim = (if vv.len() > 4 { vv[4] } else { "" }).to_string();
+                kyg.thermal_bridges.insert(
+                    nombre.to_string(),
+                    ThermalBridge {
+                        name: nombre.to_string(),
+                        l: parse_f32(l)?,
+                        psi: parse_f32(psi)?,
+                        sisdim,
+                    },
+                );
+            }
+            _ => warn!(
+                "Tipo de elemento desconocido {} en KyGananciasSolares.txt. Se ignora la línea",
+                tipo
+            ),
+        };
+    }
+    // Ganancias solares de hueco
+    else if line.starts_with('"') {
+        let vv: Vec<&str> = line.split(';').map(str::trim).collect();
+        if vv.len() < 8 {
+            bail!("Línea de datos de ganancias solares de hueco con formato desconocido")
         }
+        let name = vv[0].trim_matches('"').to_string();
+        let gains = WindowSolarGains {
+            name: name.clone(),
+            azimuth_n: parse_f32(vv[1])?,
+            a: parse_f32(vv[2])?,
+            htot: parse_f32(vv[3])?,
+            h1: parse_f32(vv[4])?,
+            h2: parse_f32(vv[5])?,
+            h3: parse_f32(vv[6])?,
+            gain: parse_f32(vv[7])?,
+        };
+        kyg.solar_gains.insert(name, gains);
     }
+    // K global
+    else if line.starts_with("Coeficiente K") {
+        let kval = line
+            .split(';')
+            .nth(1)
+            .ok_or_else(|| format_err!("No se encuentra la definición de K global"))?;
+        kyg.k = parse_f32(kval)?;
+        *has_k = true;
+    }
+    // factores insolación
+    else if "012345678".contains(line.chars().next().unwrap_or('x')) {
+        let val = line.split(';').nth(1).ok_or_else(|| {
+            format_err!(
+                "Formato inesperado de línea de factor de insolación {}",
+                line
+            )
+        })?;
+        kyg.hfactors.push(parse_f32(val)?);
+    }
+    // rg_qsolunknown
+    Ok(())
+}
 
-    Ok(kyg)
+/// Convierte a número un valor que puede usar la coma como separador decimal
+fn parse_f32(value: &str) -> Result<f32, Error> {
+    value
+        .trim()
+        .replace(',', ".")
+        .parse()
+        .map_err(|_| format_err!("Valor numérico incorrecto \"{}\"", value))
 }
 
 // Lee estructura de datos desde una ruta de archivo KyGananciasSolares.txt
@@ -167,6 +167,25 @@ pub struct Tbl {
     pub spaces: BTreeMap<String, Space>,
 }
 
+impl Tbl {
+    /// Superficie de cada espacio (sin multiplicador), m²
+    pub fn space_areas(&self) -> BTreeMap<String, f32> {
+        self.spaces
+            .iter()
+            .map(|(name, space)| (name.clone(), space.area))
+            .collect()
+    }
+
+    /// Superficie útil total de los espacios, incluyendo sus multiplicadores, m²
+    /// Los espacios no habitables tienen multiplicador nulo en el archivo .tbl y no computan
+    pub fn compute_autil(&self) -> f32 {
+        self.spaces
+            .values()
+            .map(|space| space.area * space.mult as f32)
+            .sum()
+    }
+}
+
 /// Interpreta archivo .tbl de datos de elementos y espacios del modelo
 ///
 /// path: ruta del archivo .tbl
@@ -229,6 +229,27 @@ fn bdl_material_properties() {
     assert_almost_eq!(props.vapourdiffusivity.unwrap(), 60.0, 0.1);
 }
 
+#[test]
+fn bdl_material_resistance() {
+    use bdl::{BdlBlock, Material};
+    let blk: BdlBlock = r#""Cámara de aire sin ventilar vertical 5 cm" = MATERIAL
+    TYPE              = RESISTANCE
+    THICKNESS         =           0.05
+    RESISTANCE        =           0.18
+    NAME          = "Cámara de aire sin ventilar vertical 5 cm"
+    GROUP         = "Cámaras de aire"
+    ..
+"#
+    .parse()
+    .unwrap();
+    let elem = Material::try_from(blk).unwrap();
+    assert_eq!(elem.group, "Cámaras de aire");
+    assert!(elem.properties.is_none());
+    assert_almost_eq!(elem.resistance.unwrap(), 0.18, 0.001);
+    // Conserva el espesor declarado
+    assert_almost_eq!(elem.resistance_thickness.unwrap(), 0.05, 0.001);
+}
+
 #[test]
 fn bdl_glass() {
     use bdl::{BdlBlock, Glass};
@@ -405,6 +426,261 @@ fn bdl_space() {
     assert_eq!(elem.airchanges_h, None);
 }
 
+#[test]
+fn bdl_sys_conditions() {
+    use bdl::{BdlBlock, DaySchedule, Schedule, SpaceSysConditions, WeekSchedule, YearSchedule};
+
+    let block = |s: &str| s.parse::<BdlBlock>().unwrap();
+    let schedules = vec![
+        Schedule::Day(
+            DaySchedule::try_from(block(
+                r#""Temp21" = DAY-SCHEDULE-PD
+    TYPE  = "TEMPERATURE"
+    VALUES  = ( 21)
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Day(
+            DaySchedule::try_from(block(
+                r#""Temp25" = DAY-SCHEDULE-PD
+    TYPE  = "TEMPERATURE"
+    VALUES  = ( 25)
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Day(
+            DaySchedule::try_from(block(
+                r#""Oficina" = DAY-SCHEDULE-PD
+    TYPE  = "ON/OFF"
+    VALUES  = ( 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0)
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Day(
+            DaySchedule::try_from(block(
+                r#""Apagado" = DAY-SCHEDULE-PD
+    TYPE  = "ON/OFF"
+    VALUES  = ( 0)
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Week(
+            WeekSchedule::try_from(block(
+                r#""Sem21" = WEEK-SCHEDULE-PD
+    TYPE  = "TEMPERATURE"
+    DAY-SCHEDULES = ( "Temp21")
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Week(
+            WeekSchedule::try_from(block(
+                r#""Sem25" = WEEK-SCHEDULE-PD
+    TYPE  = "TEMPERATURE"
+    DAY-SCHEDULES = ( "Temp25")
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Week(
+            WeekSchedule::try_from(block(
+                r#""SemOficina" = WEEK-SCHEDULE-PD
+    TYPE  = "ON/OFF"
+    DAY-SCHEDULES = ( "Oficina", "Oficina", "Oficina", "Oficina", "Oficina", "Apagado", "Apagado")
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Year(
+            YearSchedule::try_from(block(
+                r#""Calefaccion" = SCHEDULE-PD
+    TYPE   = "TEMPERATURE"
+    MONTH = ( 12)
+    DAY   = ( 31)
+    WEEK-SCHEDULES = ( "Sem21")
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Year(
+            YearSchedule::try_from(block(
+                r#""Refrigeracion" = SCHEDULE-PD
+    TYPE   = "TEMPERATURE"
+    MONTH = ( 12)
+    DAY   = ( 31)
+    WEEK-SCHEDULES = ( "Sem25")
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+        Schedule::Year(
+            YearSchedule::try_from(block(
+                r#""Funcionamiento" = SCHEDULE-PD
+    TYPE   = "ON/OFF"
+    MONTH = ( 6, 12)
+    DAY   = ( 30, 31)
+    WEEK-SCHEDULES = ( "SemOficina", "SemOficina")
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+    ];
+
+    let cond = SpaceSysConditions::from_bdl(
+        &block(
+            r#""Oficinas" = SYSTEM-CONDITIONS
+    NAME               = "Oficinas"
+    HEAT-TEMP-SCH      = "Calefaccion"
+    COOL-TEMP-SCH      = "Refrigeracion"
+    TYPE               = CONDITIONED
+    HEATING-SCHEDULE   = "Funcionamiento"
+    COOLING-SCHEDULE   = "Funcionamiento"
+    ..
+"#,
+        ),
+        &schedules,
+    )
+    .unwrap();
+    assert!(cond.conditioned);
+    assert_eq!(cond.heat_temp_sch.as_deref(), Some("Calefaccion"));
+    assert_eq!(cond.heating_setpoints.len(), 8760);
+    assert_eq!(cond.cooling_setpoints.len(), 8760);
+    // Lunes 1 de enero, de 8 a 18h
+    assert_eq!(cond.heating_setpoints[7], None);
+    assert_eq!(cond.heating_setpoints[8], Some(21.0));
+    assert_eq!(cond.cooling_setpoints[17], Some(25.0));
+    assert_eq!(cond.cooling_setpoints[18], None);
+    // Sábado 6 de enero y lunes 2 de julio, tras el cambio de horario semanal
+    assert_eq!(cond.heating_setpoints[5 * 24 + 12], None);
+    assert_eq!(cond.heating_setpoints[182 * 24 + 12], Some(21.0));
+    // 10 horas durante los 261 días laborables del año
+    assert_eq!(
+        cond.heating_setpoints
+            .iter()
+            .filter(|v| v.is_some())
+            .count(),
+        2610
+    );
+
+    // Espacio no acondicionado
+    let cond = SpaceSysConditions::from_bdl(
+        &block(
+            r#""NoHabitable" = SYSTEM-CONDITIONS
+    HEAT-TEMP-SCH      = "Calefaccion"
+    COOL-TEMP-SCH      = "Refrigeracion"
+    TYPE               = UNHABITED
+    HEATING-SCHEDULE   = "Funcionamiento"
+    ..
+"#,
+        ),
+        &schedules,
+    )
+    .unwrap();
+    assert!(!cond.conditioned);
+    assert!(cond.heating_setpoints.iter().all(Option::is_none));
+}
+
+#[test]
+fn bdl_schedules_day_types() {
+    use bdl::{year_values, BdlBlock, DaySchedule, Schedule, WeekSchedule, YearSchedule};
+
+    let block = |s: &str| s.parse::<BdlBlock>().unwrap();
+    let day = |s: &str| Schedule::Day(DaySchedule::try_from(block(s)).unwrap());
+
+    // Valores fraccionarios
+    let laborable = DaySchedule::try_from(block(
+        r#""Laborable" = DAY-SCHEDULE-PD
+    TYPE  = "FRACTION"
+    VALUES  = ( 0, 0, 0, 0, 0, 0, 0, 0.25, 0.5, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0.75, 0.5, 0.1, 0, 0, 0)
+    ..
+"#,
+    ))
+    .unwrap();
+    assert_almost_eq!(laborable.values[7], 0.25, 0.001);
+    assert_almost_eq!(laborable.values[20], 0.1, 0.001);
+
+    // Semana con sábado, domingo, festivos y días de diseño
+    let week = WeekSchedule::try_from(block(
+        r#""Semana" = WEEK-SCHEDULE-PD
+    TYPE   = "FRACTION"
+    DAY-SCHEDULES = ( "Laborable", "Laborable", "Laborable", "Laborable", "Laborable",
+                      "Sabado", "Apagado", "Apagado", "Encendido", "Encendido")
+    ..
+"#,
+    ))
+    .unwrap();
+    assert_eq!(week.days.len(), 7);
+    assert_eq!(week.days[5], "Sabado");
+    assert_eq!(week.days[6], "Apagado");
+    assert_eq!(week.holiday.as_deref(), Some("Apagado"));
+    assert_eq!(week.heating_design_day.as_deref(), Some("Encendido"));
+    assert_eq!(week.cooling_design_day.as_deref(), Some("Encendido"));
+
+    // Semana con un único horario diario
+    let single = WeekSchedule::try_from(block(
+        r#""Continuo" = WEEK-SCHEDULE-PD
+    TYPE   = "FRACTION"
+    DAY-SCHEDULES = ( "Encendido")
+    ..
+"#,
+    ))
+    .unwrap();
+    assert_eq!(single.days, vec!["Encendido".to_string()]);
+    assert_eq!(single.holiday, None);
+
+    let schedules = vec![
+        Schedule::Day(laborable),
+        day(r#""Sabado" = DAY-SCHEDULE-PD
+    TYPE  = "FRACTION"
+    VALUES  = ( 0.5)
+    ..
+"#),
+        day(r#""Apagado" = DAY-SCHEDULE-PD
+    TYPE  = "FRACTION"
+    VALUES  = ( 0)
+    ..
+"#),
+        day(r#""Encendido" = DAY-SCHEDULE-PD
+    TYPE  = "FRACTION"
+    VALUES  = ( 1)
+    ..
+"#),
+        Schedule::Week(week),
+        Schedule::Year(
+            YearSchedule::try_from(block(
+                r#""Anual" = SCHEDULE-PD
+    TYPE   = "FRACTION"
+    MONTH = ( 12)
+    DAY   = ( 31)
+    WEEK-SCHEDULES = ( "Semana")
+    ..
+"#,
+            ))
+            .unwrap(),
+        ),
+    ];
+    let values = year_values(&schedules, "Anual").unwrap();
+    assert_eq!(values.len(), 8760);
+    // Lunes 1 de enero a las 9h, sábado 6 y domingo 7 de enero
+    assert_almost_eq!(values[8], 0.5, 0.001);
+    assert_almost_eq!(values[5 * 24 + 12], 0.5, 0.001);
+    assert!(values[6 * 24..7 * 24].iter().all(|v| *v == 0.0));
+}
+
 #[test]
 fn bdl_thermalbridge() {
     use bdl::{BdlBlock, ThermalBridge};
@@ -590,3 +866,112 @@ fn bdl_window() {
     assert_eq!(coefs.len(), 4);
     assert_almost_eq!(coefs[0], 1.0, 0.1);
 }
+
+#[test]
+fn bdl_attrmap_units_and_lists() {
+    use bdl::{extract_f32vec, extract_u32vec, AttrMap};
+
+    let mut attrs = AttrMap::new();
+    attrs.insert(&"POWER", "3.5 W");
+    attrs.insert(&"U", "0.8W/m2K");
+    attrs.insert(&"FRACTION", "25 %");
+    attrs.insert(&"SMALL", "1e-3");
+    attrs.insert(&"NAME", "MURO");
+    assert_almost_eq!(attrs.get_f32("POWER").unwrap(), 3.5, 0.001);
+    assert_almost_eq!(attrs.get_f32("U").unwrap(), 0.8, 0.001);
+    assert_almost_eq!(attrs.get_f32("FRACTION").unwrap(), 25.0, 0.001);
+    assert_almost_eq!(attrs.get_f32("SMALL").unwrap(), 0.001, 0.0001);
+    assert_almost_eq!(attrs.remove_f32("POWER").unwrap(), 3.5, 0.001);
+
+    // Los errores indican el atributo y el valor original
+    let err = attrs.get_f32("NAME").unwrap_err().to_string();
+    assert!(err.contains("'NAME'") && err.contains("'MURO'"));
+    assert!(attrs.get_f32("POWER").is_err());
+
+    // Listas con separadores variados
+    assert_eq!(
+        extract_f32vec("( 1.0, 2.5 3;4 )").unwrap(),
+        vec![1.0, 2.5, 3.0, 4.0]
+    );
+    assert_eq!(extract_f32vec("(0.5W,1.5W)").unwrap(), vec![0.5, 1.5]);
+    assert_eq!(extract_u32vec("(1,2 ,\t3)").unwrap(), vec![1, 2, 3]);
+    let err = extract_f32vec("(1.0, abc)").unwrap_err().to_string();
+    assert!(err.contains("'abc'"));
+}
+
+#[test]
+fn bdl_block_lines() {
+    use bdl::{build_blocks, Data};
+
+    let input = "$ Archivo de prueba
+\"P01\" = FLOOR
+    Z = 0
+    ..
+
+$ Polígono de la planta
+\"P01_E01_Pol\" = POLYGON
+    V1 = ( 0, 0 )
+    V2 = ( 1, 0 )
+    V3 = ( 1, 1 )
+    ..
+";
+    let blocks = build_blocks(input).unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!((blocks[0].name.as_str(), blocks[0].line), ("P01", 2));
+    assert_eq!(
+        (blocks[1].name.as_str(), blocks[1].line),
+        ("P01_E01_Pol", 7)
+    );
+
+    // Bloque mal formado en la línea 13
+    let malformed = format!(
+        "{}\n\"Roto\" = POLYGON\n    V1 = ( 0, 0 )\n    SIN ATRIBUTO\n    ..\n",
+        input
+    );
+    let err = format!("{:#}", build_blocks(&malformed).unwrap_err());
+    assert!(err.contains("línea 13"));
+
+    // Error en la conversión de un bloque bien formado, en la línea 13
+    let invalid = format!("{}\n\"Roto\" = POLYGON\n    V1 = ( a, b )\n    ..\n", input);
+    let err = format!("{:#}", Data::new(&invalid).unwrap_err());
+    assert!(err.contains("Roto") && err.contains("línea 13"));
+}
+
+#[test]
+fn ctehexml_block_lines() {
+    use hulc::ctehexml;
+
+    // Las líneas se cuentan desde el inicio del archivo .ctehexml y no del texto BDL
+    let ctehexml = |bdl: &str| {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<CTE-HE-XML>
+    <DatosGenerales>
+        <nomPro>Prueba</nomPro><numViviendasBloque>1</numViviendasBloque><valorImpulsionAire>0</valorImpulsionAire>
+    </DatosGenerales>
+    <EntradaGraficaLIDER><![CDATA[
+\"P01\" = FLOOR
+    Z = 0
+    SPACE-HEIGHT = 3
+    PREVIOUS = \"\"
+    ..
+{}]]>    </EntradaGraficaLIDER>
+</CTE-HE-XML>
+",
+            bdl
+        )
+    };
+
+    let data = ctehexml::parse(&ctehexml("")).unwrap();
+    assert_eq!(data.bdldata.floors[0].name, "P01");
+
+    // Bloque mal formado en la línea 12
+    let malformed = ctehexml("\"Roto\" = POLYGON\n    V1 = ( 0, 0 )\n    SIN ATRIBUTO\n    ..\n");
+    let err = format!("{:#}", ctehexml::parse(&malformed).unwrap_err());
+    assert!(err.contains("línea 12"), "{}", err);
+
+    // Error en la conversión de un bloque bien formado, en la línea 12
+    let invalid = ctehexml("\"Roto\" = POLYGON\n    V1 = ( a, b )\n    ..\n");
+    let err = format!("{:#}", ctehexml::parse(&invalid).unwrap_err());
+    assert!(err.contains("Roto") && err.contains("línea 12"), "{}", err);
+}
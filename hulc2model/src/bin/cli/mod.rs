@@ -5,16 +5,19 @@
 
 use std::process::exit;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 use hulc2model::{collect_hulc_data, get_copytxt, PROGNAME};
 
 fn get_help() -> String {
     format!(
-        "Uso: {} [--use-kyg] DIRECTORIO
+        "Uso: {} [--use-kyg] [--use-tbl] [--use-extra] [--indicators-only] DIRECTORIO
 
 Opciones:
---use-extra      Utiliza datos de transmitancia y radiación de KyGananciasSolares.txt y NewBDL_O.tbl
+--use-kyg           Utiliza datos de transmitancia y factor de obstáculos remotos de KyGananciasSolares.txt
+--use-tbl           Utiliza datos de transmitancia de particiones interiores de NewBDL_O.tbl
+--use-extra         Equivale a --use-kyg --use-tbl
+--indicators-only   Emite solo los indicadores energéticos, en lugar del modelo completo
 
 Argumentos:
 DIRECTORIO     Directorio del proyecto de HULC
@@ -23,6 +26,7 @@ Descripción:
 Exporta al formato JSON de EnvolventeCTE los datos de un proyecto HULC.
 
 Emite en formato JSON de EnvolventeCTE los datos de un proyecto HULC.
+Con la opción --indicators-only emite, en su lugar, solo los indicadores energéticos en formato JSON.
 Puede redirigir la salida de resultados a un archivo para su uso posterior:
     {} DIRECTORIO > archivo_salida.json
 ",
@@ -30,9 +34,43 @@ Puede redirigir la salida de resultados a un archivo para su uso posterior:
     )
 }
 
+/// Contenido de la salida de resultados
+///
+/// La salida del modelo completo y la de indicadores son excluyentes
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+enum Output {
+    /// Modelo completo en formato JSON de EnvolventeCTE
+    #[default]
+    Model,
+    /// Solo los indicadores energéticos en formato JSON
+    Indicators,
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 struct Options {
-    use_extra_files: bool,
+    use_kyg: bool,
+    use_tbl: bool,
+    output: Output,
+}
+
+/// Interpreta las opciones de la línea de comandos
+///
+/// Devuelve un error si alguna de las opciones no es reconocida
+fn parse_options(args: &[String]) -> Result<Options> {
+    let mut opts = Options::default();
+    for opt in args {
+        match opt.as_str() {
+            "--use-kyg" => opts.use_kyg = true,
+            "--use-tbl" => opts.use_tbl = true,
+            "--use-extra" => {
+                opts.use_kyg = true;
+                opts.use_tbl = true;
+            }
+            "--indicators-only" => opts.output = Output::Indicators,
+            _ => bail!("Opción desconocida: {}\n\n{}", opt, get_help()),
+        }
+    }
+    Ok(opts)
 }
 
 pub fn cli_main() -> Result<()> {
@@ -51,27 +89,22 @@ pub fn cli_main() -> Result<()> {
         // Directorio de proyecto
         2 => (Options::default(), &args[1]),
         // Opciones + directorio de proyecto
-        _ => {
-            let mut opts = Options::default();
-            for opt in &args[1..args.len() - 1] {
-                if opt.as_str() == "--use-extra" {
-                    eprintln!(
-                            "Se usará la información en los archivos KyGananciasSolares.txt y NewBDL_O.tbl"
-                        );
-                    opts.use_extra_files = true;
-                }
-            }
-            (opts, &args[args.len() - 1])
-        }
+        _ => (
+            parse_options(&args[1..args.len() - 1])?,
+            &args[args.len() - 1],
+        ),
     };
 
     // Localiza archivos
     eprintln!("Localizando archivos de datos en '{}'", dir);
-    if opts.use_extra_files {
-        eprintln!("- Se usarán los datos de los archivos KyGananciasSolares.txt y NewBDL_O.tbl");
+    if opts.use_kyg {
+        eprintln!("- Se usarán los datos del archivo KyGananciasSolares.txt");
+    };
+    if opts.use_tbl {
+        eprintln!("- Se usarán los datos del archivo NewBDL_O.tbl");
     };
     // Lee datos
-    let model = collect_hulc_data(dir, opts.use_extra_files, opts.use_extra_files)?;
+    let model = collect_hulc_data(dir, opts.use_kyg, opts.use_tbl)?;
     let ind = model.energy_indicators();
     // Información general
     let climatezone = model.meta.climate;
@@ -90,12 +123,65 @@ pub fn cli_main() -> Result<()> {
     );
 
     // Convierte a JSON
-    if let Ok(json) = model.as_json() {
-        eprintln!("Salida de resultados en formato JSON de EnvolventeCTE");
+    let json = match opts.output {
+        Output::Model => {
+            eprintln!("Salida de resultados en formato JSON de EnvolventeCTE");
+            model.as_json()
+        }
+        Output::Indicators => {
+            eprintln!("Salida de indicadores energéticos en formato JSON");
+            ind.as_json()
+        }
+    };
+    if let Ok(json) = json {
         println!("{}", json);
         Ok(())
     } else {
-        eprintln!("Error al guardar la información en formato JSON de EnvolventeCTE");
+        eprintln!("Error al guardar la información en formato JSON");
         exit(1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options> {
+        parse_options(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parse_extra_files_options() {
+        let cases = [
+            (vec![], (false, false)),
+            (vec!["--use-kyg"], (true, false)),
+            (vec!["--use-tbl"], (false, true)),
+            (vec!["--use-kyg", "--use-tbl"], (true, true)),
+            (vec!["--use-extra"], (true, true)),
+            (vec!["--use-kyg", "--use-extra"], (true, true)),
+        ];
+        for (args, expected) in cases {
+            let opts = parse(&args).unwrap();
+            assert_eq!((opts.use_kyg, opts.use_tbl), expected, "{:?}", args);
+            assert_eq!(opts.output, Output::Model);
+        }
+    }
+
+    #[test]
+    fn parse_indicators_only_option() {
+        assert_eq!(parse(&[]).unwrap().output, Output::Model);
+        assert_eq!(
+            parse(&["--indicators-only"]).unwrap().output,
+            Output::Indicators
+        );
+        let opts = parse(&["--use-extra", "--indicators-only"]).unwrap();
+        assert_eq!(opts.output, Output::Indicators);
+        assert!(opts.use_kyg && opts.use_tbl);
+    }
+
+    #[test]
+    fn parse_unknown_option() {
+        assert!(parse(&["--use-kgy"]).is_err());
+        assert!(parse(&["--use-kyg", "--extra"]).is_err());
+    }
+}
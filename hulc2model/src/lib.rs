@@ -7,8 +7,9 @@ pub(crate) mod utils;
 use std::{convert::TryFrom, path::Path};
 
 use anyhow::{format_err, Error};
+use log::warn;
 
-use bemodel::{BoundaryType, ExtraData, Model};
+use bemodel::{BoundaryType, ExtraData, Model, Warning, WarningLevel};
 use hulc::{ctehexml, kyg, tbl};
 use utils::fround2;
 
@@ -18,6 +19,9 @@ pub const PROGNAME: &str = env!("CARGO_PKG_NAME");
 /// Versión del programa
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Diferencia relativa admisible entre la superficie de los espacios del .tbl y la calculada geométricamente
+pub const TBL_AREA_TOLERANCE: f32 = 0.01;
+
 /// Texto de descripción, copyright y licencia del programa
 pub fn get_copytxt() -> String {
     format!(
@@ -60,7 +64,7 @@ pub fn collect_hulc_data<T: AsRef<str>>(
 
     let mut ecdata = Model::try_from(&ctehexmldata)?;
     // Interpreta .kyg y añade datos que faltan con archivos adicionales
-    fix_ecdata_from_extra(&mut ecdata, &kygpath, &tblpath);
+    fix_ecdata_from_extra(&mut ecdata, &kygpath, &tblpath)?;
     // Devuelve datos ampliados y corregidos (U, Fshobst)
     Ok(ecdata)
 }
@@ -70,7 +74,7 @@ pub fn fix_ecdata_from_extra<T: AsRef<Path>>(
     model: &mut Model,
     kygpath: &Option<T>,
     tblpath: &Option<T>,
-) {
+) -> Result<(), Error> {
     let ind = model.energy_indicators();
 
     let mut extra = model
@@ -103,7 +107,7 @@ pub fn fix_ecdata_from_extra<T: AsRef<Path>>(
     // Interpreta .kyg y añade datos que faltan
     // TODO: Los añadimos al overrides... podríamos eliminar el extra
     if let Some(kygpath) = &kygpath {
-        let kygdata = kyg::parse_from_path(kygpath).unwrap();
+        let kygdata = kyg::parse_from_path(kygpath)?;
 
         // Modifica U de muros con datos del .kyg
         // XXX: hay que tener cuidado porque estos valores tienen desviaciones con los que se muestran en
@@ -158,7 +162,10 @@ pub fn fix_ecdata_from_extra<T: AsRef<Path>>(
 
     // Actualizamos datos de U de particiones interiores desde el archivo .tbl
     if let Some(tblpath) = &tblpath {
-        let tbldata = tbl::parse(tblpath).unwrap();
+        let tbldata = tbl::parse(tblpath)?;
+        for warning in check_space_areas(model, &tbldata, TBL_AREA_TOLERANCE) {
+            warn!("{}", warning.msg);
+        }
         for e in &mut extra {
             if e.bounds != BoundaryType::INTERIOR {
                 continue;
@@ -185,4 +192,42 @@ pub fn fix_ecdata_from_extra<T: AsRef<Path>>(
     extra.retain(|e| f32::abs(e.u - e.computed_u) > 0.001);
 
     model.extra = Some(extra);
+
+    Ok(())
+}
+
+/// Compara la superficie de los espacios del archivo .tbl con la calculada geométricamente en el modelo
+///
+/// Devuelve un aviso para cada espacio cuya diferencia relativa de superficie supera la tolerancia indicada
+/// o que no se encuentra en el modelo
+pub fn check_space_areas(model: &Model, tbldata: &tbl::Tbl, tolerance: f32) -> Vec<Warning> {
+    let mut warnings = vec![];
+    for (name, tbl_area) in tbldata.space_areas() {
+        let space = match model.get_space_by_name(&name) {
+            Some(space) => space,
+            None => {
+                warnings.push(Warning {
+                    level: WarningLevel::WARNING,
+                    id: None,
+                    msg: format!(
+                        "Espacio {} del archivo .tbl no encontrado en el modelo",
+                        name
+                    ),
+                });
+                continue;
+            }
+        };
+        let area = space.area(&model.walls);
+        if f32::abs(area - tbl_area) > tolerance * tbl_area.max(area) {
+            warnings.push(Warning {
+                level: WarningLevel::WARNING,
+                id: Some(space.id),
+                msg: format!(
+                    "Superficie del espacio {} ({:.2} m²) distinta a la del archivo .tbl ({:.2} m²)",
+                    name, area, tbl_area
+                ),
+            });
+        }
+    }
+    warnings
 }
@@ -132,6 +132,7 @@ fn parse_test_e4h_medianeras() {
     // Se pueden probar los valores de HULC con use_kyg = true, y use_tbl a true)
     let model = collect_hulc_data("tests/e4h_medianeras", false, false).unwrap();
     assert_eq!(&model.meta.climate.to_string(), "D3");
+    assert_eq!(model.meta.province.as_deref(), Some("Madrid"));
     assert_eq!(model.windows.len(), 92);
     assert_eq!(model.walls.len(), 127); // 68 en ET
     assert_eq!(model.thermal_bridges.len(), 10); // 6 en kyg
@@ -169,6 +170,35 @@ fn parse_test_ejemplopmt_huecosok() {
     assert_almost_eq!(ind.q_soljul_data.q_soljul, 3.49, 0.01); // HULC 3.47
 }
 
+#[test]
+fn test_floors_ejemplopmt_huecosok() {
+    // Edificio de 3 plantas
+    let model = collect_hulc_data("tests/ejemplopmt_huecosok", false, false).unwrap();
+    let floors = model
+        .floors
+        .iter()
+        .map(|f| (f.name.as_str(), f.z, f.height, f.multiplier))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        floors,
+        vec![
+            ("P01", 0.0, 3.0, 1.0),
+            ("P02", 3.0, 3.0, 1.0),
+            ("P03", 6.0, 3.0, 1.0)
+        ]
+    );
+    // Los espacios referencian su planta
+    let floor = model.get_floor_by_name("P02").unwrap();
+    let space = model.get_space_by_name("P02_E01").unwrap();
+    assert_eq!(space.floor, Some(floor.id));
+    assert!(model.spaces.iter().all(|s| s.floor.is_some()));
+    assert!(model
+        .spaces
+        .iter()
+        .filter(|s| s.floor == Some(floor.id))
+        .all(|s| s.name.starts_with("P02")));
+}
+
 #[test]
 fn test_kyg() {
     let kygpath = kyg::find_kyg("tests/casoA").unwrap().unwrap();
@@ -180,6 +210,53 @@ fn test_kyg() {
     assert_almost_eq!(kyg.k, 0.46, 0.01);
 }
 
+#[test]
+fn test_kyg_truncated() {
+    let kygpath = kyg::find_kyg("tests/casoA").unwrap().unwrap();
+    let bytes = std::fs::read(kygpath).unwrap();
+    let data: String = bytes.iter().map(|&b| b as char).collect();
+    let kyg = kyg::parse(&data).unwrap();
+    assert_eq!(kyg.solar_gains.len(), 10);
+
+    // Archivo truncado a mitad de una línea de hueco
+    let pos = data.find("Ventana;").unwrap();
+    let err = kyg::parse(&data[..pos + 15]).unwrap_err();
+    assert!(format!("{:#}", err).contains("Error en la línea"));
+
+    // Archivo truncado antes de las ganancias solares de los huecos
+    let pos = data.find("\n\"").unwrap();
+    let err = kyg::parse(&data[..pos]).unwrap_err();
+    assert!(format!("{:#}", err).contains("incompleto tras la línea 51"));
+
+    // Archivo truncado antes de la K global
+    let pos = data.find("Coeficiente K").unwrap();
+    let err = kyg::parse(&data[..pos]).unwrap_err();
+    assert!(format!("{:#}", err).contains("incompleto tras la línea 40"));
+
+    // Archivo sin datos utilizables
+    assert!(kyg::parse("# Sin datos\n").is_err());
+}
+
+#[test]
+fn test_kyg_unknown_element() {
+    let kygpath = kyg::find_kyg("tests/casoA").unwrap().unwrap();
+    let bytes = std::fs::read(kygpath).unwrap();
+    let data: String = bytes.iter().map(|&b| b as char).collect();
+
+    // Las líneas de elementos de tipo desconocido se ignoran
+    let pos = data.find("Muro;").unwrap();
+    let data = format!(
+        "{}MuroCortina;MC1;10,00;1,50;1,00\n{}",
+        &data[..pos],
+        &data[pos..]
+    );
+    let kyg = kyg::parse(&data).unwrap();
+    assert_eq!(kyg.walls.len(), 23);
+    assert!(!kyg.walls.contains_key("MC1"));
+    assert_eq!(kyg.windows.len(), 10);
+    assert_almost_eq!(kyg.k, 0.46, 0.01);
+}
+
 #[test]
 fn test_test_spaces_caso_a() {
     let tbl = tbl::parse("tests/casoA/NewBDL_O.tbl").unwrap();
@@ -194,6 +271,150 @@ fn test_test_spaces_caso_a() {
     }
 }
 
+#[test]
+fn test_tbl_space_areas_caso_a() {
+    let tbl = tbl::parse("tests/casoA/NewBDL_O.tbl").unwrap();
+    let model = collect_hulc_data("tests/casoA", false, false).unwrap();
+
+    // Los espacios no habitables tienen multiplicador nulo en el .tbl
+    let geometric_autil: f32 = model
+        .spaces
+        .iter()
+        .filter_map(|s| {
+            tbl.spaces
+                .get(&s.name)
+                .map(|ts| s.area(&model.walls) * ts.mult as f32)
+        })
+        .sum();
+    assert_almost_eq!(tbl.compute_autil(), geometric_autil, 0.1);
+    assert!(hulc2model::check_space_areas(&model, &tbl, hulc2model::TBL_AREA_TOLERANCE).is_empty());
+
+    // Espacio con superficie distinta a la del .tbl
+    let mut model = model;
+    let space_name = tbl.spaces.keys().next().unwrap().clone();
+    let space_id = model.get_space_by_name(&space_name).unwrap().id;
+    model.walls.retain(|w| w.space != space_id);
+    let warnings = hulc2model::check_space_areas(&model, &tbl, hulc2model::TBL_AREA_TOLERANCE);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].id, Some(space_id));
+}
+
+#[test]
+fn test_extra_files_combinations() {
+    use bemodel::{BoundaryType, Model};
+
+    // Número de overrides de U de opacos interiores, de U de otros opacos y de Fshobst de huecos
+    let count_overrides = |model: &Model| {
+        let interior = model
+            .overrides
+            .walls
+            .keys()
+            .filter(|id| model.get_wall(**id).unwrap().bounds == BoundaryType::INTERIOR)
+            .count();
+        (
+            interior,
+            model.overrides.walls.len() - interior,
+            model.overrides.windows.len(),
+        )
+    };
+
+    let dir = "tests/ejemploviv_unif";
+    // Sin archivos extra no hay overrides
+    let model = collect_hulc_data(dir, false, false).unwrap();
+    assert_eq!(count_overrides(&model), (0, 0, 0));
+    // El .kyg fija U de opacos exteriores y Fshobst de huecos
+    let model = collect_hulc_data(dir, true, false).unwrap();
+    assert_eq!(count_overrides(&model), (0, 13, 12));
+    // El .tbl fija U de particiones interiores
+    let model = collect_hulc_data(dir, false, true).unwrap();
+    assert_eq!(count_overrides(&model), (17, 0, 0));
+    // Ambos archivos
+    let model = collect_hulc_data(dir, true, true).unwrap();
+    assert_eq!(count_overrides(&model), (17, 13, 12));
+}
+
+#[test]
+fn test_global_north_deviation() {
+    use bemodel::{utils::normalize, Model, Orientation};
+
+    let hdata = ctehexml::parse_with_catalog_from_path("tests/casoA/casoa.ctehexml").unwrap();
+    let model = Model::try_from(&hdata).unwrap();
+
+    // Giramos todo el edificio 90º respecto al norte (sentido horario, criterio BDL)
+    let mut rotated_hdata = hdata.clone();
+    let deviation = rotated_hdata
+        .bdldata
+        .meta
+        .get(&bdl::BdlBlockType::BuildParameters)
+        .unwrap()
+        .attrs
+        .get_f32("AZIMUTH")
+        .unwrap();
+    rotated_hdata
+        .bdldata
+        .meta
+        .get_mut(&bdl::BdlBlockType::BuildParameters)
+        .unwrap()
+        .attrs
+        .insert(&"AZIMUTH", &(deviation + 90.0).to_string());
+    let rotated = Model::try_from(&rotated_hdata).unwrap();
+
+    // Los opacos verticales pasan de S a O, de E a S, de N a E y de O a N
+    // y las posiciones giran 90º en sentido horario: (x, y) -> (y, -x)
+    for (wall, rwall) in model.walls.iter().zip(&rotated.walls) {
+        assert_eq!(wall.name, rwall.name);
+        if Orientation::from(wall) != Orientation::HZ {
+            let diff = normalize(
+                rwall.geometry.azimuth - wall.geometry.azimuth,
+                -180.0,
+                180.0,
+            );
+            assert_almost_eq!(diff, -90.0, 0.01);
+        }
+        let pos = wall.geometry.position.unwrap();
+        let rpos = rwall.geometry.position.unwrap();
+        assert_almost_eq!(rpos.x, pos.y, 0.01);
+        assert_almost_eq!(rpos.y, -pos.x, 0.01);
+        assert_almost_eq!(rpos.z, pos.z, 0.01);
+    }
+    let count = |model: &Model, orientation: Orientation| {
+        model
+            .walls
+            .iter()
+            .filter(|w| Orientation::from(*w) == orientation)
+            .count()
+    };
+    assert_eq!(
+        count(&model, Orientation::S),
+        count(&rotated, Orientation::W)
+    );
+    assert_eq!(
+        count(&model, Orientation::E),
+        count(&rotated, Orientation::S)
+    );
+    assert_eq!(
+        count(&model, Orientation::N),
+        count(&rotated, Orientation::E)
+    );
+    assert_eq!(
+        count(&model, Orientation::W),
+        count(&rotated, Orientation::N)
+    );
+
+    // Los huecos giran con sus opacos
+    for (win, rwin) in model.windows.iter().zip(&rotated.windows) {
+        let pos = win
+            .position_3d(&model.get_wall(win.wall).unwrap().geometry)
+            .unwrap();
+        let rpos = rwin
+            .position_3d(&rotated.get_wall(rwin.wall).unwrap().geometry)
+            .unwrap();
+        assert_almost_eq!(rpos.x, pos.y, 0.01);
+        assert_almost_eq!(rpos.y, -pos.x, 0.01);
+        assert_almost_eq!(rpos.z, pos.z, 0.01);
+    }
+}
+
 #[test]
 fn test_bdl_parse() {
     let _data =
@@ -206,6 +427,7 @@ fn test_bdl_parse() {
     let bdl::Data {
         meta,
         db,
+        floors,
         spaces,
         walls,
         windows,
@@ -386,3 +608,69 @@ fn test_casos_gt() {
     )
     .unwrap();
 }
+
+#[test]
+fn test_gt_thermal_zones() {
+    let data = ctehexml::parse_with_catalog_from_path(
+        "tests/paracuellos_gim_05/paracuellos_gim_05.ctehexml",
+    )
+    .unwrap();
+    assert_eq!(data.zonas.len(), 2);
+    assert_eq!(data.zonas[0].name, "Zonas 1");
+    assert_eq!(data.zonas[0].space, "P01_E01");
+
+    assert_eq!(
+        data.zonas[0].system.as_deref(),
+        Some("Subsistema secundario 1")
+    );
+
+    // Las zonas GT servidas por el mismo sistema forman una única zona térmica
+    let model = bemodel::Model::try_from(&data).unwrap();
+    assert_eq!(model.zones.len(), 1);
+    let zone = model.get_zone_by_name("Subsistema secundario 1").unwrap();
+    let spaces = [
+        model.get_space_by_name("P01_E01").unwrap(),
+        model.get_space_by_name("P02_E01").unwrap(),
+    ];
+    assert_eq!(zone.spaces, vec![spaces[0].id, spaces[1].id]);
+
+    // Superficie y volumen de la zona como suma de las de sus espacios
+    let area: f32 = spaces
+        .iter()
+        .map(|s| s.area(&model.walls) * s.multiplier)
+        .sum();
+    let volume: f32 = spaces
+        .iter()
+        .map(|s| s.area(&model.walls) * s.multiplier * s.height_net(&model.walls, &model.cons))
+        .sum();
+    assert!(area > 0.0);
+    assert_almost_eq!(model.zone_area(zone.id), area);
+    assert_almost_eq!(model.zone_volume(zone.id), volume, 0.01);
+
+    // Las zonas que referencian espacios inexistentes se descartan sin impedir la importación
+    let mut stale_data = data.clone();
+    stale_data.zonas[1].space = "P99_E99".to_string();
+    let model = bemodel::Model::try_from(&stale_data).unwrap();
+    assert_eq!(model.zones.len(), 1);
+    assert_eq!(model.zones[0].spaces, vec![spaces[0].id]);
+
+    // Los proyectos sin sistemas GT no definen zonas
+    let data = ctehexml::parse_with_catalog_from_path("tests/casoA/casoa.ctehexml").unwrap();
+    assert!(data.zonas.is_empty());
+}
+
+#[test]
+fn test_performance_curves() {
+    let data =
+        ctehexml::parse_from_path("tests/00_plurif_s3_v0_d3/00_plurif_s3_v0_d3.ctehexml").unwrap();
+    let generator = data
+        .sistemas
+        .iter()
+        .flat_map(|s| s.generators())
+        .find(|g| g.curve("capTotRef_T").is_some())
+        .unwrap();
+    assert!(generator.curve("conRef_FCP").is_some());
+    let cap_tot = generator.curve("capTotRef_T").unwrap();
+    assert_eq!(cap_tot.variables.len(), 2);
+    assert_almost_eq!(cap_tot.eval(&[19.0, 35.0]), 1.0, 0.02);
+}